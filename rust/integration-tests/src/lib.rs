@@ -117,7 +117,7 @@ impl TestContext {
         let mut conn = PgConnection::establish(&db_url)
             .with_context(|| format!("Error connecting to {}", db_url))?;
         let db_pool = new_db_pool(&db_url, None).await.unwrap();
-        let producer = CustomProducerEnum::new("");
+        let producer = CustomProducerEnum::new("")?;
 
         self.create_schema().await?;
         let processor = build_processor_for_testing(
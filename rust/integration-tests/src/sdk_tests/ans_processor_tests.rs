@@ -53,6 +53,8 @@ pub fn setup_ans_processor_config(
             bootstrap_config: None,
             testing_config: Some(testing_config),
             mode: ProcessorMode::Testing,
+            instance_id: None,
+            status_history_config: None,
         },
         processor_name,
     )
@@ -41,6 +41,8 @@ pub fn setup_default_processor_config(
             bootstrap_config: None,
             testing_config: Some(testing_config),
             mode: ProcessorMode::Testing,
+            instance_id: None,
+            status_history_config: None,
         },
         processor_name,
     )
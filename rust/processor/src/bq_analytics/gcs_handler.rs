@@ -20,6 +20,8 @@ pub async fn upload_parquet_to_gcs(
     bucket_name: &str,
     bucket_root: &Path,
     processor_name: String,
+    start_version: i64,
+    end_version: i64,
 ) -> Result<(), ParquetProcessorError> {
     if buffer.is_empty() {
         error!("The file is empty and has no data to upload.",);
@@ -43,8 +45,15 @@ pub async fn upload_parquet_to_gcs(
     let highwater_s = start_of_month.timestamp_millis();
     let highwater_ms = now.timestamp_millis();
     let counter = 0; // THIS NEED TO BE REPLACED OR REIMPLEMENTED WITH AN ACTUAL LOGIC TO ENSURE FILE UNIQUENESS.
-    let object_name: PathBuf =
-        generate_parquet_file_path(bucket_root, table_name, highwater_s, highwater_ms, counter);
+    let object_name: PathBuf = generate_parquet_file_path(
+        bucket_root,
+        table_name,
+        highwater_s,
+        highwater_ms,
+        counter,
+        start_version,
+        end_version,
+    );
 
     let file_name = object_name.to_str().unwrap().to_owned();
     let upload_type: UploadType = UploadType::Simple(Media::new(file_name.clone()));
@@ -101,15 +110,62 @@ pub async fn upload_parquet_to_gcs(
     }
 }
 
+/// The versions the file covers are the last two parts of its name, so readers can skip files
+/// outside of a version range without downloading them.
 fn generate_parquet_file_path(
     gcs_bucket_root: &Path,
     table: &str,
     highwater_s: i64,
     highwater_ms: i64,
     counter: u32,
+    start_version: i64,
+    end_version: i64,
 ) -> PathBuf {
     gcs_bucket_root.join(format!(
-        "{}/{}/{}_{}.parquet",
-        table, highwater_s, highwater_ms, counter
+        "{}/{}/{}_{}_{}_{}.parquet",
+        table, highwater_s, highwater_ms, counter, start_version, end_version
     ))
 }
+
+/// Start and end version of a file named by `generate_parquet_file_path`. Files uploaded before
+/// the versions were part of the name return `None`.
+pub fn parse_parquet_file_version_range(file_name: &str) -> Option<(i64, i64)> {
+    let stem = Path::new(file_name).file_stem()?.to_str()?;
+    let parts = stem.split('_').collect::<Vec<_>>();
+    match parts.as_slice() {
+        [_, _, start_version, end_version] => {
+            Some((start_version.parse().ok()?, end_version.parse().ok()?))
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parquet_file_version_range() {
+        let path = generate_parquet_file_path(
+            Path::new("root"),
+            "events",
+            1_700_000_000_000,
+            1_700_000_123_456,
+            0,
+            100,
+            199,
+        );
+        assert_eq!(
+            path,
+            PathBuf::from("root/events/1700000000000/1700000123456_0_100_199.parquet")
+        );
+        assert_eq!(
+            parse_parquet_file_version_range(path.to_str().unwrap()),
+            Some((100, 199))
+        );
+        assert_eq!(
+            parse_parquet_file_version_range("root/events/1700000000000/1700000123456_0.parquet"),
+            None
+        );
+    }
+}
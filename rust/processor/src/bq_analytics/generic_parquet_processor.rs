@@ -1,6 +1,8 @@
 use super::ParquetProcessingResult;
 use crate::{
-    bq_analytics::gcs_handler::upload_parquet_to_gcs,
+    bq_analytics::{
+        gcs_handler::upload_parquet_to_gcs, parquet_writer_config::ParquetWriterConfig,
+    },
     gap_detectors::ProcessingResult,
     utils::{
         counters::{PARQUET_HANDLER_CURRENT_BUFFER_SIZE, PARQUET_STRUCT_SIZE},
@@ -73,15 +75,15 @@ where
     pub max_buffer_size: usize,
     pub last_upload_time: Instant,
     pub processor_name: String,
+    pub writer_properties: Arc<WriterProperties>,
 }
 
-fn create_new_writer(schema: Arc<Type>) -> Result<SerializedFileWriter<Vec<u8>>> {
-    let props = WriterProperties::builder()
-        .set_compression(parquet::basic::Compression::LZ4)
-        .build();
-    let props_arc = Arc::new(props);
-
-    SerializedFileWriter::new(Vec::new(), schema, props_arc).context("Failed to create new writer")
+fn create_new_writer(
+    schema: Arc<Type>,
+    writer_properties: Arc<WriterProperties>,
+) -> Result<SerializedFileWriter<Vec<u8>>> {
+    SerializedFileWriter::new(Vec::new(), schema, writer_properties)
+        .context("Failed to create new writer")
 }
 
 impl<ParquetType> ParquetHandler<ParquetType>
@@ -90,7 +92,7 @@ where
     for<'a> &'a [ParquetType]: RecordWriter<ParquetType>,
 {
     fn create_new_writer(&self) -> Result<SerializedFileWriter<Vec<u8>>> {
-        create_new_writer(self.schema.clone())
+        create_new_writer(self.schema.clone(), self.writer_properties.clone())
     }
 
     fn close_writer(&mut self) -> Result<SerializedFileWriter<Vec<u8>>> {
@@ -107,9 +109,15 @@ where
         upload_interval: Duration,
         max_buffer_size: usize,
         processor_name: String,
+        writer_config: &ParquetWriterConfig,
     ) -> Result<Self> {
+        let writer_properties = Arc::new(
+            writer_config
+                .writer_properties()
+                .context("Failed to build parquet writer properties")?,
+        );
         // had to append unique id to avoid concurrent write issues
-        let writer = create_new_writer(schema.clone())?;
+        let writer = create_new_writer(schema.clone(), writer_properties.clone())?;
 
         Ok(Self {
             writer,
@@ -124,6 +132,7 @@ where
             max_buffer_size,
             last_upload_time: Instant::now(),
             processor_name,
+            writer_properties,
         })
     }
 
@@ -243,6 +252,8 @@ where
             &self.bucket_name,
             &bucket_root,
             self.processor_name.clone(),
+            start_version,
+            end_version,
         )
         .await?;
 
@@ -1,10 +1,14 @@
 pub mod gcs_handler;
 pub mod generic_parquet_processor;
+pub mod parquet_writer_config;
 
 use crate::{
-    bq_analytics::generic_parquet_processor::{
-        GetTimeStamp, HasParquetSchema, HasVersion, NamedTable, ParquetDataGeneric,
-        ParquetHandler as GenericParquetHandler,
+    bq_analytics::{
+        generic_parquet_processor::{
+            GetTimeStamp, HasParquetSchema, HasVersion, NamedTable, ParquetDataGeneric,
+            ParquetHandler as GenericParquetHandler,
+        },
+        parquet_writer_config::ParquetWriterConfig,
     },
     gap_detectors::ProcessingResult,
     worker::PROCESSOR_SERVICE_TYPE,
@@ -95,6 +99,7 @@ pub fn create_parquet_handler_loop<ParquetType>(
     parquet_handler_response_channel_size: usize,
     max_buffer_size: usize,
     upload_interval: Duration,
+    writer_config: ParquetWriterConfig,
 ) -> AsyncSender<ParquetDataGeneric<ParquetType>>
 where
     ParquetType: GetTimeStamp
@@ -126,6 +131,7 @@ where
         upload_interval,
         max_buffer_size,
         processor_name.clone(),
+        &writer_config,
     )
     .expect("Failed to create parquet manager");
 
@@ -0,0 +1,165 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use ahash::AHashMap;
+use anyhow::{Context, Result};
+use parquet::{
+    basic::{BrotliLevel, Compression, GzipLevel, ZstdLevel},
+    file::properties::{EnabledStatistics, WriterProperties},
+    schema::types::ColumnPath,
+};
+use serde::{Deserialize, Serialize};
+
+/// Compression codecs that can be configured for a parquet table.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetCompressionCodec {
+    Uncompressed,
+    Snappy,
+    Gzip,
+    Brotli,
+    #[default]
+    Lz4,
+    Lz4Raw,
+    Zstd,
+}
+
+/// Level of column statistics written to the parquet file.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetStatisticsLevel {
+    None,
+    Chunk,
+    #[default]
+    Page,
+}
+
+impl From<ParquetStatisticsLevel> for EnabledStatistics {
+    fn from(level: ParquetStatisticsLevel) -> Self {
+        match level {
+            ParquetStatisticsLevel::None => EnabledStatistics::None,
+            ParquetStatisticsLevel::Chunk => EnabledStatistics::Chunk,
+            ParquetStatisticsLevel::Page => EnabledStatistics::Page,
+        }
+    }
+}
+
+/// Writer settings for a single parquet table. The defaults match the settings we used before
+/// this was configurable (LZ4, parquet's default row group and page sizes), so tables without an
+/// entry in the config keep producing the same files.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParquetWriterConfig {
+    #[serde(default)]
+    pub compression: ParquetCompressionCodec,
+    // Only used by gzip, brotli and zstd. Falls back to the codec's default level if unset.
+    #[serde(default)]
+    pub compression_level: Option<i32>,
+    #[serde(default)]
+    pub max_row_group_size: Option<usize>,
+    #[serde(default)]
+    pub data_page_size_limit: Option<usize>,
+    // Dictionary encoding is enabled for every column unless explicitly disabled.
+    #[serde(default)]
+    pub dictionary_enabled: Option<bool>,
+    #[serde(default)]
+    pub statistics: ParquetStatisticsLevel,
+    // Columns to write bloom filters for, e.g. `transaction_version` or `owner_address`.
+    #[serde(default)]
+    pub bloom_filter_columns: Vec<String>,
+    #[serde(default)]
+    pub bloom_filter_fpp: Option<f64>,
+    #[serde(default)]
+    pub bloom_filter_ndv: Option<u64>,
+}
+
+impl ParquetWriterConfig {
+    pub fn compression(&self) -> Result<Compression> {
+        let level = self.compression_level;
+        let compression = match self.compression {
+            ParquetCompressionCodec::Uncompressed => Compression::UNCOMPRESSED,
+            ParquetCompressionCodec::Snappy => Compression::SNAPPY,
+            ParquetCompressionCodec::Lz4 => Compression::LZ4,
+            ParquetCompressionCodec::Lz4Raw => Compression::LZ4_RAW,
+            ParquetCompressionCodec::Gzip => Compression::GZIP(match level {
+                Some(level) => GzipLevel::try_new(level as u32)
+                    .with_context(|| format!("Invalid gzip compression level {}", level))?,
+                None => GzipLevel::default(),
+            }),
+            ParquetCompressionCodec::Brotli => Compression::BROTLI(match level {
+                Some(level) => BrotliLevel::try_new(level as u32)
+                    .with_context(|| format!("Invalid brotli compression level {}", level))?,
+                None => BrotliLevel::default(),
+            }),
+            ParquetCompressionCodec::Zstd => Compression::ZSTD(match level {
+                Some(level) => ZstdLevel::try_new(level)
+                    .with_context(|| format!("Invalid zstd compression level {}", level))?,
+                None => ZstdLevel::default(),
+            }),
+        };
+        Ok(compression)
+    }
+
+    /// Builds the parquet `WriterProperties` for this config.
+    pub fn writer_properties(&self) -> Result<WriterProperties> {
+        let mut builder = WriterProperties::builder()
+            .set_compression(self.compression()?)
+            .set_statistics_enabled(self.statistics.into());
+
+        if let Some(max_row_group_size) = self.max_row_group_size {
+            builder = builder.set_max_row_group_size(max_row_group_size);
+        }
+        if let Some(data_page_size_limit) = self.data_page_size_limit {
+            builder = builder.set_data_page_size_limit(data_page_size_limit);
+        }
+        if let Some(dictionary_enabled) = self.dictionary_enabled {
+            builder = builder.set_dictionary_enabled(dictionary_enabled);
+        }
+        for column in &self.bloom_filter_columns {
+            let column_path = ColumnPath::from(column.as_str());
+            builder = builder.set_column_bloom_filter_enabled(column_path.clone(), true);
+            if let Some(fpp) = self.bloom_filter_fpp {
+                builder = builder.set_column_bloom_filter_fpp(column_path.clone(), fpp);
+            }
+            if let Some(ndv) = self.bloom_filter_ndv {
+                builder = builder.set_column_bloom_filter_ndv(column_path, ndv);
+            }
+        }
+
+        Ok(builder.build())
+    }
+}
+
+/// Parquet writer settings for the given table, falling back to the defaults if the table has no
+/// entry in `table_writer_configs`.
+pub fn writer_config_for_table(
+    table_writer_configs: &AHashMap<String, ParquetWriterConfig>,
+    table_name: &str,
+) -> ParquetWriterConfig {
+    table_writer_configs
+        .get(table_name)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Fails if `table_writer_configs` has an entry for a table the processor doesn't write, since
+/// the settings of a misspelled table would otherwise silently be replaced by the defaults.
+pub fn validate_table_writer_configs<'a>(
+    table_writer_configs: &AHashMap<String, ParquetWriterConfig>,
+    table_names: impl IntoIterator<Item = &'a str>,
+) -> Result<()> {
+    let mut table_names: Vec<&str> = table_names.into_iter().collect();
+    table_names.sort_unstable();
+    let mut unknown_tables: Vec<&String> = table_writer_configs
+        .keys()
+        .filter(|table_name| !table_names.contains(&table_name.as_str()))
+        .collect();
+    unknown_tables.sort_unstable();
+    anyhow::ensure!(
+        unknown_tables.is_empty(),
+        "Unknown tables {:?} in table_writer_configs, expected one of {:?}",
+        unknown_tables,
+        table_names
+    );
+    Ok(())
+}
@@ -2,18 +2,41 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    gap_detectors::DEFAULT_GAP_DETECTION_BATCH_SIZE, processors::ProcessorConfig,
-    transaction_filter::TransactionFilter, worker::Worker,
+    gap_detectors::DEFAULT_GAP_DETECTION_BATCH_SIZE,
+    processors::ProcessorConfig,
+    transaction_filter::TransactionFilter,
+    utils::{
+        mq::FILE_BROKERS_PREFIX,
+        status_history::StatusHistoryConfig,
+        util::{processor_instance_name, validate_instance_id},
+        validation::{check_gcs_bucket, check_kafka, with_timeout, ValidationReport},
+    },
+    worker::Worker,
 };
 use ahash::AHashMap;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use server_framework::RunnableConfig;
-use std::{collections::HashSet, time::Duration};
+use server_framework::{
+    reload::{config_diff, ConfigChange},
+    RunnableConfig,
+};
+use std::{
+    collections::HashSet,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+use tokio::sync::watch;
+use tracing::warn;
 use url::Url;
 
 pub const QUERY_DEFAULT_RETRIES: u32 = 5;
 pub const QUERY_DEFAULT_RETRY_DELAY_MS: u64 = 500;
+/// Fields of `IndexerGrpcProcessorConfig` that are applied on reload, see `ReloadableConfig`.
+const RELOADABLE_FIELDS: [&str; 3] = [
+    "per_table_chunk_sizes",
+    "deprecated_tables",
+    "transaction_filter",
+];
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
@@ -56,6 +79,19 @@ pub struct IndexerGrpcProcessorConfig {
     // String vector for deprecated tables to skip db writes
     #[serde(default)]
     pub deprecated_tables: HashSet<String>,
+    // Distinguishes deployments of the same processor type, e.g. a filtered and an unfiltered
+    // `events_processor`. Namespaces the checkpoint, metrics, gRPC request name and MQ topics.
+    #[serde(default)]
+    pub instance_id: Option<String>,
+    // Thresholds of the readiness and liveness probes on the health check port
+    #[serde(default)]
+    pub health_check_config: HealthCheckConfig,
+    // Periodic samples of the checkpoint in processor_status_history, disabled if unset
+    #[serde(default)]
+    pub status_history_config: Option<StatusHistoryConfig>,
+    // Set once the worker runs, reloads are sent through it
+    #[serde(skip)]
+    reloadable_config: OnceLock<Arc<watch::Sender<ReloadableConfig>>>,
 }
 
 impl IndexerGrpcProcessorConfig {
@@ -81,11 +117,107 @@ impl IndexerGrpcProcessorConfig {
     pub const fn default_grpc_response_item_timeout_in_secs() -> u64 {
         60
     }
+
+    pub fn reloadable_config(&self) -> ReloadableConfig {
+        ReloadableConfig {
+            per_table_chunk_sizes: self.per_table_chunk_sizes.clone(),
+            deprecated_tables: self.deprecated_tables.clone(),
+            transaction_filter: self.transaction_filter.clone(),
+        }
+    }
+
+    /// Checks the config and connectivity to everything the processor uses without processing or
+    /// writing anything, see `utils::validation`.
+    pub async fn validation_report(&self) -> ValidationReport {
+        let processor_name =
+            processor_instance_name(self.processor_config.name(), self.instance_id.as_deref());
+        let mut report = ValidationReport::new(processor_name.clone());
+        if let Some(instance_id) = &self.instance_id {
+            report.record(
+                "instance_id",
+                validate_instance_id(instance_id).map(|_| ((), instance_id.clone())),
+            );
+        }
+        report.check_deprecated_tables(&self.deprecated_tables);
+        report.check_per_table_chunk_sizes(self.per_table_chunk_sizes.keys());
+        if let Some(config) = self.processor_config.parquet_processor_config() {
+            let valid_tables = config
+                .table_names()
+                .iter()
+                .map(|table_name| table_name.to_string())
+                .collect();
+            report.check_parquet_tables(
+                "table_writer_configs",
+                config.table_writer_configs().keys(),
+                &valid_tables,
+            );
+        }
+        if let ProcessorConfig::NftMetadataProcessor(config) = &self.processor_config {
+            report.record(
+                "nft_metadata_output",
+                config
+                    .validate(self.brokers.as_deref())
+                    .map(|message| ((), message)),
+            );
+        }
+
+        // The chain id is fetched like on startup, which panics on errors.
+        let grpc_chain_id = tokio::spawn(crate::grpc_stream::get_chain_id(
+            self.indexer_grpc_data_service_address.clone(),
+            self.grpc_http2_config.grpc_http2_ping_interval_in_secs(),
+            self.grpc_http2_config.grpc_http2_ping_timeout_in_secs(),
+            self.grpc_http2_config.grpc_connection_timeout_secs(),
+            self.auth_token.clone(),
+            processor_name,
+        ));
+        let grpc_chain_id = report.record(
+            "grpc",
+            with_timeout(async {
+                let chain_id = grpc_chain_id
+                    .await
+                    .context("Failed to get the chain id from the gRPC stream")?;
+                Ok((chain_id, format!("Connected, serving chain {}", chain_id)))
+            })
+            .await,
+        );
+        report
+            .check_db(&self.postgres_connection_string, grpc_chain_id)
+            .await;
+
+        match self.brokers.as_deref() {
+            Some(brokers) if brokers.starts_with(FILE_BROKERS_PREFIX) => {
+                report.skip("kafka", "Writing to local files")
+            },
+            Some(brokers) if !brokers.is_empty() => {
+                report.record("kafka", check_kafka(brokers).await);
+            },
+            _ => report.skip("kafka", "No brokers configured"),
+        }
+        match self.processor_config.parquet_bucket() {
+            Some((bucket_name, credentials)) => {
+                report.record(
+                    "gcs",
+                    with_timeout(check_gcs_bucket(bucket_name, credentials)).await,
+                );
+            },
+            None => report.skip("gcs", "Not a parquet processor"),
+        }
+        report
+    }
 }
 
 #[async_trait::async_trait]
 impl RunnableConfig for IndexerGrpcProcessorConfig {
     async fn run(&self) -> Result<()> {
+        if let Some(instance_id) = &self.instance_id {
+            validate_instance_id(instance_id)?;
+        }
+        if let ProcessorConfig::NftMetadataProcessor(config) = &self.processor_config {
+            config.validate(self.brokers.as_deref())?;
+        }
+        if let Some(config) = self.processor_config.parquet_processor_config() {
+            config.validate_table_writer_configs()?;
+        }
         let mut worker = Worker::new(
             self.processor_config.clone(),
             self.brokers.clone(),
@@ -105,9 +237,20 @@ impl RunnableConfig for IndexerGrpcProcessorConfig {
             self.transaction_filter.clone(),
             self.grpc_response_item_timeout_in_secs,
             self.deprecated_tables.clone(),
+            self.instance_id.clone(),
         )
         .await
         .context("Failed to build worker")?;
+        worker.status_history_config = self.status_history_config.clone();
+        worker
+            .admin
+            .set_effective_config(server_framework::admin::redact_config(self)?);
+        worker
+            .admin
+            .set_health_check_config(self.health_check_config.clone());
+        server_framework::admin::register_admin_hook(worker.admin.clone());
+        server_framework::health::register_health_check(worker.admin.clone());
+        let _ = self.reloadable_config.set(worker.reloadable_config.clone());
         worker.run().await;
         Ok(())
     }
@@ -122,6 +265,54 @@ impl RunnableConfig for IndexerGrpcProcessorConfig {
             .unwrap_or("unknown");
         before_underscore[..before_underscore.len().min(12)].to_string()
     }
+
+    /// Applies the `RELOADABLE_FIELDS` to the running worker. Changes to other fields are logged
+    /// and only take effect after a restart. The new config is rejected if its tables don't pass
+    /// the same checks as `validate`.
+    fn reload(&self, new_config: &Self) -> Result<Vec<ConfigChange>> {
+        if let Some(instance_id) = &new_config.instance_id {
+            validate_instance_id(instance_id)?;
+        }
+        let mut report = ValidationReport::new(self.processor_config.name());
+        report.check_deprecated_tables(&new_config.deprecated_tables);
+        report.check_per_table_chunk_sizes(new_config.per_table_chunk_sizes.keys());
+        report
+            .ensure_valid()
+            .context("Rejected the reloaded config")?;
+        let sender = self
+            .reloadable_config
+            .get()
+            .context("Processor hasn't started yet")?;
+
+        // Parquet processors aren't rebuilt on reload, see `Worker::run`, so only the transaction
+        // filter of the fetcher changes for them.
+        let reloadable_fields: &[&str] = if self.processor_config.is_parquet_processor() {
+            &["transaction_filter"]
+        } else {
+            &RELOADABLE_FIELDS
+        };
+        let restart_required: Vec<String> = config_diff(self, new_config)?
+            .into_iter()
+            .map(|change| change.field)
+            .filter(|field| !reloadable_fields.contains(&field.as_str()))
+            .collect();
+        if !restart_required.is_empty() {
+            warn!(
+                fields = ?restart_required,
+                "[Parser] Config changes to these fields only take effect after a restart"
+            );
+        }
+
+        let new_reloadable_config = new_config.reloadable_config();
+        let changes: Vec<ConfigChange> = config_diff(&*sender.borrow(), &new_reloadable_config)?
+            .into_iter()
+            .filter(|change| reloadable_fields.contains(&change.field.as_str()))
+            .collect();
+        if !changes.is_empty() {
+            sender.send_replace(new_reloadable_config);
+        }
+        Ok(changes)
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -162,3 +353,69 @@ impl Default for IndexerGrpcHttp2Config {
         }
     }
 }
+
+/// The processor is never ready before migrations and the chain id check have completed. Both
+/// thresholds are disabled by default.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+#[serde(default)]
+pub struct HealthCheckConfig {
+    /// Not ready while the last processed transaction is older than this many seconds.
+    pub max_lag_secs: Option<u64>,
+    /// Not alive if no batch was processed in this many seconds, e.g. because processing is stuck.
+    /// Doesn't apply while starting or paused through the admin API.
+    pub max_secs_without_progress: Option<u64>,
+}
+
+/// The part of the config a running worker picks up on reload without restarting the stream.
+/// Batches already being processed finish with the previous values.
+#[derive(Clone, Debug, Serialize)]
+pub struct ReloadableConfig {
+    pub per_table_chunk_sizes: AHashMap<String, usize>,
+    pub deprecated_tables: HashSet<String>,
+    pub transaction_filter: TransactionFilter,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_reload() {
+        let config_json = json!({
+            "processor_config": { "type": "events_processor" },
+            "postgres_connection_string": "postgresql://localhost:5432/postgres",
+            "indexer_grpc_data_service_address": "http://127.0.0.1:50051",
+            "auth_token": "abc",
+        });
+        let config: IndexerGrpcProcessorConfig =
+            serde_json::from_value(config_json.clone()).unwrap();
+        let serialized = serde_json::to_value(&config).unwrap();
+        for field in RELOADABLE_FIELDS {
+            assert!(serialized.get(field).is_some(), "{} is not a field", field);
+        }
+
+        let mut new_config_json = config_json;
+        new_config_json["ending_version"] = json!(100);
+        new_config_json["per_table_chunk_sizes"] = json!({ "events": 1000 });
+        let new_config: IndexerGrpcProcessorConfig =
+            serde_json::from_value(new_config_json).unwrap();
+        assert!(config.reload(&new_config).is_err());
+
+        let sender = Arc::new(watch::Sender::new(config.reloadable_config()));
+        config.reloadable_config.set(sender.clone()).unwrap();
+        let changes = config.reload(&new_config).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "per_table_chunk_sizes");
+        assert_eq!(
+            sender.borrow().per_table_chunk_sizes.get("events"),
+            Some(&1000)
+        );
+        assert!(config.reload(&new_config).unwrap().is_empty());
+
+        let mut misspelled_config = new_config.clone();
+        misspelled_config.deprecated_tables = HashSet::from(["EVENT".to_string()]);
+        assert!(config.reload(&misspelled_config).is_err());
+    }
+}
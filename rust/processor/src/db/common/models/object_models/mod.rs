@@ -1,5 +1,6 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod raw_v2_object_ultimate_owners;
 pub mod raw_v2_objects;
 pub mod v2_object_utils;
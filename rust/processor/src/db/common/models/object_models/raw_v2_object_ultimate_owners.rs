@@ -0,0 +1,281 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use super::raw_v2_objects::RawCurrentObject;
+use crate::{
+    schema::current_objects,
+    utils::database::{DbContext, DbPoolConnection},
+};
+use ahash::{AHashMap, AHashSet};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Same as 0x1::object::MAXIMUM_OBJECT_NESTING
+const MAXIMUM_OBJECT_NESTING: usize = 8;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RawCurrentObjectUltimateOwner {
+    pub object_address: String,
+    pub direct_owner_address: String,
+    pub ultimate_owner_address: String,
+    /// Owners from the direct owner to the ultimate owner
+    pub ownership_path: Value,
+    pub depth: i32,
+    pub is_deleted: bool,
+    pub last_transaction_version: i64,
+}
+
+pub trait CurrentObjectUltimateOwnerConvertible {
+    fn from_raw(raw_item: RawCurrentObjectUltimateOwner) -> Self;
+}
+
+/// The direct owner of an object, from the batch or from `current_objects`
+#[derive(Clone, Debug)]
+struct ObjectOwner {
+    owner_address: String,
+    is_deleted: bool,
+    last_transaction_version: i64,
+}
+
+impl RawCurrentObjectUltimateOwner {
+    /// Resolves the ultimate owner of every object written in the batch and of every object they
+    /// own, directly or not, since a transfer anywhere up the chain changes it. Owners of the chain
+    /// that weren't written in the batch are looked up in `current_objects`. Without a DB only the
+    /// objects of the batch are resolved, and the chain stops at the first owner not in the batch.
+    pub async fn from_current_objects(
+        current_objects: &[RawCurrentObject],
+        db_context: &mut Option<DbContext<'_>>,
+    ) -> anyhow::Result<Vec<Self>> {
+        let mut objects: AHashMap<String, ObjectOwner> = current_objects
+            .iter()
+            .map(|object| {
+                (object.object_address.clone(), ObjectOwner {
+                    owner_address: object.owner_address.clone(),
+                    is_deleted: object.is_deleted,
+                    last_transaction_version: object.last_transaction_version,
+                })
+            })
+            .collect();
+        let mut object_addresses: Vec<String> = objects.keys().cloned().collect();
+
+        if let Some(db_context) = db_context {
+            // Objects owned by the objects of the batch, level by level
+            let mut parents = object_addresses.clone();
+            for _ in 0..MAXIMUM_OBJECT_NESTING {
+                if parents.is_empty() {
+                    break;
+                }
+                let children = Self::get_current_objects(
+                    &mut db_context.conn,
+                    true,
+                    &parents,
+                    db_context.query_retries,
+                    db_context.query_retry_delay_ms,
+                )
+                .await?;
+                parents = vec![];
+                for (object_address, owner) in children {
+                    // The owner of an object written in the batch is already up to date
+                    if owner.is_deleted || objects.contains_key(&object_address) {
+                        continue;
+                    }
+                    objects.insert(object_address.clone(), owner);
+                    object_addresses.push(object_address.clone());
+                    parents.push(object_address);
+                }
+            }
+
+            // Owners up the chain, level by level. Owners that aren't in current_objects aren't
+            // objects.
+            let mut not_objects: AHashSet<String> = AHashSet::new();
+            for _ in 0..MAXIMUM_OBJECT_NESTING {
+                let mut owners: Vec<String> = objects
+                    .values()
+                    .filter(|object| {
+                        !object.is_deleted
+                            && !objects.contains_key(&object.owner_address)
+                            && !not_objects.contains(&object.owner_address)
+                    })
+                    .map(|object| object.owner_address.clone())
+                    .collect();
+                if owners.is_empty() {
+                    break;
+                }
+                owners.sort_unstable();
+                owners.dedup();
+                let found = Self::get_current_objects(
+                    &mut db_context.conn,
+                    false,
+                    &owners,
+                    db_context.query_retries,
+                    db_context.query_retry_delay_ms,
+                )
+                .await?;
+                for (object_address, owner) in found {
+                    objects.insert(object_address, owner);
+                }
+                not_objects.extend(owners.into_iter().filter(|o| !objects.contains_key(o)));
+            }
+        }
+
+        object_addresses.sort();
+        Ok(object_addresses
+            .iter()
+            .map(|object_address| Self::resolve(object_address, &objects))
+            .collect())
+    }
+
+    /// Follows the owners of an object until one that isn't a known, live object
+    fn resolve(object_address: &str, objects: &AHashMap<String, ObjectOwner>) -> Self {
+        let object = &objects[object_address];
+        let mut path = vec![object.owner_address.clone()];
+        let mut last_transaction_version = object.last_transaction_version;
+        while let Some(owner) = objects
+            .get(path.last().unwrap())
+            .filter(|owner| !owner.is_deleted)
+        {
+            // Ownership can't be cyclic or deeper than the nesting limit on chain, but an
+            // inconsistent DB shouldn't hang the processor
+            if path.len() > MAXIMUM_OBJECT_NESTING
+                || owner.owner_address == object_address
+                || path.contains(&owner.owner_address)
+            {
+                tracing::warn!(
+                    object_address = object_address,
+                    ownership_path = ?path,
+                    "Object ownership is cyclic or too deep",
+                );
+                break;
+            }
+            last_transaction_version = last_transaction_version.max(owner.last_transaction_version);
+            path.push(owner.owner_address.clone());
+        }
+        Self {
+            object_address: object_address.to_string(),
+            direct_owner_address: object.owner_address.clone(),
+            ultimate_owner_address: path.last().unwrap().clone(),
+            depth: path.len() as i32,
+            ownership_path: Value::from(path),
+            is_deleted: object.is_deleted,
+            last_transaction_version,
+        }
+    }
+
+    /// The objects of `current_objects` owned by one of `addresses` if `by_owner`, otherwise the
+    /// objects at `addresses`
+    async fn get_current_objects(
+        conn: &mut DbPoolConnection<'_>,
+        by_owner: bool,
+        addresses: &[String],
+        query_retries: u32,
+        query_retry_delay_ms: u64,
+    ) -> anyhow::Result<Vec<(String, ObjectOwner)>> {
+        let mut tried = 0;
+        while tried < query_retries {
+            tried += 1;
+            let query = current_objects::table.select((
+                current_objects::object_address,
+                current_objects::owner_address,
+                current_objects::is_deleted,
+                current_objects::last_transaction_version,
+            ));
+            let result = if by_owner {
+                query
+                    .filter(current_objects::owner_address.eq_any(addresses.to_vec()))
+                    .load::<(String, String, bool, i64)>(conn)
+                    .await
+            } else {
+                query
+                    .filter(current_objects::object_address.eq_any(addresses.to_vec()))
+                    .load::<(String, String, bool, i64)>(conn)
+                    .await
+            };
+            match result {
+                Ok(rows) => {
+                    return Ok(rows
+                        .into_iter()
+                        .map(
+                            |(
+                                object_address,
+                                owner_address,
+                                is_deleted,
+                                last_transaction_version,
+                            )| {
+                                (object_address, ObjectOwner {
+                                    owner_address,
+                                    is_deleted,
+                                    last_transaction_version,
+                                })
+                            },
+                        )
+                        .collect())
+                },
+                Err(_) => {
+                    if tried < query_retries {
+                        tokio::time::sleep(std::time::Duration::from_millis(query_retry_delay_ms))
+                            .await;
+                    }
+                },
+            }
+        }
+        Err(anyhow::anyhow!(
+            "Failed to get the current objects of {} addresses",
+            addresses.len()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn owner(owner_address: &str, is_deleted: bool, version: i64) -> ObjectOwner {
+        ObjectOwner {
+            owner_address: owner_address.to_string(),
+            is_deleted,
+            last_transaction_version: version,
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        // 0xtoken -> 0xbag -> 0xchest -> 0xwallet, 0xorphan -> 0xburnt (deleted) -> 0xwallet
+        let objects = AHashMap::from([
+            ("0xtoken".to_string(), owner("0xbag", false, 1)),
+            ("0xbag".to_string(), owner("0xchest", false, 5)),
+            ("0xchest".to_string(), owner("0xwallet", false, 3)),
+            ("0xorphan".to_string(), owner("0xburnt", false, 2)),
+            ("0xburnt".to_string(), owner("0xwallet", true, 4)),
+            ("0xa".to_string(), owner("0xb", false, 1)),
+            ("0xb".to_string(), owner("0xa", false, 1)),
+        ]);
+
+        let token = RawCurrentObjectUltimateOwner::resolve("0xtoken", &objects);
+        assert_eq!(token.direct_owner_address, "0xbag");
+        assert_eq!(token.ultimate_owner_address, "0xwallet");
+        assert_eq!(
+            token.ownership_path,
+            json!(["0xbag", "0xchest", "0xwallet"])
+        );
+        assert_eq!(token.depth, 3);
+        assert_eq!(token.last_transaction_version, 5);
+
+        let orphan = RawCurrentObjectUltimateOwner::resolve("0xorphan", &objects);
+        assert_eq!(orphan.ultimate_owner_address, "0xburnt");
+        assert_eq!(orphan.depth, 1);
+
+        let burnt = RawCurrentObjectUltimateOwner::resolve("0xburnt", &objects);
+        assert_eq!(burnt.ultimate_owner_address, "0xwallet");
+        assert!(burnt.is_deleted);
+
+        let cyclic = RawCurrentObjectUltimateOwner::resolve("0xa", &objects);
+        assert_eq!(cyclic.ownership_path, json!(["0xb"]));
+    }
+}
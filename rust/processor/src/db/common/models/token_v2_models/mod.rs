@@ -1,7 +1,11 @@
 pub mod raw_token_claims;
+pub mod raw_token_royalty;
+pub mod raw_token_v1_to_v2_mappings;
 pub mod raw_v1_token_royalty;
 pub mod raw_v2_token_activities;
 pub mod raw_v2_token_datas;
 pub mod raw_v2_token_metadata;
 pub mod raw_v2_token_ownerships;
+pub mod raw_v2_token_property_mutations;
+pub mod raw_v2_token_transferability;
 pub mod v2_token_utils;
@@ -0,0 +1,301 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    db::{
+        common::models::{
+            object_models::v2_object_utils::ObjectAggregatedDataMapping,
+            token_v2_models::{
+                raw_v1_token_royalty::RawCurrentTokenRoyaltyV1,
+                v2_token_utils::{Royalty, TokenStandard},
+            },
+        },
+        postgres::models::{
+            default_models::move_resources::MoveResource,
+            resources::{FromWriteResource, TYPE_ROYALTY},
+        },
+    },
+    utils::util::standardize_address,
+};
+use aptos_protos::transaction::v1::{DeleteResource, WriteResource};
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Formatter};
+
+/// Whether a royalty applies to a single token or to every token of a collection without its own
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum RoyaltySourceLevel {
+    Collection,
+    Token,
+}
+
+impl fmt::Display for RoyaltySourceLevel {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let res = match self {
+            RoyaltySourceLevel::Collection => "collection",
+            RoyaltySourceLevel::Token => "token",
+        };
+        write!(f, "{}", res)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RawCurrentTokenRoyalty {
+    /// token_data_id for token royalties, collection_id for collection royalties
+    pub token_or_collection_id: String,
+    pub source_level: String,
+    pub token_standard: String,
+    pub payee_address: String,
+    pub royalty_points_numerator: BigDecimal,
+    pub royalty_points_denominator: BigDecimal,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+    pub is_deleted: bool,
+}
+
+impl Ord for RawCurrentTokenRoyalty {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.token_or_collection_id
+            .cmp(&other.token_or_collection_id)
+    }
+}
+impl PartialOrd for RawCurrentTokenRoyalty {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl RawCurrentTokenRoyalty {
+    pub fn pk(&self) -> String {
+        self.token_or_collection_id.clone()
+    }
+
+    /// v1 royalties are always part of the token data
+    pub fn from_v1(royalty: &RawCurrentTokenRoyaltyV1) -> Self {
+        Self {
+            token_or_collection_id: royalty.token_data_id.clone(),
+            source_level: RoyaltySourceLevel::Token.to_string(),
+            token_standard: TokenStandard::V1.to_string(),
+            payee_address: royalty.payee_address.clone(),
+            royalty_points_numerator: royalty.royalty_points_numerator.clone(),
+            royalty_points_denominator: royalty.royalty_points_denominator.clone(),
+            last_transaction_version: royalty.last_transaction_version,
+            last_transaction_timestamp: royalty.last_transaction_timestamp,
+            is_deleted: false,
+        }
+    }
+
+    /// 0x4::royalty::Royalty can only be added to collection and token objects, so an object
+    /// without 0x4::token::Token is a collection.
+    pub fn from_v2_write_resource(
+        write_resource: &WriteResource,
+        txn_version: i64,
+        txn_timestamp: chrono::NaiveDateTime,
+        object_metadatas: &ObjectAggregatedDataMapping,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(royalty) = Royalty::from_write_resource(write_resource)? else {
+            return Ok(None);
+        };
+        let address = standardize_address(&write_resource.address.to_string());
+        let Some(object_data) = object_metadatas.get(&address) else {
+            return Ok(None);
+        };
+        let source_level = if object_data.token.is_some() {
+            RoyaltySourceLevel::Token
+        } else {
+            RoyaltySourceLevel::Collection
+        };
+        Ok(Some(Self {
+            token_or_collection_id: address,
+            source_level: source_level.to_string(),
+            token_standard: TokenStandard::V2.to_string(),
+            payee_address: royalty.get_payee_address(),
+            royalty_points_numerator: royalty.numerator,
+            royalty_points_denominator: royalty.denominator,
+            last_transaction_version: txn_version,
+            last_transaction_timestamp: txn_timestamp,
+            is_deleted: false,
+        }))
+    }
+
+    /// Only 0x4::token deletes royalties, when a token is burned or its royalty removed, so a deleted
+    /// royalty is always a token royalty. The payee and points of the row are kept on upsert.
+    pub fn from_v2_delete_resource(
+        delete_resource: &DeleteResource,
+        txn_version: i64,
+        txn_timestamp: chrono::NaiveDateTime,
+    ) -> Option<Self> {
+        if MoveResource::get_outer_type_from_delete_resource(delete_resource) != TYPE_ROYALTY {
+            return None;
+        }
+        Some(Self {
+            token_or_collection_id: standardize_address(&delete_resource.address.to_string()),
+            source_level: RoyaltySourceLevel::Token.to_string(),
+            token_standard: TokenStandard::V2.to_string(),
+            payee_address: "".to_string(),
+            royalty_points_numerator: BigDecimal::default(),
+            royalty_points_denominator: BigDecimal::default(),
+            last_transaction_version: txn_version,
+            last_transaction_timestamp: txn_timestamp,
+            is_deleted: true,
+        })
+    }
+}
+
+pub trait CurrentTokenRoyaltyConvertible {
+    fn from_raw(raw_item: RawCurrentTokenRoyalty) -> Self;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::common::models::{
+        object_models::v2_object_utils::ObjectAggregatedData,
+        token_v2_models::v2_token_utils::TokenV2,
+    };
+    use aptos_protos::transaction::v1::MoveStructTag;
+    use serde_json::json;
+
+    const OBJECT: &str = "0x0a";
+
+    fn royalty_type() -> Option<MoveStructTag> {
+        Some(MoveStructTag {
+            address: "0x4".to_string(),
+            module: "royalty".to_string(),
+            name: "Royalty".to_string(),
+            generic_type_params: vec![],
+        })
+    }
+
+    fn write_royalty(payee_address: &str) -> WriteResource {
+        WriteResource {
+            address: OBJECT.to_string(),
+            type_str: "0x4::royalty::Royalty".to_string(),
+            r#type: royalty_type(),
+            data: json!({
+                "numerator": "5",
+                "denominator": "100",
+                "payee_address": payee_address,
+            })
+            .to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Metadata of the object at `OBJECT`, a token if `is_token` and a collection otherwise
+    fn object_metadatas(is_token: bool) -> ObjectAggregatedDataMapping {
+        let token: Option<TokenV2> = is_token.then(|| {
+            serde_json::from_value(json!({
+                "collection": {"inner": "0xc0"},
+                "description": "",
+                "name": "Token",
+                "uri": "",
+            }))
+            .unwrap()
+        });
+        ObjectAggregatedDataMapping::from([(standardize_address(OBJECT), ObjectAggregatedData {
+            token,
+            ..Default::default()
+        })])
+    }
+
+    #[test]
+    fn test_from_v2_write_resource_source_level() {
+        let collection_royalty = RawCurrentTokenRoyalty::from_v2_write_resource(
+            &write_royalty("0xbeef"),
+            1,
+            chrono::NaiveDateTime::default(),
+            &object_metadatas(false),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(collection_royalty.source_level, "collection");
+        assert_eq!(
+            collection_royalty.token_or_collection_id,
+            standardize_address(OBJECT)
+        );
+
+        let token_royalty = RawCurrentTokenRoyalty::from_v2_write_resource(
+            &write_royalty("0xbeef"),
+            1,
+            chrono::NaiveDateTime::default(),
+            &object_metadatas(true),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(token_royalty.source_level, "token");
+        assert_eq!(token_royalty.royalty_points_numerator, BigDecimal::from(5));
+        assert_eq!(
+            token_royalty.royalty_points_denominator,
+            BigDecimal::from(100)
+        );
+        assert!(!token_royalty.is_deleted);
+    }
+
+    #[test]
+    fn test_from_v2_write_resource_standardizes_payee() {
+        let royalty = RawCurrentTokenRoyalty::from_v2_write_resource(
+            &write_royalty("0xbeef"),
+            1,
+            chrono::NaiveDateTime::default(),
+            &object_metadatas(true),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            royalty.payee_address,
+            "0x000000000000000000000000000000000000000000000000000000000000beef"
+        );
+    }
+
+    #[test]
+    fn test_from_v2_write_resource_without_object() {
+        // Royalties of objects that aren't collections or tokens are skipped
+        let royalty = RawCurrentTokenRoyalty::from_v2_write_resource(
+            &write_royalty("0xbeef"),
+            1,
+            chrono::NaiveDateTime::default(),
+            &ObjectAggregatedDataMapping::new(),
+        )
+        .unwrap();
+        assert!(royalty.is_none());
+    }
+
+    #[test]
+    fn test_from_v2_delete_resource() {
+        let delete_resource = DeleteResource {
+            address: OBJECT.to_string(),
+            type_str: "0x4::royalty::Royalty".to_string(),
+            r#type: royalty_type(),
+            ..Default::default()
+        };
+        let royalty = RawCurrentTokenRoyalty::from_v2_delete_resource(
+            &delete_resource,
+            2,
+            chrono::NaiveDateTime::default(),
+        )
+        .unwrap();
+        assert_eq!(royalty.token_or_collection_id, standardize_address(OBJECT));
+        assert_eq!(royalty.source_level, "token");
+        assert!(royalty.is_deleted);
+
+        let other_resource = DeleteResource {
+            r#type: Some(MoveStructTag {
+                module: "token".to_string(),
+                name: "Token".to_string(),
+                ..royalty_type().unwrap()
+            }),
+            ..delete_resource
+        };
+        assert!(RawCurrentTokenRoyalty::from_v2_delete_resource(
+            &other_resource,
+            2,
+            chrono::NaiveDateTime::default()
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,311 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::db::{
+    common::models::token_v2_models::{
+        raw_v2_token_datas::RawTokenDataV2,
+        v2_token_utils::{TokenStandard, TokenV2Minted},
+    },
+    postgres::models::{
+        token_models::token_utils::{TokenEvent, TokenIdType},
+        token_v2_models::v2_collections::CollectionV2,
+    },
+};
+use ahash::AHashMap;
+use aptos_protos::transaction::v1::Event;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+/// A v1 token that was burned in exchange for a v2 token, linking the v1 token_data_id (hash of
+/// creator, collection and name) to the address of the v2 token object.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RawTokenV1ToV2Mapping {
+    pub token_data_id_v2: String,
+    pub token_data_id_v1: String,
+    pub property_version_v1: BigDecimal,
+    pub collection_id_v1: String,
+    pub collection_id_v2: String,
+    /// Name of the v1 token, the v2 token can have been renamed
+    pub token_name: String,
+    pub transaction_version: i64,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl RawTokenV1ToV2Mapping {
+    /// Id of the v1 token burned by a 0x3::token burn event
+    pub fn get_v1_burned_token(
+        event: &Event,
+        txn_version: i64,
+    ) -> anyhow::Result<Option<TokenIdType>> {
+        Ok(
+            match TokenEvent::from_event(&event.type_str, &event.data, txn_version)? {
+                Some(TokenEvent::BurnTokenEvent(inner)) => Some(inner.id),
+                Some(TokenEvent::Burn(inner)) => Some(inner.id),
+                _ => None,
+            },
+        )
+    }
+
+    /// There is no migration resource or event on chain, so a migration is inferred from a
+    /// transaction that burns a v1 token and mints a v2 NFT into a collection written in the same
+    /// transaction, with the same creator or the same name as the v1 collection. Every burned v1
+    /// token is paired with the first unpaired v2 NFT with its name. A token renamed by the
+    /// migration is only paired when it's the last burned and the last minted token left.
+    pub fn from_burns_and_mints(
+        v1_burned_tokens: &[TokenIdType],
+        collections: &[CollectionV2],
+        token_datas: &[RawTokenDataV2],
+        tokens_minted: &TokenV2Minted,
+        txn_version: i64,
+        txn_timestamp: chrono::NaiveDateTime,
+    ) -> Vec<Self> {
+        let collections: AHashMap<&str, &CollectionV2> = collections
+            .iter()
+            .map(|collection| (collection.collection_id.as_str(), collection))
+            .collect();
+        let is_same_collection = |token_id: &TokenIdType, token_data: &RawTokenDataV2| {
+            collections
+                .get(token_data.collection_id.as_str())
+                .is_some_and(|collection| {
+                    collection.creator_address == token_id.token_data_id.get_creator_address()
+                        || collection.collection_name
+                            == token_id.token_data_id.get_collection_trunc()
+                })
+        };
+        let mut minted_nfts: Vec<&RawTokenDataV2> = token_datas
+            .iter()
+            .filter(|token_data| {
+                token_data.token_standard == TokenStandard::V2.to_string()
+                    && token_data.is_fungible_v2 != Some(true)
+                    && tokens_minted.contains(&token_data.token_data_id)
+            })
+            .collect();
+        let mut pairs = vec![];
+        let mut unpaired_tokens = vec![];
+        for token_id in v1_burned_tokens {
+            let token_name = token_id.token_data_id.get_name_trunc();
+            match minted_nfts.iter().position(|token_data| {
+                token_data.token_name == token_name && is_same_collection(token_id, token_data)
+            }) {
+                Some(position) => pairs.push((token_id, minted_nfts.remove(position))),
+                None => unpaired_tokens.push(token_id),
+            }
+        }
+        if let ([token_id], [token_data]) = (unpaired_tokens.as_slice(), minted_nfts.as_slice()) {
+            if is_same_collection(token_id, token_data) {
+                pairs.push((token_id, token_data));
+            }
+        }
+
+        pairs
+            .into_iter()
+            .map(|(token_id, token_data)| Self {
+                token_data_id_v2: token_data.token_data_id.clone(),
+                token_data_id_v1: token_id.token_data_id.to_id(),
+                property_version_v1: token_id.property_version.clone(),
+                collection_id_v1: token_id.token_data_id.get_collection_id(),
+                collection_id_v2: token_data.collection_id.clone(),
+                token_name: token_id.token_data_id.get_name_trunc(),
+                transaction_version: txn_version,
+                transaction_timestamp: txn_timestamp,
+            })
+            .collect()
+    }
+}
+
+pub trait TokenV1ToV2MappingConvertible {
+    fn from_raw(raw_item: RawTokenV1ToV2Mapping) -> Self;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::postgres::models::token_models::token_utils::TokenDataIdType,
+        utils::util::standardize_address,
+    };
+    use ahash::AHashSet;
+
+    fn collection(
+        collection_id: &str,
+        creator_address: &str,
+        collection_name: &str,
+    ) -> CollectionV2 {
+        CollectionV2 {
+            transaction_version: 1,
+            write_set_change_index: 0,
+            collection_id: collection_id.to_string(),
+            creator_address: standardize_address(creator_address),
+            collection_name: collection_name.to_string(),
+            description: "".to_string(),
+            uri: "".to_string(),
+            current_supply: BigDecimal::from(1),
+            max_supply: None,
+            total_minted_v2: None,
+            mutable_description: None,
+            mutable_uri: None,
+            table_handle_v1: None,
+            collection_properties: None,
+            token_standard: TokenStandard::V2.to_string(),
+            transaction_timestamp: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    fn token_data(
+        token_data_id: &str,
+        collection_id: &str,
+        token_name: &str,
+        token_standard: TokenStandard,
+    ) -> RawTokenDataV2 {
+        RawTokenDataV2 {
+            transaction_version: 1,
+            write_set_change_index: 0,
+            token_data_id: token_data_id.to_string(),
+            collection_id: collection_id.to_string(),
+            token_name: token_name.to_string(),
+            maximum: None,
+            supply: None,
+            largest_property_version_v1: None,
+            token_uri: "".to_string(),
+            token_properties: serde_json::Value::Null,
+            description: "".to_string(),
+            token_standard: token_standard.to_string(),
+            is_fungible_v2: Some(false),
+            transaction_timestamp: chrono::NaiveDateTime::default(),
+            decimals: None,
+            is_deleted_v2: None,
+        }
+    }
+
+    fn token_id(name: &str) -> TokenIdType {
+        TokenIdType {
+            token_data_id: TokenDataIdType::new(
+                "0x1".to_string(),
+                "c".to_string(),
+                name.to_string(),
+            ),
+            property_version: BigDecimal::from(0),
+        }
+    }
+
+    fn minted(token_data_ids: &[&str]) -> TokenV2Minted {
+        token_data_ids
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<AHashSet<_>>()
+    }
+
+    fn pairs(mappings: &[RawTokenV1ToV2Mapping]) -> Vec<(&str, String)> {
+        mappings
+            .iter()
+            .map(|m| (m.token_data_id_v2.as_str(), m.token_data_id_v1.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_from_burns_and_mints() {
+        let token_datas = vec![
+            token_data("0xa", "0xc2", "one", TokenStandard::V2),
+            token_data("0xb", "0xc2", "two", TokenStandard::V2),
+            token_data("0xc", "0xc2", "two", TokenStandard::V2),
+            // Written but not minted in the transaction
+            token_data("0xd", "0xc2", "three", TokenStandard::V2),
+            token_data(
+                &token_id("one").token_data_id.to_id(),
+                &token_id("one").token_data_id.get_collection_id(),
+                "one",
+                TokenStandard::V1,
+            ),
+        ];
+        let burned = vec![
+            token_id("two"),
+            token_id("one"),
+            token_id("two"),
+            token_id("two"),
+            token_id("three"),
+        ];
+
+        let mappings = RawTokenV1ToV2Mapping::from_burns_and_mints(
+            &burned,
+            &[collection("0xc2", "0x1", "c v2")],
+            &token_datas,
+            &minted(&["0xa", "0xb", "0xc"]),
+            1,
+            chrono::NaiveDateTime::default(),
+        );
+
+        assert_eq!(pairs(&mappings), vec![
+            ("0xb", token_id("two").token_data_id.to_id()),
+            ("0xa", token_id("one").token_data_id.to_id()),
+            ("0xc", token_id("two").token_data_id.to_id()),
+        ]);
+        assert_eq!(
+            mappings[0].collection_id_v1,
+            token_id("two").token_data_id.get_collection_id()
+        );
+        assert_eq!(mappings[0].collection_id_v2, "0xc2");
+    }
+
+    #[test]
+    fn test_from_burns_and_mints_unrelated_collection() {
+        // Burning a v1 token to mint a v2 token with the same name in a collection of another
+        // creator, e.g. crafting in a game, isn't a migration
+        let mappings = RawTokenV1ToV2Mapping::from_burns_and_mints(
+            &[token_id("sword")],
+            &[collection("0xc2", "0x2", "game items")],
+            &[token_data("0xa", "0xc2", "sword", TokenStandard::V2)],
+            &minted(&["0xa"]),
+            1,
+            chrono::NaiveDateTime::default(),
+        );
+        assert!(mappings.is_empty());
+
+        // Nor is minting into a collection that isn't written in the transaction
+        let mappings = RawTokenV1ToV2Mapping::from_burns_and_mints(
+            &[token_id("sword")],
+            &[],
+            &[token_data("0xa", "0xc2", "sword", TokenStandard::V2)],
+            &minted(&["0xa"]),
+            1,
+            chrono::NaiveDateTime::default(),
+        );
+        assert!(mappings.is_empty());
+    }
+
+    #[test]
+    fn test_from_burns_and_mints_renamed() {
+        // Another creator, but the collection kept its name
+        let collections = [collection("0xc2", "0x2", "c")];
+        let mappings = RawTokenV1ToV2Mapping::from_burns_and_mints(
+            &[token_id("one")],
+            &collections,
+            &[token_data("0xa", "0xc2", "One #1", TokenStandard::V2)],
+            &minted(&["0xa"]),
+            1,
+            chrono::NaiveDateTime::default(),
+        );
+        assert_eq!(pairs(&mappings), vec![(
+            "0xa",
+            token_id("one").token_data_id.to_id()
+        )]);
+        assert_eq!(mappings[0].token_name, "one");
+
+        // Which renamed token is which can't be told when there are several
+        let mappings = RawTokenV1ToV2Mapping::from_burns_and_mints(
+            &[token_id("one"), token_id("two")],
+            &collections,
+            &[
+                token_data("0xa", "0xc2", "One #1", TokenStandard::V2),
+                token_data("0xb", "0xc2", "Two #2", TokenStandard::V2),
+            ],
+            &minted(&["0xa", "0xb"]),
+            1,
+            chrono::NaiveDateTime::default(),
+        );
+        assert!(mappings.is_empty());
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    db::{
+        common::models::{
+            object_models::v2_object_utils::ObjectAggregatedDataMapping,
+            token_v2_models::{
+                raw_v2_token_datas::RawTokenDataV2,
+                v2_token_utils::{PropertyMapModel, TokenStandard},
+            },
+        },
+        postgres::models::resources::FromWriteResource,
+    },
+    schema::current_token_datas_v2,
+    utils::{
+        database::{DbContext, DbPoolConnection},
+        util::standardize_address,
+    },
+};
+use ahash::AHashMap;
+use aptos_protos::transaction::v1::WriteResource;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RawTokenPropertyMutation {
+    pub transaction_version: i64,
+    pub write_set_change_index: i64,
+    pub token_data_id: String,
+    pub old_properties: Value,
+    pub new_properties: Value,
+    pub token_standard: String,
+    pub entry_function_id_str: Option<String>,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+pub trait TokenPropertyMutationConvertible {
+    fn from_raw(raw_item: RawTokenPropertyMutation) -> Self;
+}
+
+/// The properties of a token written by a transaction, before it's known whether they changed.
+#[derive(Clone, Debug)]
+pub struct TokenPropertyWrite {
+    pub transaction_version: i64,
+    pub write_set_change_index: i64,
+    pub token_data_id: String,
+    pub properties: Value,
+    pub token_standard: String,
+    pub entry_function_id_str: Option<String>,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl TokenPropertyWrite {
+    /// The default properties of a v1 token data
+    pub fn from_v1_token_data(
+        token_data: &RawTokenDataV2,
+        entry_function_id_str: &Option<String>,
+    ) -> Option<Self> {
+        if token_data.token_standard != TokenStandard::V1.to_string() {
+            return None;
+        }
+        Some(Self {
+            transaction_version: token_data.transaction_version,
+            write_set_change_index: token_data.write_set_change_index,
+            token_data_id: token_data.token_data_id.clone(),
+            properties: token_data.token_properties.clone(),
+            token_standard: token_data.token_standard.clone(),
+            entry_function_id_str: entry_function_id_str.clone(),
+            transaction_timestamp: token_data.transaction_timestamp,
+        })
+    }
+
+    /// The 0x4::property_map::PropertyMap of a v2 token. Objects of the batch that aren't tokens are
+    /// skipped; objects that weren't seen are assumed to be tokens, since only tokens already in
+    /// `current_token_datas_v2` result in a mutation.
+    pub fn from_v2_write_resource(
+        write_resource: &WriteResource,
+        txn_version: i64,
+        write_set_change_index: i64,
+        txn_timestamp: chrono::NaiveDateTime,
+        entry_function_id_str: &Option<String>,
+        object_metadatas: &ObjectAggregatedDataMapping,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(property_map) = PropertyMapModel::from_write_resource(write_resource)? else {
+            return Ok(None);
+        };
+        let token_data_id = standardize_address(&write_resource.address.to_string());
+        if object_metadatas
+            .get(&token_data_id)
+            .is_some_and(|object| object.token.is_none())
+        {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            transaction_version: txn_version,
+            write_set_change_index,
+            token_data_id,
+            properties: property_map.inner,
+            token_standard: TokenStandard::V2.to_string(),
+            entry_function_id_str: entry_function_id_str.clone(),
+            transaction_timestamp: txn_timestamp,
+        }))
+    }
+}
+
+impl RawTokenPropertyMutation {
+    /// Turns the property writes of a batch, in order, into mutations. A write is a mutation if it
+    /// changes the properties written before it in the batch or, for the first write of a token,
+    /// the `token_properties` of `current_token_datas_v2`. Without a DB only writes after the first
+    /// one of every token can be compared, and the first write of a new token isn't a mutation.
+    pub async fn from_writes(
+        writes: Vec<TokenPropertyWrite>,
+        db_context: &mut Option<DbContext<'_>>,
+    ) -> anyhow::Result<Vec<Self>> {
+        let mut properties: AHashMap<String, Value> = AHashMap::new();
+        if let Some(db_context) = db_context {
+            let mut token_data_ids: Vec<&str> =
+                writes.iter().map(|w| w.token_data_id.as_str()).collect();
+            token_data_ids.sort_unstable();
+            token_data_ids.dedup();
+            if !token_data_ids.is_empty() {
+                properties = Self::get_current_properties(
+                    &mut db_context.conn,
+                    &token_data_ids,
+                    db_context.query_retries,
+                    db_context.query_retry_delay_ms,
+                )
+                .await?;
+            }
+        }
+        Ok(Self::from_writes_with_previous(writes, properties))
+    }
+
+    fn from_writes_with_previous(
+        writes: Vec<TokenPropertyWrite>,
+        mut properties: AHashMap<String, Value>,
+    ) -> Vec<Self> {
+        let mut mutations = vec![];
+        for write in writes {
+            let old_properties = properties.insert(write.token_data_id.clone(), write.properties);
+            let new_properties = &properties[&write.token_data_id];
+            if let Some(old_properties) = old_properties {
+                if &old_properties != new_properties {
+                    mutations.push(Self {
+                        transaction_version: write.transaction_version,
+                        write_set_change_index: write.write_set_change_index,
+                        new_properties: new_properties.clone(),
+                        token_data_id: write.token_data_id,
+                        old_properties,
+                        token_standard: write.token_standard,
+                        entry_function_id_str: write.entry_function_id_str,
+                        transaction_timestamp: write.transaction_timestamp,
+                    });
+                }
+            }
+        }
+        mutations
+    }
+
+    async fn get_current_properties(
+        conn: &mut DbPoolConnection<'_>,
+        token_data_ids: &[&str],
+        query_retries: u32,
+        query_retry_delay_ms: u64,
+    ) -> anyhow::Result<AHashMap<String, Value>> {
+        let mut tried = 0;
+        while tried < query_retries {
+            tried += 1;
+            match current_token_datas_v2::table
+                .filter(current_token_datas_v2::token_data_id.eq_any(token_data_ids.to_vec()))
+                .select((
+                    current_token_datas_v2::token_data_id,
+                    current_token_datas_v2::token_properties,
+                ))
+                .load::<(String, Value)>(conn)
+                .await
+            {
+                Ok(rows) => return Ok(rows.into_iter().collect()),
+                Err(_) => {
+                    if tried < query_retries {
+                        tokio::time::sleep(std::time::Duration::from_millis(query_retry_delay_ms))
+                            .await;
+                    }
+                },
+            }
+        }
+        Err(anyhow::anyhow!(
+            "Failed to get the properties of {} token datas",
+            token_data_ids.len()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn write(version: i64, token_data_id: &str, properties: Value) -> TokenPropertyWrite {
+        TokenPropertyWrite {
+            transaction_version: version,
+            write_set_change_index: 0,
+            token_data_id: token_data_id.to_string(),
+            properties,
+            token_standard: TokenStandard::V2.to_string(),
+            entry_function_id_str: Some("0x1::game::level_up".to_string()),
+            transaction_timestamp: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn test_from_writes_with_previous() {
+        let mutations = RawTokenPropertyMutation::from_writes_with_previous(
+            vec![
+                // Unchanged from the DB
+                write(1, "0xa", json!({"level": "1"})),
+                write(2, "0xa", json!({"level": "2"})),
+                // New token, nothing to compare to
+                write(2, "0xb", json!({"level": "1"})),
+                write(3, "0xb", json!({"level": "1"})),
+                write(4, "0xb", json!({"level": "3"})),
+            ],
+            AHashMap::from([("0xa".to_string(), json!({"level": "1"}))]),
+        );
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| (
+                    m.transaction_version,
+                    m.token_data_id.as_str(),
+                    m.old_properties.clone(),
+                    m.new_properties.clone()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, "0xa", json!({"level": "1"}), json!({"level": "2"})),
+                (4, "0xb", json!({"level": "1"}), json!({"level": "3"})),
+            ]
+        );
+    }
+}
@@ -0,0 +1,274 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::db::common::models::{
+    object_models::v2_object_utils::ObjectAggregatedDataMapping,
+    token_v2_models::{raw_v2_token_datas::RawTokenDataV2, v2_token_utils::TokenV2Minted},
+};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Formatter};
+
+/// Why the owner of a token can't transfer it
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum TransferRestriction {
+    /// The object has 0x1::object::Untransferable, so nobody can ever transfer it
+    Untransferable,
+    /// Only a holder of a TransferRef can transfer it
+    UngatedTransferDisabled,
+}
+
+impl fmt::Display for TransferRestriction {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let res = match self {
+            TransferRestriction::Untransferable => "untransferable",
+            TransferRestriction::UngatedTransferDisabled => "ungated_transfer_disabled",
+        };
+        write!(f, "{}", res)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RawTokenTransferabilityV2 {
+    pub transaction_version: i64,
+    pub write_set_change_index: i64,
+    pub token_data_id: String,
+    pub owner_address: String,
+    pub allow_ungated_transfer: bool,
+    pub is_untransferable: bool,
+    pub is_transferable_by_owner: bool,
+    pub restriction_reason: Option<String>,
+    pub soulbound_at_mint: Option<bool>,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RawCurrentTokenTransferabilityV2 {
+    pub token_data_id: String,
+    pub owner_address: String,
+    pub allow_ungated_transfer: bool,
+    pub is_untransferable: bool,
+    pub is_transferable_by_owner: bool,
+    pub restriction_reason: Option<String>,
+    pub soulbound_at_mint: Option<bool>,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+pub trait TokenTransferabilityV2Convertible {
+    fn from_raw(raw_item: RawTokenTransferabilityV2) -> Self;
+}
+
+pub trait CurrentTokenTransferabilityV2Convertible {
+    fn from_raw(raw_item: RawCurrentTokenTransferabilityV2) -> Self;
+}
+
+impl RawTokenTransferabilityV2 {
+    /// Transferability of a v2 token whose token data was written in the transaction.
+    /// `soulbound_at_mint` is only set if the token was minted in the transaction.
+    pub fn from_v2_token_data(
+        token_data: &RawTokenDataV2,
+        object_metadatas: &ObjectAggregatedDataMapping,
+        tokens_minted: &TokenV2Minted,
+    ) -> anyhow::Result<(Self, RawCurrentTokenTransferabilityV2)> {
+        let object_data = object_metadatas
+            .get(&token_data.token_data_id)
+            .context("If token data exists objectcore must exist")?;
+        let object_core = &object_data.object.object_core;
+        let allow_ungated_transfer = object_core.allow_ungated_transfer;
+        let is_untransferable = object_data.untransferable.is_some();
+        let restriction = if is_untransferable {
+            Some(TransferRestriction::Untransferable)
+        } else if !allow_ungated_transfer {
+            Some(TransferRestriction::UngatedTransferDisabled)
+        } else {
+            None
+        };
+        let soulbound_at_mint = tokens_minted
+            .contains(&token_data.token_data_id)
+            .then_some(restriction.is_some());
+        let owner_address = object_core.get_owner_address();
+
+        Ok((
+            Self {
+                transaction_version: token_data.transaction_version,
+                write_set_change_index: token_data.write_set_change_index,
+                token_data_id: token_data.token_data_id.clone(),
+                owner_address: owner_address.clone(),
+                allow_ungated_transfer,
+                is_untransferable,
+                is_transferable_by_owner: restriction.is_none(),
+                restriction_reason: restriction.map(|r| r.to_string()),
+                soulbound_at_mint,
+                transaction_timestamp: token_data.transaction_timestamp,
+            },
+            RawCurrentTokenTransferabilityV2 {
+                token_data_id: token_data.token_data_id.clone(),
+                owner_address,
+                allow_ungated_transfer,
+                is_untransferable,
+                is_transferable_by_owner: restriction.is_none(),
+                restriction_reason: restriction.map(|r| r.to_string()),
+                soulbound_at_mint,
+                last_transaction_version: token_data.transaction_version,
+                last_transaction_timestamp: token_data.transaction_timestamp,
+            },
+        ))
+    }
+}
+
+impl RawCurrentTokenTransferabilityV2 {
+    /// Keeps how the token was minted if it was already written earlier in the batch
+    pub fn merge_previous(mut self, previous: Option<&Self>) -> Self {
+        if let Some(previous) = previous {
+            self.soulbound_at_mint = self.soulbound_at_mint.or(previous.soulbound_at_mint);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::common::models::object_models::v2_object_utils::{
+        ObjectAggregatedData, ObjectWithMetadata,
+    };
+    use ahash::{AHashMap, AHashSet};
+
+    const TOKEN: &str = "0x0000000000000000000000000000000000000000000000000000000000000a11";
+    const OWNER: &str = "0x0000000000000000000000000000000000000000000000000000000000000b0b";
+
+    fn token_data(transaction_version: i64) -> RawTokenDataV2 {
+        RawTokenDataV2 {
+            transaction_version,
+            token_data_id: TOKEN.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn object_metadatas(
+        allow_ungated_transfer: bool,
+        untransferable: bool,
+    ) -> ObjectAggregatedDataMapping {
+        let object_data = ObjectAggregatedData {
+            object: ObjectWithMetadata {
+                object_core: serde_json::from_value(serde_json::json!({
+                    "allow_ungated_transfer": allow_ungated_transfer,
+                    "guid_creation_num": "1125899906842624",
+                    "owner": OWNER,
+                }))
+                .unwrap(),
+                state_key_hash: TOKEN.to_string(),
+            },
+            untransferable: untransferable.then(|| {
+                serde_json::from_value(serde_json::json!({"dummy_field": false})).unwrap()
+            }),
+            ..Default::default()
+        };
+        AHashMap::from_iter([(TOKEN.to_string(), object_data)])
+    }
+
+    fn minted(is_minted: bool) -> TokenV2Minted {
+        if is_minted {
+            AHashSet::from_iter([TOKEN.to_string()])
+        } else {
+            AHashSet::new()
+        }
+    }
+
+    #[test]
+    fn test_untransferable() {
+        let (transferability, current) = RawTokenTransferabilityV2::from_v2_token_data(
+            &token_data(1),
+            &object_metadatas(false, true),
+            &minted(true),
+        )
+        .unwrap();
+        assert!(transferability.is_untransferable);
+        assert!(!transferability.is_transferable_by_owner);
+        assert_eq!(
+            transferability.restriction_reason.as_deref(),
+            Some("untransferable")
+        );
+        assert_eq!(transferability.soulbound_at_mint, Some(true));
+        assert_eq!(current.owner_address, OWNER);
+        assert_eq!(
+            current.restriction_reason.as_deref(),
+            Some("untransferable")
+        );
+    }
+
+    #[test]
+    fn test_ungated_transfer_disabled() {
+        let (transferability, _) = RawTokenTransferabilityV2::from_v2_token_data(
+            &token_data(1),
+            &object_metadatas(false, false),
+            &minted(true),
+        )
+        .unwrap();
+        assert!(!transferability.is_untransferable);
+        assert!(!transferability.allow_ungated_transfer);
+        assert!(!transferability.is_transferable_by_owner);
+        assert_eq!(
+            transferability.restriction_reason.as_deref(),
+            Some("ungated_transfer_disabled")
+        );
+        assert_eq!(transferability.soulbound_at_mint, Some(true));
+    }
+
+    #[test]
+    fn test_transferable() {
+        let (transferability, current) = RawTokenTransferabilityV2::from_v2_token_data(
+            &token_data(1),
+            &object_metadatas(true, false),
+            &minted(true),
+        )
+        .unwrap();
+        assert!(transferability.is_transferable_by_owner);
+        assert_eq!(transferability.restriction_reason, None);
+        assert_eq!(transferability.soulbound_at_mint, Some(false));
+        assert!(current.is_transferable_by_owner);
+    }
+
+    #[test]
+    fn test_soulbound_at_mint_only_set_when_minted() {
+        let (transferability, current) = RawTokenTransferabilityV2::from_v2_token_data(
+            &token_data(1),
+            &object_metadatas(false, true),
+            &minted(false),
+        )
+        .unwrap();
+        assert_eq!(transferability.soulbound_at_mint, None);
+        assert_eq!(current.soulbound_at_mint, None);
+    }
+
+    #[test]
+    fn test_keeps_soulbound_at_mint_when_written_again_in_batch() {
+        // Minted soulbound, then made transferable later in the same batch
+        let (_, minted_current) = RawTokenTransferabilityV2::from_v2_token_data(
+            &token_data(1),
+            &object_metadatas(false, false),
+            &minted(true),
+        )
+        .unwrap();
+        let (_, current) = RawTokenTransferabilityV2::from_v2_token_data(
+            &token_data(2),
+            &object_metadatas(true, false),
+            &minted(false),
+        )
+        .unwrap();
+
+        let current = current.merge_previous(Some(&minted_current));
+        assert!(current.is_transferable_by_owner);
+        assert_eq!(current.soulbound_at_mint, Some(true));
+        assert_eq!(current.last_transaction_version, 2);
+        assert_eq!(
+            current.clone().merge_previous(None).soulbound_at_mint,
+            Some(true)
+        );
+    }
+}
@@ -379,6 +379,30 @@ impl TryFrom<&WriteResource> for PropertyMapModel {
     }
 }
 
+/// 0x4::royalty::Royalty, on a collection (default for its tokens) or on a token (override)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Royalty {
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub numerator: BigDecimal,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub denominator: BigDecimal,
+    payee_address: String,
+}
+
+impl TryFrom<&WriteResource> for Royalty {
+    type Error = anyhow::Error;
+
+    fn try_from(write_resource: &WriteResource) -> anyhow::Result<Self> {
+        serde_json::from_str(write_resource.data.as_str()).map_err(anyhow::Error::msg)
+    }
+}
+
+impl Royalty {
+    pub fn get_payee_address(&self) -> String {
+        standardize_address(&self.payee_address)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TokenIdentifiers {
     name: DerivedStringSnapshot,
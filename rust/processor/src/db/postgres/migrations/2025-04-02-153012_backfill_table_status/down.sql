@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP VIEW IF EXISTS backfill_table_status;
+ALTER TABLE backfill_processor_status
+ALTER COLUMN backfill_alias TYPE VARCHAR(50);
@@ -0,0 +1,25 @@
+-- Your SQL goes here
+-- Per table backfill aliases are `{processor}_{backfill_id}.{table_name}`, which can exceed 50 characters.
+ALTER TABLE backfill_processor_status
+ALTER COLUMN backfill_alias TYPE VARCHAR(100);
+-- Progress of every per table backfill job.
+CREATE OR REPLACE VIEW backfill_table_status AS
+SELECT split_part(backfill_alias, '.', 1) AS backfill_alias,
+  split_part(backfill_alias, '.', 2) AS table_name,
+  backfill_status,
+  backfill_start_version,
+  backfill_end_version,
+  last_success_version,
+  GREATEST(backfill_end_version - last_success_version, 0) AS remaining_versions,
+  CASE
+    WHEN backfill_end_version < backfill_start_version THEN NULL
+    WHEN backfill_status = 'complete' THEN 100.0
+    ELSE round(
+      100.0 * GREATEST(last_success_version - backfill_start_version + 1, 0) / (backfill_end_version - backfill_start_version + 1),
+      2
+    )
+  END AS progress_percentage,
+  last_updated,
+  last_transaction_timestamp
+FROM backfill_processor_status
+WHERE backfill_alias LIKE '%.%';
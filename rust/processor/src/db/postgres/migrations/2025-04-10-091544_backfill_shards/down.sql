@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE backfill_processor_status DROP COLUMN IF EXISTS claimed_by;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- Sharded backfills record one row per shard, `{processor}_{backfill_id}#shard_{index}`, which a
+-- worker claims by setting `claimed_by`. Claims expire when `last_updated` stops advancing.
+ALTER TABLE backfill_processor_status
+ADD COLUMN IF NOT EXISTS claimed_by VARCHAR(100);
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- Periodic samples of the progress of processors and backfills, written when
+-- `status_history_config` is set. `processor` is the name the checkpoint is stored under in
+-- `processor_status` or the alias in `backfill_processor_status`.
+CREATE TABLE IF NOT EXISTS processor_status_history (
+  processor VARCHAR(100) NOT NULL,
+  sampled_at TIMESTAMP NOT NULL,
+  last_success_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP,
+  -- Versions processed since the previous sample
+  versions_processed BIGINT NOT NULL,
+  tps DOUBLE PRECISION NOT NULL,
+  -- Average time from a transaction being committed on chain to its checkpoint being written,
+  -- over the checkpoints since the previous sample
+  avg_checkpoint_lag_ms BIGINT,
+  PRIMARY KEY (processor, sampled_at)
+);
+CREATE INDEX IF NOT EXISTS psh_sampled_at_index ON processor_status_history (sampled_at);
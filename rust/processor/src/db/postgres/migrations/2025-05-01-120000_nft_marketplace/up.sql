@@ -0,0 +1,106 @@
+-- Your SQL goes here
+-- Listings, token offers and collection offers of the marketplaces configured for
+-- `nft_marketplace_processor`. Token and collection ids are the ones of token_datas_v2 and
+-- collections_v2, so they can be joined for both token standards.
+CREATE TABLE IF NOT EXISTS nft_marketplace_activities (
+  transaction_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  -- Address of the listing, token offer or collection offer
+  offer_or_listing_id VARCHAR(66) NOT NULL,
+  fee_schedule_id VARCHAR(66) NOT NULL,
+  collection_id VARCHAR(66) NOT NULL,
+  token_data_id VARCHAR(66),
+  creator_address VARCHAR(66) NOT NULL,
+  collection_name VARCHAR(128) NOT NULL,
+  token_name VARCHAR(128),
+  property_version NUMERIC,
+  price NUMERIC NOT NULL,
+  token_amount NUMERIC NOT NULL,
+  token_standard VARCHAR(10) NOT NULL,
+  seller VARCHAR(66),
+  buyer VARCHAR(66),
+  coin_type VARCHAR(1000),
+  marketplace VARCHAR(100) NOT NULL,
+  contract_address VARCHAR(66) NOT NULL,
+  entry_function_id_str VARCHAR(1000),
+  -- e.g. listing_place, listing_fill, token_offer_cancel, collection_offer_fill, auction_bid
+  event_type VARCHAR(50) NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, event_index)
+);
+CREATE INDEX IF NOT EXISTS nma_offer_or_listing_index ON nft_marketplace_activities (offer_or_listing_id);
+CREATE INDEX IF NOT EXISTS nma_token_data_id_index ON nft_marketplace_activities (token_data_id);
+CREATE INDEX IF NOT EXISTS nma_collection_id_index ON nft_marketplace_activities (collection_id);
+CREATE INDEX IF NOT EXISTS nma_fee_schedule_index ON nft_marketplace_activities (fee_schedule_id);
+
+CREATE TABLE IF NOT EXISTS current_nft_marketplace_listings (
+  listing_id VARCHAR(66) NOT NULL,
+  token_data_id VARCHAR(66) NOT NULL,
+  collection_id VARCHAR(66) NOT NULL,
+  fee_schedule_id VARCHAR(66) NOT NULL,
+  seller VARCHAR(66) NOT NULL,
+  price NUMERIC NOT NULL,
+  token_amount NUMERIC NOT NULL,
+  token_standard VARCHAR(10) NOT NULL,
+  -- Set once the listing is filled or canceled
+  is_deleted BOOLEAN NOT NULL,
+  coin_type VARCHAR(1000),
+  marketplace VARCHAR(100) NOT NULL,
+  contract_address VARCHAR(66) NOT NULL,
+  entry_function_id_str VARCHAR(1000),
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (listing_id, token_data_id)
+);
+CREATE INDEX IF NOT EXISTS cnml_collection_index ON current_nft_marketplace_listings (collection_id);
+CREATE INDEX IF NOT EXISTS cnml_collection_price_index ON current_nft_marketplace_listings (collection_id, price);
+CREATE INDEX IF NOT EXISTS cnml_seller_index ON current_nft_marketplace_listings (seller);
+
+CREATE TABLE IF NOT EXISTS current_nft_marketplace_token_offers (
+  offer_id VARCHAR(66) NOT NULL,
+  token_data_id VARCHAR(66) NOT NULL,
+  collection_id VARCHAR(66) NOT NULL,
+  fee_schedule_id VARCHAR(66) NOT NULL,
+  buyer VARCHAR(66) NOT NULL,
+  price NUMERIC NOT NULL,
+  token_amount NUMERIC NOT NULL,
+  -- Only known if the offer resource was written in the same transaction
+  expiration_time NUMERIC,
+  is_deleted BOOLEAN NOT NULL,
+  token_standard VARCHAR(10) NOT NULL,
+  coin_type VARCHAR(1000),
+  marketplace VARCHAR(100) NOT NULL,
+  contract_address VARCHAR(66) NOT NULL,
+  entry_function_id_str VARCHAR(1000),
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (offer_id, token_data_id)
+);
+CREATE INDEX IF NOT EXISTS cnmto_collection_index ON current_nft_marketplace_token_offers (collection_id);
+CREATE INDEX IF NOT EXISTS cnmto_buyer_index ON current_nft_marketplace_token_offers (buyer);
+
+CREATE TABLE IF NOT EXISTS current_nft_marketplace_collection_offers (
+  collection_offer_id VARCHAR(66) NOT NULL,
+  collection_id VARCHAR(66) NOT NULL,
+  fee_schedule_id VARCHAR(66) NOT NULL,
+  buyer VARCHAR(66) NOT NULL,
+  item_price NUMERIC NOT NULL,
+  remaining_token_amount NUMERIC NOT NULL,
+  -- Only known if the offer resource was written in the same transaction
+  expiration_time NUMERIC,
+  is_deleted BOOLEAN NOT NULL,
+  token_standard VARCHAR(10) NOT NULL,
+  coin_type VARCHAR(1000),
+  marketplace VARCHAR(100) NOT NULL,
+  contract_address VARCHAR(66) NOT NULL,
+  entry_function_id_str VARCHAR(1000),
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (collection_offer_id, collection_id)
+);
+CREATE INDEX IF NOT EXISTS cnmco_buyer_index ON current_nft_marketplace_collection_offers (buyer);
+CREATE INDEX IF NOT EXISTS cnmco_item_price_index ON current_nft_marketplace_collection_offers (item_price);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP VIEW IF EXISTS current_collection_transfer_counts;
+DROP TABLE IF EXISTS current_collection_stats;
+DROP TABLE IF EXISTS current_collection_owners;
+DROP TABLE IF EXISTS collection_hourly_transfers;
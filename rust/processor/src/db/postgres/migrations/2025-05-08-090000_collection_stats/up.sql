@@ -0,0 +1,57 @@
+-- Your SQL goes here
+-- Aggregates of every collection, maintained by the token v2 processor from the ownership and
+-- activity changes of each batch.
+CREATE TABLE IF NOT EXISTS current_collection_stats (
+  collection_id VARCHAR(66) UNIQUE PRIMARY KEY NOT NULL,
+  -- Owners holding a non zero amount of any token of the collection
+  distinct_owners BIGINT NOT NULL,
+  total_tokens_owned NUMERIC NOT NULL,
+  burned_count NUMERIC NOT NULL,
+  last_mint_version BIGINT,
+  last_transfer_version BIGINT,
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS ccs_distinct_owners_index ON current_collection_stats (distinct_owners);
+CREATE INDEX IF NOT EXISTS ccs_insat_index ON current_collection_stats (inserted_at);
+
+-- Amount of every collection held by every owner, to count distinct owners incrementally
+CREATE TABLE IF NOT EXISTS current_collection_owners (
+  collection_id VARCHAR(66) NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  token_amount NUMERIC NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (collection_id, owner_address)
+);
+CREATE INDEX IF NOT EXISTS cco_owner_index ON current_collection_owners (owner_address);
+
+-- Transfers of every collection per hour, for the 24h and 7d counts. Hours older than 7 days are
+-- deleted as new ones are written.
+CREATE TABLE IF NOT EXISTS collection_hourly_transfers (
+  collection_id VARCHAR(66) NOT NULL,
+  hour TIMESTAMP NOT NULL,
+  transfer_count BIGINT NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (collection_id, hour)
+);
+CREATE INDEX IF NOT EXISTS cht_hour_index ON collection_hourly_transfers (hour);
+
+-- Transfer counts of every collection in the last 24 hours and 7 days. They're computed when they're
+-- read, so that they're relative to the current time rather than to the last batch that touched the
+-- collection. Hours are in UTC, like every timestamp the processors write.
+CREATE OR REPLACE VIEW current_collection_transfer_counts AS
+SELECT collection_id,
+  COALESCE(
+    SUM(transfer_count) FILTER (
+      WHERE hour > (NOW() AT TIME ZONE 'UTC') - INTERVAL '24 hours'
+    ),
+    0
+  ) AS transfer_count_24h,
+  SUM(transfer_count) AS transfer_count_7d
+FROM collection_hourly_transfers
+WHERE hour > (NOW() AT TIME ZONE 'UTC') - INTERVAL '7 days'
+GROUP BY collection_id;
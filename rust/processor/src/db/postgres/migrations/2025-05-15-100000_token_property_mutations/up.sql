@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- Every change to the properties of a token: the default properties of v1 token datas and the
+-- 0x4::property_map::PropertyMap of v2 tokens
+CREATE TABLE IF NOT EXISTS token_property_mutations (
+  transaction_version BIGINT NOT NULL,
+  write_set_change_index BIGINT NOT NULL,
+  token_data_id VARCHAR(66) NOT NULL,
+  old_properties JSONB NOT NULL,
+  new_properties JSONB NOT NULL,
+  token_standard VARCHAR(10) NOT NULL,
+  entry_function_id_str VARCHAR(1000),
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, write_set_change_index)
+);
+CREATE INDEX IF NOT EXISTS tpm_token_data_id_index ON token_property_mutations (token_data_id, transaction_version);
+CREATE INDEX IF NOT EXISTS tpm_insat_index ON token_property_mutations (inserted_at);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS token_transferability_v2;
+DROP TABLE IF EXISTS current_token_transferability_v2;
@@ -0,0 +1,38 @@
+-- Your SQL goes here
+-- Whether a v2 token can be transferred and why not, every time the token object is written
+CREATE TABLE IF NOT EXISTS token_transferability_v2 (
+  transaction_version BIGINT NOT NULL,
+  write_set_change_index BIGINT NOT NULL,
+  token_data_id VARCHAR(66) NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  -- 0x1::object::ObjectCore allow_ungated_transfer
+  allow_ungated_transfer BOOLEAN NOT NULL,
+  -- Whether the object has 0x1::object::Untransferable
+  is_untransferable BOOLEAN NOT NULL,
+  is_transferable_by_owner BOOLEAN NOT NULL,
+  -- untransferable or ungated_transfer_disabled, null if the owner can transfer the token
+  restriction_reason VARCHAR(50),
+  -- Only set in the transaction that mints the token: whether it was minted without ungated transfer
+  soulbound_at_mint BOOLEAN,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, write_set_change_index)
+);
+CREATE INDEX IF NOT EXISTS tt2_token_data_id_index ON token_transferability_v2 (token_data_id, transaction_version);
+CREATE INDEX IF NOT EXISTS tt2_insat_index ON token_transferability_v2 (inserted_at);
+
+CREATE TABLE IF NOT EXISTS current_token_transferability_v2 (
+  token_data_id VARCHAR(66) UNIQUE PRIMARY KEY NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  allow_ungated_transfer BOOLEAN NOT NULL,
+  is_untransferable BOOLEAN NOT NULL,
+  is_transferable_by_owner BOOLEAN NOT NULL,
+  restriction_reason VARCHAR(50),
+  soulbound_at_mint BOOLEAN,
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS ctt2_owner_index ON current_token_transferability_v2 (owner_address);
+CREATE INDEX IF NOT EXISTS ctt2_restriction_reason_index ON current_token_transferability_v2 (restriction_reason);
+CREATE INDEX IF NOT EXISTS ctt2_insat_index ON current_token_transferability_v2 (inserted_at);
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+-- The owner at the top of the ownership chain of every object, e.g. the wallet owning the object that owns a token
+CREATE TABLE IF NOT EXISTS current_object_ultimate_owners (
+  object_address VARCHAR(66) UNIQUE PRIMARY KEY NOT NULL,
+  -- Same as current_objects.owner_address
+  direct_owner_address VARCHAR(66) NOT NULL,
+  -- The first owner in the chain that isn't an object (or is a deleted one)
+  ultimate_owner_address VARCHAR(66) NOT NULL,
+  -- JSON array of the owners from the direct owner to the ultimate owner
+  ownership_path JSONB NOT NULL,
+  -- Number of owners in ownership_path, 1 if the object is directly owned by its ultimate owner
+  depth INT NOT NULL,
+  is_deleted BOOLEAN NOT NULL,
+  -- Latest version at which the object or any object in its ownership path was written
+  last_transaction_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS coo_ultimate_owner_index ON current_object_ultimate_owners (ultimate_owner_address);
+CREATE INDEX IF NOT EXISTS coo_direct_owner_index ON current_object_ultimate_owners (direct_owner_address);
+CREATE INDEX IF NOT EXISTS coo_insat_index ON current_object_ultimate_owners (inserted_at);
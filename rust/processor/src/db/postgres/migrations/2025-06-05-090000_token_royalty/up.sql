@@ -0,0 +1,21 @@
+-- Your SQL goes here
+-- Royalties of v1 token datas and of v2 collections and tokens. A v2 token without a royalty of its own uses the
+-- royalty of its collection.
+CREATE TABLE IF NOT EXISTS current_token_royalty (
+  -- token_data_id for token royalties, collection_id for collection royalties
+  token_or_collection_id VARCHAR(66) UNIQUE PRIMARY KEY NOT NULL,
+  -- token or collection
+  source_level VARCHAR(10) NOT NULL,
+  token_standard VARCHAR(10) NOT NULL,
+  payee_address VARCHAR(66) NOT NULL,
+  royalty_points_numerator NUMERIC NOT NULL,
+  royalty_points_denominator NUMERIC NOT NULL,
+  -- Set when the royalty of a token is deleted, e.g. when the token is burned. The royalty of its collection applies
+  -- again.
+  is_deleted BOOLEAN NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS ctr_payee_index ON current_token_royalty (payee_address);
+CREATE INDEX IF NOT EXISTS ctr_insat_index ON current_token_royalty (inserted_at);
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+-- Latest fetch of the metadata at the uri of every token, written by the nft metadata processor when it fetches
+-- metadata itself instead of publishing uris to Pub/Sub
+CREATE TABLE IF NOT EXISTS current_nft_metadata (
+  token_data_id VARCHAR(66) UNIQUE PRIMARY KEY NOT NULL,
+  token_uri VARCHAR(512) NOT NULL,
+  -- success, fetch_failed, invalid_json or unsupported_uri
+  status VARCHAR(20) NOT NULL,
+  error_message TEXT,
+  -- Attempts to fetch token_uri since it last succeeded or changed
+  num_attempts INT NOT NULL,
+  metadata JSONB,
+  image_uri TEXT,
+  -- Hex SHA-256 of the image content, only if image hashes are enabled
+  image_content_hash VARCHAR(64),
+  last_transaction_version BIGINT NOT NULL,
+  last_fetched_at TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS cnm_status_index ON current_nft_metadata (status);
+CREATE INDEX IF NOT EXISTS cnm_image_content_hash_index ON current_nft_metadata (image_content_hash);
+CREATE INDEX IF NOT EXISTS cnm_insat_index ON current_nft_metadata (inserted_at);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS fab_at_storage_version_index;
+DROP INDEX IF EXISTS to2_id_version_index;
+DROP TABLE IF EXISTS fungible_asset_balance_snapshots;
+DROP TABLE IF EXISTS token_ownership_snapshots;
@@ -0,0 +1,48 @@
+-- Your SQL goes here
+-- Holders of the tokens of a collection at a version, materialized from token_ownerships_v2
+CREATE TABLE IF NOT EXISTS token_ownership_snapshots (
+  collection_id VARCHAR(66) NOT NULL,
+  snapshot_version BIGINT NOT NULL,
+  token_data_id VARCHAR(66) NOT NULL,
+  property_version_v1 NUMERIC NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  storage_id VARCHAR(66) NOT NULL,
+  amount NUMERIC NOT NULL,
+  token_standard VARCHAR(10) NOT NULL,
+  -- Version of the token_ownerships_v2 row the holding comes from
+  last_transaction_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (
+    collection_id,
+    snapshot_version,
+    token_data_id,
+    property_version_v1,
+    owner_address,
+    storage_id
+  )
+);
+CREATE INDEX IF NOT EXISTS tos_owner_index ON token_ownership_snapshots (owner_address);
+-- Balances of a fungible asset at a version, materialized from fungible_asset_balances
+CREATE TABLE IF NOT EXISTS fungible_asset_balance_snapshots (
+  asset_type VARCHAR(1000) NOT NULL,
+  snapshot_version BIGINT NOT NULL,
+  storage_id VARCHAR(66) NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  is_primary BOOLEAN NOT NULL,
+  is_frozen BOOLEAN NOT NULL,
+  amount NUMERIC NOT NULL,
+  token_standard VARCHAR(10) NOT NULL,
+  -- Version of the fungible_asset_balances row the balance comes from
+  last_transaction_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (asset_type, snapshot_version, storage_id)
+);
+CREATE INDEX IF NOT EXISTS fabs_owner_index ON fungible_asset_balance_snapshots (owner_address);
+-- The latest history row of every holding at or before a version, see sdk_processor::snapshot
+CREATE INDEX IF NOT EXISTS to2_id_version_index ON token_ownerships_v2 (token_data_id, transaction_version);
+CREATE INDEX IF NOT EXISTS fab_at_storage_version_index ON fungible_asset_balances (
+  asset_type,
+  storage_id,
+  transaction_version,
+  write_set_change_index
+);
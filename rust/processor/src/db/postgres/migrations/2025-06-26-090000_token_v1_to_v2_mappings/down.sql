@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS tv1v2m_version_index;
+DROP INDEX IF EXISTS tv1v2m_collection_index;
+DROP INDEX IF EXISTS tv1v2m_v1_index;
+DROP TABLE IF EXISTS token_v1_to_v2_mappings;
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- v2 tokens minted in exchange for a burned v1 token, see RawTokenV1ToV2Mapping
+CREATE TABLE IF NOT EXISTS token_v1_to_v2_mappings (
+  token_data_id_v2 VARCHAR(66) NOT NULL,
+  token_data_id_v1 VARCHAR(66) NOT NULL,
+  property_version_v1 NUMERIC NOT NULL,
+  collection_id_v1 VARCHAR(66) NOT NULL,
+  collection_id_v2 VARCHAR(66) NOT NULL,
+  token_name VARCHAR(128) NOT NULL,
+  transaction_version BIGINT NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (token_data_id_v2)
+);
+CREATE INDEX IF NOT EXISTS tv1v2m_v1_index ON token_v1_to_v2_mappings (token_data_id_v1);
+CREATE INDEX IF NOT EXISTS tv1v2m_collection_index ON token_v1_to_v2_mappings (collection_id_v1, collection_id_v2);
+CREATE INDEX IF NOT EXISTS tv1v2m_version_index ON token_v1_to_v2_mappings (transaction_version);
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS cnm_next_attempt_at_index;
+DELETE FROM current_nft_metadata
+WHERE last_fetched_at IS NULL;
+ALTER TABLE current_nft_metadata
+ALTER COLUMN last_fetched_at
+SET NOT NULL;
+ALTER TABLE current_nft_metadata DROP COLUMN IF EXISTS next_attempt_at;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+-- current_nft_metadata doubles as the queue of uris to fetch: the processor writes new uris as pending rows and the
+-- fetcher claims the rows whose next_attempt_at is due
+ALTER TABLE current_nft_metadata
+ADD COLUMN IF NOT EXISTS next_attempt_at TIMESTAMP;
+ALTER TABLE current_nft_metadata
+ALTER COLUMN last_fetched_at DROP NOT NULL;
+CREATE INDEX IF NOT EXISTS cnm_next_attempt_at_index ON current_nft_metadata (next_attempt_at)
+WHERE next_attempt_at IS NOT NULL;
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+DROP VIEW IF EXISTS collection_v1_to_v2_mappings;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- v2 collections the tokens of a v1 collection were migrated to, see token_v1_to_v2_mappings
+CREATE OR REPLACE VIEW collection_v1_to_v2_mappings AS
+SELECT collection_id_v1,
+  collection_id_v2,
+  COUNT(*) AS token_count,
+  MIN(transaction_version) AS first_transaction_version,
+  MAX(transaction_version) AS last_transaction_version
+FROM token_v1_to_v2_mappings
+GROUP BY collection_id_v1,
+  collection_id_v2;
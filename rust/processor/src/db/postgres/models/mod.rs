@@ -9,6 +9,7 @@ pub mod default_models;
 pub mod events_models;
 pub mod fungible_asset_models;
 pub mod ledger_info;
+pub mod nft_marketplace_models;
 pub mod object_models;
 pub mod processor_status;
 pub mod property_map;
@@ -0,0 +1,49 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::schema::current_nft_marketplace_collection_offers;
+use bigdecimal::BigDecimal;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+/// Latest state of an offer on any tokens of a collection. Each fill lowers
+/// `remaining_token_amount`, and the offer is deleted once none remain or it is canceled.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize, PartialEq)]
+#[diesel(primary_key(collection_offer_id, collection_id))]
+#[diesel(table_name = current_nft_marketplace_collection_offers)]
+pub struct CurrentNftMarketplaceCollectionOffer {
+    pub collection_offer_id: String,
+    pub collection_id: String,
+    pub fee_schedule_id: String,
+    pub buyer: String,
+    pub item_price: BigDecimal,
+    pub remaining_token_amount: BigDecimal,
+    pub expiration_time: Option<BigDecimal>,
+    pub is_deleted: bool,
+    pub token_standard: String,
+    pub coin_type: Option<String>,
+    pub marketplace: String,
+    pub contract_address: String,
+    pub entry_function_id_str: Option<String>,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl CurrentNftMarketplaceCollectionOffer {
+    pub fn pk(&self) -> (String, String) {
+        (self.collection_offer_id.clone(), self.collection_id.clone())
+    }
+
+    /// Fills don't carry the expiration when the offer is already gone, so it's kept from the
+    /// previous row of the offer in the same batch, like for token offers.
+    pub fn merge_previous(mut self, previous: Option<&Self>) -> Self {
+        if self.expiration_time.is_none() {
+            self.expiration_time = previous.and_then(|previous| previous.expiration_time.clone());
+        }
+        self
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::schema::current_nft_marketplace_token_offers;
+use bigdecimal::BigDecimal;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+/// Latest state of an offer on a single token, deleted once it is filled or canceled.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize, PartialEq)]
+#[diesel(primary_key(offer_id, token_data_id))]
+#[diesel(table_name = current_nft_marketplace_token_offers)]
+pub struct CurrentNftMarketplaceTokenOffer {
+    pub offer_id: String,
+    pub token_data_id: String,
+    pub collection_id: String,
+    pub fee_schedule_id: String,
+    pub buyer: String,
+    pub price: BigDecimal,
+    pub token_amount: BigDecimal,
+    pub expiration_time: Option<BigDecimal>,
+    pub is_deleted: bool,
+    pub token_standard: String,
+    pub coin_type: Option<String>,
+    pub marketplace: String,
+    pub contract_address: String,
+    pub entry_function_id_str: Option<String>,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl CurrentNftMarketplaceTokenOffer {
+    pub fn pk(&self) -> (String, String) {
+        (self.offer_id.clone(), self.token_data_id.clone())
+    }
+
+    /// Cancels and fills don't carry the expiration when the offer is already gone, so it's kept
+    /// from the previous row of the offer in the same batch. The upsert does the same for rows
+    /// written by earlier batches.
+    pub fn merge_previous(mut self, previous: Option<&Self>) -> Self {
+        if self.expiration_time.is_none() {
+            self.expiration_time = previous.and_then(|previous| previous.expiration_time.clone());
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_previous() {
+        let placed = CurrentNftMarketplaceTokenOffer {
+            offer_id: "0x1".to_string(),
+            token_data_id: "0x2".to_string(),
+            collection_id: "0x3".to_string(),
+            fee_schedule_id: "0x4".to_string(),
+            buyer: "0x5".to_string(),
+            price: BigDecimal::from(100),
+            token_amount: BigDecimal::from(1),
+            expiration_time: Some(BigDecimal::from(1_700_000_000)),
+            is_deleted: false,
+            token_standard: "v2".to_string(),
+            coin_type: None,
+            marketplace: "example_marketplace".to_string(),
+            contract_address: "0x6".to_string(),
+            entry_function_id_str: None,
+            last_transaction_version: 10,
+            last_transaction_timestamp: chrono::NaiveDateTime::default(),
+        };
+        let filled = CurrentNftMarketplaceTokenOffer {
+            expiration_time: None,
+            is_deleted: true,
+            last_transaction_version: 11,
+            ..placed.clone()
+        };
+
+        let merged = filled.clone().merge_previous(Some(&placed));
+        assert_eq!(merged.expiration_time, placed.expiration_time);
+        assert!(merged.is_deleted);
+        assert_eq!(merged.last_transaction_version, 11);
+        assert_eq!(filled.clone().merge_previous(None), filled);
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! BlueMove emits listings from `marketplaceV2` and token and collection offers from `offer_lib`.
+//! Collection offer events carry the whole offer, including how many tokens it can still buy.
+
+use super::{get_sender, ContractEvent};
+use crate::db::postgres::models::nft_marketplace_models::nft_marketplace_utils::{
+    MarketplaceEventType, NftSubject, ParsedMarketplaceEvent,
+};
+use aptos_protos::transaction::v1::UserTransaction;
+use bigdecimal::Zero;
+
+pub fn parse_event(
+    event: &ContractEvent,
+    user_txn: &UserTransaction,
+) -> Option<ParsedMarketplaceEvent> {
+    let event_type = match event.event_type.as_str() {
+        "marketplaceV2::ListEvent" => MarketplaceEventType::ListingPlace,
+        "marketplaceV2::ChangePriceEvent" => MarketplaceEventType::ListingChange,
+        "marketplaceV2::DelistEvent" => MarketplaceEventType::ListingCancel,
+        "marketplaceV2::BuyEvent" => MarketplaceEventType::ListingFill,
+        "offer_lib::OfferEvent" => MarketplaceEventType::TokenOfferPlace,
+        "offer_lib::CancelOfferEvent" => MarketplaceEventType::TokenOfferCancel,
+        "offer_lib::AcceptOfferEvent" => MarketplaceEventType::TokenOfferFill,
+        "offer_lib::OfferCollectionEvent" => MarketplaceEventType::CollectionOfferPlace,
+        "offer_lib::CancelOfferCollectionEvent" => MarketplaceEventType::CollectionOfferCancel,
+        "offer_lib::AcceptOfferCollectionEvent" => MarketplaceEventType::CollectionOfferFill,
+        _ => return None,
+    };
+    let subject = match event.token_data_id(&["/token_id/token_data_id", "/id/token_data_id"]) {
+        Some(token_data_id) => NftSubject::v1_token(
+            &token_data_id,
+            event.decimal(&["/token_id/property_version", "/id/property_version"]),
+        ),
+        None => match (
+            event.string(&["/offer_collection_item/creator_address"]),
+            event.string(&["/offer_collection_item/collection_name"]),
+        ) {
+            (Some(creator), Some(collection_name)) => {
+                NftSubject::v1_collection(&creator, &collection_name)
+            },
+            _ => return event.skip("a token or collection"),
+        },
+    };
+    let buyer = event.address(&[
+        "/bider_address",
+        "/offer_collection_item/offerer",
+        "/offerer",
+        "/buyer_address",
+    ]);
+    let seller = match event_type {
+        // The seller accepts offers
+        MarketplaceEventType::TokenOfferFill | MarketplaceEventType::CollectionOfferFill => {
+            get_sender(user_txn)
+        },
+        _ => event.address(&["/seller_address", "/owner_address", "/owner_token"]),
+    };
+    let token_amount = match event_type {
+        MarketplaceEventType::CollectionOfferPlace => {
+            event.decimal(&["/offer_collection_item/quantity_offer_items"])
+        },
+        _ => event.decimal(&["/quantity_cancel_items"]),
+    };
+    let id = match event_type {
+        MarketplaceEventType::ListingPlace
+        | MarketplaceEventType::ListingChange
+        | MarketplaceEventType::ListingCancel
+        | MarketplaceEventType::ListingFill => event.contract_address.to_string(),
+        _ => match &buyer {
+            Some(buyer) => buyer.clone(),
+            None => return event.skip("a buyer"),
+        },
+    };
+    let remaining_token_amount = match event_type {
+        MarketplaceEventType::CollectionOfferFill => {
+            event.decimal(&["/offer_collection_item/quantity_offer_items"])
+        },
+        _ => None,
+    };
+
+    Some(ParsedMarketplaceEvent {
+        remaining_token_amount,
+        ..event.to_parsed(
+            event_type,
+            id,
+            subject,
+            event.decimal(&[
+                "/bid",
+                "/min_selling_price",
+                "/amount",
+                "/offer_collection_item/amount_per_item",
+            ]),
+            token_amount.filter(|amount| !amount.is_zero()),
+            seller,
+            buyer,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::test_utils::parse_fixture, *};
+    use crate::{
+        db::postgres::models::{
+            nft_marketplace_models::nft_marketplace_utils::MarketplaceParser,
+            token_models::token_utils::CollectionDataIdType,
+        },
+        utils::util::standardize_address,
+    };
+    use bigdecimal::BigDecimal;
+
+    const CONTRACT: &str = "0xd1fd99c1944b84d1670a2536417e997864ad12303d19eac725891691b04d614e";
+
+    #[test]
+    fn test_bluemove_accept_offer_collection() {
+        let models = parse_fixture(
+            include_str!("fixtures/bluemove_accept_offer_collection.json"),
+            MarketplaceParser::Bluemove,
+            CONTRACT,
+        );
+
+        let activity = &models.activities[0];
+        assert_eq!(activity.event_type, "collection_offer_fill");
+        assert_eq!(activity.price, BigDecimal::from(80_000_000));
+        assert_eq!(activity.buyer, Some(standardize_address("0xb0b")));
+        assert_eq!(activity.seller, Some(standardize_address("0x5e11")));
+        assert!(activity.token_data_id.is_some());
+
+        let offer = &models.current_collection_offers[0];
+        assert_eq!(offer.collection_offer_id, standardize_address("0xb0b"));
+        assert_eq!(
+            offer.collection_id,
+            CollectionDataIdType::new("0xb10e".to_string(), "Bruh Bears".to_string()).to_id()
+        );
+        // The last token of the offer was sold
+        assert_eq!(offer.remaining_token_amount, BigDecimal::from(0));
+        assert!(offer.is_deleted);
+    }
+
+    #[test]
+    fn test_bluemove_change_price() {
+        let models = parse_fixture(
+            include_str!("fixtures/bluemove_change_price.json"),
+            MarketplaceParser::Bluemove,
+            CONTRACT,
+        );
+
+        let activity = &models.activities[0];
+        assert_eq!(activity.event_type, "listing_change");
+        let listing = &models.current_listings[0];
+        assert_eq!(listing.listing_id, CONTRACT);
+        assert_eq!(listing.price, BigDecimal::from(95_000_000));
+        assert_eq!(listing.seller, standardize_address("0x5e11"));
+        assert_eq!(listing.token_amount, BigDecimal::from(1));
+        assert!(!listing.is_deleted);
+    }
+}
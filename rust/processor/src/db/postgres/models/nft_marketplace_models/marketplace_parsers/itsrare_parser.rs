@@ -0,0 +1,73 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! ItsRare only has listings, from its `Events` module.
+
+use super::ContractEvent;
+use crate::db::postgres::models::nft_marketplace_models::nft_marketplace_utils::{
+    MarketplaceEventType, NftSubject, ParsedMarketplaceEvent,
+};
+
+pub fn parse_event(event: &ContractEvent) -> Option<ParsedMarketplaceEvent> {
+    let event_type = match event.event_type.as_str() {
+        "Events::ListEvent" => MarketplaceEventType::ListingPlace,
+        "Events::DelistEvent" => MarketplaceEventType::ListingCancel,
+        "Events::BuyEvent" => MarketplaceEventType::ListingFill,
+        _ => return None,
+    };
+    let Some(token_data_id) = event.token_data_id(&["/token_id/token_data_id"]) else {
+        return event.skip("a token");
+    };
+
+    Some(event.to_parsed(
+        event_type,
+        event.contract_address.to_string(),
+        NftSubject::v1_token(
+            &token_data_id,
+            event.decimal(&["/token_id/property_version"]),
+        ),
+        event.decimal(&["/price", "/min_price", "/coin_amount"]),
+        event.decimal(&["/amount"]),
+        event.address(&["/seller", "/lister"]),
+        event.address(&["/buyer"]),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::test_utils::parse_fixture, *};
+    use crate::{
+        db::postgres::models::nft_marketplace_models::nft_marketplace_utils::MarketplaceParser,
+        utils::util::standardize_address,
+    };
+    use bigdecimal::BigDecimal;
+
+    const CONTRACT: &str = "0x143f6a7a07c76eae1fc9ea030dbd9be3c2d46e538c7ad61fe64529218ff44bc4";
+
+    #[test]
+    fn test_itsrare_list_and_buy() {
+        let models = parse_fixture(
+            include_str!("fixtures/itsrare_list_and_buy.json"),
+            MarketplaceParser::Itsrare,
+            CONTRACT,
+        );
+
+        assert_eq!(models.activities.len(), 2);
+        assert_eq!(models.activities[0].event_type, "listing_place");
+        assert_eq!(
+            models.activities[0].seller,
+            Some(standardize_address("0x5e11"))
+        );
+        assert_eq!(models.activities[1].event_type, "listing_fill");
+        assert_eq!(models.activities[1].price, BigDecimal::from(61_000_000));
+        assert_eq!(
+            models.activities[1].buyer,
+            Some(standardize_address("0xb0b"))
+        );
+
+        // The buy later in the transaction deletes the listing
+        assert_eq!(models.current_listings.len(), 2);
+        assert!(!models.current_listings[0].is_deleted);
+        assert!(models.current_listings[1].is_deleted);
+    }
+}
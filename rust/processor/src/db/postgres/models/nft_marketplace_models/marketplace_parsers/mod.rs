@@ -0,0 +1,226 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parsers of the marketplaces that predate the marketplace framework, ported from the Python
+//! `nft_orderbooks` parsers. Each one maps the events of its contract to the same listing, offer
+//! and activity rows as the framework.
+//!
+//! These contracts only trade token v1, and their listings and offers don't have an address of
+//! their own. A listing is therefore identified by the contract address and the token, so a token
+//! has at most one listing per contract, and an offer by its buyer and the token or collection.
+//! `fee_schedule_id` is the contract address, and `coin_type` the type argument of the event if it
+//! has one and APT otherwise. Prices are in the smallest unit of the coin, like for the framework.
+
+pub mod bluemove_parser;
+pub mod itsrare_parser;
+pub mod okx_parser;
+pub mod souffle_parser;
+pub mod topaz_parser;
+
+use super::nft_marketplace_utils::{
+    MarketplaceEventType, MarketplaceParser, NftSubject, ParsedMarketplaceEvent, COIN_TYPE_LENGTH,
+};
+use crate::{
+    db::postgres::models::token_models::token_utils::TokenDataIdType,
+    utils::util::{standardize_address, truncate_str, APTOS_COIN_TYPE_STR},
+};
+use anyhow::Context;
+use aptos_protos::transaction::v1::{Event, Transaction, UserTransaction};
+use bigdecimal::BigDecimal;
+use serde_json::Value;
+use std::str::FromStr;
+
+/// Events of a marketplace that isn't built on the framework, with their index in the
+/// transaction. Events that fail to parse are logged and skipped, so that one malformed event
+/// doesn't stall the processor.
+pub fn parse_events(
+    parser: MarketplaceParser,
+    transaction: &Transaction,
+    user_txn: &UserTransaction,
+    contract_address: &str,
+    txn_version: i64,
+) -> anyhow::Result<Vec<(usize, ParsedMarketplaceEvent)>> {
+    let mut parsed = vec![];
+    for (event_index, event) in user_txn.events.iter().enumerate() {
+        let event = match ContractEvent::from_event(event, contract_address, txn_version) {
+            Ok(Some(event)) => event,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!(
+                    transaction_version = txn_version,
+                    event_index = event_index,
+                    error = ?e,
+                    "Skipping nft marketplace event that failed to parse"
+                );
+                continue;
+            },
+        };
+        let event = match parser {
+            MarketplaceParser::Topaz => topaz_parser::parse_event(&event, transaction, user_txn),
+            MarketplaceParser::Bluemove => bluemove_parser::parse_event(&event, user_txn),
+            MarketplaceParser::Okx => okx_parser::parse_event(&event, user_txn),
+            MarketplaceParser::Souffle => souffle_parser::parse_event(&event, user_txn),
+            MarketplaceParser::Itsrare => itsrare_parser::parse_event(&event),
+            MarketplaceParser::Framework => None,
+        };
+        if let Some(event) = event {
+            parsed.push((event_index, event));
+        }
+    }
+    Ok(parsed)
+}
+
+/// An event emitted by a module of a marketplace contract.
+pub struct ContractEvent<'a> {
+    pub contract_address: &'a str,
+    /// Module and name of the event, e.g. `events::BuyEvent`
+    pub event_type: String,
+    /// Type arguments of the event, e.g. the coin of a Souffle listing
+    pub type_args: Option<String>,
+    /// Address the event was emitted from
+    pub account_address: String,
+    pub data: Value,
+    pub txn_version: i64,
+}
+
+impl<'a> ContractEvent<'a> {
+    pub fn from_event(
+        event: &Event,
+        contract_address: &'a str,
+        txn_version: i64,
+    ) -> anyhow::Result<Option<Self>> {
+        let (type_str, type_args) = match event.type_str.split_once('<') {
+            Some((type_str, type_args)) => {
+                (type_str, type_args.strip_suffix('>').map(|t| t.to_string()))
+            },
+            None => (event.type_str.as_str(), None),
+        };
+        let Some((address, event_type)) = type_str.split_once("::") else {
+            return Ok(None);
+        };
+        if standardize_address(address) != contract_address {
+            return Ok(None);
+        }
+        let data = serde_json::from_str(&event.data).context(format!(
+            "version {} failed! failed to parse type {}, data {:?}",
+            txn_version, event.type_str, event.data
+        ))?;
+        Ok(Some(Self {
+            contract_address,
+            event_type: event_type.to_string(),
+            type_args,
+            account_address: event
+                .key
+                .as_ref()
+                .map(|key| standardize_address(&key.account_address))
+                .unwrap_or_default(),
+            data,
+            txn_version,
+        }))
+    }
+
+    /// The first of the fields at `pointers` that is set. Move serializes u64 as strings.
+    pub fn decimal(&self, pointers: &[&str]) -> Option<BigDecimal> {
+        pointers
+            .iter()
+            .find_map(|pointer| match self.data.pointer(pointer)? {
+                Value::String(value) => BigDecimal::from_str(value).ok(),
+                Value::Number(value) => BigDecimal::from_str(&value.to_string()).ok(),
+                _ => None,
+            })
+    }
+
+    pub fn string(&self, pointers: &[&str]) -> Option<String> {
+        pointers.iter().find_map(|pointer| {
+            self.data
+                .pointer(pointer)?
+                .as_str()
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+        })
+    }
+
+    pub fn address(&self, pointers: &[&str]) -> Option<String> {
+        self.string(pointers)
+            .map(|address| standardize_address(&address))
+    }
+
+    pub fn token_data_id(&self, pointers: &[&str]) -> Option<TokenDataIdType> {
+        pointers
+            .iter()
+            .find_map(|pointer| serde_json::from_value(self.data.pointer(pointer)?.clone()).ok())
+    }
+
+    /// A row for the event with the fields every contract shares
+    pub fn to_parsed(
+        &self,
+        event_type: MarketplaceEventType,
+        id: String,
+        subject: NftSubject,
+        price: Option<BigDecimal>,
+        token_amount: Option<BigDecimal>,
+        seller: Option<String>,
+        buyer: Option<String>,
+    ) -> ParsedMarketplaceEvent {
+        ParsedMarketplaceEvent {
+            event_type,
+            id,
+            fee_schedule_id: self.contract_address.to_string(),
+            subject,
+            price: price.unwrap_or_default(),
+            token_amount: token_amount.unwrap_or_else(|| BigDecimal::from(1)),
+            seller,
+            buyer,
+            coin_type: Some(truncate_str(
+                self.type_args.as_deref().unwrap_or(APTOS_COIN_TYPE_STR),
+                COIN_TYPE_LENGTH,
+            )),
+            expiration_time: None,
+            remaining_token_amount: None,
+        }
+    }
+
+    /// Logs an event that is missing the token or the offerer it needs to be stored
+    pub fn skip(&self, missing: &str) -> Option<ParsedMarketplaceEvent> {
+        tracing::warn!(
+            transaction_version = self.txn_version,
+            event_type = %self.event_type,
+            "Skipping marketplace event without {}",
+            missing
+        );
+        None
+    }
+}
+
+/// Address of the sender of a user transaction
+fn get_sender(user_txn: &UserTransaction) -> Option<String> {
+    user_txn
+        .request
+        .as_ref()
+        .map(|request| standardize_address(&request.sender))
+}
+
+#[cfg(test)]
+mod test_utils {
+    use super::super::nft_marketplace_utils::{
+        parse_nft_marketplace_transaction, MarketplaceContract, MarketplaceParser,
+        NftMarketplaceModels,
+    };
+    use aptos_protos::transaction::v1::Transaction;
+
+    /// Parses a fixture transaction with the contract at `contract_address`
+    pub fn parse_fixture(
+        fixture: &str,
+        parser: MarketplaceParser,
+        contract_address: &str,
+    ) -> NftMarketplaceModels {
+        let transaction: Transaction =
+            serde_json::from_str(fixture).expect("Failed to parse fixture transaction");
+        parse_nft_marketplace_transaction(&transaction, &[MarketplaceContract {
+            name: format!("{:?}", parser).to_lowercase(),
+            contract_address: contract_address.to_string(),
+            parser,
+        }])
+        .unwrap()
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! OKX only has listings. A filled listing doesn't say which token it sold, so the token is taken
+//! from the deposit to the buyer in the same transaction.
+
+use super::ContractEvent;
+use crate::{
+    db::postgres::models::{
+        nft_marketplace_models::nft_marketplace_utils::{
+            MarketplaceEventType, NftSubject, ParsedMarketplaceEvent,
+        },
+        token_models::token_utils::TokenDataIdType,
+    },
+    utils::util::standardize_address,
+};
+use aptos_protos::transaction::v1::UserTransaction;
+use serde_json::Value;
+
+const DEPOSIT_EVENT_TYPES: [&str; 2] = ["0x3::token::DepositEvent", "0x3::token::Deposit"];
+
+pub fn parse_event(
+    event: &ContractEvent,
+    user_txn: &UserTransaction,
+) -> Option<ParsedMarketplaceEvent> {
+    let event_type = match event.event_type.as_str() {
+        "okx_listing_utils::ListingEvent" => MarketplaceEventType::ListingPlace,
+        "okx_listing_utils::CancelListingEvent" => MarketplaceEventType::ListingCancel,
+        "okx_bid_utils::OrderExecutedEvent" => MarketplaceEventType::ListingFill,
+        _ => return None,
+    };
+    let buyer = event.address(&["/buyer"]);
+    let token_data_id = event
+        .token_data_id(&["/token_id/token_data_id"])
+        .or_else(|| get_deposited_token(user_txn, &event.account_address))
+        .or_else(|| get_deposited_token(user_txn, buyer.as_deref()?));
+    let Some(token_data_id) = token_data_id else {
+        return event.skip("a token");
+    };
+
+    Some(event.to_parsed(
+        event_type,
+        event.contract_address.to_string(),
+        NftSubject::v1_token(
+            &token_data_id,
+            event.decimal(&["/token_id/property_version"]),
+        ),
+        event.decimal(&["/executed_price", "/min_price"]),
+        event.decimal(&["/amount"]),
+        event.address(&["/id/addr"]),
+        buyer,
+    ))
+}
+
+/// Token deposited to `account_address` by the transaction
+fn get_deposited_token(
+    user_txn: &UserTransaction,
+    account_address: &str,
+) -> Option<TokenDataIdType> {
+    user_txn.events.iter().find_map(|event| {
+        if !DEPOSIT_EVENT_TYPES.contains(&event.type_str.as_str()) {
+            return None;
+        }
+        let event_address = standardize_address(&event.key.as_ref()?.account_address);
+        if event_address != account_address {
+            return None;
+        }
+        let data: Value = serde_json::from_str(&event.data).ok()?;
+        serde_json::from_value(data.pointer("/id/token_data_id")?.clone()).ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::test_utils::parse_fixture, *};
+    use crate::db::postgres::models::nft_marketplace_models::nft_marketplace_utils::MarketplaceParser;
+    use bigdecimal::BigDecimal;
+
+    const CONTRACT: &str = "0x1e6009ce9d288f3d5031c06ca0b19a334214ead798a0cb38808485bd6d997a43";
+
+    #[test]
+    fn test_okx_order_executed() {
+        let models = parse_fixture(
+            include_str!("fixtures/okx_order_executed.json"),
+            MarketplaceParser::Okx,
+            CONTRACT,
+        );
+
+        assert_eq!(models.activities.len(), 1);
+        let activity = &models.activities[0];
+        assert_eq!(activity.event_type, "listing_fill");
+        // The token comes from the deposit event before the fill
+        assert_eq!(
+            activity.token_data_id,
+            Some(
+                TokenDataIdType::new(
+                    "0x0c".to_string(),
+                    "OKX Owls".to_string(),
+                    "Owl #3".to_string()
+                )
+                .to_id()
+            )
+        );
+        assert_eq!(activity.price, BigDecimal::from(230_000_000));
+        assert_eq!(activity.buyer, Some(standardize_address("0xb0b")));
+        assert_eq!(activity.seller, Some(standardize_address("0x5e11")));
+
+        let listing = &models.current_listings[0];
+        assert_eq!(listing.listing_id, CONTRACT);
+        assert!(listing.is_deleted);
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Souffle only has fixed price listings, from its `FixedPriceMarket` module. The events are
+//! generic over the coin of the listing.
+
+use super::{get_sender, ContractEvent};
+use crate::db::postgres::models::nft_marketplace_models::nft_marketplace_utils::{
+    MarketplaceEventType, NftSubject, ParsedMarketplaceEvent,
+};
+use aptos_protos::transaction::v1::UserTransaction;
+
+pub fn parse_event(
+    event: &ContractEvent,
+    user_txn: &UserTransaction,
+) -> Option<ParsedMarketplaceEvent> {
+    let event_type = match event.event_type.as_str() {
+        "FixedPriceMarket::ListTokenEvent" => MarketplaceEventType::ListingPlace,
+        "FixedPriceMarket::CancelListTokenEvent" => MarketplaceEventType::ListingCancel,
+        "FixedPriceMarket::BuyTokenEvent" => MarketplaceEventType::ListingFill,
+        _ => return None,
+    };
+    let Some(token_data_id) = event.token_data_id(&["/token_id/token_data_id"]) else {
+        return event.skip("a token");
+    };
+    let seller = match event_type {
+        // Only the owner can cancel a listing
+        MarketplaceEventType::ListingCancel => get_sender(user_txn),
+        _ => event.address(&["/token_owner"]),
+    };
+
+    Some(event.to_parsed(
+        event_type,
+        event.contract_address.to_string(),
+        NftSubject::v1_token(
+            &token_data_id,
+            event.decimal(&["/token_id/property_version"]),
+        ),
+        event.decimal(&["/price", "/coin_per_token", "/coin_amount"]),
+        event.decimal(&["/token_amount"]),
+        seller,
+        event.address(&["/buyer"]),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::test_utils::parse_fixture, *};
+    use crate::{
+        db::postgres::models::nft_marketplace_models::nft_marketplace_utils::MarketplaceParser,
+        utils::util::standardize_address,
+    };
+    use bigdecimal::BigDecimal;
+
+    const CONTRACT: &str = "0xf6994988bd40261af9431cd6dd3fcf765569719e66322c7a05cc78a89cd366d4";
+
+    #[test]
+    fn test_souffle_list_token() {
+        let models = parse_fixture(
+            include_str!("fixtures/souffle_list_token.json"),
+            MarketplaceParser::Souffle,
+            CONTRACT,
+        );
+
+        let activity = &models.activities[0];
+        assert_eq!(activity.event_type, "listing_place");
+        // The coin is the type argument of the event
+        assert_eq!(
+            activity.coin_type.as_deref(),
+            Some("0x1::aptos_coin::AptosCoin")
+        );
+        let listing = &models.current_listings[0];
+        assert_eq!(listing.listing_id, CONTRACT);
+        assert_eq!(listing.price, BigDecimal::from(42_000_000));
+        assert_eq!(listing.token_amount, BigDecimal::from(1));
+        assert_eq!(listing.seller, standardize_address("0x5e11"));
+        assert!(!listing.is_deleted);
+    }
+
+    #[test]
+    fn test_souffle_cancel_list_token() {
+        let models = parse_fixture(
+            include_str!("fixtures/souffle_cancel_list_token.json"),
+            MarketplaceParser::Souffle,
+            CONTRACT,
+        );
+
+        let activity = &models.activities[0];
+        assert_eq!(activity.event_type, "listing_cancel");
+        // The event has no owner, the sender cancelled it
+        assert_eq!(activity.seller, Some(standardize_address("0x5e11")));
+        assert!(models.current_listings[0].is_deleted);
+    }
+}
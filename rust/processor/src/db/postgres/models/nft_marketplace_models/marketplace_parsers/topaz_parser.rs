@@ -0,0 +1,197 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Topaz emits every listing, token bid and collection bid from its `events` module. How many
+//! tokens a collection bid can still buy is only in the collection bids table.
+
+use super::{get_sender, ContractEvent};
+use crate::{
+    db::postgres::models::{
+        nft_marketplace_models::nft_marketplace_utils::{
+            MarketplaceEventType, NftSubject, ParsedMarketplaceEvent,
+        },
+        token_models::token_utils::CollectionDataIdType,
+    },
+    utils::util::{deserialize_from_string, standardize_address},
+};
+use aptos_protos::transaction::v1::{write_set_change::Change, Transaction, UserTransaction};
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+pub const TOPAZ_COLLECTION_BIDS_TABLE_HANDLE: &str =
+    "0x8f89a3d01d95119fbbb49e416a04394da369792b620536fcc9aa797589b18e5b";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CollectionBid {
+    buyer: String,
+    creator: String,
+    collection_name: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    amount: BigDecimal,
+}
+
+pub fn parse_event(
+    event: &ContractEvent,
+    transaction: &Transaction,
+    user_txn: &UserTransaction,
+) -> Option<ParsedMarketplaceEvent> {
+    let event_type = match event.event_type.as_str() {
+        "events::ListEvent" => MarketplaceEventType::ListingPlace,
+        "events::DelistEvent" => MarketplaceEventType::ListingCancel,
+        "events::BuyEvent" => MarketplaceEventType::ListingFill,
+        "events::BidEvent" => MarketplaceEventType::TokenOfferPlace,
+        "events::CancelBidEvent" => MarketplaceEventType::TokenOfferCancel,
+        "events::SellEvent" => MarketplaceEventType::TokenOfferFill,
+        "events::CollectionBidEvent" => MarketplaceEventType::CollectionOfferPlace,
+        "events::CancelCollectionBidEvent" => MarketplaceEventType::CollectionOfferCancel,
+        "events::FillCollectionBidEvent" => MarketplaceEventType::CollectionOfferFill,
+        _ => return None,
+    };
+    let subject = match event.token_data_id(&["/token_id/token_data_id"]) {
+        Some(token_data_id) => NftSubject::v1_token(
+            &token_data_id,
+            event.decimal(&["/token_id/property_version"]),
+        ),
+        None => match (
+            event.string(&["/creator"]),
+            event.string(&["/collection_name"]),
+        ) {
+            (Some(creator), Some(collection_name)) => {
+                NftSubject::v1_collection(&creator, &collection_name)
+            },
+            _ => return event.skip("a token or collection"),
+        },
+    };
+    let buyer = event.address(&["/buyer", "/token_buyer"]);
+    let seller = match event_type {
+        // The seller fills bids
+        MarketplaceEventType::TokenOfferFill | MarketplaceEventType::CollectionOfferFill => {
+            event.address(&["/seller"]).or_else(|| get_sender(user_txn))
+        },
+        _ => event.address(&["/seller"]),
+    };
+    let id = match event_type {
+        MarketplaceEventType::ListingPlace
+        | MarketplaceEventType::ListingCancel
+        | MarketplaceEventType::ListingFill => event.contract_address.to_string(),
+        _ => match &buyer {
+            Some(buyer) => buyer.clone(),
+            None => return event.skip("a buyer"),
+        },
+    };
+    let remaining_token_amount = match event_type {
+        MarketplaceEventType::CollectionOfferFill => {
+            get_collection_bid_amount(transaction, &id, &subject.collection_id)
+        },
+        _ => None,
+    };
+
+    Some(ParsedMarketplaceEvent {
+        remaining_token_amount,
+        ..event.to_parsed(
+            event_type,
+            id,
+            subject,
+            event.decimal(&["/price", "/min_price", "/coin_amount"]),
+            event.decimal(&["/amount", "/token_amount"]),
+            seller,
+            buyer,
+        )
+    })
+}
+
+/// Tokens the collection bid of `buyer` can still buy after the transaction
+fn get_collection_bid_amount(
+    transaction: &Transaction,
+    buyer: &str,
+    collection_id: &str,
+) -> Option<BigDecimal> {
+    let changes = transaction
+        .info
+        .as_ref()
+        .map(|info| info.changes.as_slice())
+        .unwrap_or_default();
+    changes.iter().rev().find_map(|wsc| {
+        let Some(Change::WriteTableItem(table_item)) = wsc.change.as_ref() else {
+            return None;
+        };
+        if standardize_address(&table_item.handle) != TOPAZ_COLLECTION_BIDS_TABLE_HANDLE {
+            return None;
+        }
+        let bid: CollectionBid = serde_json::from_str(&table_item.data.as_ref()?.value).ok()?;
+        let bid_collection_id = CollectionDataIdType::new(bid.creator, bid.collection_name).to_id();
+        (standardize_address(&bid.buyer) == buyer && bid_collection_id == collection_id)
+            .then_some(bid.amount)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::test_utils::parse_fixture, *};
+    use crate::db::postgres::models::{
+        nft_marketplace_models::nft_marketplace_utils::MarketplaceParser,
+        token_models::token_utils::TokenDataIdType,
+    };
+
+    const CONTRACT: &str = "0x2c7bccf7b31baf770fdbcc768d9e9cb3d87805e255355df5db32ac9a669010a2";
+
+    #[test]
+    fn test_topaz_collection_bid_fill() {
+        let models = parse_fixture(
+            include_str!("fixtures/topaz_fill_collection_bid.json"),
+            MarketplaceParser::Topaz,
+            CONTRACT,
+        );
+
+        assert_eq!(models.activities.len(), 1);
+        let activity = &models.activities[0];
+        assert_eq!(activity.event_type, "collection_offer_fill");
+        assert_eq!(activity.event_index, 1);
+        assert_eq!(
+            activity.token_data_id,
+            Some(
+                TokenDataIdType::new(
+                    "0xc4e5".to_string(),
+                    "Aptos Monkeys".to_string(),
+                    "Monkey #42".to_string()
+                )
+                .to_id()
+            )
+        );
+        assert_eq!(activity.price, BigDecimal::from(150_000_000));
+        assert_eq!(activity.seller, Some(standardize_address("0x5e11")));
+        assert_eq!(activity.buyer, Some(standardize_address("0xb0b")));
+        assert_eq!(activity.fee_schedule_id, CONTRACT);
+
+        let offer = &models.current_collection_offers[0];
+        assert_eq!(offer.collection_offer_id, standardize_address("0xb0b"));
+        assert_eq!(
+            offer.collection_id,
+            CollectionDataIdType::new("0xc4e5".to_string(), "Aptos Monkeys".to_string()).to_id()
+        );
+        assert_eq!(offer.remaining_token_amount, BigDecimal::from(2));
+        assert!(!offer.is_deleted);
+        assert_eq!(
+            offer.coin_type.as_deref(),
+            Some("0x1::aptos_coin::AptosCoin")
+        );
+    }
+
+    #[test]
+    fn test_topaz_listing() {
+        let models = parse_fixture(
+            include_str!("fixtures/topaz_buy.json"),
+            MarketplaceParser::Topaz,
+            CONTRACT,
+        );
+
+        let activity = &models.activities[0];
+        assert_eq!(activity.event_type, "listing_fill");
+        assert_eq!(activity.token_standard, "v1");
+        assert_eq!(activity.property_version, Some(BigDecimal::from(0)));
+        let listing = &models.current_listings[0];
+        assert_eq!(listing.listing_id, CONTRACT);
+        assert_eq!(listing.seller, standardize_address("0x5e11"));
+        assert!(listing.is_deleted);
+    }
+}
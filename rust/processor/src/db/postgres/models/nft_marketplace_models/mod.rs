@@ -0,0 +1,9 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod current_nft_marketplace_collection_offers;
+pub mod current_nft_marketplace_listings;
+pub mod current_nft_marketplace_token_offers;
+pub mod marketplace_parsers;
+pub mod nft_marketplace_activities;
+pub mod nft_marketplace_utils;
@@ -0,0 +1,40 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::schema::nft_marketplace_activities;
+use bigdecimal::BigDecimal;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+/// One row per marketplace event: a listing, token offer or collection offer being placed,
+/// canceled or filled, or a bid on an auction.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize, PartialEq)]
+#[diesel(primary_key(transaction_version, event_index))]
+#[diesel(table_name = nft_marketplace_activities)]
+pub struct NftMarketplaceActivity {
+    pub transaction_version: i64,
+    pub event_index: i64,
+    pub offer_or_listing_id: String,
+    pub fee_schedule_id: String,
+    pub collection_id: String,
+    pub token_data_id: Option<String>,
+    pub creator_address: String,
+    pub collection_name: String,
+    pub token_name: Option<String>,
+    pub property_version: Option<BigDecimal>,
+    pub price: BigDecimal,
+    pub token_amount: BigDecimal,
+    pub token_standard: String,
+    pub seller: Option<String>,
+    pub buyer: Option<String>,
+    pub coin_type: Option<String>,
+    pub marketplace: String,
+    pub contract_address: String,
+    pub entry_function_id_str: Option<String>,
+    pub event_type: String,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
@@ -0,0 +1,1047 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parses the events of marketplaces built on the marketplace framework in aptos-core, which emits
+//! the same `events` module from every contract that deploys it. The events carry the token and
+//! collection, so listings and offers are tracked without looking anything up. Token and
+//! collection ids are derived the same way as in `parse_v2_token`: the object address for token
+//! v2 and the hash of creator, collection and name for token v1.
+//!
+//! Marketplaces that predate the framework have their own events, parsed in `marketplace_parsers`
+//! into the same rows.
+
+use super::{
+    current_nft_marketplace_collection_offers::CurrentNftMarketplaceCollectionOffer,
+    current_nft_marketplace_listings::CurrentNftMarketplaceListing,
+    current_nft_marketplace_token_offers::CurrentNftMarketplaceTokenOffer, marketplace_parsers,
+    nft_marketplace_activities::NftMarketplaceActivity,
+};
+use crate::{
+    db::{
+        common::models::token_v2_models::v2_token_utils::TokenStandard,
+        postgres::models::{
+            default_models::move_resources::MoveResource,
+            token_models::token_utils::{CollectionDataIdType, TokenDataIdType, NAME_LENGTH},
+        },
+    },
+    utils::util::{
+        deserialize_from_string, get_entry_function_from_user_request, parse_timestamp,
+        standardize_address, truncate_str,
+    },
+};
+use ahash::{AHashMap, AHashSet};
+use anyhow::Context;
+use aptos_protos::transaction::v1::{
+    transaction::TxnData, write_set_change::Change, Event, Transaction, UserTransaction,
+};
+use bigdecimal::{BigDecimal, Zero};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub const MARKETPLACE_NAME_LENGTH: usize = 100;
+pub const COIN_TYPE_LENGTH: usize = 1000;
+
+/// A marketplace contract to index.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct MarketplaceContract {
+    /// Stored in the `marketplace` column, e.g. `wapal`.
+    pub name: String,
+    pub contract_address: String,
+    /// How the events of the contract are parsed. Defaults to `framework`.
+    #[serde(default)]
+    pub parser: MarketplaceParser,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketplaceParser {
+    /// A deployment of the marketplace framework in aptos-core
+    #[default]
+    Framework,
+    Topaz,
+    Bluemove,
+    Okx,
+    Souffle,
+    Itsrare,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarketplaceEventType {
+    ListingPlace,
+    /// The price of a listing changed, only emitted by some marketplaces
+    ListingChange,
+    ListingCancel,
+    ListingFill,
+    TokenOfferPlace,
+    TokenOfferCancel,
+    TokenOfferFill,
+    CollectionOfferPlace,
+    CollectionOfferCancel,
+    CollectionOfferFill,
+    AuctionBid,
+}
+
+impl fmt::Display for MarketplaceEventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let res = match self {
+            MarketplaceEventType::ListingPlace => "listing_place",
+            MarketplaceEventType::ListingChange => "listing_change",
+            MarketplaceEventType::ListingCancel => "listing_cancel",
+            MarketplaceEventType::ListingFill => "listing_fill",
+            MarketplaceEventType::TokenOfferPlace => "token_offer_place",
+            MarketplaceEventType::TokenOfferCancel => "token_offer_cancel",
+            MarketplaceEventType::TokenOfferFill => "token_offer_fill",
+            MarketplaceEventType::CollectionOfferPlace => "collection_offer_place",
+            MarketplaceEventType::CollectionOfferCancel => "collection_offer_cancel",
+            MarketplaceEventType::CollectionOfferFill => "collection_offer_fill",
+            MarketplaceEventType::AuctionBid => "auction_bid",
+        };
+        write!(f, "{}", res)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ObjectReference {
+    inner: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct OptionalObject {
+    vec: Vec<ObjectReference>,
+}
+
+impl OptionalObject {
+    fn get_address(&self) -> Option<String> {
+        self.vec
+            .first()
+            .map(|object| standardize_address(&object.inner))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct BigDecimalWrapper(#[serde(deserialize_with = "deserialize_from_string")] pub BigDecimal);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct OptionalBigDecimal {
+    vec: Vec<BigDecimalWrapper>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenMetadata {
+    creator_address: String,
+    collection_name: String,
+    collection: OptionalObject,
+    token_name: String,
+    token: OptionalObject,
+    property_version: OptionalBigDecimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CollectionMetadata {
+    creator_address: String,
+    collection_name: String,
+    collection: OptionalObject,
+}
+
+/// The token or collection an event is about.
+#[derive(Debug, Clone)]
+pub struct NftSubject {
+    pub collection_id: String,
+    pub token_data_id: Option<String>,
+    pub creator_address: String,
+    pub collection_name: String,
+    pub token_name: Option<String>,
+    pub property_version: Option<BigDecimal>,
+    pub token_standard: TokenStandard,
+}
+
+impl NftSubject {
+    pub fn v1_token(token_data_id: &TokenDataIdType, property_version: Option<BigDecimal>) -> Self {
+        Self {
+            collection_id: token_data_id.get_collection_id(),
+            token_data_id: Some(token_data_id.to_id()),
+            creator_address: token_data_id.get_creator_address(),
+            collection_name: token_data_id.get_collection_trunc(),
+            token_name: Some(token_data_id.get_name_trunc()),
+            property_version,
+            token_standard: TokenStandard::V1,
+        }
+    }
+
+    pub fn v1_collection(creator_address: &str, collection_name: &str) -> Self {
+        collection_subject(creator_address, collection_name, &OptionalObject {
+            vec: vec![],
+        })
+    }
+}
+
+impl TokenMetadata {
+    fn subject(&self) -> NftSubject {
+        let token_data_id = match self.token.get_address() {
+            Some(token_address) => token_address,
+            None => TokenDataIdType::new(
+                self.creator_address.clone(),
+                self.collection_name.clone(),
+                self.token_name.clone(),
+            )
+            .to_id(),
+        };
+        NftSubject {
+            token_data_id: Some(token_data_id),
+            token_name: Some(truncate_str(&self.token_name, NAME_LENGTH)),
+            property_version: self.property_version.vec.first().map(|v| v.0.clone()),
+            // Only token v2 listings point to the token object
+            token_standard: if self.token.vec.is_empty() {
+                TokenStandard::V1
+            } else {
+                TokenStandard::V2
+            },
+            ..collection_subject(
+                &self.creator_address,
+                &self.collection_name,
+                &self.collection,
+            )
+        }
+    }
+}
+
+impl CollectionMetadata {
+    fn subject(&self) -> NftSubject {
+        collection_subject(
+            &self.creator_address,
+            &self.collection_name,
+            &self.collection,
+        )
+    }
+}
+
+fn collection_subject(
+    creator_address: &str,
+    collection_name: &str,
+    collection: &OptionalObject,
+) -> NftSubject {
+    let (collection_id, token_standard) = match collection.get_address() {
+        Some(collection_address) => (collection_address, TokenStandard::V2),
+        None => (
+            CollectionDataIdType::new(creator_address.to_string(), collection_name.to_string())
+                .to_id(),
+            TokenStandard::V1,
+        ),
+    };
+    NftSubject {
+        collection_id,
+        token_data_id: None,
+        creator_address: standardize_address(creator_address),
+        collection_name: truncate_str(collection_name, NAME_LENGTH),
+        token_name: None,
+        property_version: None,
+        token_standard,
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListingEvent {
+    listing: String,
+    seller: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    price: BigDecimal,
+    token_metadata: TokenMetadata,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListingFilledEvent {
+    listing: String,
+    seller: String,
+    purchaser: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    price: BigDecimal,
+    token_metadata: TokenMetadata,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenOfferEvent {
+    token_offer: String,
+    purchaser: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    price: BigDecimal,
+    token_metadata: TokenMetadata,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenOfferFilledEvent {
+    token_offer: String,
+    purchaser: String,
+    seller: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    price: BigDecimal,
+    token_metadata: TokenMetadata,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CollectionOfferPlacedEvent {
+    collection_offer: String,
+    purchaser: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    price: BigDecimal,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    token_amount: BigDecimal,
+    collection_metadata: CollectionMetadata,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CollectionOfferCanceledEvent {
+    collection_offer: String,
+    purchaser: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    price: BigDecimal,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    remaining_token_amount: BigDecimal,
+    collection_metadata: CollectionMetadata,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CollectionOfferFilledEvent {
+    collection_offer: String,
+    purchaser: String,
+    seller: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    price: BigDecimal,
+    token_metadata: TokenMetadata,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuctionBidEvent {
+    listing: String,
+    new_bidder: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    new_bid: BigDecimal,
+    token_metadata: TokenMetadata,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum MarketplaceEvent {
+    ListingPlacedEvent(ListingEvent),
+    ListingCanceledEvent(ListingEvent),
+    ListingFilledEvent(ListingFilledEvent),
+    TokenOfferPlacedEvent(TokenOfferEvent),
+    TokenOfferCanceledEvent(TokenOfferEvent),
+    TokenOfferFilledEvent(TokenOfferFilledEvent),
+    CollectionOfferPlacedEvent(CollectionOfferPlacedEvent),
+    CollectionOfferCanceledEvent(CollectionOfferCanceledEvent),
+    CollectionOfferFilledEvent(CollectionOfferFilledEvent),
+    AuctionBidEvent(AuctionBidEvent),
+}
+
+impl MarketplaceEvent {
+    /// Parses `event` if it is emitted by the `events` module of `contract_address`.
+    pub fn from_event(
+        event: &Event,
+        contract_address: &str,
+        txn_version: i64,
+    ) -> anyhow::Result<Option<Self>> {
+        let mut parts = event.type_str.splitn(3, "::");
+        let (Some(address), Some("events"), Some(name)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Ok(None);
+        };
+        if standardize_address(address) != contract_address {
+            return Ok(None);
+        }
+        let data = event.data.as_str();
+        match name {
+            "ListingPlacedEvent" => serde_json::from_str(data).map(Self::ListingPlacedEvent),
+            "ListingCanceledEvent" => serde_json::from_str(data).map(Self::ListingCanceledEvent),
+            "ListingFilledEvent" => serde_json::from_str(data).map(Self::ListingFilledEvent),
+            "TokenOfferPlacedEvent" => serde_json::from_str(data).map(Self::TokenOfferPlacedEvent),
+            "TokenOfferCanceledEvent" => {
+                serde_json::from_str(data).map(Self::TokenOfferCanceledEvent)
+            },
+            "TokenOfferFilledEvent" => serde_json::from_str(data).map(Self::TokenOfferFilledEvent),
+            "CollectionOfferPlacedEvent" => {
+                serde_json::from_str(data).map(Self::CollectionOfferPlacedEvent)
+            },
+            "CollectionOfferCanceledEvent" => {
+                serde_json::from_str(data).map(Self::CollectionOfferCanceledEvent)
+            },
+            "CollectionOfferFilledEvent" => {
+                serde_json::from_str(data).map(Self::CollectionOfferFilledEvent)
+            },
+            "AuctionBidEvent" => serde_json::from_str(data).map(Self::AuctionBidEvent),
+            _ => return Ok(None),
+        }
+        .map(Some)
+        .context(format!(
+            "version {} failed! failed to parse type {}, data {:?}",
+            txn_version, event.type_str, data
+        ))
+    }
+}
+
+/// State of the offers that isn't part of the events, from the resources written in the same
+/// transaction.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct TokenOfferResource {
+    #[serde(deserialize_with = "deserialize_from_string")]
+    expiration_time: BigDecimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CollectionOfferResource {
+    #[serde(deserialize_with = "deserialize_from_string")]
+    expiration_time: BigDecimal,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    remaining: BigDecimal,
+}
+
+/// Resources of one marketplace written or deleted in a transaction, keyed by the address of the
+/// listing or offer.
+#[derive(Default)]
+struct MarketplaceResources {
+    /// The generic type of e.g. `coin_listing::FixedPriceListing<CoinType>`
+    coin_types: AHashMap<String, String>,
+    token_offers: AHashMap<String, TokenOfferResource>,
+    collection_offers: AHashMap<String, CollectionOfferResource>,
+    deleted_collection_offers: AHashSet<String>,
+}
+
+impl MarketplaceResources {
+    fn from_transaction(
+        transaction: &Transaction,
+        contract_address: &str,
+        txn_version: i64,
+    ) -> anyhow::Result<Self> {
+        let mut resources = Self::default();
+        let changes = transaction
+            .info
+            .as_ref()
+            .map(|info| info.changes.as_slice())
+            .unwrap_or_default();
+        for wsc in changes {
+            let (address, type_str, outer_type) = match wsc.change.as_ref() {
+                Some(Change::WriteResource(resource)) => (
+                    &resource.address,
+                    &resource.type_str,
+                    MoveResource::get_outer_type_from_write_resource(resource),
+                ),
+                Some(Change::DeleteResource(resource)) => (
+                    &resource.address,
+                    &resource.type_str,
+                    MoveResource::get_outer_type_from_delete_resource(resource),
+                ),
+                _ => continue,
+            };
+            let Some(resource_type) = outer_type
+                .strip_prefix(contract_address)
+                .and_then(|t| t.strip_prefix("::"))
+            else {
+                continue;
+            };
+            let address = standardize_address(address);
+            if let Some(coin_type) = type_str
+                .split_once('<')
+                .and_then(|(_, generics)| generics.strip_suffix('>'))
+            {
+                resources
+                    .coin_types
+                    .insert(address.clone(), truncate_str(coin_type, COIN_TYPE_LENGTH));
+            }
+            match (wsc.change.as_ref(), resource_type) {
+                (Some(Change::WriteResource(resource)), "token_offer::TokenOffer") => {
+                    let offer = serde_json::from_str(&resource.data).context(format!(
+                        "version {} failed! failed to parse token offer {:?}",
+                        txn_version, resource.data
+                    ))?;
+                    resources.token_offers.insert(address, offer);
+                },
+                (Some(Change::WriteResource(resource)), "collection_offer::CollectionOffer") => {
+                    let offer = serde_json::from_str(&resource.data).context(format!(
+                        "version {} failed! failed to parse collection offer {:?}",
+                        txn_version, resource.data
+                    ))?;
+                    resources.collection_offers.insert(address, offer);
+                },
+                (Some(Change::DeleteResource(_)), "collection_offer::CollectionOffer") => {
+                    resources.deleted_collection_offers.insert(address);
+                },
+                _ => {},
+            }
+        }
+        Ok(resources)
+    }
+}
+
+/// Fields shared by every row written for a transaction.
+struct MarketplaceTransaction<'a> {
+    marketplace: &'a str,
+    contract_address: &'a str,
+    entry_function_id_str: Option<String>,
+    txn_version: i64,
+    txn_timestamp: chrono::NaiveDateTime,
+}
+
+/// Rows produced from the events of one transaction.
+#[derive(Debug, Default)]
+pub struct NftMarketplaceModels {
+    pub activities: Vec<NftMarketplaceActivity>,
+    pub current_listings: Vec<CurrentNftMarketplaceListing>,
+    pub current_token_offers: Vec<CurrentNftMarketplaceTokenOffer>,
+    pub current_collection_offers: Vec<CurrentNftMarketplaceCollectionOffer>,
+}
+
+/// Parses the marketplace events of a user transaction. `contract_address` of every marketplace
+/// must be standardized.
+pub fn parse_nft_marketplace_transaction(
+    transaction: &Transaction,
+    marketplaces: &[MarketplaceContract],
+) -> anyhow::Result<NftMarketplaceModels> {
+    let mut models = NftMarketplaceModels::default();
+    let Some(TxnData::User(user_txn)) = transaction.txn_data.as_ref() else {
+        return Ok(models);
+    };
+    let txn_version = transaction.version as i64;
+    let txn_timestamp = parse_timestamp(
+        transaction
+            .timestamp
+            .as_ref()
+            .context("Transaction timestamp doesn't exist!")?,
+        txn_version,
+    );
+    let entry_function_id_str = user_txn
+        .request
+        .as_ref()
+        .and_then(get_entry_function_from_user_request);
+
+    for marketplace in marketplaces {
+        let events = match marketplace.parser {
+            MarketplaceParser::Framework => parse_framework_events(
+                transaction,
+                user_txn,
+                &marketplace.contract_address,
+                txn_version,
+            )?,
+            parser => marketplace_parsers::parse_events(
+                parser,
+                transaction,
+                user_txn,
+                &marketplace.contract_address,
+                txn_version,
+            )?,
+        };
+        let txn = MarketplaceTransaction {
+            marketplace: &marketplace.name,
+            contract_address: &marketplace.contract_address,
+            entry_function_id_str: entry_function_id_str.clone(),
+            txn_version,
+            txn_timestamp,
+        };
+        for (event_index, event) in events {
+            txn.add_event(&mut models, event_index as i64, event);
+        }
+    }
+    Ok(models)
+}
+
+/// A marketplace event in the terms of the tables, whichever contract emitted it.
+#[derive(Debug, Clone)]
+pub struct ParsedMarketplaceEvent {
+    pub event_type: MarketplaceEventType,
+    /// Address of the listing or offer, or what stands in for it on contracts that don't have one
+    pub id: String,
+    pub fee_schedule_id: String,
+    pub subject: NftSubject,
+    pub price: BigDecimal,
+    pub token_amount: BigDecimal,
+    pub seller: Option<String>,
+    pub buyer: Option<String>,
+    pub coin_type: Option<String>,
+    pub expiration_time: Option<BigDecimal>,
+    /// Tokens a collection offer can still buy after the event, if known
+    pub remaining_token_amount: Option<BigDecimal>,
+}
+
+/// Events of the framework deployed at `contract_address`, with their index in the transaction.
+/// Events that fail to parse are logged and skipped, like for the other marketplaces.
+fn parse_framework_events(
+    transaction: &Transaction,
+    user_txn: &UserTransaction,
+    contract_address: &str,
+    txn_version: i64,
+) -> anyhow::Result<Vec<(usize, ParsedMarketplaceEvent)>> {
+    let events: Vec<(usize, MarketplaceEvent, String)> = user_txn
+        .events
+        .iter()
+        .enumerate()
+        .filter_map(|(event_index, event)| {
+            let parsed = match MarketplaceEvent::from_event(event, contract_address, txn_version) {
+                Ok(parsed) => parsed?,
+                Err(e) => {
+                    tracing::warn!(
+                        transaction_version = txn_version,
+                        event_index = event_index,
+                        error = ?e,
+                        "Skipping nft marketplace event that failed to parse"
+                    );
+                    return None;
+                },
+            };
+            // The framework emits the events from the fee schedule of the listing
+            let fee_schedule_id = event
+                .key
+                .as_ref()
+                .map(|key| standardize_address(&key.account_address))
+                .unwrap_or_default();
+            Some((event_index, parsed, fee_schedule_id))
+        })
+        .collect();
+    if events.is_empty() {
+        return Ok(vec![]);
+    }
+    let resources =
+        MarketplaceResources::from_transaction(transaction, contract_address, txn_version)?;
+    Ok(events
+        .into_iter()
+        .map(|(event_index, event, fee_schedule_id)| {
+            (event_index, event.into_parsed(&resources, fee_schedule_id))
+        })
+        .collect())
+}
+
+impl MarketplaceEvent {
+    fn into_parsed(
+        self,
+        resources: &MarketplaceResources,
+        fee_schedule_id: String,
+    ) -> ParsedMarketplaceEvent {
+        let one = BigDecimal::from(1);
+        let (event_type, id, subject, price, token_amount, seller, buyer) = match self {
+            MarketplaceEvent::ListingPlacedEvent(e) => (
+                MarketplaceEventType::ListingPlace,
+                e.listing,
+                e.token_metadata.subject(),
+                e.price,
+                one,
+                Some(e.seller),
+                None,
+            ),
+            MarketplaceEvent::ListingCanceledEvent(e) => (
+                MarketplaceEventType::ListingCancel,
+                e.listing,
+                e.token_metadata.subject(),
+                e.price,
+                one,
+                Some(e.seller),
+                None,
+            ),
+            MarketplaceEvent::ListingFilledEvent(e) => (
+                MarketplaceEventType::ListingFill,
+                e.listing,
+                e.token_metadata.subject(),
+                e.price,
+                one,
+                Some(e.seller),
+                Some(e.purchaser),
+            ),
+            MarketplaceEvent::TokenOfferPlacedEvent(e) => (
+                MarketplaceEventType::TokenOfferPlace,
+                e.token_offer,
+                e.token_metadata.subject(),
+                e.price,
+                one,
+                None,
+                Some(e.purchaser),
+            ),
+            MarketplaceEvent::TokenOfferCanceledEvent(e) => (
+                MarketplaceEventType::TokenOfferCancel,
+                e.token_offer,
+                e.token_metadata.subject(),
+                e.price,
+                one,
+                None,
+                Some(e.purchaser),
+            ),
+            MarketplaceEvent::TokenOfferFilledEvent(e) => (
+                MarketplaceEventType::TokenOfferFill,
+                e.token_offer,
+                e.token_metadata.subject(),
+                e.price,
+                one,
+                Some(e.seller),
+                Some(e.purchaser),
+            ),
+            MarketplaceEvent::CollectionOfferPlacedEvent(e) => (
+                MarketplaceEventType::CollectionOfferPlace,
+                e.collection_offer,
+                e.collection_metadata.subject(),
+                e.price,
+                e.token_amount,
+                None,
+                Some(e.purchaser),
+            ),
+            MarketplaceEvent::CollectionOfferCanceledEvent(e) => (
+                MarketplaceEventType::CollectionOfferCancel,
+                e.collection_offer,
+                e.collection_metadata.subject(),
+                e.price,
+                e.remaining_token_amount,
+                None,
+                Some(e.purchaser),
+            ),
+            MarketplaceEvent::CollectionOfferFilledEvent(e) => (
+                MarketplaceEventType::CollectionOfferFill,
+                e.collection_offer,
+                e.token_metadata.subject(),
+                e.price,
+                one,
+                Some(e.seller),
+                Some(e.purchaser),
+            ),
+            MarketplaceEvent::AuctionBidEvent(e) => (
+                MarketplaceEventType::AuctionBid,
+                e.listing,
+                e.token_metadata.subject(),
+                e.new_bid,
+                one,
+                None,
+                Some(e.new_bidder),
+            ),
+        };
+        let id = standardize_address(&id);
+        let expiration_time = match event_type {
+            MarketplaceEventType::TokenOfferPlace
+            | MarketplaceEventType::TokenOfferCancel
+            | MarketplaceEventType::TokenOfferFill => resources
+                .token_offers
+                .get(&id)
+                .map(|offer| offer.expiration_time.clone()),
+            _ => resources
+                .collection_offers
+                .get(&id)
+                .map(|offer| offer.expiration_time.clone()),
+        };
+        // A fill leaves a collection offer in place until the last token is filled
+        let remaining_token_amount = if resources.deleted_collection_offers.contains(&id) {
+            Some(BigDecimal::zero())
+        } else {
+            resources
+                .collection_offers
+                .get(&id)
+                .map(|offer| offer.remaining.clone())
+        };
+        ParsedMarketplaceEvent {
+            event_type,
+            coin_type: resources.coin_types.get(&id).cloned(),
+            id,
+            fee_schedule_id,
+            subject,
+            price,
+            token_amount,
+            seller: seller.as_deref().map(standardize_address),
+            buyer: buyer.as_deref().map(standardize_address),
+            expiration_time,
+            remaining_token_amount,
+        }
+    }
+}
+
+impl MarketplaceTransaction<'_> {
+    fn add_event(
+        &self,
+        models: &mut NftMarketplaceModels,
+        event_index: i64,
+        event: ParsedMarketplaceEvent,
+    ) {
+        let ParsedMarketplaceEvent {
+            event_type,
+            id,
+            fee_schedule_id,
+            subject,
+            price,
+            token_amount,
+            seller,
+            buyer,
+            coin_type,
+            expiration_time,
+            remaining_token_amount,
+        } = event;
+
+        match event_type {
+            MarketplaceEventType::ListingPlace
+            | MarketplaceEventType::ListingChange
+            | MarketplaceEventType::ListingCancel
+            | MarketplaceEventType::ListingFill => {
+                let is_deleted = matches!(
+                    event_type,
+                    MarketplaceEventType::ListingCancel | MarketplaceEventType::ListingFill
+                );
+                models.current_listings.push(CurrentNftMarketplaceListing {
+                    listing_id: id.clone(),
+                    token_data_id: subject.token_data_id.clone().unwrap_or_default(),
+                    collection_id: subject.collection_id.clone(),
+                    fee_schedule_id: fee_schedule_id.clone(),
+                    seller: seller.clone().unwrap_or_default(),
+                    price: price.clone(),
+                    token_amount: if is_deleted {
+                        BigDecimal::zero()
+                    } else {
+                        token_amount.clone()
+                    },
+                    token_standard: subject.token_standard.to_string(),
+                    is_deleted,
+                    coin_type: coin_type.clone(),
+                    marketplace: self.marketplace_name(),
+                    contract_address: self.contract_address.to_string(),
+                    entry_function_id_str: self.entry_function_id_str.clone(),
+                    last_transaction_version: self.txn_version,
+                    last_transaction_timestamp: self.txn_timestamp,
+                });
+            },
+            MarketplaceEventType::TokenOfferPlace
+            | MarketplaceEventType::TokenOfferCancel
+            | MarketplaceEventType::TokenOfferFill => {
+                models
+                    .current_token_offers
+                    .push(CurrentNftMarketplaceTokenOffer {
+                        offer_id: id.clone(),
+                        token_data_id: subject.token_data_id.clone().unwrap_or_default(),
+                        collection_id: subject.collection_id.clone(),
+                        fee_schedule_id: fee_schedule_id.clone(),
+                        buyer: buyer.clone().unwrap_or_default(),
+                        price: price.clone(),
+                        token_amount: token_amount.clone(),
+                        expiration_time: expiration_time.clone(),
+                        is_deleted: event_type != MarketplaceEventType::TokenOfferPlace,
+                        token_standard: subject.token_standard.to_string(),
+                        coin_type: coin_type.clone(),
+                        marketplace: self.marketplace_name(),
+                        contract_address: self.contract_address.to_string(),
+                        entry_function_id_str: self.entry_function_id_str.clone(),
+                        last_transaction_version: self.txn_version,
+                        last_transaction_timestamp: self.txn_timestamp,
+                    });
+            },
+            MarketplaceEventType::CollectionOfferPlace
+            | MarketplaceEventType::CollectionOfferCancel
+            | MarketplaceEventType::CollectionOfferFill => {
+                let remaining_token_amount = match (event_type, remaining_token_amount) {
+                    (MarketplaceEventType::CollectionOfferCancel, _) => BigDecimal::zero(),
+                    (_, Some(remaining)) => remaining,
+                    (MarketplaceEventType::CollectionOfferPlace, None) => token_amount.clone(),
+                    _ => BigDecimal::zero(),
+                };
+                let is_deleted = event_type != MarketplaceEventType::CollectionOfferPlace
+                    && remaining_token_amount.is_zero();
+                models
+                    .current_collection_offers
+                    .push(CurrentNftMarketplaceCollectionOffer {
+                        collection_offer_id: id.clone(),
+                        collection_id: subject.collection_id.clone(),
+                        fee_schedule_id: fee_schedule_id.clone(),
+                        buyer: buyer.clone().unwrap_or_default(),
+                        item_price: price.clone(),
+                        remaining_token_amount,
+                        expiration_time: expiration_time.clone(),
+                        is_deleted,
+                        token_standard: subject.token_standard.to_string(),
+                        coin_type: coin_type.clone(),
+                        marketplace: self.marketplace_name(),
+                        contract_address: self.contract_address.to_string(),
+                        entry_function_id_str: self.entry_function_id_str.clone(),
+                        last_transaction_version: self.txn_version,
+                        last_transaction_timestamp: self.txn_timestamp,
+                    });
+            },
+            // Bids only change the auction, which isn't tracked beyond its listing
+            MarketplaceEventType::AuctionBid => {},
+        }
+
+        models.activities.push(NftMarketplaceActivity {
+            transaction_version: self.txn_version,
+            event_index,
+            offer_or_listing_id: id,
+            fee_schedule_id,
+            collection_id: subject.collection_id,
+            token_data_id: subject.token_data_id,
+            creator_address: subject.creator_address,
+            collection_name: subject.collection_name,
+            token_name: subject.token_name,
+            property_version: subject.property_version,
+            price,
+            token_amount,
+            token_standard: subject.token_standard.to_string(),
+            seller,
+            buyer,
+            coin_type,
+            marketplace: self.marketplace_name(),
+            contract_address: self.contract_address.to_string(),
+            entry_function_id_str: self.entry_function_id_str.clone(),
+            event_type: event_type.to_string(),
+            transaction_timestamp: self.txn_timestamp,
+        });
+    }
+
+    fn marketplace_name(&self) -> String {
+        truncate_str(self.marketplace, MARKETPLACE_NAME_LENGTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_protos::{
+        transaction::v1::{
+            DeleteResource, EventKey, MoveStructTag, TransactionInfo, UserTransaction,
+            WriteSetChange,
+        },
+        util::timestamp::Timestamp,
+    };
+
+    const CONTRACT: &str = "0x00000000000000000000000000000000000000000000000000000000000000aa";
+
+    fn event(name: &str, data: serde_json::Value) -> Event {
+        Event {
+            key: Some(EventKey {
+                creation_number: 0,
+                account_address: "0xfee".to_string(),
+            }),
+            type_str: format!("{}::events::{}", CONTRACT, name),
+            data: data.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn token_metadata() -> serde_json::Value {
+        serde_json::json!({
+            "creator_address": "0xc",
+            "collection_name": "Collection",
+            "collection": {"vec": [{"inner": "0xc0"}]},
+            "token_name": "Token",
+            "token": {"vec": [{"inner": "0x70"}]},
+            "property_version": {"vec": []},
+        })
+    }
+
+    fn transaction(events: Vec<Event>, changes: Vec<WriteSetChange>) -> Transaction {
+        Transaction {
+            version: 100,
+            timestamp: Some(Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 0,
+            }),
+            info: Some(TransactionInfo {
+                changes,
+                ..Default::default()
+            }),
+            txn_data: Some(TxnData::User(UserTransaction {
+                request: None,
+                events,
+            })),
+            ..Default::default()
+        }
+    }
+
+    fn marketplaces() -> Vec<MarketplaceContract> {
+        vec![MarketplaceContract {
+            name: "example".to_string(),
+            contract_address: CONTRACT.to_string(),
+            parser: MarketplaceParser::Framework,
+        }]
+    }
+
+    #[test]
+    fn test_listing_fill() {
+        let txn = transaction(
+            vec![
+                event(
+                    "ListingFilledEvent",
+                    serde_json::json!({
+                        "type": "fixed price",
+                        "listing": "0x1157",
+                        "seller": "0x5e",
+                        "purchaser": "0xb0",
+                        "price": "1000",
+                        "commission": "10",
+                        "royalties": "20",
+                        "token_metadata": token_metadata(),
+                    }),
+                ),
+                // Other contracts are ignored
+                Event {
+                    type_str: "0xbb::events::ListingFilledEvent".to_string(),
+                    ..Default::default()
+                },
+            ],
+            vec![WriteSetChange {
+                change: Some(Change::DeleteResource(DeleteResource {
+                    address: "0x1157".to_string(),
+                    r#type: Some(MoveStructTag {
+                        address: CONTRACT.to_string(),
+                        module: "coin_listing".to_string(),
+                        name: "FixedPriceListing".to_string(),
+                        ..Default::default()
+                    }),
+                    type_str: format!(
+                        "{}::coin_listing::FixedPriceListing<0x1::aptos_coin::AptosCoin>",
+                        CONTRACT
+                    ),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            }],
+        );
+        let models = parse_nft_marketplace_transaction(&txn, &marketplaces()).unwrap();
+
+        assert_eq!(models.activities.len(), 1);
+        let activity = &models.activities[0];
+        assert_eq!(activity.event_type, "listing_fill");
+        assert_eq!(activity.offer_or_listing_id, standardize_address("0x1157"));
+        assert_eq!(activity.fee_schedule_id, standardize_address("0xfee"));
+        assert_eq!(activity.token_data_id, Some(standardize_address("0x70")));
+        assert_eq!(activity.collection_id, standardize_address("0xc0"));
+        assert_eq!(activity.token_standard, "v2");
+        assert_eq!(activity.buyer, Some(standardize_address("0xb0")));
+        assert_eq!(
+            activity.coin_type.as_deref(),
+            Some("0x1::aptos_coin::AptosCoin")
+        );
+
+        assert_eq!(models.current_listings.len(), 1);
+        let listing = &models.current_listings[0];
+        assert!(listing.is_deleted);
+        assert_eq!(listing.token_amount, BigDecimal::zero());
+        assert_eq!(listing.price, BigDecimal::from(1000));
+    }
+
+    #[test]
+    fn test_collection_offer_v1() {
+        let txn = transaction(
+            vec![event(
+                "CollectionOfferPlacedEvent",
+                serde_json::json!({
+                    "collection_offer": "0x0ff",
+                    "purchaser": "0xb0",
+                    "price": "50",
+                    "token_amount": "3",
+                    "collection_metadata": {
+                        "creator_address": "0xc",
+                        "collection_name": "Collection",
+                        "collection": {"vec": []},
+                    },
+                }),
+            )],
+            vec![],
+        );
+        let models = parse_nft_marketplace_transaction(&txn, &marketplaces()).unwrap();
+
+        let offer = &models.current_collection_offers[0];
+        assert_eq!(
+            offer.collection_id,
+            CollectionDataIdType::new("0xc".to_string(), "Collection".to_string()).to_id()
+        );
+        assert_eq!(offer.token_standard, "v1");
+        assert_eq!(offer.remaining_token_amount, BigDecimal::from(3));
+        assert!(!offer.is_deleted);
+        assert_eq!(models.activities[0].token_data_id, None);
+    }
+}
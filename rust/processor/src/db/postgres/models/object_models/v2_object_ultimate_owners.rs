@@ -0,0 +1,44 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    db::common::models::object_models::raw_v2_object_ultimate_owners::{
+        CurrentObjectUltimateOwnerConvertible, RawCurrentObjectUltimateOwner,
+    },
+    schema::current_object_ultimate_owners,
+};
+use diesel::prelude::*;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(object_address))]
+#[diesel(table_name = current_object_ultimate_owners)]
+pub struct CurrentObjectUltimateOwner {
+    pub object_address: String,
+    pub direct_owner_address: String,
+    pub ultimate_owner_address: String,
+    pub ownership_path: Value,
+    pub depth: i32,
+    pub is_deleted: bool,
+    pub last_transaction_version: i64,
+}
+
+impl CurrentObjectUltimateOwnerConvertible for CurrentObjectUltimateOwner {
+    fn from_raw(raw_item: RawCurrentObjectUltimateOwner) -> Self {
+        Self {
+            object_address: raw_item.object_address,
+            direct_owner_address: raw_item.direct_owner_address,
+            ultimate_owner_address: raw_item.ultimate_owner_address,
+            ownership_path: raw_item.ownership_path,
+            depth: raw_item.depth,
+            is_deleted: raw_item.is_deleted,
+            last_transaction_version: raw_item.last_transaction_version,
+        }
+    }
+}
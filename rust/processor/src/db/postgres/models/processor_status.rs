@@ -3,7 +3,10 @@
 
 #![allow(clippy::extra_unused_lifetimes)]
 
-use crate::{schema::processor_status, utils::database::DbPoolConnection};
+use crate::{
+    schema::{processor_status, processor_status_history},
+    utils::database::DbPoolConnection,
+};
 use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
 use diesel_async::RunQueryDsl;
 
@@ -38,3 +41,16 @@ impl ProcessorStatusQuery {
             .optional()
     }
 }
+
+#[derive(Clone, Debug, Insertable, PartialEq)]
+#[diesel(table_name = processor_status_history)]
+/// A sample of the progress of a processor, see `utils::status_history`.
+pub struct ProcessorStatusHistory {
+    pub processor: String,
+    pub sampled_at: chrono::NaiveDateTime,
+    pub last_success_version: i64,
+    pub last_transaction_timestamp: Option<chrono::NaiveDateTime>,
+    pub versions_processed: i64,
+    pub tps: f64,
+    pub avg_checkpoint_lag_ms: Option<i64>,
+}
@@ -5,7 +5,7 @@ use crate::db::{
     common::models::{
         object_models::v2_object_utils::{ObjectCore, Untransferable},
         token_v2_models::v2_token_utils::{
-            AptosCollection, Collection, ConcurrentSupply, FixedSupply, PropertyMapModel,
+            AptosCollection, Collection, ConcurrentSupply, FixedSupply, PropertyMapModel, Royalty,
             TokenIdentifiers, TokenV2, UnlimitedSupply,
         },
     },
@@ -44,6 +44,7 @@ pub const TYPE_APOTS_COLLECTION: &str = formatcp!("{TOKEN_V2_ADDR}::aptos_token:
 pub const TYPE_TOKEN_V2: &str = formatcp!("{TOKEN_V2_ADDR}::token::Token");
 pub const TYPE_TOKEN_IDENTIFIERS: &str = formatcp!("{TOKEN_V2_ADDR}::token::TokenIdentifiers");
 pub const TYPE_PROPERTY_MAP: &str = formatcp!("{TOKEN_V2_ADDR}::property_map::PropertyMap");
+pub const TYPE_ROYALTY: &str = formatcp!("{TOKEN_V2_ADDR}::royalty::Royalty");
 
 pub trait Resource {
     fn type_str() -> &'static str;
@@ -172,6 +173,12 @@ impl Resource for PropertyMapModel {
     }
 }
 
+impl Resource for Royalty {
+    fn type_str() -> &'static str {
+        TYPE_ROYALTY
+    }
+}
+
 impl Resource for TokenIdentifiers {
     fn type_str() -> &'static str {
         TYPE_TOKEN_IDENTIFIERS
@@ -1,9 +1,15 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod token_royalty;
+pub mod token_v1_to_v2_mappings;
 pub mod v1_token_royalty;
+pub mod v2_collection_stats;
 pub mod v2_collections;
+pub mod v2_nft_metadata;
 pub mod v2_token_activities;
 pub mod v2_token_datas;
 pub mod v2_token_metadata;
 pub mod v2_token_ownerships;
+pub mod v2_token_property_mutations;
+pub mod v2_token_transferability;
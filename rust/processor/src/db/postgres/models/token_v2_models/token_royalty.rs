@@ -0,0 +1,49 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    db::common::models::token_v2_models::raw_token_royalty::{
+        CurrentTokenRoyaltyConvertible, RawCurrentTokenRoyalty,
+    },
+    schema::current_token_royalty,
+};
+use bigdecimal::BigDecimal;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize, PartialEq, Eq,
+)]
+#[diesel(primary_key(token_or_collection_id))]
+#[diesel(table_name = current_token_royalty)]
+pub struct CurrentTokenRoyalty {
+    pub token_or_collection_id: String,
+    pub source_level: String,
+    pub token_standard: String,
+    pub payee_address: String,
+    pub royalty_points_numerator: BigDecimal,
+    pub royalty_points_denominator: BigDecimal,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+    pub is_deleted: bool,
+}
+
+impl CurrentTokenRoyaltyConvertible for CurrentTokenRoyalty {
+    fn from_raw(raw_item: RawCurrentTokenRoyalty) -> Self {
+        Self {
+            token_or_collection_id: raw_item.token_or_collection_id,
+            source_level: raw_item.source_level,
+            token_standard: raw_item.token_standard,
+            payee_address: raw_item.payee_address,
+            royalty_points_numerator: raw_item.royalty_points_numerator,
+            royalty_points_denominator: raw_item.royalty_points_denominator,
+            last_transaction_version: raw_item.last_transaction_version,
+            last_transaction_timestamp: raw_item.last_transaction_timestamp,
+            is_deleted: raw_item.is_deleted,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    db::common::models::token_v2_models::raw_token_v1_to_v2_mappings::{
+        RawTokenV1ToV2Mapping, TokenV1ToV2MappingConvertible,
+    },
+    schema::token_v1_to_v2_mappings,
+};
+use bigdecimal::BigDecimal;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize, PartialEq, Eq,
+)]
+#[diesel(primary_key(token_data_id_v2))]
+#[diesel(table_name = token_v1_to_v2_mappings)]
+pub struct TokenV1ToV2Mapping {
+    pub token_data_id_v2: String,
+    pub token_data_id_v1: String,
+    pub property_version_v1: BigDecimal,
+    pub collection_id_v1: String,
+    pub collection_id_v2: String,
+    pub token_name: String,
+    pub transaction_version: i64,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl TokenV1ToV2MappingConvertible for TokenV1ToV2Mapping {
+    fn from_raw(raw_item: RawTokenV1ToV2Mapping) -> Self {
+        Self {
+            token_data_id_v2: raw_item.token_data_id_v2,
+            token_data_id_v1: raw_item.token_data_id_v1,
+            property_version_v1: raw_item.property_version_v1,
+            collection_id_v1: raw_item.collection_id_v1,
+            collection_id_v2: raw_item.collection_id_v2,
+            token_name: raw_item.token_name,
+            transaction_version: raw_item.transaction_version,
+            transaction_timestamp: raw_item.transaction_timestamp,
+        }
+    }
+}
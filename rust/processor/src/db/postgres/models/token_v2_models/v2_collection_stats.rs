@@ -0,0 +1,735 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+//! Aggregates of every collection, maintained incrementally from the token v2 models of each
+//! batch. The changes of a batch are applied to the state read right before the batch is written,
+//! so they must be written in order by a single writer. Every row keeps the last version applied
+//! to it, so a batch that is processed again after a restart doesn't count twice.
+
+use crate::{
+    db::common::models::token_v2_models::{
+        raw_v2_token_activities::RawTokenActivityV2, raw_v2_token_datas::RawTokenDataV2,
+        raw_v2_token_ownerships::RawTokenOwnershipV2,
+    },
+    schema::{
+        collection_hourly_transfers, current_collection_owners, current_collection_stats,
+        current_token_datas_v2, current_token_ownerships_v2,
+    },
+    utils::database::DbPoolConnection,
+};
+use ahash::{AHashMap, AHashSet};
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{Duration, NaiveDateTime, Timelike};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+const MINT_EVENT_TYPES: [&str; 4] = [
+    "0x3::token::MintTokenEvent",
+    "0x3::token::Mint",
+    "0x4::collection::MintEvent",
+    "0x4::collection::Mint",
+];
+const BURN_EVENT_TYPES: [&str; 4] = [
+    "0x3::token::BurnTokenEvent",
+    "0x3::token::Burn",
+    "0x4::collection::BurnEvent",
+    "0x4::collection::Burn",
+];
+const TRANSFER_EVENT_TYPES: [&str; 4] = [
+    "0x1::object::TransferEvent",
+    "0x1::object::Transfer",
+    "0x3::token::DepositEvent",
+    "0x3::token::TokenDeposit",
+];
+
+/// The longest window of the `current_collection_transfer_counts` view. Older hours are pruned.
+fn transfer_window_7d() -> Duration {
+    Duration::days(7)
+}
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize, PartialEq)]
+#[diesel(primary_key(collection_id))]
+#[diesel(table_name = current_collection_stats)]
+pub struct CurrentCollectionStats {
+    pub collection_id: String,
+    pub distinct_owners: i64,
+    pub total_tokens_owned: BigDecimal,
+    pub burned_count: BigDecimal,
+    pub last_mint_version: Option<i64>,
+    pub last_transfer_version: Option<i64>,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize, PartialEq)]
+#[diesel(primary_key(collection_id, owner_address))]
+#[diesel(table_name = current_collection_owners)]
+pub struct CurrentCollectionOwner {
+    pub collection_id: String,
+    pub owner_address: String,
+    pub token_amount: BigDecimal,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize, PartialEq)]
+#[diesel(primary_key(collection_id, hour))]
+#[diesel(table_name = collection_hourly_transfers)]
+pub struct CollectionHourlyTransfers {
+    pub collection_id: String,
+    pub hour: NaiveDateTime,
+    pub transfer_count: i64,
+    pub last_transaction_version: i64,
+}
+
+/// token_data_id, property_version_v1, owner_address, storage_id
+type OwnershipKey = (String, BigDecimal, String, String);
+
+#[derive(Clone, Debug)]
+struct OwnershipChange {
+    amount: BigDecimal,
+    transaction_version: i64,
+    transaction_timestamp: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Default)]
+struct TokenActivityChanges {
+    burned: BigDecimal,
+    last_mint_version: Option<i64>,
+    last_transfer_version: Option<i64>,
+    /// Number of transfers and the last version per hour
+    hourly_transfers: AHashMap<NaiveDateTime, (i64, i64)>,
+    last_transaction_version: i64,
+    last_transaction_timestamp: NaiveDateTime,
+}
+
+/// What a batch changes about the collections, before it's known which collection every token
+/// belongs to.
+#[derive(Clone, Debug, Default)]
+pub struct CollectionStatsChanges {
+    /// Collection of the tokens whose token data is written in the batch
+    token_collections: AHashMap<String, String>,
+    /// Last amount of every ownership written in the batch
+    ownerships: AHashMap<OwnershipKey, OwnershipChange>,
+    activities: AHashMap<String, TokenActivityChanges>,
+}
+
+impl CollectionStatsChanges {
+    pub fn from_raw(
+        token_datas: &[RawTokenDataV2],
+        token_ownerships: &[RawTokenOwnershipV2],
+        token_activities: &[RawTokenActivityV2],
+    ) -> Self {
+        let token_collections = token_datas
+            .iter()
+            .map(|td| (td.token_data_id.clone(), td.collection_id.clone()))
+            .collect();
+
+        // Ownerships without a known owner can't be attributed to anyone
+        let ownerships = token_ownerships
+            .iter()
+            .filter_map(|o| {
+                let owner_address = o.owner_address.clone()?;
+                Some((
+                    (
+                        o.token_data_id.clone(),
+                        o.property_version_v1.clone(),
+                        owner_address,
+                        o.storage_id.clone(),
+                    ),
+                    OwnershipChange {
+                        amount: o.amount.clone(),
+                        transaction_version: o.transaction_version,
+                        transaction_timestamp: o.transaction_timestamp,
+                    },
+                ))
+            })
+            .collect();
+
+        // Minting a token can move it to its first owner, which isn't counted as a transfer
+        let minted: AHashSet<(i64, &str)> = token_activities
+            .iter()
+            .filter(|a| MINT_EVENT_TYPES.contains(&a.type_.as_str()))
+            .map(|a| (a.transaction_version, a.token_data_id.as_str()))
+            .collect();
+        let mut activities: AHashMap<String, TokenActivityChanges> = AHashMap::new();
+        for activity in token_activities {
+            let changes = activities
+                .entry(activity.token_data_id.clone())
+                .or_default();
+            let version = activity.transaction_version;
+            changes.last_transaction_version = version;
+            changes.last_transaction_timestamp = activity.transaction_timestamp;
+            let event_type = activity.type_.as_str();
+            if MINT_EVENT_TYPES.contains(&event_type) {
+                changes.last_mint_version = Some(version);
+            } else if BURN_EVENT_TYPES.contains(&event_type) {
+                changes.burned += &activity.token_amount;
+            } else if TRANSFER_EVENT_TYPES.contains(&event_type)
+                && !minted.contains(&(version, activity.token_data_id.as_str()))
+            {
+                changes.last_transfer_version = Some(version);
+                let hour = truncate_to_hour(activity.transaction_timestamp);
+                let (count, last_version) = changes.hourly_transfers.entry(hour).or_default();
+                *count += 1;
+                *last_version = version;
+            }
+        }
+
+        Self {
+            token_collections,
+            ownerships,
+            activities,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ownerships.is_empty() && self.activities.is_empty()
+    }
+
+    fn token_data_ids(&self) -> AHashSet<&str> {
+        self.ownerships
+            .keys()
+            .map(|(token_data_id, ..)| token_data_id.as_str())
+            .chain(self.activities.keys().map(String::as_str))
+            .collect()
+    }
+
+    /// Applies the changes to the state they were read from, returning the rows to write. Tokens
+    /// whose collection isn't known are skipped.
+    pub fn apply(self, previous: PreviousCollectionStats) -> CollectionStatsRows {
+        let collection_of = |token_data_id: &str| {
+            self.token_collections
+                .get(token_data_id)
+                .or_else(|| previous.token_collections.get(token_data_id))
+                .cloned()
+        };
+
+        let mut collections: AHashMap<String, CollectionChanges> = AHashMap::new();
+        let mut owner_deltas: AHashMap<(String, String), OwnershipChange> = AHashMap::new();
+        for (key, change) in &self.ownerships {
+            let Some(collection_id) = collection_of(&key.0) else {
+                continue;
+            };
+            let previous_amount = previous
+                .ownership_amounts
+                .get(key)
+                .cloned()
+                .unwrap_or_default();
+            let delta = owner_deltas
+                .entry((collection_id.clone(), key.2.clone()))
+                .or_insert_with(|| OwnershipChange {
+                    amount: BigDecimal::zero(),
+                    ..change.clone()
+                });
+            delta.amount += &change.amount - previous_amount;
+            if change.transaction_version > delta.transaction_version {
+                delta.transaction_version = change.transaction_version;
+                delta.transaction_timestamp = change.transaction_timestamp;
+            }
+            collections
+                .entry(collection_id)
+                .or_default()
+                .touch(change.transaction_version, change.transaction_timestamp);
+        }
+
+        let mut owners = vec![];
+        for ((collection_id, owner_address), delta) in owner_deltas {
+            let previous_owner = previous
+                .owners
+                .get(&(collection_id.clone(), owner_address.clone()));
+            if previous_owner
+                .is_some_and(|o| o.last_transaction_version >= delta.transaction_version)
+                || delta.amount.is_zero()
+            {
+                continue;
+            }
+            let previous_amount = previous_owner
+                .map(|o| o.token_amount.clone())
+                .unwrap_or_default();
+            let token_amount = &previous_amount + &delta.amount;
+            let collection = collections.entry(collection_id.clone()).or_default();
+            collection.total_tokens_owned += &delta.amount;
+            collection.distinct_owners += (token_amount > BigDecimal::zero()) as i64
+                - (previous_amount > BigDecimal::zero()) as i64;
+            owners.push(CurrentCollectionOwner {
+                collection_id,
+                owner_address,
+                token_amount,
+                last_transaction_version: delta.transaction_version,
+                last_transaction_timestamp: delta.transaction_timestamp,
+            });
+        }
+
+        let mut hourly_transfers: AHashMap<(String, NaiveDateTime), (i64, i64)> = AHashMap::new();
+        for (token_data_id, activity) in &self.activities {
+            let Some(collection_id) = collection_of(token_data_id) else {
+                continue;
+            };
+            let collection = collections.entry(collection_id.clone()).or_default();
+            collection.touch(
+                activity.last_transaction_version,
+                activity.last_transaction_timestamp,
+            );
+            collection.burned_count += &activity.burned;
+            collection.last_mint_version =
+                collection.last_mint_version.max(activity.last_mint_version);
+            collection.last_transfer_version = collection
+                .last_transfer_version
+                .max(activity.last_transfer_version);
+            for (hour, (count, last_version)) in &activity.hourly_transfers {
+                let (total, version) = hourly_transfers
+                    .entry((collection_id.clone(), *hour))
+                    .or_default();
+                *total += count;
+                *version = (*version).max(*last_version);
+            }
+        }
+
+        let mut new_hours = vec![];
+        for ((collection_id, hour), (count, last_version)) in hourly_transfers {
+            let transfer_count = match previous
+                .hourly_transfers
+                .get(&(collection_id.clone(), hour))
+            {
+                Some(h) if h.last_transaction_version >= last_version => continue,
+                Some(h) => h.transfer_count + count,
+                None => count,
+            };
+            new_hours.push(CollectionHourlyTransfers {
+                collection_id,
+                hour,
+                transfer_count,
+                last_transaction_version: last_version,
+            });
+        }
+
+        let mut stats = vec![];
+        for (collection_id, changes) in collections {
+            let previous_stats = previous.stats.get(&collection_id);
+            if previous_stats
+                .is_some_and(|s| s.last_transaction_version >= changes.last_transaction_version)
+            {
+                continue;
+            }
+            stats.push(CurrentCollectionStats {
+                distinct_owners: previous_stats
+                    .map(|s| s.distinct_owners)
+                    .unwrap_or_default()
+                    + changes.distinct_owners,
+                total_tokens_owned: previous_stats
+                    .map(|s| s.total_tokens_owned.clone())
+                    .unwrap_or_default()
+                    + changes.total_tokens_owned,
+                burned_count: previous_stats
+                    .map(|s| s.burned_count.clone())
+                    .unwrap_or_default()
+                    + changes.burned_count,
+                last_mint_version: previous_stats
+                    .and_then(|s| s.last_mint_version)
+                    .max(changes.last_mint_version),
+                last_transfer_version: previous_stats
+                    .and_then(|s| s.last_transfer_version)
+                    .max(changes.last_transfer_version),
+                last_transaction_version: changes.last_transaction_version,
+                last_transaction_timestamp: changes.last_transaction_timestamp,
+                collection_id,
+            });
+        }
+
+        // Sorted to write in a consistent order and avoid deadlocks
+        stats.sort_by(|a, b| a.collection_id.cmp(&b.collection_id));
+        owners.sort_by(|a, b| {
+            (&a.collection_id, &a.owner_address).cmp(&(&b.collection_id, &b.owner_address))
+        });
+        new_hours.sort_by(|a, b| (&a.collection_id, a.hour).cmp(&(&b.collection_id, b.hour)));
+        CollectionStatsRows {
+            stats,
+            owners,
+            hourly_transfers: new_hours,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CollectionChanges {
+    distinct_owners: i64,
+    total_tokens_owned: BigDecimal,
+    burned_count: BigDecimal,
+    last_mint_version: Option<i64>,
+    last_transfer_version: Option<i64>,
+    last_transaction_version: i64,
+    last_transaction_timestamp: NaiveDateTime,
+}
+
+impl CollectionChanges {
+    fn touch(&mut self, transaction_version: i64, transaction_timestamp: NaiveDateTime) {
+        if transaction_version >= self.last_transaction_version {
+            self.last_transaction_version = transaction_version;
+            self.last_transaction_timestamp = transaction_timestamp;
+        }
+    }
+}
+
+/// The rows a batch writes. The stats have to be written before the owners and the owners before
+/// the token ownerships, so that a batch that only got partially written is applied again
+/// correctly.
+#[derive(Clone, Debug, Default)]
+pub struct CollectionStatsRows {
+    pub stats: Vec<CurrentCollectionStats>,
+    pub owners: Vec<CurrentCollectionOwner>,
+    pub hourly_transfers: Vec<CollectionHourlyTransfers>,
+}
+
+impl CollectionStatsRows {
+    /// Hours before this are outside of every window.
+    pub fn prune_hourly_transfers_before(&self) -> Option<NaiveDateTime> {
+        self.stats
+            .iter()
+            .map(|s| s.last_transaction_timestamp)
+            .max()
+            .map(|timestamp| truncate_to_hour(timestamp - transfer_window_7d()))
+    }
+}
+
+/// The state the changes of a batch are applied to.
+#[derive(Debug, Default)]
+pub struct PreviousCollectionStats {
+    token_collections: AHashMap<String, String>,
+    ownership_amounts: AHashMap<OwnershipKey, BigDecimal>,
+    stats: AHashMap<String, CurrentCollectionStats>,
+    owners: AHashMap<(String, String), CurrentCollectionOwner>,
+    hourly_transfers: AHashMap<(String, NaiveDateTime), CollectionHourlyTransfers>,
+}
+
+impl PreviousCollectionStats {
+    /// Reads the state touched by `changes`. Must be called before the batch is written.
+    pub async fn load(
+        conn: &mut DbPoolConnection<'_>,
+        changes: &CollectionStatsChanges,
+    ) -> anyhow::Result<Self> {
+        if changes.is_empty() {
+            return Ok(Self::default());
+        }
+        let token_data_ids = changes.token_data_ids();
+        let unknown_token_data_ids: Vec<&str> = token_data_ids
+            .iter()
+            .filter(|id| !changes.token_collections.contains_key(**id))
+            .copied()
+            .collect();
+        let token_collections: AHashMap<String, String> = current_token_datas_v2::table
+            .filter(current_token_datas_v2::token_data_id.eq_any(unknown_token_data_ids))
+            .select((
+                current_token_datas_v2::token_data_id,
+                current_token_datas_v2::collection_id,
+            ))
+            .load::<(String, String)>(conn)
+            .await?
+            .into_iter()
+            .collect();
+
+        let collection_ids: Vec<&str> = token_data_ids
+            .iter()
+            .filter_map(|id| {
+                changes
+                    .token_collections
+                    .get(*id)
+                    .or_else(|| token_collections.get(*id))
+                    .map(String::as_str)
+            })
+            .collect::<AHashSet<_>>()
+            .into_iter()
+            .collect();
+        let owner_addresses: Vec<&str> = changes
+            .ownerships
+            .keys()
+            .map(|(_, _, owner_address, _)| owner_address.as_str())
+            .collect::<AHashSet<_>>()
+            .into_iter()
+            .collect();
+
+        // Superset of the ownerships of the batch, narrowed down below
+        let ownership_token_data_ids: AHashSet<&str> = changes
+            .ownerships
+            .keys()
+            .map(|(token_data_id, ..)| token_data_id.as_str())
+            .collect();
+        let ownership_amounts = current_token_ownerships_v2::table
+            .filter(current_token_ownerships_v2::token_data_id.eq_any(ownership_token_data_ids))
+            .filter(current_token_ownerships_v2::owner_address.eq_any(owner_addresses.clone()))
+            .select((
+                current_token_ownerships_v2::token_data_id,
+                current_token_ownerships_v2::property_version_v1,
+                current_token_ownerships_v2::owner_address,
+                current_token_ownerships_v2::storage_id,
+                current_token_ownerships_v2::amount,
+            ))
+            .load::<(String, BigDecimal, String, String, BigDecimal)>(conn)
+            .await?
+            .into_iter()
+            .map(
+                |(token_data_id, property_version_v1, owner_address, storage_id, amount)| {
+                    (
+                        (
+                            token_data_id,
+                            property_version_v1,
+                            owner_address,
+                            storage_id,
+                        ),
+                        amount,
+                    )
+                },
+            )
+            .filter(|(key, _)| changes.ownerships.contains_key(key))
+            .collect();
+
+        let stats = current_collection_stats::table
+            .filter(current_collection_stats::collection_id.eq_any(collection_ids.clone()))
+            .select((
+                current_collection_stats::collection_id,
+                current_collection_stats::distinct_owners,
+                current_collection_stats::total_tokens_owned,
+                current_collection_stats::burned_count,
+                current_collection_stats::last_mint_version,
+                current_collection_stats::last_transfer_version,
+                current_collection_stats::last_transaction_version,
+                current_collection_stats::last_transaction_timestamp,
+            ))
+            .load::<(
+                String,
+                i64,
+                BigDecimal,
+                BigDecimal,
+                Option<i64>,
+                Option<i64>,
+                i64,
+                NaiveDateTime,
+            )>(conn)
+            .await?
+            .into_iter()
+            .map(|s| {
+                (s.0.clone(), CurrentCollectionStats {
+                    collection_id: s.0,
+                    distinct_owners: s.1,
+                    total_tokens_owned: s.2,
+                    burned_count: s.3,
+                    last_mint_version: s.4,
+                    last_transfer_version: s.5,
+                    last_transaction_version: s.6,
+                    last_transaction_timestamp: s.7,
+                })
+            })
+            .collect();
+
+        let owners = current_collection_owners::table
+            .filter(current_collection_owners::collection_id.eq_any(collection_ids.clone()))
+            .filter(current_collection_owners::owner_address.eq_any(owner_addresses.clone()))
+            .select((
+                current_collection_owners::collection_id,
+                current_collection_owners::owner_address,
+                current_collection_owners::token_amount,
+                current_collection_owners::last_transaction_version,
+                current_collection_owners::last_transaction_timestamp,
+            ))
+            .load::<(String, String, BigDecimal, i64, NaiveDateTime)>(conn)
+            .await?
+            .into_iter()
+            .map(|o| {
+                ((o.0.clone(), o.1.clone()), CurrentCollectionOwner {
+                    collection_id: o.0,
+                    owner_address: o.1,
+                    token_amount: o.2,
+                    last_transaction_version: o.3,
+                    last_transaction_timestamp: o.4,
+                })
+            })
+            .collect();
+
+        let earliest_timestamp = changes
+            .ownerships
+            .values()
+            .map(|o| o.transaction_timestamp)
+            .chain(
+                changes
+                    .activities
+                    .values()
+                    .map(|a| a.last_transaction_timestamp),
+            )
+            .min()
+            .unwrap_or_default();
+        let hourly_transfers = collection_hourly_transfers::table
+            .filter(collection_hourly_transfers::collection_id.eq_any(collection_ids.clone()))
+            .filter(
+                collection_hourly_transfers::hour
+                    .gt(truncate_to_hour(earliest_timestamp - transfer_window_7d())),
+            )
+            .select((
+                collection_hourly_transfers::collection_id,
+                collection_hourly_transfers::hour,
+                collection_hourly_transfers::transfer_count,
+                collection_hourly_transfers::last_transaction_version,
+            ))
+            .load::<(String, NaiveDateTime, i64, i64)>(conn)
+            .await?
+            .into_iter()
+            .map(|h| {
+                ((h.0.clone(), h.1), CollectionHourlyTransfers {
+                    collection_id: h.0,
+                    hour: h.1,
+                    transfer_count: h.2,
+                    last_transaction_version: h.3,
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            token_collections,
+            ownership_amounts,
+            stats,
+            owners,
+            hourly_transfers,
+        })
+    }
+}
+
+fn truncate_to_hour(timestamp: NaiveDateTime) -> NaiveDateTime {
+    timestamp
+        .date()
+        .and_hms_opt(timestamp.hour(), 0, 0)
+        .unwrap_or(timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Versions are a second apart, all within the same hour.
+    fn transaction_timestamp(version: i64) -> NaiveDateTime {
+        chrono::DateTime::from_timestamp(1_700_006_400 + version, 0)
+            .unwrap()
+            .naive_utc()
+    }
+
+    fn ownership(version: i64, token: &str, owner: &str, amount: i64) -> RawTokenOwnershipV2 {
+        RawTokenOwnershipV2 {
+            transaction_version: version,
+            write_set_change_index: 0,
+            token_data_id: token.to_string(),
+            property_version_v1: BigDecimal::zero(),
+            owner_address: Some(owner.to_string()),
+            storage_id: token.to_string(),
+            amount: BigDecimal::from(amount),
+            table_type_v1: None,
+            token_properties_mutated_v1: None,
+            is_soulbound_v2: None,
+            token_standard: "v2".to_string(),
+            is_fungible_v2: None,
+            transaction_timestamp: transaction_timestamp(version),
+            non_transferrable_by_owner: None,
+        }
+    }
+
+    fn activity(version: i64, token: &str, type_: &str) -> RawTokenActivityV2 {
+        RawTokenActivityV2 {
+            transaction_version: version,
+            event_index: 0,
+            event_account_address: token.to_string(),
+            token_data_id: token.to_string(),
+            property_version_v1: BigDecimal::zero(),
+            type_: type_.to_string(),
+            from_address: None,
+            to_address: None,
+            token_amount: BigDecimal::from(1),
+            before_value: None,
+            after_value: None,
+            entry_function_id_str: None,
+            token_standard: "v2".to_string(),
+            is_fungible_v2: None,
+            transaction_timestamp: transaction_timestamp(version),
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        // 0xa owned token 1 of collection 0xc before the batch. The batch mints token 2 to 0xa,
+        // transfers token 1 to 0xb and burns a third token that nobody tracked an owner for.
+        let changes = CollectionStatsChanges::from_raw(
+            &[],
+            &[
+                ownership(10, "0x2", "0xa", 1),
+                ownership(20, "0x1", "0xa", 0),
+                ownership(20, "0x1", "0xb", 1),
+            ],
+            &[
+                activity(10, "0x2", "0x4::collection::MintEvent"),
+                activity(10, "0x2", "0x1::object::TransferEvent"),
+                activity(20, "0x1", "0x1::object::TransferEvent"),
+                activity(30, "0x3", "0x4::collection::BurnEvent"),
+            ],
+        );
+        let previous = PreviousCollectionStats {
+            token_collections: ["0x1", "0x2", "0x3"]
+                .into_iter()
+                .map(|id| (id.to_string(), "0xc".to_string()))
+                .collect(),
+            ownership_amounts: AHashMap::from([(
+                (
+                    "0x1".to_string(),
+                    BigDecimal::zero(),
+                    "0xa".to_string(),
+                    "0x1".to_string(),
+                ),
+                BigDecimal::from(1),
+            )]),
+            stats: AHashMap::from([("0xc".to_string(), CurrentCollectionStats {
+                collection_id: "0xc".to_string(),
+                distinct_owners: 1,
+                total_tokens_owned: BigDecimal::from(2),
+                burned_count: BigDecimal::zero(),
+                last_mint_version: Some(1),
+                last_transfer_version: None,
+                last_transaction_version: 1,
+                last_transaction_timestamp: transaction_timestamp(1),
+            })]),
+            owners: AHashMap::from([(
+                ("0xc".to_string(), "0xa".to_string()),
+                CurrentCollectionOwner {
+                    collection_id: "0xc".to_string(),
+                    owner_address: "0xa".to_string(),
+                    token_amount: BigDecimal::from(2),
+                    last_transaction_version: 1,
+                    last_transaction_timestamp: transaction_timestamp(1),
+                },
+            )]),
+            hourly_transfers: AHashMap::new(),
+        };
+
+        let rows = changes.apply(previous);
+        assert_eq!(rows.stats, vec![CurrentCollectionStats {
+            collection_id: "0xc".to_string(),
+            distinct_owners: 2,
+            total_tokens_owned: BigDecimal::from(3),
+            burned_count: BigDecimal::from(1),
+            last_mint_version: Some(10),
+            last_transfer_version: Some(20),
+            last_transaction_version: 30,
+            last_transaction_timestamp: transaction_timestamp(30),
+        }]);
+        assert_eq!(
+            rows.owners
+                .iter()
+                .map(|o| (o.owner_address.as_str(), o.token_amount.clone()))
+                .collect::<Vec<_>>(),
+            vec![("0xa", BigDecimal::from(2)), ("0xb", BigDecimal::from(1))]
+        );
+        // The transfer of the minted token isn't counted
+        assert_eq!(rows.hourly_transfers.len(), 1);
+        assert_eq!(rows.hourly_transfers[0].transfer_count, 1);
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{schema::current_nft_metadata, utils::database::DbPoolConnection};
+use diesel::{
+    prelude::*,
+    sql_query,
+    sql_types::{BigInt, Double, Integer, Text},
+};
+use diesel_async::RunQueryDsl;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(token_data_id))]
+#[diesel(table_name = current_nft_metadata)]
+pub struct CurrentNftMetadata {
+    pub token_data_id: String,
+    pub token_uri: String,
+    pub status: String,
+    pub error_message: Option<String>,
+    pub num_attempts: i32,
+    pub metadata: Option<Value>,
+    pub image_uri: Option<String>,
+    pub image_content_hash: Option<String>,
+    pub last_transaction_version: i64,
+    /// None until the uri is fetched the first time
+    pub last_fetched_at: Option<chrono::NaiveDateTime>,
+    /// When the uri is due to be fetched, None when no fetch is queued
+    pub next_attempt_at: Option<chrono::NaiveDateTime>,
+}
+
+/// A queued uri that's due to be fetched
+#[derive(Clone, Debug, QueryableByName)]
+pub struct NftMetadataFetchTask {
+    #[diesel(sql_type = Text)]
+    pub token_data_id: String,
+    #[diesel(sql_type = Text)]
+    pub token_uri: String,
+    /// Attempts made before this one
+    #[diesel(sql_type = Integer)]
+    pub num_attempts: i32,
+    #[diesel(sql_type = BigInt)]
+    pub last_transaction_version: i64,
+}
+
+impl NftMetadataFetchTask {
+    /// Claims up to `limit` uris that are due by pushing their `next_attempt_at` `claim_secs` into
+    /// the future, so that concurrent fetchers skip them. The fetch result overwrites the claim.
+    pub async fn claim(
+        conn: &mut DbPoolConnection<'_>,
+        limit: i64,
+        claim_secs: i64,
+    ) -> diesel::QueryResult<Vec<Self>> {
+        sql_query(
+            "UPDATE current_nft_metadata
+            SET next_attempt_at = (NOW() AT TIME ZONE 'UTC') + make_interval(secs => $2)
+            WHERE token_data_id IN (
+                SELECT token_data_id FROM current_nft_metadata
+                WHERE next_attempt_at <= (NOW() AT TIME ZONE 'UTC')
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING token_data_id, token_uri, num_attempts, last_transaction_version",
+        )
+        .bind::<BigInt, _>(limit)
+        .bind::<Double, _>(claim_secs as f64)
+        .get_results(conn)
+        .await
+    }
+}
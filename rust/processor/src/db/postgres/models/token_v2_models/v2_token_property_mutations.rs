@@ -0,0 +1,45 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    db::common::models::token_v2_models::raw_v2_token_property_mutations::{
+        RawTokenPropertyMutation, TokenPropertyMutationConvertible,
+    },
+    schema::token_property_mutations,
+};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, write_set_change_index))]
+#[diesel(table_name = token_property_mutations)]
+pub struct TokenPropertyMutation {
+    pub transaction_version: i64,
+    pub write_set_change_index: i64,
+    pub token_data_id: String,
+    pub old_properties: Value,
+    pub new_properties: Value,
+    pub token_standard: String,
+    pub entry_function_id_str: Option<String>,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl TokenPropertyMutationConvertible for TokenPropertyMutation {
+    fn from_raw(raw_item: RawTokenPropertyMutation) -> Self {
+        Self {
+            transaction_version: raw_item.transaction_version,
+            write_set_change_index: raw_item.write_set_change_index,
+            token_data_id: raw_item.token_data_id,
+            old_properties: raw_item.old_properties,
+            new_properties: raw_item.new_properties,
+            token_standard: raw_item.token_standard,
+            entry_function_id_str: raw_item.entry_function_id_str,
+            transaction_timestamp: raw_item.transaction_timestamp,
+        }
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    db::common::models::token_v2_models::raw_v2_token_transferability::{
+        CurrentTokenTransferabilityV2Convertible, RawCurrentTokenTransferabilityV2,
+        RawTokenTransferabilityV2, TokenTransferabilityV2Convertible,
+    },
+    schema::{current_token_transferability_v2, token_transferability_v2},
+};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, write_set_change_index))]
+#[diesel(table_name = token_transferability_v2)]
+pub struct TokenTransferabilityV2 {
+    pub transaction_version: i64,
+    pub write_set_change_index: i64,
+    pub token_data_id: String,
+    pub owner_address: String,
+    pub allow_ungated_transfer: bool,
+    pub is_untransferable: bool,
+    pub is_transferable_by_owner: bool,
+    pub restriction_reason: Option<String>,
+    pub soulbound_at_mint: Option<bool>,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(token_data_id))]
+#[diesel(table_name = current_token_transferability_v2)]
+pub struct CurrentTokenTransferabilityV2 {
+    pub token_data_id: String,
+    pub owner_address: String,
+    pub allow_ungated_transfer: bool,
+    pub is_untransferable: bool,
+    pub is_transferable_by_owner: bool,
+    pub restriction_reason: Option<String>,
+    pub soulbound_at_mint: Option<bool>,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl TokenTransferabilityV2Convertible for TokenTransferabilityV2 {
+    fn from_raw(raw_item: RawTokenTransferabilityV2) -> Self {
+        Self {
+            transaction_version: raw_item.transaction_version,
+            write_set_change_index: raw_item.write_set_change_index,
+            token_data_id: raw_item.token_data_id,
+            owner_address: raw_item.owner_address,
+            allow_ungated_transfer: raw_item.allow_ungated_transfer,
+            is_untransferable: raw_item.is_untransferable,
+            is_transferable_by_owner: raw_item.is_transferable_by_owner,
+            restriction_reason: raw_item.restriction_reason,
+            soulbound_at_mint: raw_item.soulbound_at_mint,
+            transaction_timestamp: raw_item.transaction_timestamp,
+        }
+    }
+}
+
+impl CurrentTokenTransferabilityV2Convertible for CurrentTokenTransferabilityV2 {
+    fn from_raw(raw_item: RawCurrentTokenTransferabilityV2) -> Self {
+        Self {
+            token_data_id: raw_item.token_data_id,
+            owner_address: raw_item.owner_address,
+            allow_ungated_transfer: raw_item.allow_ungated_transfer,
+            is_untransferable: raw_item.is_untransferable,
+            is_transferable_by_owner: raw_item.is_transferable_by_owner,
+            restriction_reason: raw_item.restriction_reason,
+            soulbound_at_mint: raw_item.soulbound_at_mint,
+            last_transaction_version: raw_item.last_transaction_version,
+            last_transaction_timestamp: raw_item.last_transaction_timestamp,
+        }
+    }
+}
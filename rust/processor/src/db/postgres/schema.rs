@@ -109,7 +109,7 @@ diesel::table! {
 
 diesel::table! {
     backfill_processor_status (backfill_alias) {
-        #[max_length = 50]
+        #[max_length = 100]
         backfill_alias -> Varchar,
         #[max_length = 50]
         backfill_status -> Varchar,
@@ -118,6 +118,8 @@ diesel::table! {
         last_transaction_timestamp -> Nullable<Timestamp>,
         backfill_start_version -> Int8,
         backfill_end_version -> Int8,
+        #[max_length = 100]
+        claimed_by -> Nullable<Varchar>,
     }
 }
 
@@ -241,6 +243,17 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    collection_hourly_transfers (collection_id, hour) {
+        #[max_length = 66]
+        collection_id -> Varchar,
+        hour -> Timestamp,
+        transfer_count -> Int8,
+        last_transaction_version -> Int8,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     collections_v2 (transaction_version, write_set_change_index) {
         transaction_version -> Int8,
@@ -379,6 +392,34 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    current_collection_owners (collection_id, owner_address) {
+        #[max_length = 66]
+        collection_id -> Varchar,
+        #[max_length = 66]
+        owner_address -> Varchar,
+        token_amount -> Numeric,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    current_collection_stats (collection_id) {
+        #[max_length = 66]
+        collection_id -> Varchar,
+        distinct_owners -> Int8,
+        total_tokens_owned -> Numeric,
+        burned_count -> Numeric,
+        last_mint_version -> Nullable<Int8>,
+        last_transfer_version -> Nullable<Int8>,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     current_collections_v2 (collection_id) {
         #[max_length = 66]
@@ -506,6 +547,136 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    current_nft_marketplace_collection_offers (collection_offer_id, collection_id) {
+        #[max_length = 66]
+        collection_offer_id -> Varchar,
+        #[max_length = 66]
+        collection_id -> Varchar,
+        #[max_length = 66]
+        fee_schedule_id -> Varchar,
+        #[max_length = 66]
+        buyer -> Varchar,
+        item_price -> Numeric,
+        remaining_token_amount -> Numeric,
+        expiration_time -> Nullable<Numeric>,
+        is_deleted -> Bool,
+        #[max_length = 10]
+        token_standard -> Varchar,
+        #[max_length = 1000]
+        coin_type -> Nullable<Varchar>,
+        #[max_length = 100]
+        marketplace -> Varchar,
+        #[max_length = 66]
+        contract_address -> Varchar,
+        #[max_length = 1000]
+        entry_function_id_str -> Nullable<Varchar>,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    current_nft_marketplace_listings (listing_id, token_data_id) {
+        #[max_length = 66]
+        listing_id -> Varchar,
+        #[max_length = 66]
+        token_data_id -> Varchar,
+        #[max_length = 66]
+        collection_id -> Varchar,
+        #[max_length = 66]
+        fee_schedule_id -> Varchar,
+        #[max_length = 66]
+        seller -> Varchar,
+        price -> Numeric,
+        token_amount -> Numeric,
+        #[max_length = 10]
+        token_standard -> Varchar,
+        is_deleted -> Bool,
+        #[max_length = 1000]
+        coin_type -> Nullable<Varchar>,
+        #[max_length = 100]
+        marketplace -> Varchar,
+        #[max_length = 66]
+        contract_address -> Varchar,
+        #[max_length = 1000]
+        entry_function_id_str -> Nullable<Varchar>,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    current_nft_marketplace_token_offers (offer_id, token_data_id) {
+        #[max_length = 66]
+        offer_id -> Varchar,
+        #[max_length = 66]
+        token_data_id -> Varchar,
+        #[max_length = 66]
+        collection_id -> Varchar,
+        #[max_length = 66]
+        fee_schedule_id -> Varchar,
+        #[max_length = 66]
+        buyer -> Varchar,
+        price -> Numeric,
+        token_amount -> Numeric,
+        expiration_time -> Nullable<Numeric>,
+        is_deleted -> Bool,
+        #[max_length = 10]
+        token_standard -> Varchar,
+        #[max_length = 1000]
+        coin_type -> Nullable<Varchar>,
+        #[max_length = 100]
+        marketplace -> Varchar,
+        #[max_length = 66]
+        contract_address -> Varchar,
+        #[max_length = 1000]
+        entry_function_id_str -> Nullable<Varchar>,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    current_nft_metadata (token_data_id) {
+        #[max_length = 66]
+        token_data_id -> Varchar,
+        #[max_length = 512]
+        token_uri -> Varchar,
+        #[max_length = 20]
+        status -> Varchar,
+        error_message -> Nullable<Text>,
+        num_attempts -> Int4,
+        metadata -> Nullable<Jsonb>,
+        image_uri -> Nullable<Text>,
+        #[max_length = 64]
+        image_content_hash -> Nullable<Varchar>,
+        last_transaction_version -> Int8,
+        last_fetched_at -> Nullable<Timestamp>,
+        inserted_at -> Timestamp,
+        next_attempt_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    current_object_ultimate_owners (object_address) {
+        #[max_length = 66]
+        object_address -> Varchar,
+        #[max_length = 66]
+        direct_owner_address -> Varchar,
+        #[max_length = 66]
+        ultimate_owner_address -> Varchar,
+        ownership_path -> Jsonb,
+        depth -> Int4,
+        is_deleted -> Bool,
+        last_transaction_version -> Int8,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     current_objects (object_address) {
         #[max_length = 66]
@@ -689,6 +860,25 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    current_token_royalty (token_or_collection_id) {
+        #[max_length = 66]
+        token_or_collection_id -> Varchar,
+        #[max_length = 10]
+        source_level -> Varchar,
+        #[max_length = 10]
+        token_standard -> Varchar,
+        #[max_length = 66]
+        payee_address -> Varchar,
+        royalty_points_numerator -> Numeric,
+        royalty_points_denominator -> Numeric,
+        is_deleted -> Bool,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     current_token_royalty_v1 (token_data_id) {
         #[max_length = 66]
@@ -703,6 +893,24 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    current_token_transferability_v2 (token_data_id) {
+        #[max_length = 66]
+        token_data_id -> Varchar,
+        #[max_length = 66]
+        owner_address -> Varchar,
+        allow_ungated_transfer -> Bool,
+        is_untransferable -> Bool,
+        is_transferable_by_owner -> Bool,
+        #[max_length = 50]
+        restriction_reason -> Nullable<Varchar>,
+        soulbound_at_mint -> Nullable<Bool>,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     current_token_v2_metadata (object_address, resource_type) {
         #[max_length = 66]
@@ -832,6 +1040,25 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    fungible_asset_balance_snapshots (asset_type, snapshot_version, storage_id) {
+        #[max_length = 1000]
+        asset_type -> Varchar,
+        snapshot_version -> Int8,
+        #[max_length = 66]
+        storage_id -> Varchar,
+        #[max_length = 66]
+        owner_address -> Varchar,
+        is_primary -> Bool,
+        is_frozen -> Bool,
+        amount -> Numeric,
+        #[max_length = 10]
+        token_standard -> Varchar,
+        last_transaction_version -> Int8,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     fungible_asset_balances (transaction_version, write_set_change_index) {
         transaction_version -> Int8,
@@ -943,6 +1170,48 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    nft_marketplace_activities (transaction_version, event_index) {
+        transaction_version -> Int8,
+        event_index -> Int8,
+        #[max_length = 66]
+        offer_or_listing_id -> Varchar,
+        #[max_length = 66]
+        fee_schedule_id -> Varchar,
+        #[max_length = 66]
+        collection_id -> Varchar,
+        #[max_length = 66]
+        token_data_id -> Nullable<Varchar>,
+        #[max_length = 66]
+        creator_address -> Varchar,
+        #[max_length = 128]
+        collection_name -> Varchar,
+        #[max_length = 128]
+        token_name -> Nullable<Varchar>,
+        property_version -> Nullable<Numeric>,
+        price -> Numeric,
+        token_amount -> Numeric,
+        #[max_length = 10]
+        token_standard -> Varchar,
+        #[max_length = 66]
+        seller -> Nullable<Varchar>,
+        #[max_length = 66]
+        buyer -> Nullable<Varchar>,
+        #[max_length = 1000]
+        coin_type -> Nullable<Varchar>,
+        #[max_length = 100]
+        marketplace -> Varchar,
+        #[max_length = 66]
+        contract_address -> Varchar,
+        #[max_length = 1000]
+        entry_function_id_str -> Nullable<Varchar>,
+        #[max_length = 50]
+        event_type -> Varchar,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     nft_points (transaction_version) {
         transaction_version -> Int8,
@@ -984,6 +1253,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    processor_status_history (processor, sampled_at) {
+        #[max_length = 100]
+        processor -> Varchar,
+        sampled_at -> Timestamp,
+        last_success_version -> Int8,
+        last_transaction_timestamp -> Nullable<Timestamp>,
+        versions_processed -> Int8,
+        tps -> Float8,
+        avg_checkpoint_lag_ms -> Nullable<Int8>,
+    }
+}
+
 diesel::table! {
     proposal_votes (transaction_version, proposal_id, voter_address) {
         transaction_version -> Int8,
@@ -1212,6 +1494,26 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    token_ownership_snapshots (collection_id, snapshot_version, token_data_id, property_version_v1, owner_address, storage_id) {
+        #[max_length = 66]
+        collection_id -> Varchar,
+        snapshot_version -> Int8,
+        #[max_length = 66]
+        token_data_id -> Varchar,
+        property_version_v1 -> Numeric,
+        #[max_length = 66]
+        owner_address -> Varchar,
+        #[max_length = 66]
+        storage_id -> Varchar,
+        amount -> Numeric,
+        #[max_length = 10]
+        token_standard -> Varchar,
+        last_transaction_version -> Int8,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     token_ownerships_v2 (transaction_version, write_set_change_index) {
         transaction_version -> Int8,
@@ -1237,6 +1539,61 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    token_property_mutations (transaction_version, write_set_change_index) {
+        transaction_version -> Int8,
+        write_set_change_index -> Int8,
+        #[max_length = 66]
+        token_data_id -> Varchar,
+        old_properties -> Jsonb,
+        new_properties -> Jsonb,
+        #[max_length = 10]
+        token_standard -> Varchar,
+        #[max_length = 1000]
+        entry_function_id_str -> Nullable<Varchar>,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    token_transferability_v2 (transaction_version, write_set_change_index) {
+        transaction_version -> Int8,
+        write_set_change_index -> Int8,
+        #[max_length = 66]
+        token_data_id -> Varchar,
+        #[max_length = 66]
+        owner_address -> Varchar,
+        allow_ungated_transfer -> Bool,
+        is_untransferable -> Bool,
+        is_transferable_by_owner -> Bool,
+        #[max_length = 50]
+        restriction_reason -> Nullable<Varchar>,
+        soulbound_at_mint -> Nullable<Bool>,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    token_v1_to_v2_mappings (token_data_id_v2) {
+        #[max_length = 66]
+        token_data_id_v2 -> Varchar,
+        #[max_length = 66]
+        token_data_id_v1 -> Varchar,
+        property_version_v1 -> Numeric,
+        #[max_length = 66]
+        collection_id_v1 -> Varchar,
+        #[max_length = 66]
+        collection_id_v2 -> Varchar,
+        #[max_length = 128]
+        token_name -> Varchar,
+        transaction_version -> Int8,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     tokens (token_data_id_hash, property_version, transaction_version) {
         #[max_length = 64]
@@ -1360,6 +1717,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     coin_infos,
     coin_supply,
     collection_datas,
+    collection_hourly_transfers,
     collections_v2,
     current_ans_lookup,
     current_ans_lookup_v2,
@@ -1367,12 +1725,19 @@ diesel::allow_tables_to_appear_in_same_query!(
     current_ans_primary_name_v2,
     current_coin_balances,
     current_collection_datas,
+    current_collection_owners,
+    current_collection_stats,
     current_collections_v2,
     current_delegated_staking_pool_balances,
     current_delegated_voter,
     current_delegator_balances,
     current_fungible_asset_balances,
     current_fungible_asset_balances_legacy,
+    current_nft_marketplace_collection_offers,
+    current_nft_marketplace_listings,
+    current_nft_marketplace_token_offers,
+    current_nft_metadata,
+    current_object_ultimate_owners,
     current_objects,
     current_staking_pool_voter,
     current_table_items,
@@ -1381,7 +1746,9 @@ diesel::allow_tables_to_appear_in_same_query!(
     current_token_ownerships,
     current_token_ownerships_v2,
     current_token_pending_claims,
+    current_token_royalty,
     current_token_royalty_v1,
+    current_token_transferability_v2,
     current_token_v2_metadata,
     delegated_staking_activities,
     delegated_staking_pool_balances,
@@ -1390,6 +1757,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     event_size_info,
     events,
     fungible_asset_activities,
+    fungible_asset_balance_snapshots,
     fungible_asset_balances,
     fungible_asset_metadata,
     fungible_asset_to_coin_mappings,
@@ -1397,9 +1765,11 @@ diesel::allow_tables_to_appear_in_same_query!(
     ledger_infos,
     move_modules,
     move_resources,
+    nft_marketplace_activities,
     nft_points,
     objects,
     processor_status,
+    processor_status_history,
     proposal_votes,
     public_key_auth_keys,
     signatures,
@@ -1411,7 +1781,11 @@ diesel::allow_tables_to_appear_in_same_query!(
     token_datas,
     token_datas_v2,
     token_ownerships,
+    token_ownership_snapshots,
     token_ownerships_v2,
+    token_property_mutations,
+    token_transferability_v2,
+    token_v1_to_v2_mappings,
     tokens,
     transaction_size_info,
     transactions,
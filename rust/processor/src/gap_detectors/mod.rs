@@ -5,10 +5,16 @@ use crate::{
         parquet_gap_detector::{ParquetFileGapDetectorInner, ParquetFileGapDetectorResult},
     },
     processors::{DefaultProcessingResult, Processor, ProcessorTrait},
-    utils::counters::{PARQUET_PROCESSOR_DATA_GAP_COUNT, PROCESSOR_DATA_GAP_COUNT},
+    utils::{
+        counters::{PARQUET_PROCESSOR_DATA_GAP_COUNT, PROCESSOR_DATA_GAP_COUNT},
+        status_history::{StatusHistoryConfig, StatusHistorySampler},
+        util::parse_timestamp,
+    },
     worker::PROCESSOR_SERVICE_TYPE,
+    worker_admin::WorkerAdmin,
 };
 use anyhow::Result;
+use aptos_protos::util::timestamp::Timestamp;
 use enum_dispatch::enum_dispatch;
 use kanal::AsyncReceiver;
 use std::sync::{Arc, Mutex};
@@ -60,9 +66,12 @@ pub async fn create_gap_detector_status_tracker_loop(
     mut gap_detector: GapDetector,
     gap_detector_receiver: AsyncReceiver<ProcessingResult>,
     processor: Processor,
+    processor_name: String,
     gap_detection_batch_size: u64,
+    admin: Arc<WorkerAdmin>,
+    status_history_config: Option<StatusHistoryConfig>,
 ) {
-    let processor_name = processor.name();
+    let processor_name = processor_name.as_str();
     tracing::info!(
         processor_name = processor_name,
         service_type = PROCESSOR_SERVICE_TYPE,
@@ -70,8 +79,31 @@ pub async fn create_gap_detector_status_tracker_loop(
     );
 
     let mut last_update_time = std::time::Instant::now();
+    let mut checkpoint_requests = admin
+        .take_checkpoint_requests()
+        .expect("[Parser] Checkpoint requests are already served by another gap detector");
+    // Last version processed without gaps, written right away when a checkpoint is requested.
+    let mut last_success: Option<(u64, Option<Timestamp>)> = None;
+    let mut status_history = status_history_config.map(StatusHistorySampler::new);
     loop {
-        match gap_detector_receiver.recv().await {
+        let received = tokio::select! {
+            received = gap_detector_receiver.recv() => received,
+            Some(reply) = checkpoint_requests.recv() => {
+                let result = match last_success.clone() {
+                    Some((version, timestamp)) => processor
+                        .update_last_processed_version(processor_name, version, timestamp)
+                        .await
+                        .map(|_| {
+                            admin.record_checkpoint(version);
+                            Some(version)
+                        }),
+                    None => Ok(None),
+                };
+                let _ = reply.send(result);
+                continue;
+            },
+        };
+        match received {
             Ok(ProcessingResult::DefaultProcessingResult(result)) => {
                 match gap_detector
                     .process_versions(ProcessingResult::DefaultProcessingResult(result))
@@ -82,6 +114,13 @@ pub async fn create_gap_detector_status_tracker_loop(
                                 PROCESSOR_DATA_GAP_COUNT
                                     .with_label_values(&[processor_name])
                                     .set(res.num_gaps as i64);
+                                admin.record_progress(
+                                    res.last_success_batch.as_ref().map(|b| b.end_version),
+                                    res.last_success_batch
+                                        .as_ref()
+                                        .and_then(|b| b.last_transaction_timestamp.as_ref()),
+                                    res.num_gaps,
+                                );
                                 if res.num_gaps >= gap_detection_batch_size {
                                     tracing::debug!(
                                     processor_name,
@@ -92,16 +131,34 @@ pub async fn create_gap_detector_status_tracker_loop(
                                     // We don't panic as everything downstream will panic if it doesn't work/receive
                                 }
                                 if let Some(res_last_success_batch) = res.last_success_batch {
+                                    last_success = Some((
+                                        res_last_success_batch.end_version,
+                                        res_last_success_batch.last_transaction_timestamp.clone(),
+                                    ));
                                     if last_update_time.elapsed().as_secs()
                                         >= UPDATE_PROCESSOR_STATUS_SECS
                                     {
                                         processor
                                             .update_last_processed_version(
+                                                processor_name,
                                                 res_last_success_batch.end_version,
-                                                res_last_success_batch.last_transaction_timestamp,
+                                                res_last_success_batch
+                                                    .last_transaction_timestamp
+                                                    .clone(),
                                             )
                                             .await
                                             .unwrap();
+                                        admin.record_checkpoint(res_last_success_batch.end_version);
+                                        record_status_history(
+                                            &mut status_history,
+                                            &processor,
+                                            processor_name,
+                                            res_last_success_batch.end_version,
+                                            res_last_success_batch
+                                                .last_transaction_timestamp
+                                                .as_ref(),
+                                        )
+                                        .await;
                                         last_update_time = std::time::Instant::now();
                                     }
                                 }
@@ -138,6 +195,15 @@ pub async fn create_gap_detector_status_tracker_loop(
                                 PARQUET_PROCESSOR_DATA_GAP_COUNT
                                     .with_label_values(&[processor_name])
                                     .set(res.num_gaps as i64);
+                                admin.record_progress(
+                                    Some(res.last_success_version),
+                                    res.last_transaction_timestamp.as_ref(),
+                                    res.num_gaps,
+                                );
+                                last_success = Some((
+                                    res.last_success_version,
+                                    res.last_transaction_timestamp.clone(),
+                                ));
                                 // we need a new gap detection batch size
                                 if res.num_gaps >= gap_detection_batch_size {
                                     tracing::warn!(
@@ -159,11 +225,21 @@ pub async fn create_gap_detector_status_tracker_loop(
                                     );
                                     processor
                                         .update_last_processed_version(
+                                            processor_name,
                                             res.last_success_version,
-                                            res.last_transaction_timestamp,
+                                            res.last_transaction_timestamp.clone(),
                                         )
                                         .await
                                         .unwrap();
+                                    admin.record_checkpoint(res.last_success_version);
+                                    record_status_history(
+                                        &mut status_history,
+                                        &processor,
+                                        processor_name,
+                                        res.last_success_version,
+                                        res.last_transaction_timestamp.as_ref(),
+                                    )
+                                    .await;
                                     last_update_time = std::time::Instant::now();
                                 } else {
                                     tracing::info!("Not Updating last processed version");
@@ -197,3 +273,24 @@ pub async fn create_gap_detector_status_tracker_loop(
         };
     }
 }
+
+/// Samples the checkpoint that was just written into `processor_status_history`, if enabled.
+async fn record_status_history(
+    status_history: &mut Option<StatusHistorySampler>,
+    processor: &Processor,
+    processor_name: &str,
+    version: u64,
+    last_transaction_timestamp: Option<&Timestamp>,
+) {
+    if let Some(status_history) = status_history {
+        status_history
+            .record_and_write(
+                processor.get_pool(),
+                processor_name,
+                version as i64,
+                last_transaction_timestamp
+                    .map(|timestamp| parse_timestamp(timestamp, version as i64)),
+            )
+            .await;
+    }
+}
@@ -1,10 +1,14 @@
-use crate::utils::{
-    counters::{
-        ProcessorStep, FETCHER_THREAD_CHANNEL_SIZE, LATEST_PROCESSED_VERSION,
-        NUM_TRANSACTIONS_FILTERED_OUT_COUNT, NUM_TRANSACTIONS_PROCESSED_COUNT,
-        PROCESSED_BYTES_COUNT, TRANSACTION_UNIX_TIMESTAMP,
+use crate::{
+    utils::{
+        counters::{
+            ProcessorStep, FETCHER_THREAD_CHANNEL_SIZE, LATEST_PROCESSED_VERSION,
+            NUM_TRANSACTIONS_FILTERED_OUT_COUNT, NUM_TRANSACTIONS_PROCESSED_COUNT,
+            PROCESSED_BYTES_COUNT, TRANSACTION_UNIX_TIMESTAMP,
+        },
+        util::{timestamp_to_iso, timestamp_to_unixtime},
     },
-    util::{timestamp_to_iso, timestamp_to_unixtime},
+    worker_admin::WorkerAdmin,
+    ReloadableConfig,
 };
 use aptos_moving_average::MovingAverage;
 use aptos_protos::{
@@ -17,8 +21,8 @@ use futures_util::StreamExt;
 use itertools::Itertools;
 use kanal::AsyncSender;
 use prost::Message;
-use std::time::Duration;
-use tokio::time::timeout;
+use std::{sync::Arc, time::Duration};
+use tokio::{sync::watch, time::timeout};
 use tonic::{Response, Streaming};
 use tracing::{debug, error, info};
 use url::Url;
@@ -313,9 +317,11 @@ pub async fn create_fetcher_loop(
     request_ending_version: Option<u64>,
     auth_token: String,
     processor_name: String,
-    transaction_filter: crate::transaction_filter::TransactionFilter,
+    // Transaction filter, updated when the config is reloaded
+    mut reloadable_config: watch::Receiver<ReloadableConfig>,
     // The number of transactions per protobuf batch
     pb_channel_txn_chunk_size: usize,
+    admin: Arc<WorkerAdmin>,
 ) {
     info!(
         processor_name = processor_name,
@@ -356,6 +362,10 @@ pub async fn create_fetcher_loop(
     let mut reconnection_retries = 0;
     let mut last_fetched_version = starting_version as i64 - 1;
     let mut fetch_ma = MovingAverage::new(3000);
+    let mut transaction_filter = reloadable_config
+        .borrow_and_update()
+        .transaction_filter
+        .clone();
     let mut send_ma = MovingAverage::new(3000);
 
     loop {
@@ -387,6 +397,12 @@ pub async fn create_fetcher_loop(
                         let num_txns = r.transactions.len();
 
                         // Filter out the txns we don't care about
+                        if reloadable_config.has_changed().unwrap_or(false) {
+                            transaction_filter = reloadable_config
+                                .borrow_and_update()
+                                .transaction_filter
+                                .clone();
+                        }
                         r.transactions.retain(|txn| transaction_filter.include(txn));
 
                         let num_txn_post_filter = r.transactions.len();
@@ -561,6 +577,7 @@ pub async fn create_fetcher_loop(
                             error = ?rpc_error,
                             "[Parser] Error receiving datastream response."
                         );
+                        admin.record_error(&rpc_error);
                         false
                     },
                     // Stream is finished
@@ -590,6 +607,7 @@ pub async fn create_fetcher_loop(
                     error = ?e,
                     "[Parser] Timeout receiving datastream response."
                 );
+                admin.record_error(&e);
                 false
             },
         };
@@ -16,7 +16,7 @@ extern crate canonical_json;
 extern crate parquet;
 extern crate parquet_derive;
 
-pub use config::IndexerGrpcProcessorConfig;
+pub use config::{HealthCheckConfig, IndexerGrpcProcessorConfig, ReloadableConfig};
 
 pub mod bq_analytics;
 mod config;
@@ -29,3 +29,4 @@ pub mod schema;
 pub mod transaction_filter;
 pub mod utils;
 pub mod worker;
+pub mod worker_admin;
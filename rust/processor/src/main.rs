@@ -2,9 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use processor::IndexerGrpcProcessorConfig;
-use server_framework::ServerArgs;
+use server_framework::{load, GenericConfig, ServerArgs};
 
 #[cfg(unix)]
 #[global_allocator]
@@ -12,13 +12,32 @@ static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
 const RUNTIME_WORKER_MULTIPLIER: usize = 2;
 
+#[derive(Parser)]
+struct Args {
+    #[clap(flatten)]
+    server_args: ServerArgs,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check the config and that the DB, gRPC stream, Kafka and GCS are reachable, print a report
+    /// and exit without processing anything.
+    Validate,
+}
+
 fn main() -> Result<()> {
+    let args = Args::parse();
     let num_cpus = num_cpus::get();
     let worker_threads = (num_cpus * RUNTIME_WORKER_MULTIPLIER).max(16);
-    println!(
-        "[Processor] Starting processor tokio runtime: num_cpus={}, worker_threads={}",
-        num_cpus, worker_threads
-    );
+    // The validation report is printed as JSON, keep stdout clean for it.
+    if args.command.is_none() {
+        println!(
+            "[Processor] Starting processor tokio runtime: num_cpus={}, worker_threads={}",
+            num_cpus, worker_threads
+        );
+    }
 
     let mut builder = tokio::runtime::Builder::new_multi_thread();
     builder
@@ -28,8 +47,18 @@ fn main() -> Result<()> {
         .build()
         .unwrap()
         .block_on(async {
-            let args = ServerArgs::parse();
-            args.run::<IndexerGrpcProcessorConfig>(tokio::runtime::Handle::current())
-                .await
+            match args.command {
+                Some(Command::Validate) => {
+                    let config = load::<GenericConfig<IndexerGrpcProcessorConfig>>(
+                        &args.server_args.config_path,
+                    )?;
+                    config.server_config.validation_report().await.print()
+                },
+                None => {
+                    args.server_args
+                        .run::<IndexerGrpcProcessorConfig>(tokio::runtime::Handle::current())
+                        .await
+                },
+            }
         })
 }
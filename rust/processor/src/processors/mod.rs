@@ -54,6 +54,7 @@ use crate::{
         parquet_user_transactions_processor::{
             ParquetUserTransactionsProcessor, ParquetUserTransactionsProcessorConfig,
         },
+        ParquetProcessorTrait,
     },
     schema::processor_status,
     utils::{
@@ -132,15 +133,17 @@ pub trait ProcessorTrait: Send + Sync + Debug {
     }
 
     /// Store last processed version from database. We can assume that all previously processed
-    /// versions are successful because any gap would cause the processor to panic
+    /// versions are successful because any gap would cause the processor to panic.
+    /// `processor_name` is the name the checkpoint is stored under, see `processor_instance_name`.
     async fn update_last_processed_version(
         &self,
+        processor_name: &str,
         version: u64,
         last_transaction_timestamp: Option<aptos_protos::util::timestamp::Timestamp>,
     ) -> anyhow::Result<()> {
         let timestamp = last_transaction_timestamp.map(|t| parse_timestamp(&t, version as i64));
         let status = ProcessorStatus {
-            processor: self.name().to_string(),
+            processor: processor_name.to_string(),
             last_success_version: version as i64,
             last_transaction_timestamp: timestamp,
         };
@@ -240,6 +243,44 @@ impl ProcessorConfig {
                 | ProcessorConfig::ParquetUserTransactionsProcessor(_)
         )
     }
+
+    /// The bucket and credentials of parquet processors, `None` for other processors.
+    pub fn parquet_bucket(&self) -> Option<(&str, Option<&String>)> {
+        macro_rules! bucket {
+            ($config:expr) => {
+                Some((
+                    $config.bucket_name.as_str(),
+                    $config.google_application_credentials.as_ref(),
+                ))
+            };
+        }
+        match self {
+            ProcessorConfig::ParquetDefaultProcessor(config) => bucket!(config),
+            ProcessorConfig::ParquetFungibleAssetActivitiesProcessor(config) => bucket!(config),
+            ProcessorConfig::ParquetFungibleAssetProcessor(config) => bucket!(config),
+            ProcessorConfig::ParquetTransactionMetadataProcessor(config) => bucket!(config),
+            ProcessorConfig::ParquetAnsProcessor(config) => bucket!(config),
+            ProcessorConfig::ParquetEventsProcessor(config) => bucket!(config),
+            ProcessorConfig::ParquetTokenV2Processor(config) => bucket!(config),
+            ProcessorConfig::ParquetUserTransactionsProcessor(config) => bucket!(config),
+            _ => None,
+        }
+    }
+
+    /// The config shared by the parquet processors, `None` for other processors.
+    pub fn parquet_processor_config(&self) -> Option<&dyn ParquetProcessorTrait> {
+        match self {
+            ProcessorConfig::ParquetDefaultProcessor(config) => Some(config),
+            ProcessorConfig::ParquetFungibleAssetActivitiesProcessor(config) => Some(config),
+            ProcessorConfig::ParquetFungibleAssetProcessor(config) => Some(config),
+            ProcessorConfig::ParquetTransactionMetadataProcessor(config) => Some(config),
+            ProcessorConfig::ParquetAnsProcessor(config) => Some(config),
+            ProcessorConfig::ParquetEventsProcessor(config) => Some(config),
+            ProcessorConfig::ParquetTokenV2Processor(config) => Some(config),
+            ProcessorConfig::ParquetUserTransactionsProcessor(config) => Some(config),
+            _ => None,
+        }
+    }
 }
 
 /// This enum contains all the processors defined in this crate.
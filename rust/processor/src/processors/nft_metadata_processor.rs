@@ -15,20 +15,31 @@ use crate::{
             token_models::tokens::{TableHandleToOwner, TableMetadataForToken},
             token_v2_models::{
                 v2_collections::{CollectionV2, CurrentCollectionV2, CurrentCollectionV2PK},
+                v2_nft_metadata::CurrentNftMetadata,
                 v2_token_datas::CurrentTokenDataV2PK,
             },
         },
     },
     gap_detectors::ProcessingResult,
+    schema,
     utils::{
-        database::{ArcDbPool, DbPoolConnection},
+        database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool, DbPoolConnection},
+        mq::{CustomProducer, CustomProducerEnum},
+        network::Network,
+        nft_metadata_fetcher::{pending_fetch, NftMetadataFetcherConfig},
         util::{parse_timestamp, remove_null_bytes, standardize_address},
     },
     IndexerGrpcProcessorConfig,
 };
 use ahash::AHashMap;
+use anyhow::bail;
 use aptos_protos::transaction::v1::{write_set_change::Change, Transaction};
 use async_trait::async_trait;
+use diesel::{
+    pg::{upsert::excluded, Pg},
+    query_builder::QueryFragment,
+    ExpressionMethods,
+};
 use futures_util::future::try_join_all;
 use google_cloud_googleapis::pubsub::v1::PubsubMessage;
 use google_cloud_pubsub::client::{Client, ClientConfig};
@@ -44,22 +55,54 @@ pub const CHUNK_SIZE: usize = 1000;
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct NftMetadataProcessorConfig {
+    /// Uri updates are sent to `aptos.<network>.nft.uris` through `brokers` if empty
+    #[serde(default)]
     pub pubsub_topic_name: String,
     pub google_application_credentials: Option<String>,
+    /// If set, token uris are queued in `current_nft_metadata` and fetched in the background by
+    /// the processor itself instead of being published to `pubsub_topic_name`
+    #[serde(default)]
+    pub fetcher_config: Option<NftMetadataFetcherConfig>,
     #[serde(default = "IndexerGrpcProcessorConfig::default_query_retries")]
     pub query_retries: u32,
     #[serde(default = "IndexerGrpcProcessorConfig::default_query_retry_delay_ms")]
     pub query_retry_delay_ms: u64,
 }
 
+impl NftMetadataProcessorConfig {
+    /// Where uri updates go. Without `brokers` the producer is a noop that would silently drop
+    /// them, so one of the destinations must be configured.
+    pub fn validate(&self, brokers: Option<&str>) -> anyhow::Result<String> {
+        if self.fetcher_config.is_some() {
+            Ok("Uris are fetched by the processor".to_string())
+        } else if !self.pubsub_topic_name.is_empty() {
+            Ok(format!(
+                "Uri updates are published to {}",
+                self.pubsub_topic_name
+            ))
+        } else if brokers.is_some_and(|brokers| !brokers.is_empty()) {
+            Ok("Uri updates are sent through brokers".to_string())
+        } else {
+            bail!("One of fetcher_config, pubsub_topic_name or brokers must be set for the nft metadata processor")
+        }
+    }
+}
+
 pub struct NftMetadataProcessor {
     connection_pool: ArcDbPool,
     chain_id: u8,
     config: NftMetadataProcessorConfig,
+    producer: CustomProducerEnum,
+    per_table_chunk_sizes: AHashMap<String, usize>,
 }
 
 impl NftMetadataProcessor {
-    pub fn new(connection_pool: ArcDbPool, config: NftMetadataProcessorConfig) -> Self {
+    pub fn new(
+        producer: CustomProducerEnum,
+        connection_pool: ArcDbPool,
+        config: NftMetadataProcessorConfig,
+        per_table_chunk_sizes: AHashMap<String, usize>,
+    ) -> Self {
         tracing::info!("init NftMetadataProcessor");
 
         // Crate reads from authentication from file specified in
@@ -72,6 +115,8 @@ impl NftMetadataProcessor {
             connection_pool,
             chain_id: 0,
             config,
+            producer,
+            per_table_chunk_sizes,
         }
     }
 
@@ -121,14 +166,6 @@ impl ProcessorTrait for NftMetadataProcessor {
         let table_handle_to_owner =
             TableMetadataForToken::get_table_handle_to_owner_from_transactions(&transactions);
 
-        // Initialize pubsub client
-        let config = ClientConfig::default().with_auth().await?;
-        let client = Client::new(config).await?;
-        let topic = client.topic(&self.config.pubsub_topic_name.clone());
-        let publisher = topic.new_publisher(None);
-        let ordering_key = get_current_timestamp();
-
-        // Publish CurrentTokenDataV2 and CurrentCollectionV2 from transactions
         let (token_datas, collections) = parse_v2_token(
             &transactions,
             &table_handle_to_owner,
@@ -137,26 +174,51 @@ impl ProcessorTrait for NftMetadataProcessor {
             query_retry_delay_ms,
         )
         .await;
-        let mut pubsub_messages: Vec<PubsubMessage> =
-            Vec::with_capacity(token_datas.len() + collections.len());
 
-        // Publish all parsed token and collection data to Pubsub
-        for token_data in token_datas {
-            pubsub_messages.push(PubsubMessage {
-                data: clean_token_pubsub_message(token_data, db_chain_id).into(),
-                ordering_key: ordering_key.clone(),
-                ..Default::default()
-            })
-        }
+        if self.config.fetcher_config.is_some() {
+            // Only queued here, the fetcher spawned by the worker fetches them. Collections are
+            // left to the crawler.
+            let mut nft_metadata: Vec<CurrentNftMetadata> =
+                token_datas.iter().map(pending_fetch).collect();
+            // Sort by PK
+            nft_metadata.sort_by(|a, b| a.token_data_id.cmp(&b.token_data_id));
+
+            let processing_duration_in_secs = processing_start.elapsed().as_secs_f64();
+            let db_insertion_start = std::time::Instant::now();
+
+            execute_in_chunks(
+                self.get_pool(),
+                insert_nft_metadata_fetch_tasks_query,
+                &nft_metadata,
+                get_config_table_chunk_size::<CurrentNftMetadata>(
+                    "current_nft_metadata",
+                    &self.per_table_chunk_sizes,
+                ),
+            )
+            .await?;
 
-        for collection in collections {
-            pubsub_messages.push(PubsubMessage {
-                data: clean_collection_pubsub_message(collection, db_chain_id).into(),
-                ordering_key: ordering_key.clone(),
-                ..Default::default()
-            })
+            let db_insertion_duration_in_secs = db_insertion_start.elapsed().as_secs_f64();
+            return Ok(ProcessingResult::DefaultProcessingResult(
+                DefaultProcessingResult {
+                    start_version,
+                    end_version,
+                    processing_duration_in_secs,
+                    db_insertion_duration_in_secs,
+                    last_transaction_timestamp,
+                },
+            ));
         }
 
+        let messages: Vec<NftUriUpdate> = token_datas
+            .iter()
+            .map(|token_data| NftUriUpdate::from_token_data(token_data, db_chain_id))
+            .chain(
+                collections
+                    .iter()
+                    .map(|collection| NftUriUpdate::from_collection(collection, db_chain_id)),
+            )
+            .collect();
+
         let processing_duration_in_secs = processing_start.elapsed().as_secs_f64();
         let db_insertion_start = std::time::Instant::now();
 
@@ -166,20 +228,25 @@ impl ProcessorTrait for NftMetadataProcessor {
             "[NFT Metadata Crawler] Publishing to queue"
         );
 
-        let chunks: Vec<Vec<PubsubMessage>> = pubsub_messages
-            .chunks(CHUNK_SIZE)
-            .map(|chunk| chunk.to_vec())
-            .collect();
-
-        for chunk in chunks {
-            try_join_all(
-                publisher
-                    .publish_bulk(chunk)
-                    .await
-                    .into_iter()
-                    .map(|awaiter| awaiter.get()),
-            )
-            .await?;
+        if self.config.pubsub_topic_name.is_empty() {
+            let Some(network) = Network::from_chain_id(db_chain_id) else {
+                bail!(
+                    "Error getting network from chain id. Processor {}.",
+                    self.name()
+                )
+            };
+            let topic = format!("aptos.{}.nft.uris", network);
+            if let Err(e) = self.producer.send_to_mq(&topic, &messages).await {
+                bail!(
+                    "Error sending nft uris to mq. Processor {}. Start {}. End {}. Error {:?}",
+                    self.name(),
+                    start_version,
+                    end_version,
+                    e
+                )
+            }
+        } else {
+            self.publish_to_pubsub(&messages).await?;
         }
 
         let db_insertion_duration_in_secs = db_insertion_start.elapsed().as_secs_f64();
@@ -200,26 +267,156 @@ impl ProcessorTrait for NftMetadataProcessor {
     }
 }
 
-fn clean_token_pubsub_message(ctd: RawCurrentTokenDataV2, db_chain_id: u64) -> String {
-    remove_null_bytes(&format!(
-        "{},{},{},{},{},false",
-        ctd.token_data_id,
-        ctd.token_uri,
-        ctd.last_transaction_version,
-        ctd.last_transaction_timestamp,
-        db_chain_id,
-    ))
+impl NftMetadataProcessor {
+    async fn publish_to_pubsub(&self, messages: &[NftUriUpdate]) -> anyhow::Result<()> {
+        // Initialize pubsub client
+        let config = ClientConfig::default().with_auth().await?;
+        let client = Client::new(config).await?;
+        let topic = client.topic(&self.config.pubsub_topic_name.clone());
+        let publisher = topic.new_publisher(None);
+        let ordering_key = get_current_timestamp();
+
+        let pubsub_messages: Vec<PubsubMessage> = messages
+            .iter()
+            .map(|message| PubsubMessage {
+                data: message.to_pubsub_message().into(),
+                ordering_key: ordering_key.clone(),
+                ..Default::default()
+            })
+            .collect();
+
+        for chunk in pubsub_messages.chunks(CHUNK_SIZE) {
+            try_join_all(
+                publisher
+                    .publish_bulk(chunk.to_vec())
+                    .await
+                    .into_iter()
+                    .map(|awaiter| awaiter.get()),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Queues the uris of token datas. A token is only fetched again when its uri changes.
+pub fn insert_nft_metadata_fetch_tasks_query(
+    items_to_insert: Vec<CurrentNftMetadata>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::current_nft_metadata::dsl::*;
+    (
+        diesel::insert_into(schema::current_nft_metadata::table)
+            .values(items_to_insert)
+            .on_conflict(token_data_id)
+            .do_update()
+            .set((
+                token_uri.eq(excluded(token_uri)),
+                status.eq(excluded(status)),
+                error_message.eq(excluded(error_message)),
+                num_attempts.eq(excluded(num_attempts)),
+                metadata.eq(excluded(metadata)),
+                image_uri.eq(excluded(image_uri)),
+                image_content_hash.eq(excluded(image_content_hash)),
+                last_transaction_version.eq(excluded(last_transaction_version)),
+                last_fetched_at.eq(excluded(last_fetched_at)),
+                next_attempt_at.eq(excluded(next_attempt_at)),
+                inserted_at.eq(excluded(inserted_at)),
+            )),
+        Some(
+            " WHERE current_nft_metadata.last_transaction_version <= excluded.last_transaction_version AND current_nft_metadata.token_uri <> excluded.token_uri ",
+        ),
+    )
 }
 
-fn clean_collection_pubsub_message(cc: CurrentCollectionV2, db_chain_id: u64) -> String {
-    remove_null_bytes(&format!(
-        "{},{},{},{},{},false",
-        cc.collection_id,
-        cc.uri,
-        cc.last_transaction_version,
-        cc.last_transaction_timestamp,
-        db_chain_id,
-    ))
+/// Writes fetch results, unless the token was queued with another uri while it was fetched
+pub fn update_nft_metadata_fetch_results_query(
+    items_to_insert: Vec<CurrentNftMetadata>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::current_nft_metadata::dsl::*;
+    (
+        diesel::insert_into(schema::current_nft_metadata::table)
+            .values(items_to_insert)
+            .on_conflict(token_data_id)
+            .do_update()
+            .set((
+                status.eq(excluded(status)),
+                error_message.eq(excluded(error_message)),
+                num_attempts.eq(excluded(num_attempts)),
+                metadata.eq(excluded(metadata)),
+                image_uri.eq(excluded(image_uri)),
+                image_content_hash.eq(excluded(image_content_hash)),
+                last_fetched_at.eq(excluded(last_fetched_at)),
+                next_attempt_at.eq(excluded(next_attempt_at)),
+                inserted_at.eq(excluded(inserted_at)),
+            )),
+        Some(" WHERE current_nft_metadata.token_uri = excluded.token_uri "),
+    )
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NftUriKind {
+    Token,
+    Collection,
+}
+
+/// Sent for every token data and collection written in a batch, so that its uri gets crawled
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NftUriUpdate {
+    pub kind: NftUriKind,
+    /// token_data_id for tokens, collection_id for collections
+    pub asset_id: String,
+    pub uri: String,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+    pub chain_id: u64,
+    /// Whether the crawler should parse the uri again even if it already has
+    pub force: bool,
+}
+
+impl NftUriUpdate {
+    pub fn from_token_data(ctd: &RawCurrentTokenDataV2, chain_id: u64) -> Self {
+        remove_null_bytes(&Self {
+            kind: NftUriKind::Token,
+            asset_id: ctd.token_data_id.clone(),
+            uri: ctd.token_uri.clone(),
+            last_transaction_version: ctd.last_transaction_version,
+            last_transaction_timestamp: ctd.last_transaction_timestamp,
+            chain_id,
+            force: false,
+        })
+    }
+
+    pub fn from_collection(cc: &CurrentCollectionV2, chain_id: u64) -> Self {
+        remove_null_bytes(&Self {
+            kind: NftUriKind::Collection,
+            asset_id: cc.collection_id.clone(),
+            uri: cc.uri.clone(),
+            last_transaction_version: cc.last_transaction_version,
+            last_transaction_timestamp: cc.last_transaction_timestamp,
+            chain_id,
+            force: false,
+        })
+    }
+
+    /// The CSV the NFT metadata crawler parses from Pub/Sub
+    pub fn to_pubsub_message(&self) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.asset_id,
+            self.uri,
+            self.last_transaction_version,
+            self.last_transaction_timestamp,
+            self.chain_id,
+            self.force,
+        )
+    }
 }
 
 /// Copied from token_processor;
@@ -353,3 +550,58 @@ fn get_current_timestamp() -> String {
         .as_millis()
         .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_nft_uri_update_messages() {
+        let message = NftUriUpdate {
+            kind: NftUriKind::Collection,
+            asset_id: "0xc".to_string(),
+            uri: "ipfs://bafy".to_string(),
+            last_transaction_version: 5,
+            last_transaction_timestamp: chrono::NaiveDateTime::default(),
+            chain_id: 2,
+            force: false,
+        };
+        assert_eq!(
+            message.to_pubsub_message(),
+            "0xc,ipfs://bafy,5,1970-01-01 00:00:00,2,false"
+        );
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({
+                "kind": "collection",
+                "asset_id": "0xc",
+                "uri": "ipfs://bafy",
+                "last_transaction_version": 5,
+                "last_transaction_timestamp": "1970-01-01T00:00:00",
+                "chain_id": 2,
+                "force": false,
+            })
+        );
+    }
+    #[test]
+    fn test_config_requires_a_uri_destination() {
+        let mut config = NftMetadataProcessorConfig {
+            pubsub_topic_name: "".to_string(),
+            google_application_credentials: None,
+            fetcher_config: None,
+            query_retries: IndexerGrpcProcessorConfig::default_query_retries(),
+            query_retry_delay_ms: IndexerGrpcProcessorConfig::default_query_retry_delay_ms(),
+        };
+        assert!(config.validate(None).is_err());
+        assert!(config.validate(Some("")).is_err());
+        assert!(config.validate(Some("localhost:9092")).is_ok());
+
+        config.pubsub_topic_name = "nft-uris".to_string();
+        assert!(config.validate(None).is_ok());
+
+        config.pubsub_topic_name = "".to_string();
+        config.fetcher_config = Some(NftMetadataFetcherConfig::default());
+        assert!(config.validate(None).is_ok());
+    }
+}
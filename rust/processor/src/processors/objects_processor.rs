@@ -5,6 +5,9 @@ use super::{DefaultProcessingResult, ProcessorName, ProcessorTrait};
 use crate::{
     db::{
         common::models::object_models::{
+            raw_v2_object_ultimate_owners::{
+                CurrentObjectUltimateOwnerConvertible, RawCurrentObjectUltimateOwner,
+            },
             raw_v2_objects::{
                 CurrentObjectConvertible, ObjectConvertible, RawCurrentObject, RawObject,
             },
@@ -14,7 +17,10 @@ use crate::{
             },
         },
         postgres::models::{
-            object_models::v2_objects::{CurrentObject, Object},
+            object_models::{
+                v2_object_ultimate_owners::CurrentObjectUltimateOwner,
+                v2_objects::{CurrentObject, Object},
+            },
             resources::FromWriteResource,
         },
     },
@@ -111,7 +117,11 @@ async fn insert_to_db(
     name: &'static str,
     start_version: u64,
     end_version: u64,
-    (objects, current_objects): (&[Object], &[CurrentObject]),
+    (objects, current_objects, current_object_ultimate_owners): (
+        &[Object],
+        &[CurrentObject],
+        &[CurrentObjectUltimateOwner],
+    ),
     per_table_chunk_sizes: &AHashMap<String, usize>,
 ) -> Result<(), diesel::result::Error> {
     tracing::trace!(
@@ -128,13 +138,22 @@ async fn insert_to_db(
         get_config_table_chunk_size::<Object>("objects", per_table_chunk_sizes),
     );
     let co = execute_in_chunks(
-        conn,
+        conn.clone(),
         insert_current_objects_query,
         current_objects,
         get_config_table_chunk_size::<CurrentObject>("current_objects", per_table_chunk_sizes),
     );
-    let (io_res, co_res) = tokio::join!(io, co);
-    for res in [io_res, co_res] {
+    let coo = execute_in_chunks(
+        conn,
+        insert_current_object_ultimate_owners_query,
+        current_object_ultimate_owners,
+        get_config_table_chunk_size::<CurrentObjectUltimateOwner>(
+            "current_object_ultimate_owners",
+            per_table_chunk_sizes,
+        ),
+    );
+    let (io_res, co_res, coo_res) = tokio::join!(io, co, coo);
+    for res in [io_res, co_res, coo_res] {
         res?;
     }
 
@@ -186,6 +205,33 @@ pub fn insert_current_objects_query(
     )
 }
 
+pub fn insert_current_object_ultimate_owners_query(
+    items_to_insert: Vec<CurrentObjectUltimateOwner>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::current_object_ultimate_owners::dsl::*;
+    (
+        diesel::insert_into(schema::current_object_ultimate_owners::table)
+            .values(items_to_insert)
+            .on_conflict(object_address)
+            .do_update()
+            .set((
+                direct_owner_address.eq(excluded(direct_owner_address)),
+                ultimate_owner_address.eq(excluded(ultimate_owner_address)),
+                ownership_path.eq(excluded(ownership_path)),
+                depth.eq(excluded(depth)),
+                is_deleted.eq(excluded(is_deleted)),
+                last_transaction_version.eq(excluded(last_transaction_version)),
+                inserted_at.eq(excluded(inserted_at)),
+            )),
+        Some(
+            " WHERE current_object_ultimate_owners.last_transaction_version <= excluded.last_transaction_version ",
+        ),
+    )
+}
+
 #[async_trait]
 impl ProcessorTrait for ObjectsProcessor {
     fn name(&self) -> &'static str {
@@ -205,14 +251,20 @@ impl ProcessorTrait for ObjectsProcessor {
         let query_retries = self.config.query_retries;
         let query_retry_delay_ms = self.config.query_retry_delay_ms;
 
-        let db_connection = DbContext {
+        let mut db_context = Some(DbContext {
             conn,
             query_retries,
             query_retry_delay_ms,
-        };
+        });
 
         let (mut raw_all_objects, raw_all_current_objects) =
-            process_objects(transactions, &mut Some(db_connection)).await;
+            process_objects(transactions, &mut db_context).await;
+        let raw_current_object_ultimate_owners =
+            RawCurrentObjectUltimateOwner::from_current_objects(
+                &raw_all_current_objects,
+                &mut db_context,
+            )
+            .await?;
 
         if self.deprecated_tables.contains(TableFlags::OBJECTS) {
             raw_all_objects.clear();
@@ -224,6 +276,11 @@ impl ProcessorTrait for ObjectsProcessor {
             .into_iter()
             .map(CurrentObject::from_raw)
             .collect();
+        let postgres_current_object_ultimate_owners: Vec<CurrentObjectUltimateOwner> =
+            raw_current_object_ultimate_owners
+                .into_iter()
+                .map(CurrentObjectUltimateOwner::from_raw)
+                .collect();
 
         let processing_duration_in_secs = processing_start.elapsed().as_secs_f64();
         let db_insertion_start = std::time::Instant::now();
@@ -260,7 +317,11 @@ impl ProcessorTrait for ObjectsProcessor {
             self.name(),
             start_version,
             end_version,
-            (&postgres_objects, &postgres_current_objects),
+            (
+                &postgres_objects,
+                &postgres_current_objects,
+                &postgres_current_object_ultimate_owners,
+            ),
             &self.per_table_chunk_sizes,
         )
         .await;
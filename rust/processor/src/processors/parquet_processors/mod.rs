@@ -1,3 +1,7 @@
+use crate::bq_analytics::parquet_writer_config::{
+    validate_table_writer_configs, writer_config_for_table, ParquetWriterConfig,
+};
+use ahash::AHashMap;
 use std::time::Duration;
 
 pub mod parquet_ans_processor;
@@ -14,6 +18,22 @@ const GOOGLE_APPLICATION_CREDENTIALS: &str = "GOOGLE_APPLICATION_CREDENTIALS";
 pub trait ParquetProcessorTrait {
     fn parquet_upload_interval_in_secs(&self) -> Duration;
 
+    fn table_writer_configs(&self) -> &AHashMap<String, ParquetWriterConfig>;
+
+    /// Names of the tables the processor writes.
+    fn table_names(&self) -> &'static [&'static str];
+
+    fn writer_config(&self, table_name: &str) -> ParquetWriterConfig {
+        writer_config_for_table(self.table_writer_configs(), table_name)
+    }
+
+    fn validate_table_writer_configs(&self) -> anyhow::Result<()> {
+        validate_table_writer_configs(
+            self.table_writer_configs(),
+            self.table_names().iter().copied(),
+        )
+    }
+
     fn set_google_credentials(&self, credentials: Option<String>) {
         if let Some(credentials) = credentials {
             std::env::set_var(GOOGLE_APPLICATION_CREDENTIALS, credentials);
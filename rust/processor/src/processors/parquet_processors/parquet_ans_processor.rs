@@ -4,7 +4,9 @@
 use super::ParquetProcessorTrait;
 use crate::{
     bq_analytics::{
-        create_parquet_handler_loop, generic_parquet_processor::ParquetDataGeneric,
+        create_parquet_handler_loop,
+        generic_parquet_processor::{NamedTable, ParquetDataGeneric},
+        parquet_writer_config::ParquetWriterConfig,
         ParquetProcessingResult,
     },
     db::postgres::models::ans_models::{
@@ -38,12 +40,22 @@ pub struct ParquetAnsProcessorConfig {
     pub ans_v1_name_records_table_handle: String,
     pub ans_v2_contract_address: String,
     pub parquet_upload_interval: u64,
+    #[serde(default)]
+    pub table_writer_configs: AHashMap<String, ParquetWriterConfig>,
 }
 
 impl ParquetProcessorTrait for ParquetAnsProcessorConfig {
     fn parquet_upload_interval_in_secs(&self) -> Duration {
         Duration::from_secs(self.parquet_upload_interval)
     }
+
+    fn table_writer_configs(&self) -> &AHashMap<String, ParquetWriterConfig> {
+        &self.table_writer_configs
+    }
+
+    fn table_names(&self) -> &'static [&'static str] {
+        &[AnsPrimaryNameV2::TABLE_NAME]
+    }
 }
 
 pub struct ParquetAnsProcessor {
@@ -68,6 +80,7 @@ impl ParquetAnsProcessor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(AnsPrimaryNameV2::TABLE_NAME),
         );
 
         Self {
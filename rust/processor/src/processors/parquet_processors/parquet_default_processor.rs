@@ -3,7 +3,9 @@
 
 use crate::{
     bq_analytics::{
-        create_parquet_handler_loop, generic_parquet_processor::ParquetDataGeneric,
+        create_parquet_handler_loop,
+        generic_parquet_processor::{NamedTable, ParquetDataGeneric},
+        parquet_writer_config::ParquetWriterConfig,
         ParquetProcessingResult,
     },
     db::parquet::models::default_models::{
@@ -37,11 +39,27 @@ pub struct ParquetDefaultProcessorConfig {
     pub parquet_handler_response_channel_size: usize,
     pub max_buffer_size: usize,
     pub parquet_upload_interval: u64,
+    #[serde(default)]
+    pub table_writer_configs: AHashMap<String, ParquetWriterConfig>,
 }
 impl ParquetProcessorTrait for ParquetDefaultProcessorConfig {
     fn parquet_upload_interval_in_secs(&self) -> Duration {
         Duration::from_secs(self.parquet_upload_interval)
     }
+
+    fn table_writer_configs(&self) -> &AHashMap<String, ParquetWriterConfig> {
+        &self.table_writer_configs
+    }
+
+    fn table_names(&self) -> &'static [&'static str] {
+        &[
+            ParquetTransaction::TABLE_NAME,
+            MoveResource::TABLE_NAME,
+            WriteSetChangeModel::TABLE_NAME,
+            TableItem::TABLE_NAME,
+            MoveModule::TABLE_NAME,
+        ]
+    }
 }
 
 pub struct ParquetDefaultProcessor {
@@ -71,6 +89,7 @@ impl ParquetDefaultProcessor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(ParquetTransaction::TABLE_NAME),
         );
 
         let move_resource_sender = create_parquet_handler_loop::<MoveResource>(
@@ -81,6 +100,7 @@ impl ParquetDefaultProcessor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(MoveResource::TABLE_NAME),
         );
 
         let wsc_sender = create_parquet_handler_loop::<WriteSetChangeModel>(
@@ -91,6 +111,7 @@ impl ParquetDefaultProcessor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(WriteSetChangeModel::TABLE_NAME),
         );
 
         let table_item_sender = create_parquet_handler_loop::<TableItem>(
@@ -101,6 +122,7 @@ impl ParquetDefaultProcessor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(TableItem::TABLE_NAME),
         );
         let move_module_sender = create_parquet_handler_loop::<MoveModule>(
             new_gap_detector_sender.clone(),
@@ -110,6 +132,7 @@ impl ParquetDefaultProcessor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(MoveModule::TABLE_NAME),
         );
 
         Self {
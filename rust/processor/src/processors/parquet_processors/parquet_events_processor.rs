@@ -3,7 +3,9 @@
 
 use crate::{
     bq_analytics::{
-        create_parquet_handler_loop, generic_parquet_processor::ParquetDataGeneric,
+        create_parquet_handler_loop,
+        generic_parquet_processor::{NamedTable, ParquetDataGeneric},
+        parquet_writer_config::ParquetWriterConfig,
         ParquetProcessingResult,
     },
     db::{
@@ -30,12 +32,22 @@ pub struct ParquetEventsProcessorConfig {
     pub parquet_handler_response_channel_size: usize,
     pub max_buffer_size: usize,
     pub parquet_upload_interval: u64,
+    #[serde(default)]
+    pub table_writer_configs: AHashMap<String, ParquetWriterConfig>,
 }
 
 impl ParquetProcessorTrait for ParquetEventsProcessorConfig {
     fn parquet_upload_interval_in_secs(&self) -> Duration {
         Duration::from_secs(self.parquet_upload_interval)
     }
+
+    fn table_writer_configs(&self) -> &AHashMap<String, ParquetWriterConfig> {
+        &self.table_writer_configs
+    }
+
+    fn table_names(&self) -> &'static [&'static str] {
+        &[EventPQ::TABLE_NAME]
+    }
 }
 
 pub struct ParquetEventsProcessor {
@@ -59,6 +71,7 @@ impl ParquetEventsProcessor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(EventPQ::TABLE_NAME),
         );
 
         Self {
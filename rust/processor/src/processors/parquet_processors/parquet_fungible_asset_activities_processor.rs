@@ -4,7 +4,9 @@
 use super::ParquetProcessorTrait;
 use crate::{
     bq_analytics::{
-        create_parquet_handler_loop, generic_parquet_processor::ParquetDataGeneric,
+        create_parquet_handler_loop,
+        generic_parquet_processor::{NamedTable, ParquetDataGeneric},
+        parquet_writer_config::ParquetWriterConfig,
         ParquetProcessingResult,
     },
     db::{
@@ -51,12 +53,22 @@ pub struct ParquetFungibleAssetActivitiesProcessorConfig {
     pub parquet_handler_response_channel_size: usize,
     pub max_buffer_size: usize,
     pub parquet_upload_interval: u64,
+    #[serde(default)]
+    pub table_writer_configs: AHashMap<String, ParquetWriterConfig>,
 }
 
 impl ParquetProcessorTrait for ParquetFungibleAssetActivitiesProcessorConfig {
     fn parquet_upload_interval_in_secs(&self) -> Duration {
         Duration::from_secs(self.parquet_upload_interval)
     }
+
+    fn table_writer_configs(&self) -> &AHashMap<String, ParquetWriterConfig> {
+        &self.table_writer_configs
+    }
+
+    fn table_names(&self) -> &'static [&'static str] {
+        &[FungibleAssetActivity::TABLE_NAME]
+    }
 }
 
 pub struct ParquetFungibleAssetActivitiesProcessor {
@@ -82,6 +94,7 @@ impl ParquetFungibleAssetActivitiesProcessor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(FungibleAssetActivity::TABLE_NAME),
         );
 
         Self {
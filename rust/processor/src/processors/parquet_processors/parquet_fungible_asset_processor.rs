@@ -4,7 +4,9 @@
 use super::ParquetProcessorTrait;
 use crate::{
     bq_analytics::{
-        create_parquet_handler_loop, generic_parquet_processor::ParquetDataGeneric,
+        create_parquet_handler_loop,
+        generic_parquet_processor::{NamedTable, ParquetDataGeneric},
+        parquet_writer_config::ParquetWriterConfig,
         ParquetProcessingResult,
     },
     db::{
@@ -43,12 +45,22 @@ pub struct ParquetFungibleAssetProcessorConfig {
     pub parquet_handler_response_channel_size: usize,
     pub max_buffer_size: usize,
     pub parquet_upload_interval: u64,
+    #[serde(default)]
+    pub table_writer_configs: AHashMap<String, ParquetWriterConfig>,
 }
 
 impl ParquetProcessorTrait for ParquetFungibleAssetProcessorConfig {
     fn parquet_upload_interval_in_secs(&self) -> Duration {
         Duration::from_secs(self.parquet_upload_interval)
     }
+
+    fn table_writer_configs(&self) -> &AHashMap<String, ParquetWriterConfig> {
+        &self.table_writer_configs
+    }
+
+    fn table_names(&self) -> &'static [&'static str] {
+        &[CoinSupply::TABLE_NAME, FungibleAssetBalance::TABLE_NAME]
+    }
 }
 
 pub struct ParquetFungibleAssetProcessor {
@@ -73,6 +85,7 @@ impl ParquetFungibleAssetProcessor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(CoinSupply::TABLE_NAME),
         );
 
         let fungible_asset_balances_sender = create_parquet_handler_loop::<FungibleAssetBalance>(
@@ -83,6 +96,7 @@ impl ParquetFungibleAssetProcessor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(FungibleAssetBalance::TABLE_NAME),
         );
 
         Self {
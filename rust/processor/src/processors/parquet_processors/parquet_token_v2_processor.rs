@@ -4,7 +4,9 @@
 
 use crate::{
     bq_analytics::{
-        create_parquet_handler_loop, generic_parquet_processor::ParquetDataGeneric,
+        create_parquet_handler_loop,
+        generic_parquet_processor::{NamedTable, ParquetDataGeneric},
+        parquet_writer_config::ParquetWriterConfig,
         ParquetProcessingResult,
     },
     db::{
@@ -56,11 +58,21 @@ pub struct ParquetTokenV2ProcessorConfig {
     pub parquet_handler_response_channel_size: usize,
     pub max_buffer_size: usize,
     pub parquet_upload_interval: u64,
+    #[serde(default)]
+    pub table_writer_configs: AHashMap<String, ParquetWriterConfig>,
 }
 impl ParquetProcessorTrait for ParquetTokenV2ProcessorConfig {
     fn parquet_upload_interval_in_secs(&self) -> Duration {
         Duration::from_secs(self.parquet_upload_interval)
     }
+
+    fn table_writer_configs(&self) -> &AHashMap<String, ParquetWriterConfig> {
+        &self.table_writer_configs
+    }
+
+    fn table_names(&self) -> &'static [&'static str] {
+        &[TokenDataV2::TABLE_NAME, TokenOwnershipV2::TABLE_NAME]
+    }
 }
 
 pub struct ParquetTokenV2Processor {
@@ -85,6 +97,7 @@ impl ParquetTokenV2Processor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(TokenDataV2::TABLE_NAME),
         );
 
         let v2_token_ownerships_sender = create_parquet_handler_loop::<TokenOwnershipV2>(
@@ -95,6 +108,7 @@ impl ParquetTokenV2Processor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(TokenOwnershipV2::TABLE_NAME),
         );
 
         Self {
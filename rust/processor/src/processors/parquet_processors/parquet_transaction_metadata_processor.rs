@@ -3,7 +3,9 @@
 
 use crate::{
     bq_analytics::{
-        create_parquet_handler_loop, generic_parquet_processor::ParquetDataGeneric,
+        create_parquet_handler_loop,
+        generic_parquet_processor::{NamedTable, ParquetDataGeneric},
+        parquet_writer_config::ParquetWriterConfig,
         ParquetProcessingResult,
     },
     db::parquet::models::transaction_metadata_model::parquet_write_set_size_info::WriteSetSize,
@@ -29,12 +31,22 @@ pub struct ParquetTransactionMetadataProcessorConfig {
     pub parquet_handler_response_channel_size: usize,
     pub max_buffer_size: usize,
     pub parquet_upload_interval: u64,
+    #[serde(default)]
+    pub table_writer_configs: AHashMap<String, ParquetWriterConfig>,
 }
 
 impl ParquetProcessorTrait for ParquetTransactionMetadataProcessorConfig {
     fn parquet_upload_interval_in_secs(&self) -> Duration {
         Duration::from_secs(self.parquet_upload_interval)
     }
+
+    fn table_writer_configs(&self) -> &AHashMap<String, ParquetWriterConfig> {
+        &self.table_writer_configs
+    }
+
+    fn table_names(&self) -> &'static [&'static str] {
+        &[WriteSetSize::TABLE_NAME]
+    }
 }
 
 pub struct ParquetTransactionMetadataProcessor {
@@ -58,6 +70,7 @@ impl ParquetTransactionMetadataProcessor {
             config.parquet_handler_response_channel_size,
             config.max_buffer_size,
             config.parquet_upload_interval_in_secs(),
+            config.writer_config(WriteSetSize::TABLE_NAME),
         );
         Self {
             connection_pool,
@@ -13,6 +13,10 @@ use crate::{
                     CurrentTokenPendingClaimConvertible, RawCurrentTokenPendingClaim,
                     TokenV1Claimed,
                 },
+                raw_token_royalty::{CurrentTokenRoyaltyConvertible, RawCurrentTokenRoyalty},
+                raw_token_v1_to_v2_mappings::{
+                    RawTokenV1ToV2Mapping, TokenV1ToV2MappingConvertible,
+                },
                 raw_v1_token_royalty::{
                     CurrentTokenRoyaltyV1Convertible, RawCurrentTokenRoyaltyV1,
                 },
@@ -28,6 +32,13 @@ use crate::{
                     CurrentTokenOwnershipV2Convertible, CurrentTokenOwnershipV2PK, NFTOwnershipV2,
                     RawCurrentTokenOwnershipV2, RawTokenOwnershipV2, TokenOwnershipV2Convertible,
                 },
+                raw_v2_token_property_mutations::{
+                    RawTokenPropertyMutation, TokenPropertyMutationConvertible, TokenPropertyWrite,
+                },
+                raw_v2_token_transferability::{
+                    CurrentTokenTransferabilityV2Convertible, RawCurrentTokenTransferabilityV2,
+                    RawTokenTransferabilityV2, TokenTransferabilityV2Convertible,
+                },
                 v2_token_utils::{
                     Burn, BurnEvent, Mint, MintEvent, TokenV2Burned, TokenV2Minted, TransferEvent,
                 },
@@ -41,12 +52,16 @@ use crate::{
                 tokens::{CurrentTokenPendingClaimPK, TableHandleToOwner, TableMetadataForToken},
             },
             token_v2_models::{
+                token_royalty::CurrentTokenRoyalty,
+                token_v1_to_v2_mappings::TokenV1ToV2Mapping,
                 v1_token_royalty::CurrentTokenRoyaltyV1,
                 v2_collections::{CollectionV2, CurrentCollectionV2, CurrentCollectionV2PK},
                 v2_token_activities::TokenActivityV2,
                 v2_token_datas::{CurrentTokenDataV2, CurrentTokenDataV2PK, TokenDataV2},
                 v2_token_metadata::{CurrentTokenV2Metadata, CurrentTokenV2MetadataPK},
                 v2_token_ownerships::{CurrentTokenOwnershipV2, TokenOwnershipV2},
+                v2_token_property_mutations::TokenPropertyMutation,
+                v2_token_transferability::{CurrentTokenTransferabilityV2, TokenTransferabilityV2},
             },
         },
     },
@@ -67,8 +82,10 @@ use anyhow::bail;
 use aptos_protos::transaction::v1::{transaction::TxnData, write_set_change::Change, Transaction};
 use async_trait::async_trait;
 use diesel::{
+    dsl::sql,
     pg::{upsert::excluded, Pg},
     query_builder::QueryFragment,
+    sql_types::{Bool, Nullable, Numeric, Text},
     ExpressionMethods,
 };
 use serde::{Deserialize, Serialize};
@@ -128,12 +145,17 @@ async fn produce_to_mq(
     end_version: u64,
     network: String,
     collections_v2: &[CollectionV2],
-    token_datas_v2: &[TokenDataV2],
+    (token_datas_v2, token_v1_to_v2_mappings): (&[TokenDataV2], &[TokenV1ToV2Mapping]),
     token_ownerships_v2: &[TokenOwnershipV2],
     token_activities_v2: &[TokenActivityV2],
     current_token_v2_metadata: &[CurrentTokenV2Metadata],
-    current_token_royalties_v1: &[CurrentTokenRoyaltyV1],
+    (current_token_royalties_v1, current_token_royalties): (
+        &[CurrentTokenRoyaltyV1],
+        &[CurrentTokenRoyalty],
+    ),
     current_token_claims: &[CurrentTokenPendingClaim],
+    token_property_mutations: &[TokenPropertyMutation],
+    token_transferabilities_v2: &[TokenTransferabilityV2],
 ) -> Result<(), String> {
     tracing::trace!(
         name = name,
@@ -144,23 +166,44 @@ async fn produce_to_mq(
 
     let cv2_topic = format!("aptos.{}.collections.v2", network);
     let tdv2_topic = format!("aptos.{}.token.datas.v2", network);
+    let tv1v2m_topic = format!("aptos.{}.token.v1.to.v2.mappings", network);
     let tov2_topic = format!("aptos.{}.token.ownerships.v2", network);
     let tav2_topic = format!("aptos.{}.token.activities.v2", network);
     let ctv2m_topic = format!("aptos.{}.current.token.v2.metadata", network);
     let ctrv1_topic = format!("aptos.{}.current.token.royalty.v1", network);
+    let ctr_topic = format!("aptos.{}.current.token.royalty", network);
     let ctc_topic = format!("aptos.{}.current.token.pending.claims", network);
-    let (cv2_res, tdv2_res, tov2_res, tav2_res, ctv2m_res, ctrv1_res, ctc_res) = tokio::join!(
+    let tpm_topic = format!("aptos.{}.token.property.mutations", network);
+    let ttv2_topic = format!("aptos.{}.token.transferability.v2", network);
+    let (
+        cv2_res,
+        tdv2_res,
+        tv1v2m_res,
+        tov2_res,
+        tav2_res,
+        ctv2m_res,
+        ctrv1_res,
+        ctr_res,
+        ctc_res,
+        tpm_res,
+        ttv2_res,
+    ) = tokio::join!(
         producer.send_to_mq(cv2_topic.as_str(), collections_v2),
         producer.send_to_mq(tdv2_topic.as_str(), token_datas_v2),
+        producer.send_to_mq(tv1v2m_topic.as_str(), token_v1_to_v2_mappings),
         producer.send_to_mq(tov2_topic.as_str(), token_ownerships_v2),
         producer.send_to_mq(tav2_topic.as_str(), token_activities_v2),
         producer.send_to_mq(ctv2m_topic.as_str(), current_token_v2_metadata),
         producer.send_to_mq(ctrv1_topic.as_str(), current_token_royalties_v1),
+        producer.send_to_mq(ctr_topic.as_str(), current_token_royalties),
         producer.send_to_mq(ctc_topic.as_str(), current_token_claims),
+        producer.send_to_mq(tpm_topic.as_str(), token_property_mutations),
+        producer.send_to_mq(ttv2_topic.as_str(), token_transferabilities_v2),
     );
 
     for res in vec![
-        cv2_res, tdv2_res, tov2_res, tav2_res, ctv2m_res, ctrv1_res, ctc_res,
+        cv2_res, tdv2_res, tv1v2m_res, tov2_res, tav2_res, ctv2m_res, ctrv1_res, ctr_res, ctc_res,
+        tpm_res, ttv2_res,
     ] {
         res?;
     }
@@ -175,7 +218,7 @@ async fn insert_to_db(
     start_version: u64,
     end_version: u64,
     collections_v2: &[CollectionV2],
-    token_datas_v2: &[TokenDataV2],
+    (token_datas_v2, token_v1_to_v2_mappings): (&[TokenDataV2], &[TokenV1ToV2Mapping]),
     token_ownerships_v2: &[TokenOwnershipV2],
     current_collections_v2: &[CurrentCollectionV2],
     (current_token_datas_v2, current_deleted_token_datas_v2): (
@@ -188,8 +231,16 @@ async fn insert_to_db(
     ),
     token_activities_v2: &[TokenActivityV2],
     current_token_v2_metadata: &[CurrentTokenV2Metadata],
-    current_token_royalties_v1: &[CurrentTokenRoyaltyV1],
+    (current_token_royalties_v1, current_token_royalties): (
+        &[CurrentTokenRoyaltyV1],
+        &[CurrentTokenRoyalty],
+    ),
     current_token_claims: &[CurrentTokenPendingClaim],
+    token_property_mutations: &[TokenPropertyMutation],
+    (token_transferabilities_v2, current_token_transferabilities_v2): (
+        &[TokenTransferabilityV2],
+        &[CurrentTokenTransferabilityV2],
+    ),
     per_table_chunk_sizes: &AHashMap<String, usize>,
 ) -> Result<(), diesel::result::Error> {
     tracing::trace!(
@@ -211,6 +262,15 @@ async fn insert_to_db(
         token_datas_v2,
         get_config_table_chunk_size::<TokenDataV2>("token_datas_v2", per_table_chunk_sizes),
     );
+    let tv1v2m = execute_in_chunks(
+        conn.clone(),
+        insert_token_v1_to_v2_mappings_query,
+        token_v1_to_v2_mappings,
+        get_config_table_chunk_size::<TokenV1ToV2Mapping>(
+            "token_v1_to_v2_mappings",
+            per_table_chunk_sizes,
+        ),
+    );
     let to_v2 = execute_in_chunks(
         conn.clone(),
         insert_token_ownerships_v2_query,
@@ -292,8 +352,17 @@ async fn insert_to_db(
             per_table_chunk_sizes,
         ),
     );
+    let ctr = execute_in_chunks(
+        conn.clone(),
+        insert_current_token_royalties_query,
+        current_token_royalties,
+        get_config_table_chunk_size::<CurrentTokenRoyalty>(
+            "current_token_royalty",
+            per_table_chunk_sizes,
+        ),
+    );
     let ctc_v1 = execute_in_chunks(
-        conn,
+        conn.clone(),
         insert_current_token_claims_query,
         current_token_claims,
         get_config_table_chunk_size::<CurrentTokenPendingClaim>(
@@ -301,10 +370,38 @@ async fn insert_to_db(
             per_table_chunk_sizes,
         ),
     );
+    let tpm = execute_in_chunks(
+        conn.clone(),
+        insert_token_property_mutations_query,
+        token_property_mutations,
+        get_config_table_chunk_size::<TokenPropertyMutation>(
+            "token_property_mutations",
+            per_table_chunk_sizes,
+        ),
+    );
+    let tt_v2 = execute_in_chunks(
+        conn.clone(),
+        insert_token_transferabilities_v2_query,
+        token_transferabilities_v2,
+        get_config_table_chunk_size::<TokenTransferabilityV2>(
+            "token_transferability_v2",
+            per_table_chunk_sizes,
+        ),
+    );
+    let ctt_v2 = execute_in_chunks(
+        conn,
+        insert_current_token_transferabilities_v2_query,
+        current_token_transferabilities_v2,
+        get_config_table_chunk_size::<CurrentTokenTransferabilityV2>(
+            "current_token_transferability_v2",
+            per_table_chunk_sizes,
+        ),
+    );
 
     let (
         coll_v2_res,
         td_v2_res,
+        tv1v2m_res,
         to_v2_res,
         cc_v2_res,
         ctd_v2_res,
@@ -314,15 +411,20 @@ async fn insert_to_db(
         ta_v2_res,
         ct_v2_res,
         ctr_v1_res,
+        ctr_res,
         ctc_v1_res,
+        tpm_res,
+        tt_v2_res,
+        ctt_v2_res,
     ) = tokio::join!(
-        coll_v2, td_v2, to_v2, cc_v2, ctd_v2, cdtd_v2, cto_v2, cdto_v2, ta_v2, ct_v2, ctr_v1,
-        ctc_v1
+        coll_v2, td_v2, tv1v2m, to_v2, cc_v2, ctd_v2, cdtd_v2, cto_v2, cdto_v2, ta_v2, ct_v2,
+        ctr_v1, ctr, ctc_v1, tpm, tt_v2, ctt_v2
     );
 
     for res in [
         coll_v2_res,
         td_v2_res,
+        tv1v2m_res,
         to_v2_res,
         cc_v2_res,
         ctd_v2_res,
@@ -332,7 +434,11 @@ async fn insert_to_db(
         ta_v2_res,
         ct_v2_res,
         ctr_v1_res,
+        ctr_res,
         ctc_v1_res,
+        tpm_res,
+        tt_v2_res,
+        ctt_v2_res,
     ] {
         res?;
     }
@@ -384,6 +490,23 @@ pub fn insert_token_datas_v2_query(
     )
 }
 
+pub fn insert_token_v1_to_v2_mappings_query(
+    items_to_insert: Vec<TokenV1ToV2Mapping>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::token_v1_to_v2_mappings::dsl::*;
+
+    (
+        diesel::insert_into(schema::token_v1_to_v2_mappings::table)
+            .values(items_to_insert)
+            .on_conflict(token_data_id_v2)
+            .do_nothing(),
+        None,
+    )
+}
+
 pub fn insert_token_ownerships_v2_query(
     items_to_insert: Vec<TokenOwnershipV2>,
 ) -> (
@@ -618,6 +741,35 @@ pub fn insert_current_token_royalties_v1_query(
     )
 }
 
+pub fn insert_current_token_royalties_query(
+    items_to_insert: Vec<CurrentTokenRoyalty>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::current_token_royalty::dsl::*;
+
+    (
+        diesel::insert_into(schema::current_token_royalty::table)
+            .values(items_to_insert)
+            .on_conflict(token_or_collection_id)
+            .do_update()
+            .set((
+                source_level.eq(excluded(source_level)),
+                token_standard.eq(excluded(token_standard)),
+                // A deleted royalty keeps the payee and points it had
+                payee_address.eq(sql::<Text>("CASE WHEN EXCLUDED.is_deleted THEN current_token_royalty.payee_address ELSE EXCLUDED.payee_address END")),
+                royalty_points_numerator.eq(sql::<Numeric>("CASE WHEN EXCLUDED.is_deleted THEN current_token_royalty.royalty_points_numerator ELSE EXCLUDED.royalty_points_numerator END")),
+                royalty_points_denominator.eq(sql::<Numeric>("CASE WHEN EXCLUDED.is_deleted THEN current_token_royalty.royalty_points_denominator ELSE EXCLUDED.royalty_points_denominator END")),
+                last_transaction_version.eq(excluded(last_transaction_version)),
+                last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
+                inserted_at.eq(excluded(inserted_at)),
+                is_deleted.eq(excluded(is_deleted)),
+            )),
+        Some(" WHERE current_token_royalty.last_transaction_version <= excluded.last_transaction_version "),
+    )
+}
+
 pub fn insert_current_token_claims_query(
     items_to_insert: Vec<CurrentTokenPendingClaim>,
 ) -> (
@@ -649,6 +801,69 @@ pub fn insert_current_token_claims_query(
     )
 }
 
+pub fn insert_token_property_mutations_query(
+    items_to_insert: Vec<TokenPropertyMutation>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::token_property_mutations::dsl::*;
+
+    (
+        diesel::insert_into(schema::token_property_mutations::table)
+            .values(items_to_insert)
+            .on_conflict((transaction_version, write_set_change_index))
+            .do_nothing(),
+        None,
+    )
+}
+
+pub fn insert_token_transferabilities_v2_query(
+    items_to_insert: Vec<TokenTransferabilityV2>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::token_transferability_v2::dsl::*;
+
+    (
+        diesel::insert_into(schema::token_transferability_v2::table)
+            .values(items_to_insert)
+            .on_conflict((transaction_version, write_set_change_index))
+            .do_nothing(),
+        None,
+    )
+}
+
+pub fn insert_current_token_transferabilities_v2_query(
+    items_to_insert: Vec<CurrentTokenTransferabilityV2>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::current_token_transferability_v2::dsl::*;
+
+    (
+        diesel::insert_into(schema::current_token_transferability_v2::table)
+            .values(items_to_insert)
+            .on_conflict(token_data_id)
+            .do_update()
+            .set((
+                owner_address.eq(excluded(owner_address)),
+                allow_ungated_transfer.eq(excluded(allow_ungated_transfer)),
+                is_untransferable.eq(excluded(is_untransferable)),
+                is_transferable_by_owner.eq(excluded(is_transferable_by_owner)),
+                restriction_reason.eq(excluded(restriction_reason)),
+                // Only known in the transaction that mints the token
+                soulbound_at_mint.eq(sql::<Nullable<Bool>>("COALESCE(EXCLUDED.soulbound_at_mint, current_token_transferability_v2.soulbound_at_mint)")),
+                last_transaction_version.eq(excluded(last_transaction_version)),
+                last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
+                inserted_at.eq(excluded(inserted_at)),
+            )),
+        Some(" WHERE current_token_transferability_v2.last_transaction_version <= excluded.last_transaction_version "),
+    )
+}
+
 #[async_trait]
 impl ProcessorTrait for TokenV2Processor {
     fn name(&self) -> &'static str {
@@ -691,6 +906,11 @@ impl ProcessorTrait for TokenV2Processor {
             raw_current_token_v2_metadata,
             raw_current_token_royalties_v1,
             raw_current_token_claims,
+            raw_token_property_mutations,
+            raw_token_transferabilities_v2,
+            raw_current_token_transferabilities_v2,
+            raw_current_token_royalties,
+            raw_token_v1_to_v2_mappings,
         ) = parse_v2_token(
             &transactions,
             &table_handle_to_owner,
@@ -709,6 +929,17 @@ impl ProcessorTrait for TokenV2Processor {
                 .map(CurrentTokenRoyaltyV1::from_raw)
                 .collect();
 
+        let postgres_current_token_royalties: Vec<CurrentTokenRoyalty> =
+            raw_current_token_royalties
+                .into_iter()
+                .map(CurrentTokenRoyalty::from_raw)
+                .collect();
+
+        let postgres_token_v1_to_v2_mappings: Vec<TokenV1ToV2Mapping> = raw_token_v1_to_v2_mappings
+            .into_iter()
+            .map(TokenV1ToV2Mapping::from_raw)
+            .collect();
+
         let mut postgres_current_token_v2_metadata: Vec<CurrentTokenV2Metadata> =
             raw_current_token_v2_metadata
                 .into_iter()
@@ -753,6 +984,24 @@ impl ProcessorTrait for TokenV2Processor {
                 .map(CurrentTokenOwnershipV2::from_raw)
                 .collect();
 
+        let postgres_token_property_mutations: Vec<TokenPropertyMutation> =
+            raw_token_property_mutations
+                .into_iter()
+                .map(TokenPropertyMutation::from_raw)
+                .collect();
+
+        let postgres_token_transferabilities_v2: Vec<TokenTransferabilityV2> =
+            raw_token_transferabilities_v2
+                .into_iter()
+                .map(TokenTransferabilityV2::from_raw)
+                .collect();
+
+        let postgres_current_token_transferabilities_v2: Vec<CurrentTokenTransferabilityV2> =
+            raw_current_token_transferabilities_v2
+                .into_iter()
+                .map(CurrentTokenTransferabilityV2::from_raw)
+                .collect();
+
         let processing_duration_in_secs = processing_start.elapsed().as_secs_f64();
         let db_insertion_start = std::time::Instant::now();
 
@@ -790,12 +1039,17 @@ impl ProcessorTrait for TokenV2Processor {
             end_version,
             network.unwrap().to_string(),
             &collections_v2,
-            &postgres_token_datas_v2,
+            (&postgres_token_datas_v2, &postgres_token_v1_to_v2_mappings),
             &postgres_token_ownerships_v2,
             &postgres_token_activities_v2,
             &postgres_current_token_v2_metadata,
-            &postgres_current_token_royalties_v1,
+            (
+                &postgres_current_token_royalties_v1,
+                &postgres_current_token_royalties,
+            ),
             &postgres_current_token_claims,
+            &postgres_token_property_mutations,
+            &postgres_token_transferabilities_v2,
         )
         .await;
 
@@ -815,7 +1069,7 @@ impl ProcessorTrait for TokenV2Processor {
             start_version,
             end_version,
             &collections_v2,
-            &postgres_token_datas_v2,
+            (&postgres_token_datas_v2, &postgres_token_v1_to_v2_mappings),
             &postgres_token_ownerships_v2,
             &current_collections_v2,
             (
@@ -828,8 +1082,16 @@ impl ProcessorTrait for TokenV2Processor {
             ),
             &postgres_token_activities_v2,
             &postgres_current_token_v2_metadata,
-            &postgres_current_token_royalties_v1,
+            (
+                &postgres_current_token_royalties_v1,
+                &postgres_current_token_royalties,
+            ),
             &postgres_current_token_claims,
+            &postgres_token_property_mutations,
+            (
+                &postgres_token_transferabilities_v2,
+                &postgres_current_token_transferabilities_v2,
+            ),
             &self.per_table_chunk_sizes,
         )
         .await;
@@ -879,6 +1141,11 @@ pub async fn parse_v2_token_for_parquet(
     Vec<RawCurrentTokenV2Metadata>,
     Vec<RawCurrentTokenRoyaltyV1>,
     Vec<RawCurrentTokenPendingClaim>,
+    Vec<RawTokenPropertyMutation>,
+    Vec<RawTokenTransferabilityV2>,
+    Vec<RawCurrentTokenTransferabilityV2>,
+    Vec<RawCurrentTokenRoyalty>,
+    Vec<RawTokenV1ToV2Mapping>,
 ) {
     parse_v2_token(transactions, table_handle_to_owner, &mut None).await
 }
@@ -900,6 +1167,11 @@ pub async fn parse_v2_token(
     Vec<RawCurrentTokenV2Metadata>,
     Vec<RawCurrentTokenRoyaltyV1>,
     Vec<RawCurrentTokenPendingClaim>,
+    Vec<RawTokenPropertyMutation>,
+    Vec<RawTokenTransferabilityV2>,
+    Vec<RawCurrentTokenTransferabilityV2>,
+    Vec<RawCurrentTokenRoyalty>,
+    Vec<RawTokenV1ToV2Mapping>,
 ) {
     // Token V2 and V1 combined
     let mut collections_v2 = vec![];
@@ -931,11 +1203,23 @@ pub async fn parse_v2_token(
     > = AHashMap::new();
     let mut current_token_royalties_v1: AHashMap<CurrentTokenDataV2PK, RawCurrentTokenRoyaltyV1> =
         AHashMap::new();
+    // v1 and v2 royalties, keyed by token data or collection
+    let mut current_token_royalties: AHashMap<String, RawCurrentTokenRoyalty> = AHashMap::new();
     // migrating this from v1 token model as we don't have any replacement table for this
     let mut all_current_token_claims: AHashMap<
         CurrentTokenPendingClaimPK,
         RawCurrentTokenPendingClaim,
     > = AHashMap::new();
+    // Properties written to token datas, turned into mutations once the whole batch is parsed
+    let mut token_property_writes = vec![];
+    let mut token_transferabilities_v2 = vec![];
+    let mut current_token_transferabilities_v2: AHashMap<
+        CurrentTokenDataV2PK,
+        RawCurrentTokenTransferabilityV2,
+    > = AHashMap::new();
+
+    // v2 tokens minted in exchange for a burned v1 token
+    let mut token_v1_to_v2_mappings = vec![];
 
     // Code above is inefficient (multiple passthroughs) so I'm approaching TokenV2 with a cleaner code structure
     for txn in transactions {
@@ -973,6 +1257,9 @@ pub async fn parse_v2_token(
             // Get claim events for token v1 by table handle
             let mut tokens_claimed: TokenV1Claimed = AHashMap::new();
 
+            // Get burn events for token v1, to detect migrations to token v2
+            let mut v1_tokens_burned = vec![];
+
             // Loop 1: Need to do a first pass to get all the object addresses and insert them into the helper
             for wsc in transaction_info.changes.iter() {
                 if let Change::WriteResource(wr) = wsc.change.as_ref().unwrap() {
@@ -1074,6 +1361,11 @@ pub async fn parse_v2_token(
                             .push((index as i64, transfer_events));
                     }
                 }
+                if let Some(token_id) =
+                    RawTokenV1ToV2Mapping::get_v1_burned_token(event, txn_version).unwrap()
+                {
+                    v1_tokens_burned.push(token_id);
+                }
                 // handling all the token v1 events
                 if let Some(event) = RawTokenActivityV2::get_v1_from_parsed_event(
                     event,
@@ -1103,6 +1395,8 @@ pub async fn parse_v2_token(
                 }
             }
 
+            let txn_collections_start = collections_v2.len();
+            let txn_token_datas_start = token_datas_v2.len();
             // Loop 4: Pass through the changes for collection, token data, token ownership, and token royalties
             for (index, wsc) in transaction_info.changes.iter().enumerate() {
                 let wsc_index = index as i64;
@@ -1142,6 +1436,10 @@ pub async fn parse_v2_token(
                             )
                             .unwrap()
                         {
+                            token_property_writes.extend(TokenPropertyWrite::from_v1_token_data(
+                                &token_data,
+                                &entry_function_id_str,
+                            ));
                             token_datas_v2.push(token_data);
                             current_token_datas_v2.insert(
                                 current_token_data.token_data_id.clone(),
@@ -1156,6 +1454,10 @@ pub async fn parse_v2_token(
                             )
                             .unwrap()
                         {
+                            let unified_token_royalty =
+                                RawCurrentTokenRoyalty::from_v1(&current_token_royalty);
+                            current_token_royalties
+                                .insert(unified_token_royalty.pk(), unified_token_royalty);
                             current_token_royalties_v1.insert(
                                 current_token_royalty.token_data_id.clone(),
                                 current_token_royalty,
@@ -1318,6 +1620,22 @@ pub async fn parse_v2_token(
                             }
                             token_ownerships_v2.append(&mut ownerships);
                             current_token_ownerships_v2.extend(current_ownerships);
+                            let (transferability, current_transferability) =
+                                RawTokenTransferabilityV2::from_v2_token_data(
+                                    &raw_token_data,
+                                    &token_v2_metadata_helper,
+                                    &tokens_minted,
+                                )
+                                .unwrap();
+                            token_transferabilities_v2.push(transferability);
+                            let previous = current_token_transferabilities_v2
+                                .get(&current_transferability.token_data_id);
+                            let current_transferability =
+                                current_transferability.merge_previous(previous);
+                            current_token_transferabilities_v2.insert(
+                                current_transferability.token_data_id.clone(),
+                                current_transferability,
+                            );
                             token_datas_v2.push(raw_token_data);
                             current_token_datas_v2.insert(
                                 current_token_data.token_data_id.clone(),
@@ -1378,6 +1696,30 @@ pub async fn parse_v2_token(
                             );
                         }
 
+                        if let Some(property_write) = TokenPropertyWrite::from_v2_write_resource(
+                            resource,
+                            txn_version,
+                            wsc_index,
+                            txn_timestamp,
+                            &entry_function_id_str,
+                            &token_v2_metadata_helper,
+                        )
+                        .unwrap()
+                        {
+                            token_property_writes.push(property_write);
+                        }
+
+                        if let Some(token_royalty) = RawCurrentTokenRoyalty::from_v2_write_resource(
+                            resource,
+                            txn_version,
+                            txn_timestamp,
+                            &token_v2_metadata_helper,
+                        )
+                        .unwrap()
+                        {
+                            current_token_royalties.insert(token_royalty.pk(), token_royalty);
+                        }
+
                         // Track token properties
                         if let Some(token_metadata) =
                             RawCurrentTokenV2Metadata::from_write_resource(
@@ -1398,6 +1740,15 @@ pub async fn parse_v2_token(
                         }
                     },
                     Change::DeleteResource(resource) => {
+                        if let Some(deleted_royalty) =
+                            RawCurrentTokenRoyalty::from_v2_delete_resource(
+                                resource,
+                                txn_version,
+                                txn_timestamp,
+                            )
+                        {
+                            current_token_royalties.insert(deleted_royalty.pk(), deleted_royalty);
+                        }
                         // Add burned NFT handling for token datas (can probably be merged with below)
                         if let Some(deleted_token_data) =
                             RawTokenDataV2::get_burned_nft_v2_from_delete_resource(
@@ -1450,6 +1801,17 @@ pub async fn parse_v2_token(
                     _ => {},
                 }
             }
+
+            if !v1_tokens_burned.is_empty() {
+                token_v1_to_v2_mappings.extend(RawTokenV1ToV2Mapping::from_burns_and_mints(
+                    &v1_tokens_burned,
+                    &collections_v2[txn_collections_start..],
+                    &token_datas_v2[txn_token_datas_start..],
+                    &tokens_minted,
+                    txn_version,
+                    txn_timestamp,
+                ));
+            }
         }
     }
 
@@ -1475,9 +1837,16 @@ pub async fn parse_v2_token(
     let mut current_token_royalties_v1 = current_token_royalties_v1
         .into_values()
         .collect::<Vec<RawCurrentTokenRoyaltyV1>>();
+    let mut current_token_royalties = current_token_royalties
+        .into_values()
+        .collect::<Vec<RawCurrentTokenRoyalty>>();
     let mut all_current_token_claims = all_current_token_claims
         .into_values()
         .collect::<Vec<RawCurrentTokenPendingClaim>>();
+    let mut current_token_transferabilities_v2 = current_token_transferabilities_v2
+        .into_values()
+        .collect::<Vec<RawCurrentTokenTransferabilityV2>>(
+    );
     // Sort by PK
     current_collections_v2.sort_by(|a, b| a.collection_id.cmp(&b.collection_id));
     current_deleted_token_datas_v2.sort_by(|a, b| a.token_data_id.cmp(&b.token_data_id));
@@ -1486,7 +1855,14 @@ pub async fn parse_v2_token(
     current_token_v2_metadata.sort();
     current_deleted_token_ownerships_v2.sort();
     current_token_royalties_v1.sort();
+    current_token_royalties.sort();
     all_current_token_claims.sort();
+    current_token_transferabilities_v2.sort_by(|a, b| a.token_data_id.cmp(&b.token_data_id));
+
+    let token_property_mutations =
+        RawTokenPropertyMutation::from_writes(token_property_writes, db_context)
+            .await
+            .unwrap();
 
     (
         collections_v2,
@@ -1501,5 +1877,10 @@ pub async fn parse_v2_token(
         current_token_v2_metadata,
         current_token_royalties_v1,
         all_current_token_claims,
+        token_property_mutations,
+        token_transferabilities_v2,
+        current_token_transferabilities_v2,
+        current_token_royalties,
+        token_v1_to_v2_mappings,
     )
 }
@@ -5,5 +5,8 @@ pub mod counters;
 pub mod database;
 pub mod mq;
 pub mod network;
+pub mod nft_metadata_fetcher;
+pub mod status_history;
 pub mod table_flags;
 pub mod util;
+pub mod validation;
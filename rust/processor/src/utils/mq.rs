@@ -1,13 +1,20 @@
+use anyhow::Context;
 use rdkafka::{
     producer::{FutureProducer, FutureRecord},
     ClientConfig,
 };
+use std::{io::Write, path::PathBuf};
 
 const DEFAULT_MESSAGE_TIMEOUT_MS: &str = "5000";
 const DEFAULT_QUEUE_BUFFERING_MAX_MS: &str = "100";
+/// `brokers` starting with this write every topic to a local file instead of Kafka, e.g.
+/// `file:///tmp/mq` appends to `/tmp/mq/<topic>.jsonl`
+pub const FILE_BROKERS_PREFIX: &str = "file://";
 
 pub trait CustomProducer {
-    fn new(brokers: &str) -> Self;
+    fn new(brokers: &str) -> anyhow::Result<Self>
+    where
+        Self: Sized;
     async fn send_to_mq<'a, T>(&'a self, topic: &'a str, items: &'a [T]) -> Result<(), String>
     where
         T: serde::Serialize + 'a;
@@ -17,15 +24,34 @@ pub trait CustomProducer {
 pub enum CustomProducerEnum {
     Kafka(KafkaProducer),
     Noop(NoopProducer),
+    File(FileProducer),
+}
+
+impl CustomProducerEnum {
+    /// Namespaces every topic this producer writes to with the processor's `instance_id`, so
+    /// deployments of the same processor don't publish to the same topics.
+    pub fn with_instance_id(self, instance_id: Option<String>) -> Self {
+        match self {
+            CustomProducerEnum::Kafka(p) => {
+                CustomProducerEnum::Kafka(KafkaProducer { instance_id, ..p })
+            },
+            CustomProducerEnum::Noop(p) => CustomProducerEnum::Noop(p),
+            CustomProducerEnum::File(p) => {
+                CustomProducerEnum::File(FileProducer { instance_id, ..p })
+            },
+        }
+    }
 }
 
 impl CustomProducer for CustomProducerEnum {
-    fn new(brokers: &str) -> CustomProducerEnum {
-        if brokers.is_empty() {
-            CustomProducerEnum::Noop(NoopProducer::new(brokers))
+    fn new(brokers: &str) -> anyhow::Result<CustomProducerEnum> {
+        Ok(if brokers.is_empty() {
+            CustomProducerEnum::Noop(NoopProducer::new(brokers)?)
+        } else if brokers.starts_with(FILE_BROKERS_PREFIX) {
+            CustomProducerEnum::File(FileProducer::new(brokers)?)
         } else {
-            CustomProducerEnum::Kafka(KafkaProducer::new(brokers))
-        }
+            CustomProducerEnum::Kafka(KafkaProducer::new(brokers)?)
+        })
     }
 
     async fn send_to_mq<'a, T>(&'a self, topic: &'a str, items: &'a [T]) -> Result<(), String>
@@ -35,6 +61,7 @@ impl CustomProducer for CustomProducerEnum {
         match self {
             CustomProducerEnum::Kafka(p) => p.send_to_mq(topic, items).await,
             CustomProducerEnum::Noop(p) => p.send_to_mq(topic, items).await,
+            CustomProducerEnum::File(p) => p.send_to_mq(topic, items).await,
         }
     }
 }
@@ -42,10 +69,21 @@ impl CustomProducer for CustomProducerEnum {
 #[derive(Clone)]
 pub struct KafkaProducer {
     producer: FutureProducer,
+    // Appended to every topic, e.g. `aptos.mainnet.events.{instance_id}`
+    instance_id: Option<String>,
+}
+
+impl KafkaProducer {
+    fn topic_name(&self, topic: &str) -> String {
+        match &self.instance_id {
+            Some(instance_id) => format!("{}.{}", topic, instance_id),
+            None => topic.to_string(),
+        }
+    }
 }
 
 impl CustomProducer for KafkaProducer {
-    fn new(brokers: &str) -> KafkaProducer {
+    fn new(brokers: &str) -> anyhow::Result<KafkaProducer> {
         let producer = ClientConfig::new()
             .set("bootstrap.servers", brokers)
             .set("message.timeout.ms", DEFAULT_MESSAGE_TIMEOUT_MS)
@@ -55,19 +93,23 @@ impl CustomProducer for KafkaProducer {
             .set("message.max.bytes", "10000000")
             .set("batch.size", "50000000")
             .create()
-            .expect("Producer creation error");
-        KafkaProducer { producer }
+            .with_context(|| format!("Failed to create the Kafka producer for {}", brokers))?;
+        Ok(KafkaProducer {
+            producer,
+            instance_id: None,
+        })
     }
 
     async fn send_to_mq<'a, T>(&'a self, topic: &'a str, items: &'a [T]) -> Result<(), String>
     where
         T: serde::Serialize + 'a,
     {
+        let topic = self.topic_name(topic);
         let futures = items
             .iter()
             .map(|item| {
                 let payload = serde_json::to_string(&item).unwrap();
-                let record = FutureRecord::to(topic).key(&()).payload(&payload);
+                let record = FutureRecord::to(&topic).key(&()).payload(&payload);
                 let fut = self.producer.send_result(record).unwrap();
                 tokio::spawn(async move {
                     let delivery_status = fut.await;
@@ -91,8 +133,8 @@ impl CustomProducer for KafkaProducer {
 pub struct NoopProducer;
 
 impl CustomProducer for NoopProducer {
-    fn new(_brokers: &str) -> NoopProducer {
-        NoopProducer
+    fn new(_brokers: &str) -> anyhow::Result<NoopProducer> {
+        Ok(NoopProducer)
     }
 
     async fn send_to_mq<'a, T>(&'a self, _topic: &'a str, _items: &'a [T]) -> Result<(), String>
@@ -102,3 +144,83 @@ impl CustomProducer for NoopProducer {
         Ok(())
     }
 }
+
+/// Appends every item as a JSON line to `<directory>/<topic>.jsonl`, for consumers without Kafka
+#[derive(Clone)]
+pub struct FileProducer {
+    directory: PathBuf,
+    // Appended to every topic, same as for Kafka
+    instance_id: Option<String>,
+}
+
+impl CustomProducer for FileProducer {
+    fn new(brokers: &str) -> anyhow::Result<FileProducer> {
+        let directory =
+            PathBuf::from(brokers.strip_prefix(FILE_BROKERS_PREFIX).with_context(|| {
+                format!(
+                    "File producer brokers must start with {}, got {}",
+                    FILE_BROKERS_PREFIX, brokers
+                )
+            })?);
+        std::fs::create_dir_all(&directory).with_context(|| {
+            format!(
+                "Failed to create the file producer directory {}",
+                directory.display()
+            )
+        })?;
+        Ok(FileProducer {
+            directory,
+            instance_id: None,
+        })
+    }
+
+    async fn send_to_mq<'a, T>(&'a self, topic: &'a str, items: &'a [T]) -> Result<(), String>
+    where
+        T: serde::Serialize + 'a,
+    {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let topic = match &self.instance_id {
+            Some(instance_id) => format!("{}.{}", topic, instance_id),
+            None => topic.to_string(),
+        };
+        let mut payload = Vec::new();
+        for item in items {
+            serde_json::to_writer(&mut payload, item).map_err(|e| format!("Error: {:?}", e))?;
+            payload.push(b'\n');
+        }
+        // A single append per batch, so batches of concurrent tasks don't interleave
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.directory.join(format!("{}.jsonl", topic)))
+            .and_then(|mut file| file.write_all(&payload))
+            .map_err(|e| format!("Error: {:?}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_producer() {
+        let directory = std::env::temp_dir().join(format!("file_producer_{}", std::process::id()));
+        let producer =
+            CustomProducerEnum::new(&format!("{}{}", FILE_BROKERS_PREFIX, directory.display()))
+                .unwrap()
+                .with_instance_id(Some("test".to_string()));
+
+        producer
+            .send_to_mq("aptos.testnet.a", &[1, 2])
+            .await
+            .unwrap();
+        producer.send_to_mq("aptos.testnet.a", &[3]).await.unwrap();
+
+        let content =
+            std::fs::read_to_string(directory.join("aptos.testnet.a.test.jsonl")).unwrap();
+        assert_eq!(content, "1\n2\n3\n");
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,688 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! In-process stand-in for the NFT metadata crawler, used by `NftMetadataProcessor` when
+//! `fetcher_config` is set instead of publishing token uris to Pub/Sub. The processor only queues
+//! the uris of the token datas it sees as `pending` rows of `current_nft_metadata`, and
+//! `NftMetadataFetcher::run` fetches them in the background, so the checkpoint never waits on
+//! external hosts. Failed fetches are retried later with an exponential backoff.
+//!
+//! At most `num_workers` uris are fetched at the same time. `http(s)://` uris are fetched as is,
+//! `ipfs://` uris through `ipfs_gateway`, and `data:` uris are decoded in place. The latest fetch of
+//! every token is kept in `current_nft_metadata`.
+//!
+//! Uris come from chain data, so hosts that are, or resolve to, a loopback, private, link-local or
+//! other internal address are never fetched unless they're in `allowed_hosts`. The check is done
+//! again when connecting and on every redirect, so a host can't resolve to a public address first
+//! and to an internal one afterwards.
+
+use crate::{
+    db::{
+        common::models::token_v2_models::raw_v2_token_datas::RawCurrentTokenDataV2,
+        postgres::models::token_v2_models::v2_nft_metadata::{
+            CurrentNftMetadata, NftMetadataFetchTask,
+        },
+    },
+    processors::nft_metadata_processor::update_nft_metadata_fetch_results_query,
+    utils::{
+        database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+        util::{remove_null_bytes, truncate_str},
+    },
+};
+use ahash::AHashMap;
+use anyhow::{bail, Context};
+use futures::{stream, StreamExt};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::{self, Formatter},
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+use url::{Host, Url};
+
+const ERROR_MESSAGE_LENGTH: usize = 1000;
+const MAX_REDIRECTS: usize = 10;
+/// A claimed uri is fetched again by any fetcher after this long if its result wasn't written,
+/// e.g. because the processor stopped
+const CLAIM_TIMEOUT_SECS: i64 = 600;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+#[serde(default)]
+pub struct NftMetadataFetcherConfig {
+    /// Maximum number of uris fetched at the same time. Defaults to 16.
+    pub num_workers: usize,
+    /// Uris claimed from the queue at a time. Defaults to 100.
+    pub batch_size: i64,
+    /// How long to wait before checking the queue again when no uri is due. Defaults to 5.
+    pub poll_interval_secs: u64,
+    /// Attempts to fetch a uri before giving up on it, until its token is written with another
+    /// uri. Defaults to 5.
+    pub max_attempts: i32,
+    /// Delay before the first retry of a failed uri, doubled on every following retry. Defaults
+    /// to 60.
+    pub retry_interval_secs: i64,
+    /// Defaults to 10.
+    pub request_timeout_secs: u64,
+    /// Larger responses are rejected. Defaults to 5 MiB.
+    pub max_response_bytes: usize,
+    /// `ipfs://<cid>/<path>` is fetched from `<ipfs_gateway><cid>/<path>`. Defaults to
+    /// `https://ipfs.io/ipfs/`.
+    pub ipfs_gateway: String,
+    /// Also fetch the `image` of the metadata and store the SHA-256 of its content. Defaults to
+    /// false.
+    pub store_image_hashes: bool,
+    /// Hosts that are fetched even if they are, or resolve to, an internal address, e.g. a local
+    /// metadata server. Empty by default.
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for NftMetadataFetcherConfig {
+    fn default() -> Self {
+        Self {
+            num_workers: 16,
+            batch_size: 100,
+            poll_interval_secs: 5,
+            max_attempts: 5,
+            retry_interval_secs: 60,
+            request_timeout_secs: 10,
+            max_response_bytes: 5 * 1024 * 1024,
+            ipfs_gateway: "https://ipfs.io/ipfs/".to_string(),
+            store_image_hashes: false,
+            allowed_hosts: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NftMetadataFetchStatus {
+    /// Queued and not fetched yet
+    Pending,
+    Success,
+    /// The last attempt to fetch the uri failed, it's retried at `next_attempt_at` if set
+    FetchFailed,
+    InvalidJson,
+    UnsupportedUri,
+}
+
+impl fmt::Display for NftMetadataFetchStatus {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let res = match self {
+            NftMetadataFetchStatus::Pending => "pending",
+            NftMetadataFetchStatus::Success => "success",
+            NftMetadataFetchStatus::FetchFailed => "fetch_failed",
+            NftMetadataFetchStatus::InvalidJson => "invalid_json",
+            NftMetadataFetchStatus::UnsupportedUri => "unsupported_uri",
+        };
+        write!(f, "{}", res)
+    }
+}
+
+/// Where the content of a uri comes from
+#[derive(Debug, PartialEq)]
+enum ResolvedUri {
+    Url(String),
+    Data(Vec<u8>),
+}
+
+pub struct NftMetadataFetcher {
+    client: reqwest::Client,
+    config: NftMetadataFetcherConfig,
+}
+
+impl NftMetadataFetcher {
+    pub fn new(config: NftMetadataFetcherConfig) -> anyhow::Result<Self> {
+        let allowed_hosts = Arc::new(config.allowed_hosts.clone());
+        let redirect_allowed_hosts = allowed_hosts.clone();
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            // Hosts that are names are checked when they're resolved, ip addresses here
+            .dns_resolver(Arc::new(PublicAddressResolver { allowed_hosts }))
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("Too many redirects")
+                } else if let Err(e) = check_ip_host(attempt.url(), &redirect_allowed_hosts) {
+                    attempt.error(e)
+                } else {
+                    attempt.follow()
+                }
+            }))
+            .build()
+            .context("Failed to build the http client of the nft metadata fetcher")?;
+        Ok(Self { client, config })
+    }
+
+    /// Fetches the uris that are due until the processor stops. Errors are logged and retried at
+    /// the next poll.
+    pub async fn run(self, db_pool: ArcDbPool, per_table_chunk_sizes: AHashMap<String, usize>) {
+        let poll_interval = Duration::from_secs(self.config.poll_interval_secs);
+        loop {
+            match self.fetch_due(&db_pool, &per_table_chunk_sizes).await {
+                // There may be more due right away
+                Ok(num_fetched) if num_fetched as i64 >= self.config.batch_size => continue,
+                Ok(_) => {},
+                Err(e) => {
+                    tracing::error!(error = ?e, "[NFT Metadata Fetcher] Failed to fetch queued uris");
+                },
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// Claims up to `batch_size` uris that are due, fetches them and writes the results. Returns
+    /// the number of uris fetched.
+    pub async fn fetch_due(
+        &self,
+        db_pool: &ArcDbPool,
+        per_table_chunk_sizes: &AHashMap<String, usize>,
+    ) -> anyhow::Result<usize> {
+        let tasks = {
+            let mut conn = db_pool.get().await?;
+            NftMetadataFetchTask::claim(&mut conn, self.config.batch_size, CLAIM_TIMEOUT_SECS)
+                .await
+                .context("Failed to claim queued nft metadata uris")?
+        };
+        let num_tasks = tasks.len();
+        if num_tasks == 0 {
+            return Ok(0);
+        }
+
+        let mut fetched: Vec<CurrentNftMetadata> = stream::iter(tasks)
+            .map(|task| async move {
+                let mut row = self
+                    .fetch(
+                        &task.token_data_id,
+                        &task.token_uri,
+                        task.last_transaction_version,
+                    )
+                    .await;
+                row.num_attempts += task.num_attempts;
+                row.next_attempt_at = next_attempt_at(&self.config, &row);
+                remove_null_bytes(&row)
+            })
+            .buffer_unordered(self.config.num_workers.max(1))
+            .collect()
+            .await;
+        // Sort by PK
+        fetched.sort_by(|a, b| a.token_data_id.cmp(&b.token_data_id));
+
+        execute_in_chunks(
+            db_pool.clone(),
+            update_nft_metadata_fetch_results_query,
+            &fetched,
+            get_config_table_chunk_size::<CurrentNftMetadata>(
+                "current_nft_metadata",
+                per_table_chunk_sizes,
+            ),
+        )
+        .await
+        .context("Failed to write nft metadata")?;
+        Ok(num_tasks)
+    }
+
+    /// Fetches and parses the metadata at `token_uri`, and the hash of its image if enabled. Makes
+    /// a single attempt, retries are scheduled by the caller.
+    pub async fn fetch(
+        &self,
+        token_data_id: &str,
+        token_uri: &str,
+        last_transaction_version: i64,
+    ) -> CurrentNftMetadata {
+        let mut row = CurrentNftMetadata {
+            token_data_id: token_data_id.to_string(),
+            token_uri: token_uri.to_string(),
+            status: NftMetadataFetchStatus::Success.to_string(),
+            error_message: None,
+            num_attempts: 0,
+            metadata: None,
+            image_uri: None,
+            image_content_hash: None,
+            last_transaction_version,
+            last_fetched_at: Some(chrono::Utc::now().naive_utc()),
+            next_attempt_at: None,
+        };
+        let fail =
+            |mut row: CurrentNftMetadata, status: NftMetadataFetchStatus, e: anyhow::Error| {
+                row.status = status.to_string();
+                row.error_message = Some(truncate_str(&format!("{:#}", e), ERROR_MESSAGE_LENGTH));
+                row
+            };
+
+        let resolved = match self.resolve(token_uri).await {
+            Ok(resolved) => resolved,
+            Err(e) => return fail(row, NftMetadataFetchStatus::UnsupportedUri, e),
+        };
+        row.num_attempts = 1;
+        let content = match self.get_content(resolved).await {
+            Ok(content) => content,
+            Err(e) => return fail(row, NftMetadataFetchStatus::FetchFailed, e),
+        };
+        let metadata = match serde_json::from_slice::<Value>(&content) {
+            Ok(metadata) => metadata,
+            Err(e) => return fail(row, NftMetadataFetchStatus::InvalidJson, e.into()),
+        };
+        row.image_uri = metadata
+            .get("image")
+            .and_then(|image| image.as_str())
+            .map(str::to_string);
+        row.metadata = Some(metadata);
+
+        if self.config.store_image_hashes {
+            if let Some(image_uri) = row.image_uri.clone() {
+                // The metadata was still fetched, so a missing image only leaves the hash empty
+                let image_content = match self.resolve(&image_uri).await {
+                    Ok(resolved) => self.get_content(resolved).await,
+                    Err(e) => Err(e),
+                };
+                match image_content {
+                    Ok(image_content) => {
+                        row.image_content_hash = Some(hex::encode(Sha256::digest(&image_content)));
+                    },
+                    Err(e) => {
+                        row.error_message = Some(truncate_str(
+                            &format!("Failed to fetch image: {:#}", e),
+                            ERROR_MESSAGE_LENGTH,
+                        ));
+                    },
+                }
+            }
+        }
+        row
+    }
+
+    /// Resolves a uri, rejecting urls of internal hosts
+    async fn resolve(&self, uri: &str) -> anyhow::Result<ResolvedUri> {
+        let resolved = resolve_uri(uri, &self.config.ipfs_gateway)?;
+        if let ResolvedUri::Url(url) = &resolved {
+            check_url(url, &self.config.allowed_hosts).await?;
+        }
+        Ok(resolved)
+    }
+
+    async fn get_content(&self, resolved: ResolvedUri) -> anyhow::Result<Vec<u8>> {
+        match resolved {
+            ResolvedUri::Url(url) => self.get(&url).await,
+            ResolvedUri::Data(data) => Ok(data),
+        }
+    }
+
+    async fn get(&self, url: &str) -> anyhow::Result<Vec<u8>> {
+        let max_response_bytes = self.config.max_response_bytes;
+        let mut response = self.client.get(url).send().await?.error_for_status()?;
+        if response
+            .content_length()
+            .is_some_and(|length| length as usize > max_response_bytes)
+        {
+            bail!("Response is larger than {} bytes", max_response_bytes);
+        }
+        let mut content = vec![];
+        while let Some(chunk) = response.chunk().await? {
+            content.extend_from_slice(&chunk);
+            if content.len() > max_response_bytes {
+                bail!("Response is larger than {} bytes", max_response_bytes);
+            }
+        }
+        Ok(content)
+    }
+}
+
+/// Queues the uri of a token data to be fetched right away
+pub fn pending_fetch(token_data: &RawCurrentTokenDataV2) -> CurrentNftMetadata {
+    remove_null_bytes(&CurrentNftMetadata {
+        token_data_id: token_data.token_data_id.clone(),
+        token_uri: token_data.token_uri.clone(),
+        status: NftMetadataFetchStatus::Pending.to_string(),
+        error_message: None,
+        num_attempts: 0,
+        metadata: None,
+        image_uri: None,
+        image_content_hash: None,
+        last_transaction_version: token_data.last_transaction_version,
+        last_fetched_at: None,
+        next_attempt_at: Some(chrono::Utc::now().naive_utc()),
+    })
+}
+
+/// When a failed fetch is retried, `retry_interval_secs` after the first attempt and twice as long
+/// after every following one. None once `max_attempts` is reached or if the fetch didn't fail.
+fn next_attempt_at(
+    config: &NftMetadataFetcherConfig,
+    row: &CurrentNftMetadata,
+) -> Option<chrono::NaiveDateTime> {
+    if row.status != NftMetadataFetchStatus::FetchFailed.to_string()
+        || row.num_attempts >= config.max_attempts
+    {
+        return None;
+    }
+    let backoff = 1i64 << (row.num_attempts - 1).clamp(0, 20);
+    let delay_secs = config.retry_interval_secs.saturating_mul(backoff);
+    row.last_fetched_at
+        .map(|fetched_at| fetched_at + chrono::Duration::seconds(delay_secs))
+}
+
+/// Resolves hosts like the system resolver, but without the internal addresses
+struct PublicAddressResolver {
+    allowed_hosts: Arc<Vec<String>>,
+}
+
+impl Resolve for PublicAddressResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        let allowed = self.allowed_hosts.contains(&host);
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| allowed || !is_internal_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} only resolves to internal addresses", host).into());
+            }
+            Ok::<Addrs, Box<dyn std::error::Error + Send + Sync>>(Box::new(addrs.into_iter()))
+        })
+    }
+}
+
+/// Rejects a url whose host is an internal ip address
+fn check_ip_host(url: &Url, allowed_hosts: &[String]) -> anyhow::Result<()> {
+    let ip = match url.host() {
+        Some(Host::Ipv4(ip)) => IpAddr::V4(ip),
+        Some(Host::Ipv6(ip)) => IpAddr::V6(ip),
+        Some(Host::Domain(_)) => return Ok(()),
+        None => bail!("Url without a host"),
+    };
+    if is_internal_ip(ip) && !allowed_hosts.contains(&ip.to_string()) {
+        bail!("{} is an internal address", ip);
+    }
+    Ok(())
+}
+
+/// Rejects a url whose host is, or only resolves to, internal addresses. A host that fails to
+/// resolve is left to fail when it's fetched.
+async fn check_url(url: &str, allowed_hosts: &[String]) -> anyhow::Result<()> {
+    let url = Url::parse(url).context("Invalid url")?;
+    check_ip_host(&url, allowed_hosts)?;
+    if let Some(Host::Domain(domain)) = url.host() {
+        if allowed_hosts.iter().any(|host| host == domain) {
+            return Ok(());
+        }
+        if let Ok(addrs) = tokio::net::lookup_host((domain, 0)).await {
+            let addrs: Vec<SocketAddr> = addrs.collect();
+            if !addrs.is_empty() && addrs.iter().all(|addr| is_internal_ip(addr.ip())) {
+                bail!("{} only resolves to internal addresses", domain);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Loopback, private, link-local (which includes cloud metadata endpoints), shared, unspecified
+/// and broadcast addresses
+fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || octets[0] == 0
+                // 100.64.0.0/10, carrier-grade NAT
+                || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+        },
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_internal_ip(IpAddr::V4(ip)),
+            None => {
+                let first_segment = ip.segments()[0];
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    // fc00::/7, unique local
+                    || (first_segment & 0xfe00) == 0xfc00
+                    // fe80::/10, link-local
+                    || (first_segment & 0xffc0) == 0xfe80
+            },
+        },
+    }
+}
+
+fn resolve_uri(uri: &str, ipfs_gateway: &str) -> anyhow::Result<ResolvedUri> {
+    let uri = uri.trim();
+    if uri.starts_with("http://") || uri.starts_with("https://") {
+        Ok(ResolvedUri::Url(uri.to_string()))
+    } else if let Some(path) = uri.strip_prefix("ipfs://") {
+        let path = path.strip_prefix("ipfs/").unwrap_or(path);
+        Ok(ResolvedUri::Url(format!(
+            "{}/{}",
+            ipfs_gateway.trim_end_matches('/'),
+            path
+        )))
+    } else if let Some(data) = uri.strip_prefix("data:") {
+        // data:[<media type>][;base64],<data>
+        let (media_type, data) = data.split_once(',').context("Data uri without a comma")?;
+        if media_type.ends_with(";base64") {
+            Ok(ResolvedUri::Data(
+                base64::decode(data).context("Invalid base64 in data uri")?,
+            ))
+        } else {
+            Ok(ResolvedUri::Data(percent_decode(data)?))
+        }
+    } else {
+        bail!("Unsupported uri scheme")
+    }
+}
+
+fn percent_decode(data: &str) -> anyhow::Result<Vec<u8>> {
+    let bytes = data.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .context("Truncated percent encoding in data uri")?;
+            decoded.push(
+                u8::from_str_radix(std::str::from_utf8(hex)?, 16)
+                    .context("Invalid percent encoding in data uri")?,
+            );
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    const IMAGE: &[u8] = b"not really a png";
+
+    /// Serves `/metadata.json` and `/image.png`, and a 500 for any other path
+    async fn start_stub_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let metadata = json!({"name": "Stub #1", "image": format!("{}/image.png", base_url)})
+            .to_string()
+            .into_bytes();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let metadata = metadata.clone();
+                tokio::spawn(async move {
+                    let mut request = vec![0; 4096];
+                    let len = socket.read(&mut request).await.unwrap();
+                    let request = String::from_utf8_lossy(&request[..len]);
+                    let path = request.split_whitespace().nth(1).unwrap_or_default();
+                    let (status, body) = match path {
+                        "/metadata.json" => ("200 OK", metadata),
+                        "/image.png" => ("200 OK", IMAGE.to_vec()),
+                        _ => ("500 Internal Server Error", vec![]),
+                    };
+                    let head = format!(
+                        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        status,
+                        body.len()
+                    );
+                    socket.write_all(head.as_bytes()).await.unwrap();
+                    socket.write_all(&body).await.unwrap();
+                });
+            }
+        });
+        base_url
+    }
+
+    fn fetcher() -> NftMetadataFetcher {
+        NftMetadataFetcher::new(NftMetadataFetcherConfig {
+            store_image_hashes: true,
+            // The stub server
+            allowed_hosts: vec!["127.0.0.1".to_string()],
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_uri() {
+        let gateway = "https://gateway.example/ipfs/";
+        assert_eq!(
+            resolve_uri("https://example.com/1.json", gateway).unwrap(),
+            ResolvedUri::Url("https://example.com/1.json".to_string())
+        );
+        assert_eq!(
+            resolve_uri("ipfs://bafy/1.json", gateway).unwrap(),
+            ResolvedUri::Url("https://gateway.example/ipfs/bafy/1.json".to_string())
+        );
+        assert_eq!(
+            resolve_uri("ipfs://ipfs/bafy", gateway).unwrap(),
+            ResolvedUri::Url("https://gateway.example/ipfs/bafy".to_string())
+        );
+        assert_eq!(
+            resolve_uri("data:application/json;base64,eyJhIjoxfQ==", gateway).unwrap(),
+            ResolvedUri::Data(br#"{"a":1}"#.to_vec())
+        );
+        assert_eq!(
+            resolve_uri("data:application/json,%7B%22a%22:1%7D", gateway).unwrap(),
+            ResolvedUri::Data(br#"{"a":1}"#.to_vec())
+        );
+        assert!(resolve_uri("ar://abc", gateway).is_err());
+    }
+
+    #[test]
+    fn test_is_internal_ip() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00:ec2::254",
+            "fe80::1",
+            "::ffff:10.0.0.1",
+        ] {
+            assert!(is_internal_ip(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["8.8.8.8", "100.128.0.1", "2606:4700::1111"] {
+            assert!(!is_internal_ip(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn test_next_attempt_at() {
+        let config = NftMetadataFetcherConfig {
+            max_attempts: 3,
+            retry_interval_secs: 60,
+            ..Default::default()
+        };
+        let fetched_at = chrono::NaiveDateTime::default();
+        let row = |status: NftMetadataFetchStatus, num_attempts: i32| CurrentNftMetadata {
+            token_data_id: "0x1".to_string(),
+            token_uri: "https://example.com/1.json".to_string(),
+            status: status.to_string(),
+            error_message: None,
+            num_attempts,
+            metadata: None,
+            image_uri: None,
+            image_content_hash: None,
+            last_transaction_version: 1,
+            last_fetched_at: Some(fetched_at),
+            next_attempt_at: None,
+        };
+
+        assert_eq!(
+            next_attempt_at(&config, &row(NftMetadataFetchStatus::FetchFailed, 1)),
+            Some(fetched_at + chrono::Duration::seconds(60))
+        );
+        assert_eq!(
+            next_attempt_at(&config, &row(NftMetadataFetchStatus::FetchFailed, 2)),
+            Some(fetched_at + chrono::Duration::seconds(120))
+        );
+        assert_eq!(
+            next_attempt_at(&config, &row(NftMetadataFetchStatus::FetchFailed, 3)),
+            None
+        );
+        assert_eq!(
+            next_attempt_at(&config, &row(NftMetadataFetchStatus::Success, 1)),
+            None
+        );
+        assert_eq!(
+            next_attempt_at(&config, &row(NftMetadataFetchStatus::InvalidJson, 1)),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_internal_uris_are_unsupported() {
+        let fetcher = NftMetadataFetcher::new(NftMetadataFetcherConfig::default()).unwrap();
+        for uri in [
+            "http://127.0.0.1/metadata.json",
+            "http://10.0.0.1/metadata.json",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://[::1]/metadata.json",
+            "http://localhost/metadata.json",
+        ] {
+            let row = fetcher.fetch("0x1", uri, 10).await;
+            assert_eq!(row.status, "unsupported_uri", "{}", uri);
+            assert_eq!(row.num_attempts, 0, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_stub_server() {
+        let base_url = start_stub_server().await;
+        let fetcher = fetcher();
+
+        let row = fetcher
+            .fetch("0x1", &format!("{}/metadata.json", base_url), 10)
+            .await;
+        assert_eq!(row.status, "success");
+        assert_eq!(row.num_attempts, 1);
+        assert_eq!(row.metadata.unwrap()["name"], "Stub #1");
+        assert_eq!(
+            row.image_content_hash,
+            Some(hex::encode(Sha256::digest(IMAGE)))
+        );
+
+        let row = fetcher
+            .fetch("0x2", &format!("{}/missing.json", base_url), 10)
+            .await;
+        assert_eq!(row.status, "fetch_failed");
+        assert_eq!(row.num_attempts, 1);
+
+        let row = fetcher.fetch("0x3", "data:,not json", 10).await;
+        assert_eq!(row.status, "invalid_json");
+    }
+}
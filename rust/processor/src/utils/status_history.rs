@@ -0,0 +1,227 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Samples of processor progress written to `processor_status_history` next to the checkpoints,
+//! when `status_history_config` is set. Every checkpoint is recorded, but a sample is only written
+//! every `sample_interval_secs`, with the throughput and the lag of the checkpoints behind the chain
+//! averaged over the checkpoints since the previous sample. Samples older than `retention_days` are deleted as new ones are written.
+
+use crate::{
+    db::postgres::models::processor_status::ProcessorStatusHistory,
+    schema::processor_status_history, utils::database::ArcDbPool,
+};
+use ahash::AHashMap;
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+#[serde(default)]
+pub struct StatusHistoryConfig {
+    /// Seconds between two samples of a processor. Defaults to 60.
+    pub sample_interval_secs: u64,
+    /// Samples older than this many days are deleted, none if unset. Defaults to 30.
+    pub retention_days: Option<u64>,
+}
+
+impl Default for StatusHistoryConfig {
+    fn default() -> Self {
+        Self {
+            sample_interval_secs: 60,
+            retention_days: Some(30),
+        }
+    }
+}
+
+/// Pruning only needs to keep up with the retention, not run on every sample.
+fn prune_interval() -> Duration {
+    Duration::hours(1)
+}
+
+/// Checkpoints of a processor since its last sample.
+struct Interval {
+    started_at: NaiveDateTime,
+    start_version: i64,
+    lag_sum_ms: i64,
+    num_lags: i64,
+    last_pruned_at: Option<NaiveDateTime>,
+}
+
+impl Interval {
+    fn new(started_at: NaiveDateTime, start_version: i64) -> Self {
+        Self {
+            started_at,
+            start_version,
+            lag_sum_ms: 0,
+            num_lags: 0,
+            last_pruned_at: None,
+        }
+    }
+}
+
+/// Tracks the checkpoints of any number of processors, e.g. every table of a parquet processor.
+pub struct StatusHistorySampler {
+    config: StatusHistoryConfig,
+    intervals: AHashMap<String, Interval>,
+}
+
+impl StatusHistorySampler {
+    pub fn new(config: StatusHistoryConfig) -> Self {
+        Self {
+            config,
+            intervals: AHashMap::new(),
+        }
+    }
+
+    /// Records a checkpoint of `processor` and returns a sample once `sample_interval_secs` have
+    /// passed since the previous one. The first checkpoint only starts the first interval.
+    pub fn record(
+        &mut self,
+        processor: &str,
+        version: i64,
+        last_transaction_timestamp: Option<NaiveDateTime>,
+        now: NaiveDateTime,
+    ) -> Option<ProcessorStatusHistory> {
+        let Some(interval) = self.intervals.get_mut(processor) else {
+            self.intervals
+                .insert(processor.to_string(), Interval::new(now, version));
+            return None;
+        };
+        if let Some(timestamp) = last_transaction_timestamp {
+            interval.lag_sum_ms += (now - timestamp).num_milliseconds().max(0);
+            interval.num_lags += 1;
+        }
+
+        let elapsed = now - interval.started_at;
+        if elapsed < Duration::seconds(self.config.sample_interval_secs as i64) {
+            return None;
+        }
+        let versions_processed = (version - interval.start_version).max(0);
+        let sample = ProcessorStatusHistory {
+            processor: processor.to_string(),
+            sampled_at: now,
+            last_success_version: version,
+            last_transaction_timestamp,
+            versions_processed,
+            tps: versions_processed as f64 / (elapsed.num_milliseconds().max(1) as f64 / 1000.0),
+            avg_checkpoint_lag_ms: (interval.num_lags > 0)
+                .then(|| interval.lag_sum_ms / interval.num_lags),
+        };
+        *interval = Interval {
+            last_pruned_at: interval.last_pruned_at,
+            ..Interval::new(now, version)
+        };
+        Some(sample)
+    }
+
+    /// Samples of `processor` older than this are due to be deleted, at most once an hour.
+    pub fn prune_before(&mut self, processor: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let retention_days = self.config.retention_days?;
+        let interval = self.intervals.get_mut(processor)?;
+        if interval
+            .last_pruned_at
+            .is_some_and(|last_pruned_at| now - last_pruned_at < prune_interval())
+        {
+            return None;
+        }
+        interval.last_pruned_at = Some(now);
+        Some(now - Duration::days(retention_days as i64))
+    }
+
+    /// Records a checkpoint that was just written and writes a sample if one is due. Failing to
+    /// write a sample is only logged, the checkpoint itself is already stored.
+    pub async fn record_and_write(
+        &mut self,
+        conn_pool: ArcDbPool,
+        processor: &str,
+        version: i64,
+        last_transaction_timestamp: Option<NaiveDateTime>,
+    ) {
+        let now = chrono::Utc::now().naive_utc();
+        let Some(sample) = self.record(processor, version, last_transaction_timestamp, now) else {
+            return;
+        };
+        let prune_before = self.prune_before(processor, now);
+        if let Err(e) = write_sample(conn_pool, &sample, prune_before).await {
+            warn!(
+                processor_name = processor,
+                error = ?e,
+                "[Parser] Failed to write processor status history"
+            );
+        }
+    }
+}
+
+async fn write_sample(
+    conn_pool: ArcDbPool,
+    sample: &ProcessorStatusHistory,
+    prune_before: Option<NaiveDateTime>,
+) -> Result<()> {
+    let mut conn = conn_pool.get().await?;
+    diesel::insert_into(processor_status_history::table)
+        .values(sample)
+        .on_conflict_do_nothing()
+        .execute(&mut conn)
+        .await?;
+    if let Some(prune_before) = prune_before {
+        diesel::delete(
+            processor_status_history::table
+                .filter(processor_status_history::processor.eq(&sample.processor))
+                .filter(processor_status_history::sampled_at.lt(prune_before)),
+        )
+        .execute(&mut conn)
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(secs: i64) -> NaiveDateTime {
+        chrono::DateTime::from_timestamp(1_700_000_000 + secs, 0)
+            .unwrap()
+            .naive_utc()
+    }
+
+    #[test]
+    fn test_record() {
+        let mut sampler = StatusHistorySampler::new(StatusHistoryConfig::default());
+        assert!(sampler
+            .record("events_processor", 100, None, time(0))
+            .is_none());
+        assert!(sampler
+            .record("events_processor", 1100, Some(time(28)), time(30))
+            .is_none());
+        let sample = sampler
+            .record("events_processor", 6100, Some(time(56)), time(60))
+            .unwrap();
+        assert_eq!(sample, ProcessorStatusHistory {
+            processor: "events_processor".to_string(),
+            sampled_at: time(60),
+            last_success_version: 6100,
+            last_transaction_timestamp: Some(time(56)),
+            versions_processed: 6000,
+            tps: 100.0,
+            avg_checkpoint_lag_ms: Some(3000),
+        });
+        // A new interval starts with the sample.
+        let sample = sampler
+            .record("events_processor", 6100, None, time(120))
+            .unwrap();
+        assert_eq!(sample.versions_processed, 0);
+        assert_eq!(sample.avg_checkpoint_lag_ms, None);
+
+        assert_eq!(
+            sampler.prune_before("events_processor", time(120)),
+            Some(time(120) - Duration::days(30))
+        );
+        assert_eq!(sampler.prune_before("events_processor", time(180)), None);
+        assert_eq!(sampler.prune_before("other_processor", time(180)), None);
+    }
+}
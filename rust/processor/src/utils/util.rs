@@ -74,6 +74,30 @@ pub struct MultisigPayloadClean {
     pub transaction_payload: Option<Value>,
 }
 
+/// Name a processor runs under. Several deployments of the same processor type can run side by
+/// side with different `instance_id`s, each with its own checkpoint, metrics, gRPC request name
+/// and MQ topics.
+pub fn processor_instance_name(processor_name: &str, instance_id: Option<&str>) -> String {
+    match instance_id {
+        Some(instance_id) => format!("{}-{}", processor_name, instance_id),
+        None => processor_name.to_string(),
+    }
+}
+
+/// Instance ids end up in Kafka topic names and Prometheus labels, so only allow characters
+/// that are valid in both.
+pub fn validate_instance_id(instance_id: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !instance_id.is_empty()
+            && instance_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+        "Invalid instance_id '{}': expected a non-empty string of ASCII letters, digits, '_' or '-'",
+        instance_id
+    );
+    Ok(())
+}
+
 /// Standardizes all addresses and table handles to be length 66 (0x-64 length hash)
 pub fn standardize_address(handle: &str) -> String {
     if let Some(handle) = handle.strip_prefix("0x") {
@@ -574,6 +598,21 @@ mod tests {
         pub default_properties: serde_json::Value,
     }
 
+    #[test]
+    fn test_processor_instance_name() {
+        assert_eq!(
+            processor_instance_name("events_processor", None),
+            "events_processor"
+        );
+        assert_eq!(
+            processor_instance_name("events_processor", Some("filtered")),
+            "events_processor-filtered"
+        );
+        assert!(validate_instance_id("filtered_1").is_ok());
+        assert!(validate_instance_id("").is_err());
+        assert!(validate_instance_id("a.b").is_err());
+    }
+
     #[test]
     fn test_parse_timestamp() {
         let ts = parse_timestamp(
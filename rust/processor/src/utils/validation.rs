@@ -0,0 +1,343 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks behind the `validate` subcommand of the processor binaries. Validation loads the config
+//! like a run would and then only reads: it connects to every configured service but never
+//! writes, e.g. the chain id is compared against `ledger_infos` without being inserted.
+
+use crate::{
+    db::postgres::models::ledger_info::LedgerInfo,
+    utils::{
+        database::{new_db_pool, ArcDbPool},
+        table_flags::TableFlags,
+    },
+};
+use anyhow::{Context, Result};
+use rdkafka::{producer::BaseProducer, ClientConfig};
+use serde::Serialize;
+use std::{collections::HashSet, future::Future, time::Duration};
+
+/// How long a connectivity check may take before it fails.
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+const GOOGLE_APPLICATION_CREDENTIALS: &str = "GOOGLE_APPLICATION_CREDENTIALS";
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    /// Doesn't fail validation, e.g. a setting that is ignored.
+    Warning,
+    Error,
+    /// Doesn't apply to this config, or a check it depends on failed.
+    Skipped,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ValidationReport {
+    pub processor: String,
+    pub checks: Vec<Check>,
+}
+
+impl ValidationReport {
+    pub fn new(processor: impl Into<String>) -> Self {
+        Self {
+            processor: processor.into(),
+            checks: vec![],
+        }
+    }
+
+    pub fn add(&mut self, name: &str, status: CheckStatus, message: impl Into<String>) {
+        self.checks.push(Check {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+        });
+    }
+
+    /// Adds the result of a check, returning the value if it succeeded.
+    pub fn record<T>(&mut self, name: &str, result: Result<(T, String)>) -> Option<T> {
+        match result {
+            Ok((value, message)) => {
+                self.add(name, CheckStatus::Ok, message);
+                Some(value)
+            },
+            Err(e) => {
+                self.add(name, CheckStatus::Error, format!("{:#}", e));
+                None
+            },
+        }
+    }
+
+    pub fn skip(&mut self, name: &str, reason: &str) {
+        self.add(name, CheckStatus::Skipped, reason);
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Error)
+    }
+
+    /// Fails with the message of every check that failed, if any.
+    pub fn ensure_valid(&self) -> Result<()> {
+        let errors = self
+            .checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Error)
+            .map(|check| format!("{}: {}", check.name, check.message))
+            .collect::<Vec<_>>();
+        anyhow::ensure!(errors.is_empty(), errors.join("; "));
+        Ok(())
+    }
+
+    /// Prints the report as JSON and fails if any check failed.
+    pub fn print(&self) -> Result<()> {
+        println!("{}", serde_json::to_string_pretty(self)?);
+        anyhow::ensure!(self.is_valid(), "Config validation failed");
+        Ok(())
+    }
+
+    /// `deprecated_tables` are `TableFlags` names. Unknown names are silently ignored at runtime,
+    /// so they fail validation.
+    pub fn check_deprecated_tables(&mut self, deprecated_tables: &HashSet<String>) {
+        for table in sorted(deprecated_tables) {
+            match TableFlags::from_name(table) {
+                Some(_) => self.add(
+                    "deprecated_tables",
+                    CheckStatus::Ok,
+                    format!("Writes to {} are skipped", table),
+                ),
+                None => self.add(
+                    "deprecated_tables",
+                    CheckStatus::Error,
+                    match TableFlags::from_name(&table.to_uppercase()) {
+                        Some(_) => format!(
+                            "Unknown table {}, did you mean {}?",
+                            table,
+                            table.to_uppercase()
+                        ),
+                        None => format!("Unknown table {}", table),
+                    },
+                ),
+            }
+        }
+    }
+
+    /// `per_table_chunk_sizes` are keyed by the lowercase table name. Only tables known to
+    /// `TableFlags` can be checked, so other keys are reported as warnings.
+    pub fn check_per_table_chunk_sizes<'a>(
+        &mut self,
+        tables: impl IntoIterator<Item = &'a String>,
+    ) {
+        let tables: HashSet<&String> = tables.into_iter().collect();
+        for table in sorted(tables) {
+            if TableFlags::from_name(&table.to_uppercase()).is_some()
+                && table == &table.to_lowercase()
+            {
+                self.add(
+                    "per_table_chunk_sizes",
+                    CheckStatus::Ok,
+                    format!("Chunk size set for {}", table),
+                );
+            } else {
+                self.add(
+                    "per_table_chunk_sizes",
+                    CheckStatus::Warning,
+                    format!(
+                        "{} is not a known table name, its chunk size is ignored unless the \
+                         processor writes a table of that exact name",
+                        table
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Checks table names of a parquet processor against the tables it writes.
+    pub fn check_parquet_tables<'a>(
+        &mut self,
+        name: &str,
+        tables: impl IntoIterator<Item = &'a String>,
+        valid_tables: &HashSet<String>,
+    ) {
+        let tables: HashSet<&String> = tables.into_iter().collect();
+        for table in sorted(tables) {
+            if valid_tables.contains(table) {
+                self.add(name, CheckStatus::Ok, format!("{} is written", table));
+            } else {
+                self.add(
+                    name,
+                    CheckStatus::Error,
+                    format!(
+                        "Unknown table {}, expected one of {:?}",
+                        table,
+                        sorted(valid_tables)
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Connects to the DB and checks the chain id in `ledger_infos` against the one of the gRPC
+    /// stream, if it was reached.
+    pub async fn check_db(&mut self, postgres_connection_string: &str, grpc_chain_id: Option<u64>) {
+        let db_pool = self.record(
+            "postgres",
+            with_timeout(async {
+                let db_pool = new_db_pool(postgres_connection_string, Some(1)).await?;
+                db_pool.get().await?;
+                Ok((db_pool, "Connected".to_string()))
+            })
+            .await,
+        );
+        match (db_pool, grpc_chain_id) {
+            (Some(db_pool), Some(grpc_chain_id)) => {
+                self.record(
+                    "chain_id",
+                    with_timeout(check_chain_id(db_pool, grpc_chain_id)).await,
+                );
+            },
+            (None, _) => self.skip("chain_id", "The DB is unreachable"),
+            (_, None) => self.skip("chain_id", "The gRPC chain id is unknown"),
+        }
+    }
+}
+
+fn sorted<'a>(tables: impl IntoIterator<Item = &'a String>) -> Vec<&'a String> {
+    let mut tables: Vec<&String> = tables.into_iter().collect();
+    tables.sort();
+    tables
+}
+
+pub async fn with_timeout<T>(check: impl Future<Output = Result<T>>) -> Result<T> {
+    tokio::time::timeout(CHECK_TIMEOUT, check)
+        .await
+        .with_context(|| format!("Timed out after {:?}", CHECK_TIMEOUT))?
+}
+
+async fn check_chain_id(db_pool: ArcDbPool, grpc_chain_id: u64) -> Result<((), String)> {
+    let mut conn = db_pool.get().await?;
+    let chain_id = LedgerInfo::get(&mut conn)
+        .await
+        .context("Failed to query ledger_infos")?
+        .map(|ledger_info| ledger_info.chain_id);
+    match chain_id {
+        Some(chain_id) => {
+            anyhow::ensure!(
+                chain_id == grpc_chain_id as i64,
+                "The gRPC stream serves chain {} but the DB has data for chain {}",
+                grpc_chain_id,
+                chain_id
+            );
+            Ok(((), format!("Chain {} matches the DB", chain_id)))
+        },
+        None => Ok((
+            (),
+            format!(
+                "The DB has no chain id yet, chain {} will be stored on the first run",
+                grpc_chain_id
+            ),
+        )),
+    }
+}
+
+/// Fetches the metadata of the cluster, which fails if no broker is reachable.
+pub async fn check_kafka(brokers: &str) -> Result<((), String)> {
+    let producer: BaseProducer = ClientConfig::new()
+        .set("bootstrap.servers", brokers)
+        .create()
+        .context("Failed to create Kafka client")?;
+    let metadata =
+        tokio::task::spawn_blocking(move || producer.client().fetch_metadata(None, CHECK_TIMEOUT))
+            .await?
+            .context("Failed to fetch Kafka metadata")?;
+    Ok((
+        (),
+        format!(
+            "Connected to {} brokers, {} topics",
+            metadata.brokers().len(),
+            metadata.topics().len()
+        ),
+    ))
+}
+
+/// Checks that the credentials can read the bucket parquet files are uploaded to.
+pub async fn check_gcs_bucket(
+    bucket_name: &str,
+    google_application_credentials: Option<&String>,
+) -> Result<((), String)> {
+    use google_cloud_storage::{
+        client::{Client, ClientConfig},
+        http::buckets::get::GetBucketRequest,
+    };
+
+    if let Some(credentials) = google_application_credentials {
+        std::env::set_var(GOOGLE_APPLICATION_CREDENTIALS, credentials);
+    }
+    let client = Client::new(
+        ClientConfig::default()
+            .with_auth()
+            .await
+            .context("Failed to create GCS client config")?,
+    );
+    client
+        .get_bucket(&GetBucketRequest {
+            bucket: bucket_name.to_string(),
+            ..Default::default()
+        })
+        .await
+        .with_context(|| format!("Failed to get bucket {}", bucket_name))?;
+    Ok(((), format!("Bucket {} is accessible", bucket_name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_name_checks() {
+        let mut report = ValidationReport::new("default_processor");
+        report.check_deprecated_tables(&HashSet::from([
+            "MOVE_RESOURCES".to_string(),
+            "move_modules".to_string(),
+            "NOT_A_TABLE".to_string(),
+        ]));
+        let statuses: Vec<(CheckStatus, &str)> = report
+            .checks
+            .iter()
+            .map(|check| (check.status, check.message.as_str()))
+            .collect();
+        assert_eq!(statuses, vec![
+            (CheckStatus::Ok, "Writes to MOVE_RESOURCES are skipped"),
+            (CheckStatus::Error, "Unknown table NOT_A_TABLE"),
+            (
+                CheckStatus::Error,
+                "Unknown table move_modules, did you mean MOVE_MODULES?"
+            ),
+        ]);
+        assert!(!report.is_valid());
+
+        let mut report = ValidationReport::new("default_processor");
+        let chunk_sizes = ["move_resources".to_string(), "coin_supply".to_string()];
+        report.check_per_table_chunk_sizes(&chunk_sizes);
+        report.check_parquet_tables(
+            "backfill_table",
+            &["move_resources".to_string()],
+            &HashSet::from(["move_resources".to_string()]),
+        );
+        let statuses: Vec<CheckStatus> = report.checks.iter().map(|check| check.status).collect();
+        assert_eq!(statuses, vec![
+            CheckStatus::Warning,
+            CheckStatus::Ok,
+            CheckStatus::Ok
+        ]);
+        assert!(report.is_valid());
+    }
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    config::IndexerGrpcHttp2Config,
+    config::{IndexerGrpcHttp2Config, ReloadableConfig},
     db::postgres::models::{ledger_info::LedgerInfo, processor_status::ProcessorStatusQuery},
     gap_detectors::{
         create_gap_detector_status_tracker_loop, gap_detector::DefaultGapDetector,
@@ -51,9 +51,15 @@ use crate::{
             execute_with_better_error_conn, new_db_pool, run_pending_migrations, ArcDbPool,
         },
         mq::{CustomProducer, CustomProducerEnum},
+        nft_metadata_fetcher::NftMetadataFetcher,
+        status_history::StatusHistoryConfig,
         table_flags::TableFlags,
-        util::{time_diff_since_pb_timestamp_in_secs, timestamp_to_iso, timestamp_to_unixtime},
+        util::{
+            processor_instance_name, time_diff_since_pb_timestamp_in_secs, timestamp_to_iso,
+            timestamp_to_unixtime,
+        },
     },
+    worker_admin::WorkerAdmin,
 };
 use ahash::AHashMap;
 use anyhow::{Context, Result};
@@ -63,7 +69,7 @@ use std::{
     collections::HashSet,
     sync::{Arc, Mutex},
 };
-use tokio::task::JoinHandle;
+use tokio::{sync::watch, task::JoinHandle};
 use tracing::{debug, error, info};
 use url::Url;
 
@@ -94,6 +100,11 @@ pub struct Worker {
     pub transaction_filter: TransactionFilter,
     pub grpc_response_item_timeout_in_secs: u64,
     pub deprecated_tables: TableFlags,
+    pub instance_id: Option<String>,
+    pub status_history_config: Option<StatusHistoryConfig>,
+    pub admin: Arc<WorkerAdmin>,
+    /// Picked up by the fetcher and processor tasks when the config is reloaded.
+    pub reloadable_config: Arc<watch::Sender<ReloadableConfig>>,
 }
 
 impl Worker {
@@ -118,8 +129,11 @@ impl Worker {
         transaction_filter: TransactionFilter,
         grpc_response_item_timeout_in_secs: u64,
         deprecated_tables: HashSet<String>,
+        instance_id: Option<String>,
     ) -> Result<Self> {
-        let processor_name = processor_config.name();
+        let processor_name =
+            processor_instance_name(processor_config.name(), instance_id.as_deref());
+        let processor_name = processor_name.as_str();
         info!(processor_name = processor_name, "[Parser] Kicking off");
 
         info!(
@@ -138,7 +152,9 @@ impl Worker {
         let producer = match brokers.as_deref() {
             Some(brokers_str) => CustomProducerEnum::new(brokers_str),
             None => CustomProducerEnum::new(""), // Noop processor
-        };
+        }
+        .context("Failed to create the producer")?
+        .with_instance_id(instance_id.clone());
 
         info!(
             processor_name = processor_name,
@@ -148,12 +164,12 @@ impl Worker {
 
         let number_concurrent_processing_tasks = number_concurrent_processing_tasks.unwrap_or(10);
 
-        let mut deprecated_tables_flags = TableFlags::empty();
-        for table in deprecated_tables.iter() {
-            if let Some(flags) = TableFlags::from_name(table) {
-                deprecated_tables_flags |= flags;
-            }
-        }
+        let deprecated_tables_flags = deprecated_tables_flags(&deprecated_tables);
+        let reloadable_config = ReloadableConfig {
+            per_table_chunk_sizes: per_table_chunk_sizes.clone(),
+            deprecated_tables,
+            transaction_filter: transaction_filter.clone(),
+        };
 
         Ok(Self {
             producer,
@@ -175,9 +191,18 @@ impl Worker {
             transaction_filter,
             grpc_response_item_timeout_in_secs,
             deprecated_tables: deprecated_tables_flags,
+            instance_id,
+            status_history_config: None,
+            admin: Arc::new(WorkerAdmin::new(processor_name.to_string())),
+            reloadable_config: Arc::new(watch::Sender::new(reloadable_config)),
         })
     }
 
+    /// Name used for the checkpoint, metrics, gRPC request name and logs of this worker.
+    pub fn processor_name(&self) -> String {
+        processor_instance_name(self.processor_config.name(), self.instance_id.as_deref())
+    }
+
     /// This is the main logic of the processor. We will do a few large parts:
     /// 1. Connect to GRPC and handling all the stuff before starting the stream such as diesel migration
     /// 2. Start a thread specifically to fetch data from GRPC. We will keep a buffer of X batches of transactions
@@ -185,7 +210,8 @@ impl Worker {
     ///   * Note that the batches will be sequential so we won't have problems with gaps
     /// 4. We will keep track of the last processed version and monitoring things like TPS
     pub async fn run(&mut self) {
-        let processor_name = self.processor_config.name();
+        let processor_name = self.processor_name();
+        let processor_name = processor_name.as_str();
         info!(
             processor_name = processor_name,
             service_type = PROCESSOR_SERVICE_TYPE,
@@ -214,6 +240,7 @@ impl Worker {
             });
 
         let starting_version = self.starting_version.unwrap_or(starting_version_from_db);
+        self.admin.set_starting_version(starting_version);
 
         info!(
             processor_name = processor_name,
@@ -242,6 +269,17 @@ impl Worker {
             .unwrap();
 
         self.grpc_chain_id = Some(chain_id);
+        self.admin.set_started();
+
+        // Fetches the nft metadata uris queued by the processor. Spawned here rather than in the
+        // processor because the processor is rebuilt for every task.
+        if let ProcessorConfig::NftMetadataProcessor(config) = &self.processor_config {
+            if let Some(fetcher_config) = config.fetcher_config.clone() {
+                let fetcher = NftMetadataFetcher::new(fetcher_config)
+                    .expect("Failed to create nft metadata fetcher");
+                tokio::spawn(fetcher.run(self.db_pool.clone(), self.per_table_chunk_sizes.clone()));
+            }
+        }
 
         let ending_version = self.ending_version;
         let indexer_grpc_data_service_address = self.indexer_grpc_data_service_address.clone();
@@ -257,12 +295,16 @@ impl Worker {
         // and write into a channel
         // TODO: change channel size based on number_concurrent_processing_tasks
         let (tx, receiver) = kanal::bounded_async::<TransactionsPBResponse>(BUFFER_SIZE);
+        self.admin.set_fetch_channel(receiver.clone());
         let request_ending_version = self.ending_version;
         let auth_token = self.auth_token.clone();
-        let transaction_filter = self.transaction_filter.clone();
+        let fetcher_reloadable_config = self.reloadable_config.subscribe();
         let grpc_response_item_timeout =
             std::time::Duration::from_secs(self.grpc_response_item_timeout_in_secs);
+        let fetcher_processor_name = processor_name.to_string();
+        let fetcher_admin = self.admin.clone();
         let fetcher_task = tokio::spawn(async move {
+            let processor_name = fetcher_processor_name.as_str();
             info!(
                 processor_name = processor_name,
                 service_type = PROCESSOR_SERVICE_TYPE,
@@ -282,8 +324,9 @@ impl Worker {
                 request_ending_version,
                 auth_token.clone(),
                 processor_name.to_string(),
-                transaction_filter,
+                fetcher_reloadable_config,
                 pb_channel_txn_chunk_size,
+                fetcher_admin,
             )
             .await
         });
@@ -318,13 +361,19 @@ impl Worker {
             GapDetector::DefaultGapDetector(DefaultGapDetector::new(starting_version))
         };
         let gap_detector_clone = gap_detector.clone();
+        let gap_detector_processor_name = processor_name.to_string();
+        let gap_detector_admin = self.admin.clone();
+        let status_history_config = self.status_history_config.clone();
 
         tokio::spawn(async move {
             create_gap_detector_status_tracker_loop(
                 gap_detector_clone,
                 gap_detector_receiver,
                 processor,
+                gap_detector_processor_name,
                 gap_detection_batch_size,
+                gap_detector_admin,
+                status_history_config,
             )
             .await;
         });
@@ -378,17 +427,20 @@ impl Worker {
         gap_detector_sender: AsyncSender<ProcessingResult>,
         mut gap_detector: GapDetector,
     ) -> JoinHandle<()> {
-        let processor_name = self.processor_config.name();
+        let processor_name = self.processor_name();
         let stream_address = self.indexer_grpc_data_service_address.to_string();
         let receiver_clone = receiver.clone();
         let auth_token = self.auth_token.clone();
+        let admin = self.admin.clone();
+        let mut reloadable_config = self.reloadable_config.subscribe();
+        let initial_config = reloadable_config.borrow_and_update().clone();
 
         // Build the processor based on the config.
-        let processor = if self.processor_config.is_parquet_processor() {
+        let mut processor = if self.processor_config.is_parquet_processor() {
             build_processor(
                 &self.processor_config,
-                self.per_table_chunk_sizes.clone(),
-                self.deprecated_tables,
+                initial_config.per_table_chunk_sizes,
+                deprecated_tables_flags(&initial_config.deprecated_tables),
                 self.producer.clone(),
                 self.db_pool.clone(),
                 Some(gap_detector_sender.clone()),
@@ -396,13 +448,30 @@ impl Worker {
         } else {
             build_processor(
                 &self.processor_config,
-                self.per_table_chunk_sizes.clone(),
-                self.deprecated_tables,
+                initial_config.per_table_chunk_sizes,
+                deprecated_tables_flags(&initial_config.deprecated_tables),
                 self.producer.clone(),
                 self.db_pool.clone(),
                 None,
             )
         };
+        // Parquet processors own upload loops holding buffered rows, so they aren't rebuilt on
+        // reload. They don't use the chunk sizes or deprecated tables anyway.
+        let rebuild_processor = (!self.processor_config.is_parquet_processor()).then(|| {
+            let processor_config = self.processor_config.clone();
+            let producer = self.producer.clone();
+            let db_pool = self.db_pool.clone();
+            move |config: &ReloadableConfig| {
+                build_processor(
+                    &processor_config,
+                    config.per_table_chunk_sizes.clone(),
+                    deprecated_tables_flags(&config.deprecated_tables),
+                    producer.clone(),
+                    db_pool.clone(),
+                    None,
+                )
+            }
+        });
 
         let concurrent_tasks = self.number_concurrent_processing_tasks;
 
@@ -411,12 +480,27 @@ impl Worker {
             .expect("GRPC chain ID has not been fetched yet!");
 
         tokio::spawn(async move {
+            let processor_name = processor_name.as_str();
             let task_index_str = task_index.to_string();
             let step = ProcessorStep::ProcessedBatch.get_step();
             let label = ProcessorStep::ProcessedBatch.get_label();
             let mut ma = MovingAverage::new(3000);
 
             loop {
+                // Leaves the batches in the fetch channel while paused through the admin API.
+                admin.wait_until_resumed().await;
+                if let Some(rebuild_processor) = &rebuild_processor {
+                    if reloadable_config.has_changed().unwrap_or(false) {
+                        processor = rebuild_processor(&reloadable_config.borrow_and_update());
+                        info!(
+                            processor_name = processor_name,
+                            service_type = PROCESSOR_SERVICE_TYPE,
+                            task_index,
+                            "[Parser][T#{}] Rebuilt processor with the reloaded config",
+                            task_index
+                        );
+                    }
+                }
                 let txn_channel_fetch_latency = std::time::Instant::now();
                 match fetch_transactions(
                     processor_name,
@@ -511,6 +595,7 @@ impl Worker {
                                 PROCESSOR_SUCCESSES_COUNT
                                     .with_label_values(&[processor_name])
                                     .inc();
+                                admin.record_batch_processed(end_txn_timestamp.as_ref());
                                 versions
                             },
                             Err(e) => {
@@ -718,9 +803,7 @@ impl Worker {
     pub async fn get_start_version(&self) -> Result<Option<u64>> {
         let mut conn = self.db_pool.get().await?;
 
-        match ProcessorStatusQuery::get_by_processor(self.processor_config.name(), &mut conn)
-            .await?
-        {
+        match ProcessorStatusQuery::get_by_processor(&self.processor_name(), &mut conn).await? {
             Some(status) => Ok(Some(status.last_success_version as u64 + 1)),
             None => Ok(None),
         }
@@ -728,7 +811,8 @@ impl Worker {
 
     /// Verify the chain id from GRPC against the database.
     pub async fn check_or_update_chain_id(&self, grpc_chain_id: i64) -> Result<u64> {
-        let processor_name = self.processor_config.name();
+        let processor_name = self.processor_name();
+        let processor_name = processor_name.as_str();
         info!(
             processor_name = processor_name,
             "[Parser] Checking if chain id is correct"
@@ -866,6 +950,16 @@ pub async fn do_processor(
     processed_result
 }
 
+fn deprecated_tables_flags(deprecated_tables: &HashSet<String>) -> TableFlags {
+    let mut deprecated_tables_flags = TableFlags::empty();
+    for table in deprecated_tables.iter() {
+        if let Some(flags) = TableFlags::from_name(table) {
+            deprecated_tables_flags |= flags;
+        }
+    }
+    deprecated_tables_flags
+}
+
 pub fn build_processor_for_testing(
     processor_config: ProcessorConfig,
     producer: CustomProducerEnum,
@@ -925,9 +1019,9 @@ pub fn build_processor(
             deprecated_tables,
         )),
         ProcessorConfig::MonitoringProcessor => Processor::from(MonitoringProcessor::new(db_pool)),
-        ProcessorConfig::NftMetadataProcessor(config) => {
-            Processor::from(NftMetadataProcessor::new(db_pool, config.clone()))
-        },
+        ProcessorConfig::NftMetadataProcessor(config) => Processor::from(
+            NftMetadataProcessor::new(producer, db_pool, config.clone(), per_table_chunk_sizes),
+        ),
         ProcessorConfig::ObjectsProcessor(config) => Processor::from(ObjectsProcessor::new(
             producer,
             db_pool,
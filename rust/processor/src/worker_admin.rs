@@ -0,0 +1,401 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! State of a running [`Worker`](crate::worker::Worker) served through the admin API and the
+//! readiness and liveness probes of the server framework, see `server_framework::admin` and
+//! `server_framework::health`.
+//!
+//! Pausing stops the processor tasks from taking new batches off the fetch channel. Batches being
+//! processed finish, after which the fetch channel fills up and the fetcher stops pulling from
+//! the stream. A checkpoint writes the last version processed without gaps to `processor_status`
+//! right away instead of waiting for the next periodic update, e.g. before shutting down.
+
+use crate::{
+    config::HealthCheckConfig,
+    grpc_stream::TransactionsPBResponse,
+    utils::util::{parse_timestamp, timestamp_to_iso},
+    worker::BUFFER_SIZE,
+};
+use anyhow::{Context, Result};
+use aptos_protos::util::timestamp::Timestamp;
+use kanal::AsyncReceiver;
+use serde::Serialize;
+use serde_json::{json, Value};
+use server_framework::{admin::AdminHook, health::HealthCheck};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    time::Instant,
+};
+use tokio::sync::{mpsc, oneshot, watch};
+use tracing::info;
+
+/// Sent to the gap detector, which replies with the version it wrote, if any.
+pub type CheckpointRequest = oneshot::Sender<Result<Option<u64>>>;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ProgressStatus {
+    pub starting_version: Option<u64>,
+    /// Last version processed without gaps before it.
+    pub last_success_version: Option<u64>,
+    pub last_transaction_timestamp: Option<String>,
+    /// Last version written to `processor_status`.
+    pub last_checkpointed_version: Option<u64>,
+    /// Batches processed past a gap, waiting for the gap to be filled.
+    pub num_gaps: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FetchChannelStatus {
+    pub len: usize,
+    pub capacity: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LastError {
+    pub error: String,
+    pub timestamp: chrono::NaiveDateTime,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct WorkerStatus {
+    pub processor: String,
+    pub paused: bool,
+    pub progress: ProgressStatus,
+    pub fetch_channel: Option<FetchChannelStatus>,
+    pub last_error: Option<LastError>,
+}
+
+pub struct WorkerAdmin {
+    processor_name: String,
+    effective_config: OnceLock<Value>,
+    paused: watch::Sender<bool>,
+    fetch_channel: OnceLock<AsyncReceiver<TransactionsPBResponse>>,
+    progress: Mutex<ProgressStatus>,
+    last_error: Mutex<Option<LastError>>,
+    checkpoint_sender: mpsc::UnboundedSender<CheckpointRequest>,
+    checkpoint_receiver: Mutex<Option<mpsc::UnboundedReceiver<CheckpointRequest>>>,
+    health_check_config: OnceLock<HealthCheckConfig>,
+    started: AtomicBool,
+    /// When the last batch was processed, or processing started or resumed since.
+    last_progress_at: Mutex<Instant>,
+    /// Latest timestamp of the transactions processed, across processor tasks.
+    latest_transaction_timestamp: Mutex<Option<chrono::NaiveDateTime>>,
+}
+
+impl WorkerAdmin {
+    pub fn new(processor_name: String) -> Self {
+        let (checkpoint_sender, checkpoint_receiver) = mpsc::unbounded_channel();
+        Self {
+            processor_name,
+            effective_config: OnceLock::new(),
+            paused: watch::Sender::new(false),
+            fetch_channel: OnceLock::new(),
+            progress: Mutex::new(ProgressStatus::default()),
+            last_error: Mutex::new(None),
+            checkpoint_sender,
+            checkpoint_receiver: Mutex::new(Some(checkpoint_receiver)),
+            health_check_config: OnceLock::new(),
+            started: AtomicBool::new(false),
+            last_progress_at: Mutex::new(Instant::now()),
+            latest_transaction_timestamp: Mutex::new(None),
+        }
+    }
+
+    /// The config served by `GET /admin/config`, which must already be redacted.
+    pub fn set_effective_config(&self, config: Value) {
+        let _ = self.effective_config.set(config);
+    }
+
+    pub fn set_health_check_config(&self, config: HealthCheckConfig) {
+        let _ = self.health_check_config.set(config);
+    }
+
+    /// Called once migrations and the chain id check have completed.
+    pub fn set_started(&self) {
+        *self.last_progress_at.lock().unwrap() = Instant::now();
+        self.started.store(true, Ordering::SeqCst);
+    }
+
+    pub fn set_fetch_channel(&self, receiver: AsyncReceiver<TransactionsPBResponse>) {
+        let _ = self.fetch_channel.set(receiver);
+    }
+
+    pub fn set_starting_version(&self, starting_version: u64) {
+        self.progress.lock().unwrap().starting_version = Some(starting_version);
+    }
+
+    pub fn record_progress(
+        &self,
+        last_success_version: Option<u64>,
+        last_transaction_timestamp: Option<&Timestamp>,
+        num_gaps: u64,
+    ) {
+        let mut progress = self.progress.lock().unwrap();
+        if last_success_version.is_some() {
+            progress.last_success_version = last_success_version;
+            progress.last_transaction_timestamp = last_transaction_timestamp.map(timestamp_to_iso);
+        }
+        progress.num_gaps = num_gaps;
+    }
+
+    /// Called by the processor tasks for every batch processed successfully.
+    pub fn record_batch_processed(&self, end_transaction_timestamp: Option<&Timestamp>) {
+        *self.last_progress_at.lock().unwrap() = Instant::now();
+        if let Some(timestamp) = end_transaction_timestamp {
+            let timestamp = parse_timestamp(timestamp, 0);
+            let mut latest = self.latest_transaction_timestamp.lock().unwrap();
+            if latest.map_or(true, |latest| latest < timestamp) {
+                *latest = Some(timestamp);
+            }
+        }
+    }
+
+    pub fn record_checkpoint(&self, version: u64) {
+        self.progress.lock().unwrap().last_checkpointed_version = Some(version);
+    }
+
+    pub fn record_error(&self, error: impl std::fmt::Debug) {
+        *self.last_error.lock().unwrap() = Some(LastError {
+            error: format!("{:?}", error),
+            timestamp: chrono::Utc::now().naive_utc(),
+        });
+    }
+
+    /// Taken once by the gap detector, which serves the checkpoint requests.
+    pub fn take_checkpoint_requests(&self) -> Option<mpsc::UnboundedReceiver<CheckpointRequest>> {
+        self.checkpoint_receiver.lock().unwrap().take()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    pub async fn wait_until_resumed(&self) {
+        let mut receiver = self.paused.subscribe();
+        // The sender lives as long as `self`, so this can't fail.
+        let _ = receiver.wait_for(|paused| !paused).await;
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        if !paused {
+            // Don't count the time spent paused as time without progress.
+            *self.last_progress_at.lock().unwrap() = Instant::now();
+        }
+        if self.paused.send_replace(paused) != paused {
+            info!(
+                processor_name = self.processor_name.as_str(),
+                paused, "[Parser] Processing paused state changed through the admin API"
+            );
+        }
+    }
+
+    fn check_readiness(&self, now: chrono::NaiveDateTime) -> Result<()> {
+        anyhow::ensure!(
+            self.started.load(Ordering::SeqCst),
+            "Migrations and chain id check haven't completed yet"
+        );
+        let max_lag_secs = self
+            .health_check_config
+            .get()
+            .and_then(|config| config.max_lag_secs);
+        let latest_transaction_timestamp = *self.latest_transaction_timestamp.lock().unwrap();
+        if let (Some(max_lag_secs), Some(timestamp)) = (max_lag_secs, latest_transaction_timestamp)
+        {
+            let lag_secs = (now - timestamp).num_seconds();
+            anyhow::ensure!(
+                lag_secs <= max_lag_secs as i64,
+                "Last processed transaction is {} seconds old, more than max_lag_secs ({})",
+                lag_secs,
+                max_lag_secs
+            );
+        }
+        Ok(())
+    }
+
+    fn check_liveness(&self, now: Instant) -> Result<()> {
+        let max_secs_without_progress = self
+            .health_check_config
+            .get()
+            .and_then(|config| config.max_secs_without_progress);
+        let Some(max_secs_without_progress) = max_secs_without_progress else {
+            return Ok(());
+        };
+        if !self.started.load(Ordering::SeqCst) || self.is_paused() {
+            return Ok(());
+        }
+        let secs_without_progress = now
+            .saturating_duration_since(*self.last_progress_at.lock().unwrap())
+            .as_secs();
+        anyhow::ensure!(
+            secs_without_progress <= max_secs_without_progress,
+            "No batch processed in {} seconds, more than max_secs_without_progress ({})",
+            secs_without_progress,
+            max_secs_without_progress
+        );
+        Ok(())
+    }
+
+    pub fn status(&self) -> WorkerStatus {
+        WorkerStatus {
+            processor: self.processor_name.clone(),
+            paused: self.is_paused(),
+            progress: self.progress.lock().unwrap().clone(),
+            fetch_channel: self.fetch_channel.get().map(|receiver| FetchChannelStatus {
+                len: receiver.len(),
+                capacity: BUFFER_SIZE,
+            }),
+            last_error: self.last_error.lock().unwrap().clone(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AdminHook for WorkerAdmin {
+    async fn status(&self) -> Result<Value> {
+        Ok(serde_json::to_value(WorkerAdmin::status(self))?)
+    }
+
+    fn config(&self) -> Result<Value> {
+        self.effective_config
+            .get()
+            .cloned()
+            .context("Effective config has not been set")
+    }
+
+    async fn pause(&self) -> Result<()> {
+        self.set_paused(true);
+        Ok(())
+    }
+
+    async fn resume(&self) -> Result<()> {
+        self.set_paused(false);
+        Ok(())
+    }
+
+    async fn checkpoint(&self) -> Result<Value> {
+        let (reply_sender, reply_receiver) = oneshot::channel();
+        self.checkpoint_sender
+            .send(reply_sender)
+            .map_err(|_| anyhow::anyhow!("Gap detector is not running"))?;
+        let version = reply_receiver
+            .await
+            .context("Gap detector stopped before writing the checkpoint")??;
+        Ok(json!({ "last_success_version": version }))
+    }
+}
+
+impl HealthCheck for WorkerAdmin {
+    fn readiness(&self) -> Result<()> {
+        self.check_readiness(chrono::Utc::now().naive_utc())
+    }
+
+    fn liveness(&self) -> Result<()> {
+        self.check_liveness(Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pause_and_resume() {
+        let admin = std::sync::Arc::new(WorkerAdmin::new("events_processor".to_string()));
+        admin.pause().await.unwrap();
+        assert!(admin.is_paused());
+
+        let waiter = tokio::spawn({
+            let admin = admin.clone();
+            async move { admin.wait_until_resumed().await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        admin.resume().await.unwrap();
+        waiter.await.unwrap();
+        assert!(!WorkerAdmin::status(&admin).paused);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint() {
+        let admin = WorkerAdmin::new("events_processor".to_string());
+        let mut requests = admin.take_checkpoint_requests().unwrap();
+        assert!(admin.take_checkpoint_requests().is_none());
+
+        let gap_detector = tokio::spawn(async move {
+            let reply = requests.recv().await.unwrap();
+            reply.send(Ok(Some(42))).unwrap();
+        });
+        assert_eq!(
+            admin.checkpoint().await.unwrap(),
+            json!({ "last_success_version": 42 })
+        );
+        gap_detector.await.unwrap();
+        assert!(admin.checkpoint().await.is_err());
+    }
+
+    #[test]
+    fn test_record_progress() {
+        let admin = WorkerAdmin::new("events_processor".to_string());
+        admin.set_starting_version(10);
+        admin.record_progress(Some(20), None, 0);
+        admin.record_progress(None, None, 3);
+        admin.record_checkpoint(20);
+        assert_eq!(WorkerAdmin::status(&admin).progress, ProgressStatus {
+            starting_version: Some(10),
+            last_success_version: Some(20),
+            last_transaction_timestamp: None,
+            last_checkpointed_version: Some(20),
+            num_gaps: 3,
+        });
+    }
+
+    #[test]
+    fn test_readiness() {
+        let admin = WorkerAdmin::new("events_processor".to_string());
+        admin.set_health_check_config(HealthCheckConfig {
+            max_lag_secs: Some(60),
+            max_secs_without_progress: None,
+        });
+        let now = chrono::DateTime::from_timestamp(1_700_000_100, 0)
+            .unwrap()
+            .naive_utc();
+        assert!(admin.check_readiness(now).is_err());
+
+        admin.set_started();
+        assert!(admin.check_readiness(now).is_ok());
+
+        admin.record_batch_processed(Some(&Timestamp {
+            seconds: 1_700_000_000,
+            nanos: 0,
+        }));
+        assert!(admin.check_readiness(now).is_err());
+        admin.record_batch_processed(Some(&Timestamp {
+            seconds: 1_700_000_050,
+            nanos: 0,
+        }));
+        assert!(admin.check_readiness(now).is_ok());
+    }
+
+    #[test]
+    fn test_liveness() {
+        let admin = WorkerAdmin::new("events_processor".to_string());
+        admin.set_health_check_config(HealthCheckConfig {
+            max_lag_secs: None,
+            max_secs_without_progress: Some(60),
+        });
+        let later = Instant::now() + std::time::Duration::from_secs(120);
+        // Not started yet.
+        assert!(admin.check_liveness(later).is_ok());
+
+        admin.set_started();
+        assert!(admin.check_liveness(Instant::now()).is_ok());
+        assert!(admin.check_liveness(later).is_err());
+
+        admin.set_paused(true);
+        assert!(admin.check_liveness(later).is_ok());
+    }
+}
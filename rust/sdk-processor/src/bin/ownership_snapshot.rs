@@ -0,0 +1,60 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Materializes the holders of a collection or the balances of a fungible asset at a version, see
+//! `sdk_processor::snapshot`. Prints a JSON report with the number of holdings.
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use sdk_processor::{
+    snapshot::{run_snapshot, SnapshotConfig, SnapshotOutput, SnapshotTarget},
+    utils::database::new_db_pool,
+};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[clap(about = "Snapshot the holders of a collection or fungible asset at a version")]
+struct Args {
+    #[clap(long)]
+    postgres_connection_string: String,
+    #[clap(
+        long,
+        required_unless_present = "asset_type",
+        conflicts_with = "asset_type"
+    )]
+    collection_id: Option<String>,
+    /// asset_type of a fungible asset or coin.
+    #[clap(long)]
+    asset_type: Option<String>,
+    /// Holdings as of the end of this version.
+    #[clap(long)]
+    version: i64,
+    /// Write the snapshot to this parquet file instead of the snapshot table.
+    #[clap(long)]
+    parquet_file: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let target = match (args.collection_id, args.asset_type) {
+        (Some(collection_id), _) => SnapshotTarget::Collection(collection_id),
+        (None, Some(asset_type)) => SnapshotTarget::FungibleAsset(asset_type),
+        (None, None) => anyhow::bail!("Either --collection-id or --asset-type must be set"),
+    };
+    let config = SnapshotConfig {
+        target,
+        version: args.version,
+        output: args
+            .parquet_file
+            .map_or(SnapshotOutput::Table, SnapshotOutput::ParquetFile),
+    };
+    let db_pool = new_db_pool(&args.postgres_connection_string, Some(1))
+        .await
+        .context("Failed to create connection pool")?;
+
+    let report = run_snapshot(&config, db_pool).await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
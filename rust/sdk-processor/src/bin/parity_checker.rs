@@ -0,0 +1,76 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compares parquet output against postgres output for a version range and prints a JSON report.
+//! Exits with a non-zero status if any table differs.
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use sdk_processor::{
+    parity_checker::{run_parity_check, ParityCheckerConfig, ParquetSource},
+    utils::database::new_db_pool,
+};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[clap(about = "Compare parquet processor output against postgres processor output")]
+struct Args {
+    #[clap(long)]
+    postgres_connection_string: String,
+    /// Comma separated table names, e.g. `events,fungible_asset_activities`.
+    #[clap(long, value_delimiter = ',', required = true)]
+    tables: Vec<String>,
+    #[clap(long)]
+    starting_version: i64,
+    #[clap(long)]
+    ending_version: i64,
+    /// Local directory laid out like the bucket root ({dir}/{table_name}/...).
+    #[clap(long, conflicts_with_all = ["bucket_name", "bucket_root"])]
+    parquet_dir: Option<PathBuf>,
+    #[clap(long, requires = "bucket_root")]
+    bucket_name: Option<String>,
+    #[clap(long, requires = "bucket_name")]
+    bucket_root: Option<String>,
+    #[clap(long)]
+    google_application_credentials: Option<String>,
+    #[clap(long, default_value_t = ParityCheckerConfig::default_max_reported_diffs())]
+    max_reported_diffs: usize,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let parquet_source = match (args.parquet_dir, args.bucket_name, args.bucket_root) {
+        (Some(path), _, _) => ParquetSource::Local { path },
+        (None, Some(bucket_name), Some(bucket_root)) => ParquetSource::Gcs {
+            bucket_name,
+            bucket_root,
+            google_application_credentials: args.google_application_credentials,
+        },
+        _ => anyhow::bail!("Either --parquet-dir or --bucket-name and --bucket-root must be set"),
+    };
+    anyhow::ensure!(
+        args.starting_version <= args.ending_version,
+        "starting_version must not be greater than ending_version"
+    );
+
+    let config = ParityCheckerConfig {
+        parquet_source,
+        tables: args.tables,
+        starting_version: args.starting_version,
+        ending_version: args.ending_version,
+        max_reported_diffs: args.max_reported_diffs,
+    };
+    let db_pool = new_db_pool(&args.postgres_connection_string, Some(1))
+        .await
+        .context("Failed to create connection pool")?;
+
+    let report = run_parity_check(&config, db_pool).await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+
+    if !report.is_match() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,155 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Operator CLI to inspect processor and backfill checkpoints, edit them and run migrations.
+//! Read commands print JSON. Commands that change a checkpoint ask for confirmation unless `--yes`
+//! is passed.
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use sdk_processor::{operator, utils::database::new_db_pool};
+use std::io::{BufRead, Write};
+
+#[derive(Parser)]
+#[clap(about = "Inspect and manage processor checkpoints")]
+struct Args {
+    #[clap(long)]
+    postgres_connection_string: String,
+    /// Don't ask for confirmation before changing anything.
+    #[clap(long, global = true)]
+    yes: bool,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List processors with their checkpoint and lag.
+    Processors,
+    /// Set the checkpoint of a processor, or each per table checkpoint of a parquet processor. It
+    /// resumes from that version when restarted.
+    SetCheckpoint {
+        #[clap(long)]
+        processor: String,
+        #[clap(long)]
+        version: i64,
+    },
+    #[clap(subcommand)]
+    Backfill(BackfillCommand),
+    /// Run pending migrations.
+    Migrate,
+}
+
+#[derive(Subcommand)]
+enum BackfillCommand {
+    /// List backfills, or a single one with its per table checkpoints and shards.
+    List {
+        #[clap(long)]
+        backfill_alias: Option<String>,
+    },
+    /// (Re)start a backfill from `start_version`, discarding its progress.
+    Start {
+        #[clap(long)]
+        backfill_alias: String,
+        #[clap(long)]
+        start_version: u64,
+        #[clap(long)]
+        end_version: u64,
+    },
+    /// Delete a backfill with its per table checkpoints and shards. Stop the processors running it
+    /// first, or their next checkpoint recreates it.
+    Cancel {
+        #[clap(long)]
+        backfill_alias: String,
+    },
+    /// Mark a backfill complete with its per table checkpoints and shards. Takes effect when the
+    /// processors running it restart.
+    Complete {
+        #[clap(long)]
+        backfill_alias: String,
+    },
+}
+
+fn confirm(yes: bool, prompt: &str) -> Result<()> {
+    if yes {
+        return Ok(());
+    }
+    print!("{} Type 'yes' to continue: ", prompt);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    anyhow::ensure!(answer.trim() == "yes", "Aborted");
+    Ok(())
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let db_pool = new_db_pool(&args.postgres_connection_string, Some(1))
+        .await
+        .context("Failed to create connection pool")?;
+
+    match args.command {
+        Command::Processors => print_json(&operator::list_processors(db_pool).await?),
+        Command::SetCheckpoint { processor, version } => {
+            let current = operator::get_checkpoint(db_pool.clone(), &processor).await?;
+            confirm(
+                args.yes,
+                &format!(
+                    "Set the checkpoint of {} from {:?} to {}?",
+                    processor, current, version
+                ),
+            )?;
+            let updated = operator::set_checkpoint(db_pool, &processor, version).await?;
+            println!("Set {} checkpoints", updated);
+            Ok(())
+        },
+        Command::Backfill(BackfillCommand::List { backfill_alias }) => {
+            print_json(&operator::list_backfills(db_pool, backfill_alias.as_deref()).await?)
+        },
+        Command::Backfill(BackfillCommand::Start {
+            backfill_alias,
+            start_version,
+            end_version,
+        }) => {
+            confirm(
+                args.yes,
+                &format!(
+                    "Start {} over for versions [{}, {}]?",
+                    backfill_alias, start_version, end_version
+                ),
+            )?;
+            operator::start_backfill(db_pool, &backfill_alias, start_version, end_version).await
+        },
+        Command::Backfill(BackfillCommand::Cancel { backfill_alias }) => {
+            let rows = operator::list_backfills(db_pool.clone(), Some(&backfill_alias)).await?;
+            anyhow::ensure!(!rows.is_empty(), "No backfill found for {}", backfill_alias);
+            confirm(
+                args.yes,
+                &format!(
+                    "Delete {} and its {} per table checkpoints and shards?",
+                    backfill_alias,
+                    rows.len() - 1
+                ),
+            )?;
+            let deleted = operator::cancel_backfill(db_pool, &backfill_alias).await?;
+            println!("Deleted {} rows", deleted);
+            Ok(())
+        },
+        Command::Backfill(BackfillCommand::Complete { backfill_alias }) => {
+            confirm(args.yes, &format!("Mark {} complete?", backfill_alias))?;
+            let updated = operator::complete_backfill(db_pool, &backfill_alias).await?;
+            anyhow::ensure!(updated > 0, "No backfill found for {}", backfill_alias);
+            Ok(())
+        },
+        Command::Migrate => {
+            operator::migrate(args.postgres_connection_string, db_pool).await;
+            Ok(())
+        },
+    }
+}
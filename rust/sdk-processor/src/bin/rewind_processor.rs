@@ -0,0 +1,49 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rolls the postgres output of a processor back to a version and resets its checkpoint, see
+//! `sdk_processor::rewind`. Prints a JSON report of the affected tables.
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use sdk_processor::{
+    config::processor_config::ProcessorName,
+    rewind::{run_rewind, RewindConfig},
+    utils::database::new_db_pool,
+};
+
+#[derive(Parser)]
+#[clap(about = "Delete processor output past a version and reset the processor's checkpoint")]
+struct Args {
+    #[clap(long)]
+    postgres_connection_string: String,
+    #[clap(long, value_enum)]
+    processor_name: ProcessorName,
+    #[clap(long)]
+    instance_id: Option<String>,
+    /// The last version to keep. The processor resumes at the next one.
+    #[clap(long)]
+    version: i64,
+    /// Only count the affected rows without changing anything.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let config = RewindConfig {
+        processor_name: args.processor_name,
+        instance_id: args.instance_id,
+        version: args.version,
+        dry_run: args.dry_run,
+    };
+    let db_pool = new_db_pool(&args.postgres_connection_string, Some(1))
+        .await
+        .context("Failed to create connection pool")?;
+
+    let report = run_rewind(&config, db_pool).await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
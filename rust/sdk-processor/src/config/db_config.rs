@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// This enum captures the configs for all the different db storages that are defined.
 /// The configs for each db storage should only contain configuration specific to that
@@ -33,6 +34,7 @@ use serde::{Deserialize, Serialize};
 pub enum DbConfig {
     PostgresConfig(PostgresConfig),
     ParquetConfig(ParquetConfig),
+    ClickhouseConfig(ClickhouseConfig),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -50,6 +52,42 @@ impl PostgresConfig {
     }
 }
 
+/// Only supported by the events, fungible asset, token v2 and user transaction processors, which
+/// write `events`, `fungible_asset_activities`, `token_activities_v2` and `user_transactions`
+/// respectively. Processor status and chain id are stored in ClickHouse as well.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClickhouseConfig {
+    // HTTP interface, e.g. http://localhost:8123
+    pub url: String,
+    #[serde(default = "ClickhouseConfig::default_database")]
+    pub database: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    // Create the tables on startup if they don't exist
+    #[serde(default = "ClickhouseConfig::default_create_tables")]
+    pub create_tables: bool,
+    // Number of rows per INSERT
+    #[serde(default = "ClickhouseConfig::default_insert_chunk_size")]
+    pub insert_chunk_size: usize,
+}
+
+impl ClickhouseConfig {
+    pub fn default_database() -> String {
+        "default".to_string()
+    }
+
+    pub const fn default_create_tables() -> bool {
+        true
+    }
+
+    pub const fn default_insert_chunk_size() -> usize {
+        100_000
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct ParquetConfig {
@@ -64,4 +102,36 @@ pub struct ParquetConfig {
     pub bucket_name: String,
     #[serde(default)]
     pub bucket_root: String,
+    // If set, the parquet processors write their output to local files instead of GCS. Only the
+    // parquet processors support this, and the database is still required for processor status.
+    #[serde(default)]
+    pub file_export_config: Option<FileExportConfig>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileExportFormat {
+    /// Newline delimited JSON, one row per line.
+    #[default]
+    JsonLines,
+    /// CSV with a header row. Nested values are written as JSON.
+    Csv,
+}
+
+impl FileExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FileExportFormat::JsonLines => "jsonl",
+            FileExportFormat::Csv => "csv",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileExportConfig {
+    // Files are written to {output_dir}/{processor_name}/{table_name}/{start_version}_{end_version}.{ext}
+    pub output_dir: PathBuf,
+    #[serde(default)]
+    pub format: FileExportFormat,
 }
@@ -18,19 +18,34 @@ use crate::{
     processors::{
         account_restoration_processor::AccountRestorationProcessor,
         account_transactions_processor::AccountTransactionsProcessor, ans_processor::AnsProcessor,
-        default_processor::DefaultProcessor, events_processor::EventsProcessor,
-        fungible_asset_processor::FungibleAssetProcessor,
-        monitoring_processor::MonitoringProcessor, objects_processor::ObjectsProcessor,
+        clickhouse_processor::ClickhouseProcessor, default_processor::DefaultProcessor,
+        events_processor::EventsProcessor, fungible_asset_processor::FungibleAssetProcessor,
+        monitoring_processor::MonitoringProcessor,
+        nft_marketplace_processor::NftMarketplaceProcessor, objects_processor::ObjectsProcessor,
         stake_processor::StakeProcessor, token_v2_processor::TokenV2Processor,
         user_transaction_processor::UserTransactionProcessor,
     },
+    utils::{
+        backfill_shards::run_sharded_backfill,
+        clickhouse::ClickhouseClient,
+        parquet_processor_table_mapping::{format_table_name, VALID_TABLE_NAMES},
+    },
 };
+use ahash::AHashMap;
 use anyhow::Result;
 use aptos_indexer_processor_sdk::{
-    aptos_indexer_transaction_stream::TransactionStreamConfig,
+    aptos_indexer_transaction_stream::{TransactionStream, TransactionStreamConfig},
     traits::processor_trait::ProcessorTrait,
 };
 use aptos_indexer_processor_sdk_server_framework::RunnableConfig;
+use processor::{
+    bq_analytics::parquet_writer_config::validate_table_writer_configs,
+    utils::{
+        status_history::StatusHistoryConfig,
+        util::{processor_instance_name, validate_instance_id},
+        validation::{check_gcs_bucket, check_kafka, with_timeout, ValidationReport},
+    },
+};
 use serde::{Deserialize, Serialize};
 
 pub const QUERY_DEFAULT_RETRIES: u32 = 5;
@@ -59,66 +74,59 @@ pub struct IndexerProcessorConfig {
     pub testing_config: Option<TestingConfig>,
     #[serde(default)]
     pub mode: ProcessorMode,
+    // Distinguishes deployments of the same processor type, e.g. a filtered and an unfiltered
+    // `events_processor`. Namespaces the checkpoints and the gRPC request name.
+    #[serde(default)]
+    pub instance_id: Option<String>,
+    // Periodic samples of the checkpoints in processor_status_history, disabled if unset
+    #[serde(default)]
+    pub status_history_config: Option<StatusHistoryConfig>,
 }
 
 impl IndexerProcessorConfig {
-    fn validate(&self) -> Result<(), String> {
-        match self.mode {
-            ProcessorMode::Testing => {
-                if self.testing_config.is_none() {
-                    return Err("testing_config must be present when mode is 'testing'".to_string());
-                }
-            },
-            ProcessorMode::Backfill => {
-                if self.backfill_config.is_none() {
-                    return Err(
-                        "backfill_config must be present when mode is 'backfill'".to_string()
-                    );
-                }
-            },
-            ProcessorMode::Default => {},
-        }
-        Ok(())
+    /// Name the processor's checkpoints are stored under, see `processor_instance_name`.
+    pub fn processor_name(&self) -> String {
+        processor_instance_name(self.processor_config.name(), self.instance_id.as_deref())
     }
-}
 
-impl<'de> Deserialize<'de> for IndexerProcessorConfig {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        #[derive(Deserialize)]
-        #[serde(deny_unknown_fields)]
-        struct Inner {
-            processor_config: ProcessorConfig,
-            transaction_stream_config: TransactionStreamConfig,
-            db_config: DbConfig,
-            backfill_config: Option<BackfillConfig>,
-            bootstrap_config: Option<BootStrapConfig>,
-            testing_config: Option<TestingConfig>,
-            #[serde(default)]
-            mode: ProcessorMode,
-        }
-
-        let inner = Inner::deserialize(deserializer)?;
-        let config = IndexerProcessorConfig {
-            processor_config: inner.processor_config,
-            transaction_stream_config: inner.transaction_stream_config,
-            db_config: inner.db_config,
-            backfill_config: inner.backfill_config,
-            bootstrap_config: inner.bootstrap_config,
-            testing_config: inner.testing_config,
-            mode: inner.mode,
-        };
+    /// Same as `ProcessorConfig::get_processor_status_table_names`, with the table names
+    /// prefixed by `processor_name()` so that every instance has its own per-table checkpoints.
+    pub fn get_processor_status_table_names(&self) -> anyhow::Result<Vec<String>> {
+        let table_names = self.processor_config.get_processor_status_table_names()?;
+        let default_prefix = format_table_name(self.processor_config.name(), "");
+        let processor_name = self.processor_name();
+        Ok(table_names
+            .into_iter()
+            .map(
+                |table_name| match table_name.strip_prefix(&default_prefix) {
+                    Some(table_name) => format_table_name(&processor_name, table_name),
+                    None => table_name,
+                },
+            )
+            .collect())
+    }
 
-        config.validate().map_err(serde::de::Error::custom)?;
-        Ok(config)
+    /// The config the processors run with. The `instance_id` is added to the gRPC request name
+    /// so that the data service can tell instances apart.
+    fn runtime_config(&self) -> Self {
+        let mut config = self.clone();
+        if let Some(instance_id) = &self.instance_id {
+            config.transaction_stream_config.request_name_header = processor_instance_name(
+                &self.transaction_stream_config.request_name_header,
+                Some(instance_id),
+            );
+        }
+        config
     }
-}
 
-#[async_trait::async_trait]
-impl RunnableConfig for IndexerProcessorConfig {
-    async fn run(&self) -> Result<()> {
+    /// Runs the processor with this config as is. Sharded backfills call this once per shard.
+    pub(crate) async fn run_processor(&self) -> Result<()> {
+        // ClickHouse reuses the extractors of the supported processors with its own storer.
+        if let DbConfig::ClickhouseConfig(_) = self.db_config {
+            let clickhouse_processor = ClickhouseProcessor::new(self.clone()).await?;
+            return clickhouse_processor.run_processor().await;
+        }
+
         match self.processor_config {
             ProcessorConfig::AccountRestorationProcessor(_) => {
                 let acc_rest_processor = AccountRestorationProcessor::new(self.clone()).await?;
@@ -164,6 +172,10 @@ impl RunnableConfig for IndexerProcessorConfig {
                 let objects_processor = ObjectsProcessor::new(self.clone()).await?;
                 objects_processor.run_processor().await
             },
+            ProcessorConfig::NftMarketplaceProcessor(_) => {
+                let nft_marketplace_processor = NftMarketplaceProcessor::new(self.clone()).await?;
+                nft_marketplace_processor.run_processor().await
+            },
             ProcessorConfig::ParquetDefaultProcessor(_) => {
                 let parquet_default_processor = ParquetDefaultProcessor::new(self.clone()).await?;
                 parquet_default_processor.run_processor().await
@@ -211,6 +223,255 @@ impl RunnableConfig for IndexerProcessorConfig {
         }
     }
 
+    /// Checks the table names in the config and connectivity to everything the processor uses
+    /// without processing or writing anything. The rest of the config is validated on load.
+    pub async fn validation_report(&self) -> ValidationReport {
+        let mut report = ValidationReport::new(self.processor_name());
+        if let Some(default_config) = self.processor_config.default_config() {
+            report.check_deprecated_tables(&default_config.deprecated_tables);
+            report.check_per_table_chunk_sizes(default_config.per_table_chunk_sizes.keys());
+        }
+        if let Some(parquet_config) = self.processor_config.parquet_default_config() {
+            let valid_tables = VALID_TABLE_NAMES
+                .get(self.processor_config.name())
+                .cloned()
+                .unwrap_or_default();
+            report.check_parquet_tables(
+                "backfill_table",
+                &parquet_config.backfill_table,
+                &valid_tables,
+            );
+            report.check_parquet_tables(
+                "table_writer_configs",
+                parquet_config.table_writer_configs.keys(),
+                &valid_tables,
+            );
+        }
+
+        let grpc_chain_id = report.record(
+            "grpc",
+            with_timeout(async {
+                let chain_id = TransactionStream::new(self.transaction_stream_config.clone())
+                    .await?
+                    .get_chain_id()
+                    .await?;
+                Ok((chain_id, format!("Connected, serving chain {}", chain_id)))
+            })
+            .await,
+        );
+
+        match &self.db_config {
+            DbConfig::PostgresConfig(postgres_config) => {
+                report
+                    .check_db(&postgres_config.connection_string, grpc_chain_id)
+                    .await;
+                report.skip("gcs", "Not a parquet processor");
+            },
+            DbConfig::ParquetConfig(parquet_config) => {
+                report
+                    .check_db(&parquet_config.connection_string, grpc_chain_id)
+                    .await;
+                if parquet_config.file_export_config.is_some() {
+                    report.skip("gcs", "Files are exported locally");
+                } else {
+                    report.record(
+                        "gcs",
+                        with_timeout(check_gcs_bucket(
+                            &parquet_config.bucket_name,
+                            parquet_config.google_application_credentials.as_ref(),
+                        ))
+                        .await,
+                    );
+                }
+            },
+            DbConfig::ClickhouseConfig(clickhouse_config) => {
+                let client = ClickhouseClient::new(clickhouse_config);
+                report.record(
+                    "clickhouse",
+                    with_timeout(async {
+                        client.execute("SELECT 1").await?;
+                        Ok(((), "Connected".to_string()))
+                    })
+                    .await,
+                );
+                report.skip(
+                    "chain_id",
+                    "Checked against ClickHouse when the processor starts",
+                );
+                report.skip("gcs", "Not a parquet processor");
+            },
+        }
+        if let ProcessorConfig::NftMarketplaceProcessor(processor_config) = &self.processor_config {
+            match processor_config.brokers.as_deref() {
+                Some(brokers) if !brokers.is_empty() => {
+                    report.record("kafka", check_kafka(brokers).await);
+                },
+                _ => report.skip("kafka", "No brokers configured"),
+            }
+        }
+        report
+    }
+
+    /// The config to process a single shard of a sharded backfill with. The shard is checkpointed
+    /// under its own backfill alias.
+    pub(crate) fn backfill_shard_config(&self, shard_backfill_config: BackfillConfig) -> Self {
+        Self {
+            backfill_config: Some(shard_backfill_config),
+            ..self.clone()
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(instance_id) = &self.instance_id {
+            validate_instance_id(instance_id).map_err(|e| e.to_string())?;
+        }
+        if let Some(parquet_config) = self.processor_config.parquet_default_config() {
+            let valid_tables = VALID_TABLE_NAMES
+                .get(self.processor_config.name())
+                .cloned()
+                .unwrap_or_default();
+            validate_table_writer_configs(
+                &parquet_config.table_writer_configs,
+                valid_tables.iter().map(String::as_str),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        match self.mode {
+            ProcessorMode::Testing => {
+                if self.testing_config.is_none() {
+                    return Err("testing_config must be present when mode is 'testing'".to_string());
+                }
+            },
+            ProcessorMode::Backfill => {
+                let Some(backfill_config) = &self.backfill_config else {
+                    return Err(
+                        "backfill_config must be present when mode is 'backfill'".to_string()
+                    );
+                };
+                self.validate_per_table_versions(backfill_config)?;
+                self.validate_sharding(backfill_config)?;
+            },
+            ProcessorMode::Default => {},
+        }
+        Ok(())
+    }
+
+    fn validate_per_table_versions(&self, backfill_config: &BackfillConfig) -> Result<(), String> {
+        if backfill_config.per_table_versions.is_empty() {
+            return Ok(());
+        }
+        let processor_name = self.processor_config.name();
+        let valid_table_names = VALID_TABLE_NAMES
+            .get(processor_name)
+            .filter(|table_names| !table_names.is_empty())
+            .ok_or_else(|| {
+                format!(
+                    "per_table_versions is only supported for parquet processors, not {}",
+                    processor_name
+                )
+            })?;
+        for (table_name, table_config) in &backfill_config.per_table_versions {
+            if !valid_table_names.contains(table_name) {
+                return Err(format!(
+                    "Invalid table name '{}' in per_table_versions. Expected one of: {:?}",
+                    table_name, valid_table_names
+                ));
+            }
+            if table_config.initial_starting_version > table_config.ending_version {
+                return Err(format!(
+                    "initial_starting_version {} is greater than ending_version {} for table {}",
+                    table_config.initial_starting_version, table_config.ending_version, table_name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_sharding(&self, backfill_config: &BackfillConfig) -> Result<(), String> {
+        let Some(sharding) = &backfill_config.sharding else {
+            return Ok(());
+        };
+        if sharding.num_shards == 0 {
+            return Err("sharding.num_shards must be greater than 0".to_string());
+        }
+        if let DbConfig::ClickhouseConfig(_) = self.db_config {
+            return Err("Sharded backfills are not supported for ClickHouse".to_string());
+        }
+        // Restarting a sharded backfill would reset the progress of shards other workers are
+        // still processing.
+        if backfill_config.overwrite_checkpoint {
+            return Err(
+                "overwrite_checkpoint is not supported for sharded backfills, use a new backfill_id"
+                    .to_string(),
+            );
+        }
+        if !backfill_config.per_table_versions.is_empty() {
+            return Err("per_table_versions is not supported for sharded backfills".to_string());
+        }
+        if backfill_config.initial_starting_version > backfill_config.ending_version {
+            return Err(format!(
+                "initial_starting_version {} is greater than ending_version {}",
+                backfill_config.initial_starting_version, backfill_config.ending_version
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<'de> Deserialize<'de> for IndexerProcessorConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Inner {
+            processor_config: ProcessorConfig,
+            transaction_stream_config: TransactionStreamConfig,
+            db_config: DbConfig,
+            backfill_config: Option<BackfillConfig>,
+            bootstrap_config: Option<BootStrapConfig>,
+            testing_config: Option<TestingConfig>,
+            #[serde(default)]
+            mode: ProcessorMode,
+            #[serde(default)]
+            instance_id: Option<String>,
+            #[serde(default)]
+            status_history_config: Option<StatusHistoryConfig>,
+        }
+
+        let inner = Inner::deserialize(deserializer)?;
+        let config = IndexerProcessorConfig {
+            processor_config: inner.processor_config,
+            transaction_stream_config: inner.transaction_stream_config,
+            db_config: inner.db_config,
+            backfill_config: inner.backfill_config,
+            bootstrap_config: inner.bootstrap_config,
+            testing_config: inner.testing_config,
+            mode: inner.mode,
+            instance_id: inner.instance_id,
+            status_history_config: inner.status_history_config,
+        };
+
+        config.validate().map_err(serde::de::Error::custom)?;
+        Ok(config)
+    }
+}
+
+#[async_trait::async_trait]
+impl RunnableConfig for IndexerProcessorConfig {
+    async fn run(&self) -> Result<()> {
+        let config = self.runtime_config();
+        match (&config.mode, &config.backfill_config) {
+            (ProcessorMode::Backfill, Some(backfill_config))
+                if backfill_config.sharding.is_some() =>
+            {
+                run_sharded_backfill(&config).await
+            },
+            _ => config.run_processor().await,
+        }
+    }
+
     fn get_server_name(&self) -> String {
         // Get the part before the first _ and trim to 12 characters.
         let before_underscore = self
@@ -230,6 +491,64 @@ pub struct BackfillConfig {
     pub initial_starting_version: u64,
     pub ending_version: u64,
     pub overwrite_checkpoint: bool,
+    // Version ranges for individual tables of a parquet processor, keyed by table name. Every
+    // table of a parquet backfill is checkpointed on its own, so tables can be backfilled over
+    // different ranges and by concurrent jobs. Tables without an entry use the range above.
+    #[serde(default)]
+    pub per_table_versions: AHashMap<String, TableBackfillConfig>,
+    // Splits the range into shards that every worker running this config claims and processes in
+    // parallel, see `utils::backfill_shards`.
+    #[serde(default)]
+    pub sharding: Option<BackfillShardingConfig>,
+}
+
+impl BackfillConfig {
+    /// Alias of this backfill in the `backfill_processor_status` table.
+    pub fn backfill_alias(&self, processor_name: &str) -> String {
+        format!("{}_{}", processor_name, self.backfill_id)
+    }
+
+    /// Alias of a single table of a parquet backfill, e.g.
+    /// `parquet_default_processor_1.move_resources`.
+    pub fn table_backfill_alias(&self, processor_name: &str, table_name: &str) -> String {
+        format_table_name(&self.backfill_alias(processor_name), table_name)
+    }
+
+    /// Returns the `(initial_starting_version, ending_version)` to backfill `table_name` over.
+    pub fn table_version_range(&self, table_name: &str) -> (u64, u64) {
+        self.per_table_versions.get(table_name).map_or(
+            (self.initial_starting_version, self.ending_version),
+            |table_config| {
+                (
+                    table_config.initial_starting_version,
+                    table_config.ending_version,
+                )
+            },
+        )
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TableBackfillConfig {
+    pub initial_starting_version: u64,
+    pub ending_version: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackfillShardingConfig {
+    pub num_shards: u64,
+    // A shard claimed by a worker that hasn't reported progress for this long is claimed again,
+    // e.g. after the worker crashed.
+    #[serde(default = "BackfillShardingConfig::default_claim_timeout_secs")]
+    pub claim_timeout_secs: u64,
+}
+
+impl BackfillShardingConfig {
+    pub const fn default_claim_timeout_secs() -> u64 {
+        600
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -1,14 +1,19 @@
 use crate::{
     parquet_processors::parquet_ans_processor::ParquetAnsProcessorConfig,
     processors::{
-        ans_processor::AnsProcessorConfig, objects_processor::ObjectsProcessorConfig,
-        stake_processor::StakeProcessorConfig, token_v2_processor::TokenV2ProcessorConfig,
+        ans_processor::AnsProcessorConfig,
+        nft_marketplace_processor::NftMarketplaceProcessorConfig,
+        objects_processor::ObjectsProcessorConfig, stake_processor::StakeProcessorConfig,
+        token_v2_processor::TokenV2ProcessorConfig,
     },
     utils::parquet_processor_table_mapping::{format_table_name, VALID_TABLE_NAMES},
 };
 use ahash::AHashMap;
 use processor::{
-    bq_analytics::generic_parquet_processor::NamedTable,
+    bq_analytics::{
+        generic_parquet_processor::NamedTable,
+        parquet_writer_config::{writer_config_for_table, ParquetWriterConfig},
+    },
     db::parquet::models::{
         account_transaction_models::parquet_account_transactions::AccountTransaction,
         ans_models::{
@@ -98,6 +103,7 @@ pub enum ProcessorConfig {
     TokenV2Processor(TokenV2ProcessorConfig),
     ObjectsProcessor(ObjectsProcessorConfig),
     MonitoringProcessor(DefaultProcessorConfig),
+    NftMarketplaceProcessor(NftMarketplaceProcessorConfig),
     // ParquetProcessor
     ParquetDefaultProcessor(ParquetDefaultProcessorConfig),
     ParquetEventsProcessor(ParquetDefaultProcessorConfig),
@@ -123,24 +129,9 @@ impl ProcessorConfig {
     /// This is a convenience method to map the table names to include the processor name as a prefix, which
     /// is useful for querying the status from the processor status table in the database.
     pub fn get_processor_status_table_names(&self) -> anyhow::Result<Vec<String>> {
-        let default_config = match self {
-            ProcessorConfig::ParquetDefaultProcessor(config)
-            | ProcessorConfig::ParquetEventsProcessor(config)
-            | ProcessorConfig::ParquetUserTransactionsProcessor(config)
-            | ProcessorConfig::ParquetTransactionMetadataProcessor(config)
-            | ProcessorConfig::ParquetAccountTransactionsProcessor(config)
-            | ProcessorConfig::ParquetTokenV2Processor(config)
-            | ProcessorConfig::ParquetStakeProcessor(config)
-            | ProcessorConfig::ParquetObjectsProcessor(config)
-            | ProcessorConfig::ParquetFungibleAssetProcessor(config) => config,
-            ProcessorConfig::ParquetAnsProcessor(config) => &config.default,
-            _ => {
-                return Err(anyhow::anyhow!(
-                    "Invalid parquet processor config: {:?}",
-                    self
-                ))
-            },
-        };
+        let default_config = self
+            .parquet_default_config()
+            .ok_or_else(|| anyhow::anyhow!("Invalid parquet processor config: {:?}", self))?;
 
         // Get the processor name as a prefix
         let processor_name = self.name();
@@ -161,6 +152,42 @@ impl ProcessorConfig {
         }
     }
 
+    /// The config shared by the processors writing to postgres, `None` for parquet processors.
+    pub fn default_config(&self) -> Option<&DefaultProcessorConfig> {
+        match self {
+            ProcessorConfig::AccountRestorationProcessor(config)
+            | ProcessorConfig::AccountTransactionsProcessor(config)
+            | ProcessorConfig::DefaultProcessor(config)
+            | ProcessorConfig::EventsProcessor(config)
+            | ProcessorConfig::FungibleAssetProcessor(config)
+            | ProcessorConfig::UserTransactionProcessor(config)
+            | ProcessorConfig::MonitoringProcessor(config) => Some(config),
+            ProcessorConfig::AnsProcessor(config) => Some(&config.default),
+            ProcessorConfig::StakeProcessor(config) => Some(&config.default_config),
+            ProcessorConfig::TokenV2Processor(config) => Some(&config.default_config),
+            ProcessorConfig::ObjectsProcessor(config) => Some(&config.default_config),
+            ProcessorConfig::NftMarketplaceProcessor(config) => Some(&config.default_config),
+            _ => None,
+        }
+    }
+
+    /// The config shared by the parquet processors, `None` for other processors.
+    pub fn parquet_default_config(&self) -> Option<&ParquetDefaultProcessorConfig> {
+        match self {
+            ProcessorConfig::ParquetDefaultProcessor(config)
+            | ProcessorConfig::ParquetEventsProcessor(config)
+            | ProcessorConfig::ParquetUserTransactionsProcessor(config)
+            | ProcessorConfig::ParquetTransactionMetadataProcessor(config)
+            | ProcessorConfig::ParquetAccountTransactionsProcessor(config)
+            | ProcessorConfig::ParquetTokenV2Processor(config)
+            | ProcessorConfig::ParquetStakeProcessor(config)
+            | ProcessorConfig::ParquetObjectsProcessor(config)
+            | ProcessorConfig::ParquetFungibleAssetProcessor(config) => Some(config),
+            ProcessorConfig::ParquetAnsProcessor(config) => Some(&config.default),
+            _ => None,
+        }
+    }
+
     /// Get the set of table names to process for the given processor.
     pub fn table_names(processor: &ProcessorName) -> HashSet<String> {
         match processor {
@@ -286,6 +313,11 @@ pub struct ParquetDefaultProcessorConfig {
     // Set of table name to backfill. Using HashSet for fast lookups, and for future extensibility.
     #[serde(default)]
     pub backfill_table: HashSet<String>,
+    // Parquet writer settings (compression, row group size, bloom filters, ...) keyed by table
+    // name. Tables without an entry use `ParquetWriterConfig::default()`, entries for tables the
+    // processor doesn't write fail the config.
+    #[serde(default)]
+    pub table_writer_configs: AHashMap<String, ParquetWriterConfig>,
 }
 
 impl ParquetDefaultProcessorConfig {
@@ -304,11 +336,20 @@ impl ParquetDefaultProcessorConfig {
     pub const fn default_parquet_upload_interval() -> u64 {
         1800 // 30 minutes
     }
+
+    /// Get the parquet writer settings for the given table, falling back to the defaults.
+    pub fn writer_config_for_table(&self, table_name: &str) -> ParquetWriterConfig {
+        writer_config_for_table(&self.table_writer_configs, table_name)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use parquet::{basic::Compression, schema::types::ColumnPath};
+    use processor::bq_analytics::parquet_writer_config::{
+        validate_table_writer_configs, ParquetCompressionCodec,
+    };
 
     #[test]
     fn test_valid_table_names() {
@@ -317,6 +358,7 @@ mod tests {
             channel_size: 10,
             max_buffer_size: 100000,
             upload_interval: 1800,
+            table_writer_configs: AHashMap::new(),
         });
 
         let result = config.get_processor_status_table_names();
@@ -336,6 +378,7 @@ mod tests {
             channel_size: 10,
             max_buffer_size: 100000,
             upload_interval: 1800,
+            table_writer_configs: AHashMap::new(),
         });
 
         let result = config.get_processor_status_table_names();
@@ -353,6 +396,7 @@ mod tests {
             channel_size: 10,
             max_buffer_size: 100000,
             upload_interval: 1800,
+            table_writer_configs: AHashMap::new(),
         });
         let result = config.get_processor_status_table_names();
         assert!(result.is_ok());
@@ -383,6 +427,7 @@ mod tests {
             channel_size: 10,
             max_buffer_size: 100000,
             upload_interval: 1800,
+            table_writer_configs: AHashMap::new(),
         });
 
         let result = config.get_processor_status_table_names();
@@ -391,4 +436,65 @@ mod tests {
         let table_names = result.unwrap();
         assert_eq!(table_names, vec!["transactions".to_string(),]);
     }
+
+    #[test]
+    fn test_table_writer_configs() {
+        let config: ParquetDefaultProcessorConfig = serde_json::from_value(serde_json::json!({
+            "table_writer_configs": {
+                "write_set_changes": {
+                    "compression": "zstd",
+                    "compression_level": 3,
+                    "max_row_group_size": 65536,
+                    "bloom_filter_columns": ["transaction_version"],
+                },
+            },
+        }))
+        .unwrap();
+
+        let write_set_changes = config.writer_config_for_table("write_set_changes");
+        assert_eq!(write_set_changes.compression, ParquetCompressionCodec::Zstd);
+        let props = write_set_changes.writer_properties().unwrap();
+        assert_eq!(props.max_row_group_size(), 65536);
+        assert!(props
+            .bloom_filter_properties(&ColumnPath::from("transaction_version"))
+            .is_some());
+
+        // Tables without an override keep the previous LZ4 default
+        let transactions = config.writer_config_for_table("transactions");
+        assert_eq!(transactions, ParquetWriterConfig::default());
+        assert_eq!(
+            transactions
+                .writer_properties()
+                .unwrap()
+                .compression(&ColumnPath::from("version")),
+            Compression::LZ4
+        );
+    }
+
+    #[test]
+    fn test_unknown_table_writer_config() {
+        let valid_tables = &VALID_TABLE_NAMES["parquet_default_processor"];
+        let table_writer_configs = AHashMap::from_iter([(
+            "write_set_change".to_string(),
+            ParquetWriterConfig::default(),
+        )]);
+        let error = validate_table_writer_configs(
+            &table_writer_configs,
+            valid_tables.iter().map(String::as_str),
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Unknown tables [\"write_set_change\"]"));
+
+        let table_writer_configs = AHashMap::from_iter([(
+            "write_set_changes".to_string(),
+            ParquetWriterConfig::default(),
+        )]);
+        assert!(validate_table_writer_configs(
+            &table_writer_configs,
+            valid_tables.iter().map(String::as_str),
+        )
+        .is_ok());
+    }
 }
@@ -73,6 +73,8 @@ pub struct BackfillProcessorStatusQuery {
     pub last_transaction_timestamp: Option<chrono::NaiveDateTime>,
     pub backfill_start_version: i64,
     pub backfill_end_version: i64,
+    // Worker that claimed the shard, only set for the shards of a sharded backfill.
+    pub claimed_by: Option<String>,
 }
 
 impl BackfillProcessorStatusQuery {
@@ -82,10 +84,24 @@ impl BackfillProcessorStatusQuery {
         conn: &mut DbPoolConnection<'_>,
     ) -> diesel::QueryResult<Option<Self>> {
         let backfill_alias = format!("{}_{}", processor_type, backfill_id);
+        Self::get_by_alias(&backfill_alias, conn).await
+    }
+
+    pub async fn get_by_alias(
+        backfill_alias: &str,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Self>> {
         backfill_processor_status::table
             .filter(backfill_processor_status::backfill_alias.eq(backfill_alias))
             .first::<Self>(conn)
             .await
             .optional()
     }
+
+    pub async fn get_all(conn: &mut DbPoolConnection<'_>) -> diesel::QueryResult<Vec<Self>> {
+        backfill_processor_status::table
+            .order(backfill_processor_status::backfill_alias)
+            .load::<Self>(conn)
+            .await
+    }
 }
@@ -38,4 +38,11 @@ impl ProcessorStatusQuery {
             .await
             .optional()
     }
+
+    pub async fn get_all(conn: &mut DbPoolConnection<'_>) -> diesel::QueryResult<Vec<Self>> {
+        processor_status::table
+            .order(processor_status::processor)
+            .load::<Self>(conn)
+            .await
+    }
 }
@@ -1,6 +1,10 @@
 pub mod config;
 mod db;
+pub mod operator;
+pub mod parity_checker;
 pub mod parquet_processors;
 pub mod processors;
+pub mod rewind;
+pub mod snapshot;
 pub mod steps;
 pub mod utils;
@@ -1,7 +1,10 @@
 use anyhow::Result;
-use aptos_indexer_processor_sdk_server_framework::ServerArgs;
-use clap::Parser;
+use aptos_indexer_processor_sdk_server_framework::{
+    run_server_with_config, setup_logging, setup_panic_handler, GenericConfig, ServerArgs,
+};
+use clap::{Parser, Subcommand};
 use sdk_processor::config::indexer_processor_config::IndexerProcessorConfig;
+use server_framework::load;
 
 #[cfg(unix)]
 #[global_allocator]
@@ -9,6 +12,21 @@ static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
 const RUNTIME_WORKER_MULTIPLIER: usize = 2;
 
+#[derive(Parser)]
+struct Args {
+    #[clap(flatten)]
+    server_args: ServerArgs,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check the config and that the DB, gRPC stream and GCS are reachable, print a report and
+    /// exit without processing anything.
+    Validate,
+}
+
 fn main() -> Result<()> {
     let num_cpus = num_cpus::get();
     let worker_threads = (num_cpus * RUNTIME_WORKER_MULTIPLIER).max(16);
@@ -21,8 +39,21 @@ fn main() -> Result<()> {
         .build()
         .unwrap()
         .block_on(async {
-            let args = ServerArgs::parse();
-            args.run::<IndexerProcessorConfig>(tokio::runtime::Handle::current())
-                .await
+            let args = Args::parse();
+            // Loaded with the local server framework to apply its env and secret file overrides.
+            let load_config =
+                || load::<GenericConfig<IndexerProcessorConfig>>(&args.server_args.config_path);
+            match args.command {
+                Some(Command::Validate) => load_config()?
+                    .server_config
+                    .validation_report()
+                    .await
+                    .print(),
+                None => {
+                    setup_logging();
+                    setup_panic_handler();
+                    run_server_with_config(load_config()?, tokio::runtime::Handle::current()).await
+                },
+            }
         })
 }
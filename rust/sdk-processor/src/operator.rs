@@ -0,0 +1,334 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Operations behind the `processor_cli` binary: inspecting and editing the checkpoints in
+//! `processor_status` and `backfill_processor_status` and the chain id in `ledger_infos`.
+
+use crate::{
+    db::common::models::{
+        backfill_processor_status::{
+            BackfillProcessorStatus, BackfillProcessorStatusQuery, BackfillStatus,
+        },
+        processor_status::{ProcessorStatus, ProcessorStatusQuery},
+    },
+    utils::{
+        database::{execute_with_better_error, run_migrations, ArcDbPool},
+        starting_version::upsert_backfill_status,
+    },
+};
+use anyhow::{Context, Result};
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Text},
+    upsert::excluded,
+    ExpressionMethods,
+};
+use diesel_async::RunQueryDsl;
+use processor::{db::postgres::models::ledger_info::LedgerInfo, schema::processor_status};
+use serde::Serialize;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ProcessorLag {
+    pub processor: String,
+    pub last_success_version: i64,
+    pub last_updated: chrono::NaiveDateTime,
+    pub last_transaction_timestamp: Option<chrono::NaiveDateTime>,
+    /// Time between now and the timestamp of the last processed transaction.
+    pub seconds_behind: Option<i64>,
+    /// Versions behind the processor furthest ahead.
+    pub versions_behind_leader: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProcessorsOverview {
+    pub chain_id: Option<i64>,
+    pub processors: Vec<ProcessorLag>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BackfillOverview {
+    pub backfill_alias: String,
+    pub backfill_status: &'static str,
+    pub backfill_start_version: i64,
+    pub backfill_end_version: i64,
+    pub last_success_version: i64,
+    pub last_updated: chrono::NaiveDateTime,
+    pub last_transaction_timestamp: Option<chrono::NaiveDateTime>,
+    pub claimed_by: Option<String>,
+}
+
+impl From<BackfillProcessorStatusQuery> for BackfillOverview {
+    fn from(status: BackfillProcessorStatusQuery) -> Self {
+        Self {
+            backfill_alias: status.backfill_alias,
+            backfill_status: match status.backfill_status {
+                BackfillStatus::InProgress => "in_progress",
+                BackfillStatus::Complete => "complete",
+            },
+            backfill_start_version: status.backfill_start_version,
+            backfill_end_version: status.backfill_end_version,
+            last_success_version: status.last_success_version,
+            last_updated: status.last_updated,
+            last_transaction_timestamp: status.last_transaction_timestamp,
+            claimed_by: status.claimed_by,
+        }
+    }
+}
+
+fn processor_lags(
+    statuses: Vec<ProcessorStatusQuery>,
+    now: chrono::NaiveDateTime,
+) -> Vec<ProcessorLag> {
+    let leader_version = statuses
+        .iter()
+        .map(|s| s.last_success_version)
+        .max()
+        .unwrap_or_default();
+    statuses
+        .into_iter()
+        .map(|status| ProcessorLag {
+            seconds_behind: status
+                .last_transaction_timestamp
+                .map(|timestamp| (now - timestamp).num_seconds()),
+            versions_behind_leader: leader_version - status.last_success_version,
+            processor: status.processor,
+            last_success_version: status.last_success_version,
+            last_updated: status.last_updated,
+            last_transaction_timestamp: status.last_transaction_timestamp,
+        })
+        .collect()
+}
+
+pub async fn list_processors(db_pool: ArcDbPool) -> Result<ProcessorsOverview> {
+    let mut conn = db_pool.get().await?;
+    let chain_id = LedgerInfo::get(&mut conn)
+        .await
+        .context("Failed to query ledger_infos")?
+        .map(|ledger_info| ledger_info.chain_id);
+    let statuses = ProcessorStatusQuery::get_all(&mut conn)
+        .await
+        .context("Failed to query processor_status")?;
+    Ok(ProcessorsOverview {
+        chain_id,
+        processors: processor_lags(statuses, chrono::Utc::now().naive_utc()),
+    })
+}
+
+/// Whether `processor` is the checkpoint of `processor_name` or one of the per table checkpoints
+/// of a parquet processor, `{processor_name}.{table}`.
+fn is_checkpoint_of(processor: &str, processor_name: &str) -> bool {
+    match processor.strip_prefix(processor_name) {
+        Some(suffix) => suffix.is_empty() || suffix.starts_with('.'),
+        None => false,
+    }
+}
+
+/// The checkpoint of a processor. Parquet processors resume from the lowest of their per table
+/// checkpoints, so that one is returned for them.
+pub async fn get_checkpoint(db_pool: ArcDbPool, processor_name: &str) -> Result<Option<i64>> {
+    let mut conn = db_pool.get().await?;
+    let statuses = ProcessorStatusQuery::get_all(&mut conn)
+        .await
+        .context("Failed to query processor_status")?;
+    Ok(statuses
+        .into_iter()
+        .filter(|status| is_checkpoint_of(&status.processor, processor_name))
+        .map(|status| status.last_success_version)
+        .min())
+}
+
+/// Sets the checkpoint of a processor, also if that moves it backwards. Parquet processors are
+/// checkpointed per table, so each of their per table checkpoints is set instead. The processor
+/// resumes from `version`, or from `bootstrap_config.initial_starting_version` if that is greater,
+/// the next time it starts. Returns the number of checkpoints set.
+pub async fn set_checkpoint(
+    db_pool: ArcDbPool,
+    processor_name: &str,
+    version: i64,
+) -> Result<usize> {
+    anyhow::ensure!(version >= 0, "version must not be negative");
+    let mut conn = db_pool.get().await?;
+    let num_tables = sql_query(
+        "UPDATE processor_status SET last_success_version = $2, last_updated = NOW(), \
+         last_transaction_timestamp = NULL WHERE starts_with(processor, $1 || '.')",
+    )
+    .bind::<Text, _>(processor_name)
+    .bind::<BigInt, _>(version)
+    .execute(&mut conn)
+    .await
+    .context("Failed to set per table checkpoints")?;
+    if num_tables > 0 {
+        return Ok(num_tables);
+    }
+    // The pool of the CLI has a single connection.
+    drop(conn);
+
+    let status = ProcessorStatus {
+        processor: processor_name.to_string(),
+        last_success_version: version,
+        last_transaction_timestamp: None,
+    };
+    execute_with_better_error(
+        db_pool,
+        diesel::insert_into(processor_status::table)
+            .values(&status)
+            .on_conflict(processor_status::processor)
+            .do_update()
+            .set((
+                processor_status::last_success_version
+                    .eq(excluded(processor_status::last_success_version)),
+                processor_status::last_updated.eq(excluded(processor_status::last_updated)),
+                processor_status::last_transaction_timestamp
+                    .eq(excluded(processor_status::last_transaction_timestamp)),
+            )),
+        None,
+    )
+    .await
+    .context("Failed to set checkpoint")?;
+    Ok(1)
+}
+
+/// Lists the backfills, including the per table checkpoints of parquet backfills and the shards of
+/// sharded backfills, optionally only those of one backfill.
+pub async fn list_backfills(
+    db_pool: ArcDbPool,
+    backfill_alias: Option<&str>,
+) -> Result<Vec<BackfillOverview>> {
+    let mut conn = db_pool.get().await?;
+    let statuses = BackfillProcessorStatusQuery::get_all(&mut conn)
+        .await
+        .context("Failed to query backfill_processor_status")?;
+    Ok(statuses
+        .into_iter()
+        .filter(|status| {
+            backfill_alias.map_or(true, |alias| belongs_to(&status.backfill_alias, alias))
+        })
+        .map(BackfillOverview::from)
+        .collect())
+}
+
+/// Whether `backfill_alias` is the backfill itself or one of its per table checkpoints or shards.
+fn belongs_to(backfill_alias: &str, parent_alias: &str) -> bool {
+    match backfill_alias.strip_prefix(parent_alias) {
+        Some(suffix) => suffix.is_empty() || suffix.starts_with('.') || suffix.starts_with('#'),
+        None => false,
+    }
+}
+
+/// Starts the backfill over, the processor running it resumes at `start_version`. Overwrites any
+/// progress of an existing backfill with the same alias.
+pub async fn start_backfill(
+    db_pool: ArcDbPool,
+    backfill_alias: &str,
+    start_version: u64,
+    end_version: u64,
+) -> Result<()> {
+    anyhow::ensure!(
+        start_version <= end_version,
+        "start_version must not be greater than end_version"
+    );
+    let status = BackfillProcessorStatus {
+        backfill_alias: backfill_alias.to_string(),
+        backfill_status: BackfillStatus::InProgress,
+        last_success_version: start_version as i64 - 1,
+        last_transaction_timestamp: None,
+        backfill_start_version: start_version as i64,
+        backfill_end_version: end_version as i64,
+    };
+    upsert_backfill_status(db_pool, &status).await
+}
+
+/// Marks the backfill complete, with its per table checkpoints and shards, so that no shard is
+/// claimed again. Processors already running it keep going until they're restarted, but their
+/// checkpoints no longer change the status; once restarted they stop right away. Returns the number
+/// of rows updated.
+pub async fn complete_backfill(db_pool: ArcDbPool, backfill_alias: &str) -> Result<usize> {
+    let mut conn = db_pool.get().await?;
+    sql_query(
+        "UPDATE backfill_processor_status SET backfill_status = 'complete', last_updated = NOW() \
+         WHERE backfill_alias = $1 OR starts_with(backfill_alias, $1 || '.') \
+         OR starts_with(backfill_alias, $1 || '#')",
+    )
+    .bind::<Text, _>(backfill_alias)
+    .execute(&mut conn)
+    .await
+    .context("Failed to complete backfill")
+}
+
+/// Deletes the backfill with its per table checkpoints and shards. Returns the number of rows
+/// deleted. Processors running it must be stopped first, otherwise their next checkpoint creates it
+/// again.
+pub async fn cancel_backfill(db_pool: ArcDbPool, backfill_alias: &str) -> Result<usize> {
+    let mut conn = db_pool.get().await?;
+    sql_query(
+        "DELETE FROM backfill_processor_status WHERE backfill_alias = $1 \
+         OR starts_with(backfill_alias, $1 || '.') OR starts_with(backfill_alias, $1 || '#')",
+    )
+    .bind::<Text, _>(backfill_alias)
+    .execute(&mut conn)
+    .await
+    .context("Failed to cancel backfill")
+}
+
+pub async fn migrate(postgres_connection_string: String, db_pool: ArcDbPool) {
+    run_migrations(postgres_connection_string, db_pool).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_belongs_to() {
+        assert!(belongs_to("events_processor_1", "events_processor_1"));
+        assert!(belongs_to(
+            "events_processor_1#shard_3",
+            "events_processor_1"
+        ));
+        assert!(belongs_to(
+            "parquet_events_processor_1.events",
+            "parquet_events_processor_1"
+        ));
+        assert!(!belongs_to("events_processor_10", "events_processor_1"));
+    }
+
+    #[test]
+    fn test_is_checkpoint_of() {
+        assert!(is_checkpoint_of("events_processor", "events_processor"));
+        assert!(is_checkpoint_of(
+            "parquet_events_processor.events",
+            "parquet_events_processor"
+        ));
+        assert!(!is_checkpoint_of(
+            "parquet_events_processor_1.events",
+            "parquet_events_processor"
+        ));
+    }
+
+    #[test]
+    fn test_processor_lags() {
+        let now = chrono::DateTime::from_timestamp(1_700_000_100, 0)
+            .unwrap()
+            .naive_utc();
+        let status =
+            |processor: &str, last_success_version, timestamp: Option<i64>| ProcessorStatusQuery {
+                processor: processor.to_string(),
+                last_success_version,
+                last_updated: now,
+                last_transaction_timestamp: timestamp
+                    .map(|t| chrono::DateTime::from_timestamp(t, 0).unwrap().naive_utc()),
+            };
+
+        let lags = processor_lags(
+            vec![
+                status("events_processor", 1_000, Some(1_700_000_090)),
+                status("token_v2_processor", 400, None),
+            ],
+            now,
+        );
+        assert_eq!(lags[0].seconds_behind, Some(10));
+        assert_eq!(lags[0].versions_behind_leader, 0);
+        assert_eq!(lags[1].seconds_behind, None);
+        assert_eq!(lags[1].versions_behind_leader, 600);
+    }
+}
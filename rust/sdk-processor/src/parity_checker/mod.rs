@@ -0,0 +1,425 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compares the output of a parquet processor against the output of its postgres counterpart
+//! (e.g. `ParquetFungibleAssetProcessor` vs `FungibleAssetProcessor`) for a version range.
+//!
+//! Rows are matched on the table's key columns (transaction version plus event or write set
+//! change index) and compared column by column after normalizing the representation differences
+//! between the two outputs (numeric as string vs number, json as string vs jsonb, ...).
+
+pub mod parquet_reader;
+pub mod postgres_reader;
+pub mod table_specs;
+
+use crate::utils::database::ArcDbPool;
+use anyhow::Context;
+use bigdecimal::BigDecimal;
+use parquet_reader::read_parquet_rows;
+use postgres_reader::read_postgres_rows;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    path::PathBuf,
+    str::FromStr,
+};
+use table_specs::{get_table_spec, ColumnKind, ParityTableSpec};
+use tracing::info;
+
+/// A row keyed by raw model field name.
+pub type ParityRow = BTreeMap<String, serde_json::Value>;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ParquetSource {
+    /// A local copy of the bucket root, e.g. synced with `gsutil rsync`.
+    Local { path: PathBuf },
+    Gcs {
+        bucket_name: String,
+        bucket_root: String,
+        google_application_credentials: Option<String>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParityCheckerConfig {
+    pub parquet_source: ParquetSource,
+    pub tables: Vec<String>,
+    pub starting_version: i64,
+    pub ending_version: i64,
+    // Cap on the number of keys and row diffs listed per table. Counts are always exact.
+    #[serde(default = "ParityCheckerConfig::default_max_reported_diffs")]
+    pub max_reported_diffs: usize,
+}
+
+impl ParityCheckerConfig {
+    pub const fn default_max_reported_diffs() -> usize {
+        100
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ColumnDiff {
+    pub column: String,
+    pub parquet_value: serde_json::Value,
+    pub postgres_value: serde_json::Value,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RowDiff {
+    pub key: Vec<serde_json::Value>,
+    pub columns: Vec<ColumnDiff>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TableParityReport {
+    pub table_name: String,
+    pub parquet_row_count: usize,
+    pub postgres_row_count: usize,
+    pub missing_in_parquet_count: usize,
+    pub missing_in_postgres_count: usize,
+    pub mismatched_row_count: usize,
+    /// Rows whose key was already seen on the same side. Only the first one is compared.
+    pub duplicate_in_parquet_count: usize,
+    pub duplicate_in_postgres_count: usize,
+    pub missing_in_parquet: Vec<Vec<serde_json::Value>>,
+    pub missing_in_postgres: Vec<Vec<serde_json::Value>>,
+    pub mismatched_rows: Vec<RowDiff>,
+    pub duplicate_in_parquet: Vec<Vec<serde_json::Value>>,
+    pub duplicate_in_postgres: Vec<Vec<serde_json::Value>>,
+}
+
+impl TableParityReport {
+    pub fn is_match(&self) -> bool {
+        self.parquet_row_count == self.postgres_row_count
+            && self.missing_in_parquet_count == 0
+            && self.missing_in_postgres_count == 0
+            && self.mismatched_row_count == 0
+            && self.duplicate_in_parquet_count == 0
+            && self.duplicate_in_postgres_count == 0
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ParityReport {
+    pub starting_version: i64,
+    pub ending_version: i64,
+    pub tables: Vec<TableParityReport>,
+}
+
+impl ParityReport {
+    pub fn is_match(&self) -> bool {
+        self.tables.iter().all(|t| t.is_match())
+    }
+}
+
+pub async fn run_parity_check(
+    config: &ParityCheckerConfig,
+    db_pool: ArcDbPool,
+) -> anyhow::Result<ParityReport> {
+    let specs = config
+        .tables
+        .iter()
+        .map(|table_name| {
+            get_table_spec(table_name).with_context(|| {
+                format!(
+                    "Parity check is not supported for table '{}'. Expected one of: {:?}",
+                    table_name,
+                    table_specs::PARITY_TABLE_SPECS
+                        .iter()
+                        .map(|s| s.table_name)
+                        .collect::<Vec<_>>()
+                )
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut tables = vec![];
+    for spec in specs {
+        let parquet_rows = read_parquet_rows(
+            &config.parquet_source,
+            spec,
+            config.starting_version,
+            config.ending_version,
+        )
+        .await?;
+        let postgres_rows = read_postgres_rows(
+            db_pool.clone(),
+            spec,
+            config.starting_version,
+            config.ending_version,
+        )
+        .await?;
+
+        let report = compare_rows(spec, parquet_rows, postgres_rows, config.max_reported_diffs);
+        info!(
+            table_name = spec.table_name,
+            parquet_row_count = report.parquet_row_count,
+            postgres_row_count = report.postgres_row_count,
+            mismatched_row_count = report.mismatched_row_count,
+            duplicate_in_parquet_count = report.duplicate_in_parquet_count,
+            duplicate_in_postgres_count = report.duplicate_in_postgres_count,
+            "[Parity Checker] Finished comparing table"
+        );
+        tables.push(report);
+    }
+
+    Ok(ParityReport {
+        starting_version: config.starting_version,
+        ending_version: config.ending_version,
+        tables,
+    })
+}
+
+pub fn compare_rows(
+    spec: &ParityTableSpec,
+    parquet_rows: Vec<ParityRow>,
+    postgres_rows: Vec<ParityRow>,
+    max_reported_diffs: usize,
+) -> TableParityReport {
+    let mut report = TableParityReport {
+        table_name: spec.table_name.to_string(),
+        parquet_row_count: parquet_rows.len(),
+        postgres_row_count: postgres_rows.len(),
+        ..Default::default()
+    };
+
+    let mut postgres_rows_by_key: HashMap<String, (Vec<serde_json::Value>, ParityRow)> =
+        HashMap::new();
+    let mut duplicate_in_postgres = vec![];
+    for row in postgres_rows {
+        let row = normalize_row(spec, row);
+        let key = row_key(spec, &row);
+        match postgres_rows_by_key.entry(key_string(&key)) {
+            Entry::Occupied(_) => duplicate_in_postgres.push(key),
+            Entry::Vacant(entry) => {
+                entry.insert((key, row));
+            },
+        }
+    }
+    duplicate_in_postgres.sort_by_key(|key| key_string(key));
+    report.duplicate_in_postgres_count = duplicate_in_postgres.len();
+    report.duplicate_in_postgres = duplicate_in_postgres
+        .into_iter()
+        .take(max_reported_diffs)
+        .collect();
+
+    let mut parquet_rows = parquet_rows
+        .into_iter()
+        .map(|row| normalize_row(spec, row))
+        .collect::<Vec<_>>();
+    parquet_rows.sort_by_key(|row| key_string(&row_key(spec, row)));
+
+    let mut previous_key = None;
+    for parquet_row in parquet_rows {
+        let key = row_key(spec, &parquet_row);
+        // Rows are sorted by key, so duplicates are next to each other
+        if previous_key.as_ref() == Some(&key) {
+            report.duplicate_in_parquet_count += 1;
+            if report.duplicate_in_parquet.len() < max_reported_diffs {
+                report.duplicate_in_parquet.push(key);
+            }
+            continue;
+        }
+        previous_key = Some(key.clone());
+        match postgres_rows_by_key.remove(&key_string(&key)) {
+            None => {
+                report.missing_in_postgres_count += 1;
+                if report.missing_in_postgres.len() < max_reported_diffs {
+                    report.missing_in_postgres.push(key);
+                }
+            },
+            Some((_, postgres_row)) => {
+                let columns = spec
+                    .columns
+                    .iter()
+                    .filter_map(|c| {
+                        let parquet_value =
+                            parquet_row.get(c.raw_field).cloned().unwrap_or_default();
+                        let postgres_value =
+                            postgres_row.get(c.raw_field).cloned().unwrap_or_default();
+                        (parquet_value != postgres_value).then(|| ColumnDiff {
+                            column: c.raw_field.to_string(),
+                            parquet_value,
+                            postgres_value,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                if !columns.is_empty() {
+                    report.mismatched_row_count += 1;
+                    if report.mismatched_rows.len() < max_reported_diffs {
+                        report.mismatched_rows.push(RowDiff { key, columns });
+                    }
+                }
+            },
+        }
+    }
+
+    let mut missing_in_parquet = postgres_rows_by_key.into_values().collect::<Vec<_>>();
+    missing_in_parquet.sort_by_key(|(key, _)| key_string(key));
+    report.missing_in_parquet_count = missing_in_parquet.len();
+    report.missing_in_parquet = missing_in_parquet
+        .into_iter()
+        .take(max_reported_diffs)
+        .map(|(key, _)| key)
+        .collect();
+
+    report
+}
+
+fn row_key(spec: &ParityTableSpec, row: &ParityRow) -> Vec<serde_json::Value> {
+    spec.key_columns
+        .iter()
+        .map(|c| row.get(*c).cloned().unwrap_or_default())
+        .collect()
+}
+
+/// Zero pad numeric keys so that sorting by the string orders by version.
+fn key_string(key: &[serde_json::Value]) -> String {
+    key.iter()
+        .map(|v| match v.as_i64() {
+            Some(n) => format!("{:020}", n),
+            None => v.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+fn normalize_row(spec: &ParityTableSpec, row: ParityRow) -> ParityRow {
+    row.into_iter()
+        .map(|(field, value)| {
+            let value = match spec.column(&field) {
+                Some(c) => normalize_value(c.kind, value),
+                None => value,
+            };
+            (field, value)
+        })
+        .collect()
+}
+
+fn normalize_value(kind: ColumnKind, value: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match (kind, value) {
+        (_, Value::Null) => Value::Null,
+        (ColumnKind::Plain, value) => value,
+        (ColumnKind::Numeric, value) => {
+            let raw = match &value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            match BigDecimal::from_str(&raw) {
+                Ok(decimal) => Value::String(decimal.normalized().to_string()),
+                Err(_) => value,
+            }
+        },
+        (ColumnKind::Json, Value::String(s)) => {
+            serde_json::from_str(&s).unwrap_or(Value::String(s))
+        },
+        (ColumnKind::Json, value) => value,
+        (ColumnKind::Timestamp, Value::String(s)) => {
+            match chrono::NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S%.f") {
+                Ok(timestamp) => Value::from(timestamp.and_utc().timestamp_millis()),
+                Err(_) => Value::String(s),
+            }
+        },
+        (ColumnKind::Timestamp, value) => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn row(value: serde_json::Value) -> ParityRow {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_compare_rows_normalizes_representations() {
+        let spec = get_table_spec("fungible_asset_activities").unwrap();
+        let parquet_rows = vec![row(json!({
+            "transaction_version": 10,
+            "event_index": 0,
+            "amount": "100",
+            "storage_refund_amount": 0,
+            "transaction_timestamp": 1_700_000_000_123_i64,
+        }))];
+        let postgres_rows = vec![row(json!({
+            "transaction_version": 10,
+            "event_index": 0,
+            "amount": 100,
+            "storage_refund_amount": 0,
+            "transaction_timestamp": "2023-11-14T22:13:20.123",
+        }))];
+
+        let report = compare_rows(spec, parquet_rows, postgres_rows, 10);
+        assert!(report.is_match(), "{:?}", report);
+    }
+
+    #[test]
+    fn test_compare_rows_reports_diffs() {
+        let spec = get_table_spec("events").unwrap();
+        let parquet_rows = vec![
+            row(json!({"transaction_version": 1, "event_index": 0, "data": "{\"a\":1}"})),
+            row(json!({"transaction_version": 2, "event_index": 0, "data": "{}"})),
+        ];
+        let postgres_rows = vec![
+            row(json!({"transaction_version": 1, "event_index": 0, "data": {"a": 2}})),
+            row(json!({"transaction_version": 3, "event_index": 1, "data": {}})),
+        ];
+
+        let report = compare_rows(spec, parquet_rows, postgres_rows, 10);
+        assert!(!report.is_match());
+        assert_eq!(report.missing_in_postgres, vec![vec![json!(2), json!(0)]]);
+        assert_eq!(report.missing_in_parquet, vec![vec![json!(3), json!(1)]]);
+        assert_eq!(report.mismatched_rows, vec![RowDiff {
+            key: vec![json!(1), json!(0)],
+            columns: vec![ColumnDiff {
+                column: "data".to_string(),
+                parquet_value: json!({"a": 1}),
+                postgres_value: json!({"a": 2}),
+            }],
+        }]);
+    }
+
+    #[test]
+    fn test_compare_rows_reports_duplicates() {
+        let spec = get_table_spec("events").unwrap();
+        let parquet_rows = vec![
+            row(json!({"transaction_version": 1, "event_index": 0, "data": "{}"})),
+            row(json!({"transaction_version": 2, "event_index": 0, "data": "{}"})),
+            row(json!({"transaction_version": 2, "event_index": 0, "data": "{}"})),
+        ];
+        let postgres_rows = vec![
+            row(json!({"transaction_version": 1, "event_index": 0, "data": {}})),
+            row(json!({"transaction_version": 1, "event_index": 0, "data": {"a": 1}})),
+            row(json!({"transaction_version": 2, "event_index": 0, "data": {}})),
+        ];
+
+        let report = compare_rows(spec, parquet_rows, postgres_rows, 10);
+        assert!(!report.is_match());
+        assert_eq!(report.duplicate_in_parquet, vec![vec![json!(2), json!(0)]]);
+        assert_eq!(report.duplicate_in_postgres, vec![vec![json!(1), json!(0)]]);
+        assert_eq!(report.missing_in_parquet_count, 0);
+        assert_eq!(report.missing_in_postgres_count, 0);
+        assert_eq!(report.mismatched_row_count, 0);
+    }
+
+    #[test]
+    fn test_table_specs_have_key_columns() {
+        for spec in table_specs::PARITY_TABLE_SPECS {
+            spec.version_column();
+            for key in spec.key_columns {
+                assert!(
+                    spec.column(key).is_some(),
+                    "{} is missing key column {}",
+                    spec.table_name,
+                    key
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{table_specs::ParityTableSpec, ParityRow, ParquetSource};
+use anyhow::Context;
+use bytes::Bytes;
+use google_cloud_storage::{
+    client::{Client as GCSClient, ClientConfig as GcsClientConfig},
+    http::objects::{download::Range, get::GetObjectRequest, list::ListObjectsRequest},
+};
+use parquet::{
+    file::reader::{FileReader, SerializedFileReader},
+    record::Field,
+};
+use processor::bq_analytics::gcs_handler::parse_parquet_file_version_range;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Reads the parquet files written for the table and returns the rows whose transaction version
+/// falls within `[starting_version, ending_version]`.
+///
+/// Files named after the versions they cover are skipped when they are outside of the range.
+/// Files uploaded before versions were part of the name are always read.
+pub async fn read_parquet_rows(
+    source: &ParquetSource,
+    spec: &ParityTableSpec,
+    starting_version: i64,
+    ending_version: i64,
+) -> anyhow::Result<Vec<ParityRow>> {
+    let files = match source {
+        ParquetSource::Local { path } => read_local_files(
+            &path.join(spec.table_name),
+            starting_version,
+            ending_version,
+        )?,
+        ParquetSource::Gcs {
+            bucket_name,
+            bucket_root,
+            google_application_credentials,
+        } => {
+            download_gcs_files(
+                bucket_name,
+                bucket_root,
+                google_application_credentials.clone(),
+                spec.table_name,
+                starting_version,
+                ending_version,
+            )
+            .await?
+        },
+    };
+
+    let mut rows = vec![];
+    for (file_name, data) in files {
+        let reader = SerializedFileReader::new(data)
+            .with_context(|| format!("Failed to open parquet file {}", file_name))?;
+        let row_iter = reader
+            .get_row_iter(None)
+            .with_context(|| format!("Failed to read rows from {}", file_name))?;
+        for row in row_iter {
+            let row = row.with_context(|| format!("Failed to read row from {}", file_name))?;
+            let parity_row = row
+                .get_column_iter()
+                .filter_map(|(name, field)| {
+                    spec.columns
+                        .iter()
+                        .find(|c| c.parquet_column == name)
+                        .map(|c| (c.raw_field.to_string(), field_to_json(field)))
+                })
+                .collect::<ParityRow>();
+
+            let version = parity_row
+                .get(spec.version_column().raw_field)
+                .and_then(|v| v.as_i64())
+                .with_context(|| format!("Row in {} has no transaction version", file_name))?;
+            if version >= starting_version && version <= ending_version {
+                rows.push(parity_row);
+            }
+        }
+    }
+    Ok(rows)
+}
+
+/// Parquet timestamps are written as milliseconds, so keep them as integers instead of the
+/// formatted string `Field::to_json_value` would produce.
+fn field_to_json(field: &Field) -> serde_json::Value {
+    match field {
+        Field::TimestampMillis(millis) => serde_json::Value::from(*millis),
+        Field::TimestampMicros(micros) => serde_json::Value::from(*micros / 1000),
+        _ => field.to_json_value(),
+    }
+}
+
+/// Whether the file can contain rows within `[starting_version, ending_version]`.
+fn may_overlap(file_name: &str, starting_version: i64, ending_version: i64) -> bool {
+    match parse_parquet_file_version_range(file_name) {
+        Some((start_version, end_version)) => {
+            start_version <= ending_version && end_version >= starting_version
+        },
+        None => true,
+    }
+}
+
+fn read_local_files(
+    dir: &Path,
+    starting_version: i64,
+    ending_version: i64,
+) -> anyhow::Result<Vec<(String, Bytes)>> {
+    let mut files = vec![];
+    let mut dirs: Vec<PathBuf> = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        if !dir.exists() {
+            continue;
+        }
+        for entry in std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read directory {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "parquet")
+                && may_overlap(
+                    &path.display().to_string(),
+                    starting_version,
+                    ending_version,
+                )
+            {
+                let data = std::fs::read(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                files.push((path.display().to_string(), Bytes::from(data)));
+            }
+        }
+    }
+    info!(
+        directory = dir.display().to_string(),
+        num_files = files.len(),
+        "[Parity Checker] Found local parquet files within the version range"
+    );
+    Ok(files)
+}
+
+async fn download_gcs_files(
+    bucket_name: &str,
+    bucket_root: &str,
+    google_application_credentials: Option<String>,
+    table_name: &str,
+    starting_version: i64,
+    ending_version: i64,
+) -> anyhow::Result<Vec<(String, Bytes)>> {
+    if let Some(credentials) = google_application_credentials {
+        std::env::set_var("GOOGLE_APPLICATION_CREDENTIALS", credentials);
+    }
+    let gcs_config = GcsClientConfig::default()
+        .with_auth()
+        .await
+        .context("Failed to create GCS client config")?;
+    let client = GCSClient::new(gcs_config);
+
+    // Matches the layout used by `upload_parquet_to_gcs`: {bucket_root}/{table_name}/...
+    let prefix = format!("{}/", Path::new(bucket_root).join(table_name).display());
+    let mut object_names = vec![];
+    let mut page_token = None;
+    loop {
+        let response = client
+            .list_objects(&ListObjectsRequest {
+                bucket: bucket_name.to_string(),
+                prefix: Some(prefix.clone()),
+                page_token: page_token.clone(),
+                ..Default::default()
+            })
+            .await
+            .with_context(|| format!("Failed to list objects under {}", prefix))?;
+        object_names.extend(
+            response
+                .items
+                .unwrap_or_default()
+                .into_iter()
+                .map(|object| object.name)
+                .filter(|name| {
+                    name.ends_with(".parquet")
+                        && may_overlap(name, starting_version, ending_version)
+                }),
+        );
+        page_token = response.next_page_token;
+        if page_token.is_none() {
+            break;
+        }
+    }
+    info!(
+        prefix = prefix,
+        num_files = object_names.len(),
+        "[Parity Checker] Found parquet files in GCS within the version range"
+    );
+
+    let mut files = vec![];
+    for object_name in object_names {
+        let data = client
+            .download_object(
+                &GetObjectRequest {
+                    bucket: bucket_name.to_string(),
+                    object: object_name.clone(),
+                    ..Default::default()
+                },
+                &Range::default(),
+            )
+            .await
+            .with_context(|| format!("Failed to download {}", object_name))?;
+        files.push((object_name, Bytes::from(data)));
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_may_overlap() {
+        let file_name = "root/events/1700000000000/1700000123456_0_100_199.parquet";
+        assert!(may_overlap(file_name, 150, 300));
+        assert!(may_overlap(file_name, 0, 100));
+        assert!(!may_overlap(file_name, 200, 300));
+        assert!(!may_overlap(file_name, 0, 99));
+        // Files without versions in the name can't be skipped
+        assert!(may_overlap(
+            "root/events/1700000000000/1700000123456_0.parquet",
+            200,
+            300
+        ));
+    }
+}
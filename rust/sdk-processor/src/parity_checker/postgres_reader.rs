@@ -0,0 +1,62 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{table_specs::ParityTableSpec, ParityRow};
+use crate::utils::database::ArcDbPool;
+use anyhow::Context;
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Jsonb},
+    QueryableByName,
+};
+use diesel_async::RunQueryDsl;
+
+#[derive(QueryableByName)]
+struct JsonRow {
+    #[diesel(sql_type = Jsonb)]
+    row: serde_json::Value,
+}
+
+/// Reads the rows of the table within `[starting_version, ending_version]`, keeping only the
+/// columns in the spec and renaming them to their raw model field names.
+pub async fn read_postgres_rows(
+    db_pool: ArcDbPool,
+    spec: &ParityTableSpec,
+    starting_version: i64,
+    ending_version: i64,
+) -> anyhow::Result<Vec<ParityRow>> {
+    let mut conn = db_pool
+        .get()
+        .await
+        .context("Failed to get connection from pool")?;
+
+    // Table and column names come from the static table specs, never from user input.
+    let query = format!(
+        "SELECT row_to_json(t)::jsonb AS row FROM {} t WHERE t.{} BETWEEN $1 AND $2",
+        spec.table_name,
+        spec.version_column().postgres_column,
+    );
+    let json_rows: Vec<JsonRow> = sql_query(query)
+        .bind::<BigInt, _>(starting_version)
+        .bind::<BigInt, _>(ending_version)
+        .load(&mut conn)
+        .await
+        .with_context(|| format!("Failed to query {}", spec.table_name))?;
+
+    Ok(json_rows
+        .into_iter()
+        .map(|json_row| {
+            spec.columns
+                .iter()
+                .map(|c| {
+                    let value = json_row
+                        .row
+                        .get(c.postgres_column)
+                        .cloned()
+                        .unwrap_or_default();
+                    (c.raw_field.to_string(), value)
+                })
+                .collect()
+        })
+        .collect())
+}
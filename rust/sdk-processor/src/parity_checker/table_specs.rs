@@ -0,0 +1,321 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Column mappings between the parquet and postgres versions of a table.
+//!
+//! Both outputs are built from the same raw models in `processor::db::common::models`, so each
+//! column is identified by the raw model field name and mapped to whatever the parquet and
+//! postgres models call it (e.g. `RawEvent::type_` is `event_type` in parquet and `type` in
+//! postgres). Only columns that exist on both sides are compared.
+//!
+//! Names are taken from the model fields and the diesel schema rather than written out, so a
+//! renamed or removed field fails to compile instead of silently dropping out of the comparison.
+
+use diesel::Column;
+use processor::{
+    db::{
+        common::models::{
+            event_models::raw_events::RawEvent,
+            fungible_asset_models::raw_v2_fungible_asset_activities::RawFungibleAssetActivity,
+            token_v2_models::{
+                raw_v2_token_activities::RawTokenActivityV2, raw_v2_token_datas::RawTokenDataV2,
+            },
+        },
+        parquet::models::{
+            default_models::parquet_move_resources::MoveResource as ParquetMoveResource,
+            event_models::parquet_events::EventPQ,
+            fungible_asset_models::parquet_v2_fungible_asset_activities::FungibleAssetActivity as ParquetFungibleAssetActivity,
+            token_v2_models::{
+                v2_token_activities::TokenActivityV2 as ParquetTokenActivityV2,
+                v2_token_datas::TokenDataV2 as ParquetTokenDataV2,
+            },
+            user_transaction_models::parquet_user_transactions::UserTransaction as ParquetUserTransaction,
+        },
+        postgres::models::{
+            default_models::move_resources::MoveResource,
+            user_transactions_models::user_transactions::UserTransaction,
+        },
+    },
+    schema,
+};
+use ColumnKind::{Json, Numeric, Plain, Timestamp};
+
+/// How to normalize a column before comparing the two sides.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnKind {
+    /// Compared as is.
+    Plain,
+    /// BigDecimal columns. Postgres returns a number, parquet a string or an unsigned int.
+    Numeric,
+    /// JSON columns. Postgres returns jsonb, parquet a serialized string.
+    Json,
+    /// Timestamps, compared with millisecond precision.
+    Timestamp,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParityColumn {
+    pub raw_field: &'static str,
+    pub parquet_column: &'static str,
+    pub postgres_column: &'static str,
+    pub kind: ColumnKind,
+}
+
+/// Name of a field of `$model`, which fails to compile if the field doesn't exist.
+macro_rules! field_name {
+    ($model:ty, $field:ident) => {{
+        const _: fn(&$model) = |model| {
+            let _ = &model.$field;
+        };
+        stringify!($field)
+    }};
+}
+
+/// A column of `$table`, given as `name, kind` when the raw field, parquet column and postgres
+/// column share a name, or as `raw_field, parquet_column, postgres_column, kind`.
+macro_rules! column {
+    (
+        $raw:ty,
+        $parquet:ty,
+        $table:ident,
+        $raw_field:ident,
+        $parquet_column:ident,
+        $postgres_column:ident,
+        $kind:expr
+    ) => {
+        ParityColumn {
+            raw_field: field_name!($raw, $raw_field),
+            parquet_column: field_name!($parquet, $parquet_column),
+            postgres_column: <schema::$table::$postgres_column as Column>::NAME,
+            kind: $kind,
+        }
+    };
+    ($raw:ty, $parquet:ty, $table:ident, $name:ident, $kind:expr) => {
+        column!($raw, $parquet, $table, $name, $name, $name, $kind)
+    };
+}
+
+/// The columns of a table, each in parentheses with the arguments of `column!` after the models.
+macro_rules! columns {
+    ($raw:ty, $parquet:ty, $table:ident, [$(($($column:tt)*)),* $(,)?]) => {
+        &[$(column!($raw, $parquet, $table, $($column)*)),*]
+    };
+}
+
+#[derive(Debug)]
+pub struct ParityTableSpec {
+    pub table_name: &'static str,
+    /// Raw field names that uniquely identify a row within a transaction version range, starting
+    /// with the transaction version.
+    pub key_columns: &'static [&'static str],
+    pub columns: &'static [ParityColumn],
+}
+
+impl ParityTableSpec {
+    pub fn version_column(&self) -> &ParityColumn {
+        self.column(self.key_columns[0])
+            .expect("Every parity table spec has a transaction version column")
+    }
+
+    pub fn column(&self, raw_field: &str) -> Option<&ParityColumn> {
+        self.columns.iter().find(|c| c.raw_field == raw_field)
+    }
+}
+
+/// `RawEvent`
+const EVENTS: ParityTableSpec = ParityTableSpec {
+    table_name: "events",
+    key_columns: &[
+        field_name!(RawEvent, transaction_version),
+        field_name!(RawEvent, event_index),
+    ],
+    columns: columns!(RawEvent, EventPQ, events, [
+        (transaction_version, txn_version, transaction_version, Plain),
+        (event_index, Plain),
+        (sequence_number, Plain),
+        (creation_number, Plain),
+        (account_address, Plain),
+        (
+            transaction_block_height,
+            block_height,
+            transaction_block_height,
+            Plain
+        ),
+        (type_, event_type, type_, Plain),
+        (data, Json),
+        (indexed_type, Plain),
+    ]),
+};
+
+/// `RawFungibleAssetActivity`
+const FUNGIBLE_ASSET_ACTIVITIES: ParityTableSpec = ParityTableSpec {
+    table_name: "fungible_asset_activities",
+    key_columns: &[
+        field_name!(RawFungibleAssetActivity, transaction_version),
+        field_name!(RawFungibleAssetActivity, event_index),
+    ],
+    columns: columns!(
+        RawFungibleAssetActivity,
+        ParquetFungibleAssetActivity,
+        fungible_asset_activities,
+        [
+            (transaction_version, txn_version, transaction_version, Plain),
+            (event_index, Plain),
+            (owner_address, Plain),
+            (storage_id, Plain),
+            (asset_type, Plain),
+            (is_frozen, Plain),
+            (amount, Numeric),
+            (event_type, event_type, type_, Plain),
+            (is_gas_fee, Plain),
+            (gas_fee_payer_address, Plain),
+            (is_transaction_success, Plain),
+            (entry_function_id_str, Plain),
+            (block_height, Plain),
+            (token_standard, Plain),
+            (
+                transaction_timestamp,
+                block_timestamp,
+                transaction_timestamp,
+                Timestamp
+            ),
+            (
+                storage_refund_amount,
+                storage_refund_octa,
+                storage_refund_amount,
+                Numeric
+            ),
+        ]
+    ),
+};
+
+/// `RawTokenActivityV2`
+const TOKEN_ACTIVITIES_V2: ParityTableSpec = ParityTableSpec {
+    table_name: "token_activities_v2",
+    key_columns: &[
+        field_name!(RawTokenActivityV2, transaction_version),
+        field_name!(RawTokenActivityV2, event_index),
+    ],
+    columns: columns!(
+        RawTokenActivityV2,
+        ParquetTokenActivityV2,
+        token_activities_v2,
+        [
+            (transaction_version, txn_version, transaction_version, Plain),
+            (event_index, Plain),
+            (event_account_address, Plain),
+            (token_data_id, Plain),
+            (property_version_v1, Numeric),
+            (type_, Plain),
+            (from_address, Plain),
+            (to_address, Plain),
+            (token_amount, Numeric),
+            (before_value, Plain),
+            (after_value, Plain),
+            (entry_function_id_str, Plain),
+            (token_standard, Plain),
+            (is_fungible_v2, Plain),
+            (
+                transaction_timestamp,
+                block_timestamp,
+                transaction_timestamp,
+                Timestamp
+            ),
+        ]
+    ),
+};
+
+/// `RawTokenDataV2`
+const TOKEN_DATAS_V2: ParityTableSpec = ParityTableSpec {
+    table_name: "token_datas_v2",
+    key_columns: &[
+        field_name!(RawTokenDataV2, transaction_version),
+        field_name!(RawTokenDataV2, write_set_change_index),
+    ],
+    columns: columns!(RawTokenDataV2, ParquetTokenDataV2, token_datas_v2, [
+        (transaction_version, txn_version, transaction_version, Plain),
+        (write_set_change_index, Plain),
+        (token_data_id, Plain),
+        (collection_id, Plain),
+        (token_name, Plain),
+        (largest_property_version_v1, Numeric),
+        (token_uri, Plain),
+        (token_properties, Json),
+        (description, Plain),
+        (token_standard, Plain),
+        (is_fungible_v2, Plain),
+        (
+            transaction_timestamp,
+            block_timestamp,
+            transaction_timestamp,
+            Timestamp
+        ),
+        (is_deleted_v2, Plain),
+    ]),
+};
+
+/// `UserTransaction`. User transactions don't have a raw model yet, so the postgres model's
+/// field names are used.
+const USER_TRANSACTIONS: ParityTableSpec = ParityTableSpec {
+    table_name: "user_transactions",
+    key_columns: &[field_name!(UserTransaction, version)],
+    columns: columns!(
+        UserTransaction,
+        ParquetUserTransaction,
+        user_transactions,
+        [
+            (version, txn_version, version, Plain),
+            (block_height, Plain),
+            (epoch, Plain),
+            (parent_signature_type, Plain),
+            (sender, Plain),
+            (sequence_number, Plain),
+            (max_gas_amount, max_gas_octa, max_gas_amount, Numeric),
+            (gas_unit_price, Numeric),
+            (timestamp, block_timestamp, timestamp, Timestamp),
+            (entry_function_id_str, Plain),
+        ]
+    ),
+};
+
+/// `MoveResource`. Same as user transactions, there is no raw model for move resources.
+const MOVE_RESOURCES: ParityTableSpec = ParityTableSpec {
+    table_name: "move_resources",
+    key_columns: &[
+        field_name!(MoveResource, transaction_version),
+        field_name!(MoveResource, write_set_change_index),
+    ],
+    columns: columns!(MoveResource, ParquetMoveResource, move_resources, [
+        (transaction_version, txn_version, transaction_version, Plain),
+        (write_set_change_index, Plain),
+        (
+            transaction_block_height,
+            block_height,
+            transaction_block_height,
+            Plain
+        ),
+        (name, fun, name, Plain),
+        (address, resource_address, address, Plain),
+        (type_, resource_type, type_, Plain),
+        (module, Plain),
+        (generic_type_params, Json),
+        (data, Json),
+        (is_deleted, Plain),
+        (state_key_hash, Plain),
+    ]),
+};
+
+pub const PARITY_TABLE_SPECS: &[ParityTableSpec] = &[
+    EVENTS,
+    FUNGIBLE_ASSET_ACTIVITIES,
+    TOKEN_ACTIVITIES_V2,
+    TOKEN_DATAS_V2,
+    USER_TRANSACTIONS,
+    MOVE_RESOURCES,
+];
+
+pub fn get_table_spec(table_name: &str) -> Option<&'static ParityTableSpec> {
+    PARITY_TABLE_SPECS
+        .iter()
+        .find(|spec| spec.table_name == table_name)
+}
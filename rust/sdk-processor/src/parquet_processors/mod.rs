@@ -1,16 +1,21 @@
 use crate::{
-    config::db_config::DbConfig,
+    config::{
+        db_config::{DbConfig, ParquetConfig},
+        processor_config::ParquetDefaultProcessorConfig,
+    },
     steps::common::{
+        file_exporter::LocalFileExporter,
         gcs_uploader::{create_new_writer, GCSUploader},
         parquet_buffer_step::ParquetBufferStep,
     },
     utils::database::{new_db_pool, ArcDbPool},
 };
+use anyhow::Context;
 use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
 use async_trait::async_trait;
 use enum_dispatch::enum_dispatch;
 use google_cloud_storage::client::{Client as GCSClient, ClientConfig as GcsClientConfig};
-use parquet::schema::types::Type;
+use parquet::{file::properties::WriterProperties, schema::types::Type};
 use processor::{
     db::parquet::models::{
         account_transaction_models::parquet_account_transactions::AccountTransaction,
@@ -59,6 +64,7 @@ use processor::{
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
+    ops::RangeInclusive,
     sync::Arc,
     time::Duration,
 };
@@ -159,6 +165,8 @@ pub trait ParquetTypeTrait: std::fmt::Debug + Send + Sync {
         parquet_type: ParquetTypeEnum,
         table_name: &str,
     ) -> anyhow::Result<()>;
+
+    fn to_json_rows(&self) -> anyhow::Result<Vec<serde_json::Value>>;
 }
 
 /// Macro for implementing ParquetTypeTrait for multiple types.
@@ -184,6 +192,12 @@ macro_rules! impl_parquet_trait {
                     .upload_generic(self, parquet_type, table_name)
                     .await
             }
+
+            fn to_json_rows(&self) -> anyhow::Result<Vec<serde_json::Value>> {
+                self.iter()
+                    .map(|row| serde_json::to_value(row).context("Failed to serialize row"))
+                    .collect()
+            }
         }
     };
 }
@@ -640,40 +654,85 @@ async fn initialize_database_pool(config: &DbConfig) -> anyhow::Result<ArcDbPool
     }
 }
 
-/// Initializes the Parquet buffer step.
+/// Initializes the Parquet buffer step. Buffers are exported to local files if
+/// `file_export_config` is set and uploaded to GCS otherwise.
 async fn initialize_parquet_buffer_step(
+    parquet_type_to_schemas: HashMap<ParquetTypeEnum, Arc<Type>>,
+    parquet_processor_config: &ParquetDefaultProcessorConfig,
+    parquet_db_config: &ParquetConfig,
+    processor_name: String,
+    table_version_ranges: Option<HashMap<String, RangeInclusive<u64>>>,
+) -> anyhow::Result<ParquetBufferStep> {
+    let buffer_uploader = match &parquet_db_config.file_export_config {
+        Some(file_export_config) => {
+            LocalFileExporter::new(file_export_config, processor_name).into()
+        },
+        None => {
+            let gcs_client =
+                initialize_gcs_client(parquet_db_config.google_application_credentials.clone())
+                    .await;
+            initialize_gcs_uploader(
+                gcs_client,
+                parquet_type_to_schemas,
+                parquet_processor_config,
+                parquet_db_config.bucket_name.clone(),
+                parquet_db_config.bucket_root.clone(),
+                processor_name,
+            )?
+            .into()
+        },
+    };
+
+    let default_size_buffer_step = ParquetBufferStep::new(
+        Duration::from_secs(parquet_processor_config.upload_interval),
+        buffer_uploader,
+        parquet_processor_config.max_buffer_size,
+    )
+    .with_table_version_ranges(table_version_ranges);
+
+    Ok(default_size_buffer_step)
+}
+
+fn initialize_gcs_uploader(
     gcs_client: Arc<GCSClient>,
     parquet_type_to_schemas: HashMap<ParquetTypeEnum, Arc<Type>>,
-    upload_interval: u64,
-    max_buffer_size: usize,
+    parquet_processor_config: &ParquetDefaultProcessorConfig,
     bucket_name: String,
     bucket_root: String,
     processor_name: String,
-) -> anyhow::Result<ParquetBufferStep> {
+) -> anyhow::Result<GCSUploader> {
+    let parquet_type_to_writer_properties = parquet_type_to_schemas
+        .keys()
+        .map(|key| {
+            let writer_properties = parquet_processor_config
+                .writer_config_for_table(&key.to_string())
+                .writer_properties()
+                .with_context(|| format!("Invalid parquet writer config for table {}", key))?;
+            Ok((*key, Arc::new(writer_properties)))
+        })
+        .collect::<anyhow::Result<HashMap<ParquetTypeEnum, Arc<WriterProperties>>>>()?;
+
     let parquet_type_to_writer = parquet_type_to_schemas
         .iter()
         .map(|(key, schema)| {
-            let writer = create_new_writer(schema.clone()).expect("Failed to create writer");
+            let writer = create_new_writer(
+                schema.clone(),
+                parquet_type_to_writer_properties[key].clone(),
+            )
+            .expect("Failed to create writer");
             (*key, writer)
         })
         .collect();
 
-    let buffer_uploader = GCSUploader::new(
+    GCSUploader::new(
         gcs_client,
         parquet_type_to_schemas,
+        parquet_type_to_writer_properties,
         parquet_type_to_writer,
         bucket_name,
         bucket_root,
         processor_name,
-    )?;
-
-    let default_size_buffer_step = ParquetBufferStep::new(
-        Duration::from_secs(upload_interval),
-        buffer_uploader,
-        max_buffer_size,
-    );
-
-    Ok(default_size_buffer_step)
+    )
 }
 
 /// Sets the backfill table flag.
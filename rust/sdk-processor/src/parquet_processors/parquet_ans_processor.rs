@@ -1,12 +1,12 @@
 use crate::{
     config::{
         db_config::DbConfig,
-        indexer_processor_config::{IndexerProcessorConfig, ProcessorMode},
+        indexer_processor_config::IndexerProcessorConfig,
         processor_config::{ParquetDefaultProcessorConfig, ProcessorConfig},
     },
     parquet_processors::{
-        initialize_database_pool, initialize_gcs_client, initialize_parquet_buffer_step,
-        set_backfill_table_flag, ParquetTypeEnum,
+        initialize_database_pool, initialize_parquet_buffer_step, set_backfill_table_flag,
+        ParquetTypeEnum,
     },
     steps::{
         common::{
@@ -18,7 +18,7 @@ use crate::{
     utils::{
         chain_id::check_or_update_chain_id,
         database::{run_migrations, ArcDbPool},
-        starting_version::get_min_last_success_version_parquet,
+        starting_version::get_parquet_version_range,
     },
 };
 use anyhow::Context;
@@ -106,11 +106,10 @@ impl ProcessorTrait for ParquetAnsProcessor {
 
         let processor_status_table_names = self
             .config
-            .processor_config
             .get_processor_status_table_names()
             .context("Failed to get table names for the processor status table")?;
 
-        let starting_version = get_min_last_success_version_parquet(
+        let version_range = get_parquet_version_range(
             &self.config,
             self.db_pool.clone(),
             processor_status_table_names,
@@ -120,20 +119,8 @@ impl ProcessorTrait for ParquetAnsProcessor {
 
         // Define processor transaction stream config
         let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
-            starting_version: Some(starting_version),
-            request_ending_version: match self.config.mode {
-                ProcessorMode::Default => None,
-                ProcessorMode::Backfill => self
-                    .config
-                    .backfill_config
-                    .as_ref()
-                    .map(|c| c.ending_version),
-                ProcessorMode::Testing => self
-                    .config
-                    .testing_config
-                    .as_ref()
-                    .map(|c| c.ending_version),
-            },
+            starting_version: Some(version_range.starting_version),
+            request_ending_version: version_range.ending_version,
             ..self.config.transaction_stream_config.clone()
         })
         .await?;
@@ -145,9 +132,6 @@ impl ProcessorTrait for ParquetAnsProcessor {
             opt_in_tables: backfill_table,
         };
 
-        let gcs_client =
-            initialize_gcs_client(parquet_db_config.google_application_credentials.clone()).await;
-
         let parquet_type_to_schemas: HashMap<ParquetTypeEnum, Arc<Type>> = [
             (
                 ParquetTypeEnum::AnsPrimaryNameV2,
@@ -167,13 +151,11 @@ impl ProcessorTrait for ParquetAnsProcessor {
         .collect();
 
         let default_size_buffer_step = initialize_parquet_buffer_step(
-            gcs_client.clone(),
             parquet_type_to_schemas,
-            parquet_processor_config.default.upload_interval,
-            parquet_processor_config.default.max_buffer_size,
-            parquet_db_config.bucket_name.clone(),
-            parquet_db_config.bucket_root.clone(),
-            self.name().to_string(),
+            &parquet_processor_config.default,
+            parquet_db_config,
+            self.config.processor_name(),
+            version_range.table_version_ranges,
         )
         .await
         .unwrap_or_else(|e| {
@@ -0,0 +1,260 @@
+use crate::{
+    config::{
+        db_config::{ClickhouseConfig, DbConfig},
+        indexer_processor_config::{IndexerProcessorConfig, ProcessorMode},
+        processor_config::ProcessorConfig,
+    },
+    steps::{
+        common::clickhouse_storer::ClickhouseStorer, events_processor::EventsExtractor,
+        fungible_asset_processor::fungible_asset_extractor::FungibleAssetExtractor,
+        token_v2_processor::token_v2_extractor::TokenV2Extractor,
+        user_transaction_processor::UserTransactionExtractor,
+    },
+    utils::clickhouse::{
+        models::{get_fa_to_coin_mappings, ClickhouseModel},
+        processor_status::{
+            check_or_update_chain_id, create_backfill_processor_status_table_ddl,
+            create_ledger_infos_table_ddl, create_processor_status_table_ddl, get_starting_version,
+            ClickhouseProcessorStatusSaver,
+        },
+        ClickhouseClient,
+    },
+};
+use anyhow::Result;
+use aptos_indexer_processor_sdk::{
+    aptos_indexer_transaction_stream::{TransactionStream, TransactionStreamConfig},
+    builder::ProcessorBuilder,
+    common_steps::{
+        TransactionStreamStep, VersionTrackerStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
+    },
+    traits::{processor_trait::ProcessorTrait, IntoRunnableStep, Processable},
+};
+use processor::{
+    db::postgres::models::{
+        events_models::events::EventPG,
+        fungible_asset_models::{
+            v2_fungible_asset_activities::FungibleAssetActivity,
+            v2_fungible_asset_to_coin_mappings::FungibleAssetToCoinMapping,
+        },
+        token_v2_models::v2_token_activities::TokenActivityV2,
+        user_transactions_models::user_transactions::UserTransaction,
+    },
+    utils::table_flags::TableFlags,
+};
+use tracing::{debug, info};
+
+/// Runs the events, fungible asset, token v2 or user transaction processor against ClickHouse.
+///
+/// The extractors are the same as for postgres. Only the analytics table of each processor is
+/// stored: `events`, `fungible_asset_activities`, `token_activities_v2` and `user_transactions`,
+/// plus `fungible_asset_to_coin_mappings`, which the fungible asset extractor needs on restart.
+///
+/// There is no postgres to look up earlier state in, e.g. the previous owner of a burned token, so
+/// tables that depend on it, like the token ownerships and collections, can't be exported to
+/// ClickHouse. Token activities only depend on the transactions themselves.
+pub struct ClickhouseProcessor {
+    pub config: IndexerProcessorConfig,
+    pub client: ClickhouseClient,
+    pub clickhouse_config: ClickhouseConfig,
+}
+
+impl ClickhouseProcessor {
+    pub async fn new(config: IndexerProcessorConfig) -> Result<Self> {
+        match config.db_config {
+            DbConfig::ClickhouseConfig(ref clickhouse_config) => Ok(Self {
+                client: ClickhouseClient::new(clickhouse_config),
+                clickhouse_config: clickhouse_config.clone(),
+                config,
+            }),
+            _ => Err(anyhow::anyhow!(
+                "Invalid db config for ClickhouseProcessor {:?}",
+                config.db_config
+            )),
+        }
+    }
+
+    async fn create_tables(&self) -> Result<()> {
+        let database = &self.client.database;
+        let mut ddls = vec![
+            create_processor_status_table_ddl(database),
+            create_backfill_processor_status_table_ddl(database),
+            create_ledger_infos_table_ddl(database),
+        ];
+        ddls.extend(match self.config.processor_config {
+            ProcessorConfig::EventsProcessor(_) => vec![EventPG::create_table_ddl(database)],
+            ProcessorConfig::FungibleAssetProcessor(_) => vec![
+                FungibleAssetActivity::create_table_ddl(database),
+                FungibleAssetToCoinMapping::create_table_ddl(database),
+            ],
+            ProcessorConfig::TokenV2Processor(_) => {
+                vec![TokenActivityV2::create_table_ddl(database)]
+            },
+            ProcessorConfig::UserTransactionProcessor(_) => {
+                vec![UserTransaction::create_table_ddl(database)]
+            },
+            _ => unreachable!("Validated in run_processor"),
+        });
+        for ddl in ddls {
+            self.client.execute(&ddl).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ProcessorTrait for ClickhouseProcessor {
+    fn name(&self) -> &'static str {
+        self.config.processor_config.name()
+    }
+
+    async fn run_processor(&self) -> Result<()> {
+        if !matches!(
+            self.config.processor_config,
+            ProcessorConfig::EventsProcessor(_)
+                | ProcessorConfig::FungibleAssetProcessor(_)
+                | ProcessorConfig::TokenV2Processor(_)
+                | ProcessorConfig::UserTransactionProcessor(_)
+        ) {
+            return Err(anyhow::anyhow!(
+                "ClickHouse is not supported for {}",
+                self.name()
+            ));
+        }
+
+        if self.clickhouse_config.create_tables {
+            self.create_tables().await?;
+        }
+
+        let starting_version = get_starting_version(&self.config, &self.client).await?;
+
+        // Check and update the ledger chain id to ensure we're indexing the correct chain
+        let grpc_chain_id = TransactionStream::new(self.config.transaction_stream_config.clone())
+            .await?
+            .get_chain_id()
+            .await?;
+        check_or_update_chain_id(grpc_chain_id as i64, &self.client).await?;
+
+        let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
+            starting_version: Some(starting_version),
+            request_ending_version: match self.config.mode {
+                ProcessorMode::Default => None,
+                ProcessorMode::Backfill => self
+                    .config
+                    .backfill_config
+                    .as_ref()
+                    .map(|c| c.ending_version),
+                ProcessorMode::Testing => self
+                    .config
+                    .testing_config
+                    .as_ref()
+                    .map(|c| c.ending_version),
+            },
+            ..self.config.transaction_stream_config.clone()
+        })
+        .await?;
+        let version_tracker = VersionTrackerStep::new(
+            ClickhouseProcessorStatusSaver::new(self.client.clone(), &self.config),
+            DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
+        );
+        let insert_chunk_size = self.clickhouse_config.insert_chunk_size;
+
+        // Every processor has a different extractor output, so each pipeline is built separately.
+        let buffer_receiver = match self.config.processor_config.clone() {
+            ProcessorConfig::EventsProcessor(processor_config) => {
+                let channel_size = processor_config.channel_size;
+                let storer = ClickhouseStorer::new(
+                    self.client.clone(),
+                    insert_chunk_size,
+                    |events: Vec<EventPG>| events,
+                );
+                ProcessorBuilder::new_with_inputless_first_step(
+                    transaction_stream.into_runnable_step(),
+                )
+                .connect_to(EventsExtractor {}.into_runnable_step(), channel_size)
+                .connect_to(storer.into_runnable_step(), channel_size)
+                .connect_to(version_tracker.into_runnable_step(), channel_size)
+                .end_and_return_output_receiver(channel_size)
+                .1
+            },
+            ProcessorConfig::FungibleAssetProcessor(processor_config) => {
+                let channel_size = processor_config.channel_size;
+                // The coin mapping is stored with the activities, so a restart bootstraps it from
+                // the mappings of every version processed so far.
+                let mut extractor = FungibleAssetExtractor::new();
+                extractor.fa_to_coin_mapping = get_fa_to_coin_mappings(&self.client).await?;
+                let storer = ClickhouseStorer::new(
+                    self.client.clone(),
+                    insert_chunk_size,
+                    |output: <FungibleAssetExtractor as Processable>::Output| (output.0, output.5),
+                );
+                ProcessorBuilder::new_with_inputless_first_step(
+                    transaction_stream.into_runnable_step(),
+                )
+                .connect_to(extractor.into_runnable_step(), channel_size)
+                .connect_to(storer.into_runnable_step(), channel_size)
+                .connect_to(version_tracker.into_runnable_step(), channel_size)
+                .end_and_return_output_receiver(channel_size)
+                .1
+            },
+            ProcessorConfig::TokenV2Processor(processor_config) => {
+                let channel_size = processor_config.default_config.channel_size;
+                // Without a pool the extractor skips the lookups of earlier state, which only the
+                // ownerships and collections need. Only the activities are stored, see above.
+                let extractor = TokenV2Extractor::new(
+                    processor_config.query_retries,
+                    processor_config.query_retry_delay_ms,
+                    None,
+                );
+                let storer = ClickhouseStorer::new(
+                    self.client.clone(),
+                    insert_chunk_size,
+                    |output: <TokenV2Extractor as Processable>::Output| output.8,
+                );
+                ProcessorBuilder::new_with_inputless_first_step(
+                    transaction_stream.into_runnable_step(),
+                )
+                .connect_to(extractor.into_runnable_step(), channel_size)
+                .connect_to(storer.into_runnable_step(), channel_size)
+                .connect_to(version_tracker.into_runnable_step(), channel_size)
+                .end_and_return_output_receiver(channel_size)
+                .1
+            },
+            ProcessorConfig::UserTransactionProcessor(processor_config) => {
+                let channel_size = processor_config.channel_size;
+                let deprecated_tables = TableFlags::from_set(&processor_config.deprecated_tables);
+                let storer = ClickhouseStorer::new(
+                    self.client.clone(),
+                    insert_chunk_size,
+                    |output: <UserTransactionExtractor as Processable>::Output| output.0,
+                );
+                ProcessorBuilder::new_with_inputless_first_step(
+                    transaction_stream.into_runnable_step(),
+                )
+                .connect_to(
+                    UserTransactionExtractor::new(deprecated_tables).into_runnable_step(),
+                    channel_size,
+                )
+                .connect_to(storer.into_runnable_step(), channel_size)
+                .connect_to(version_tracker.into_runnable_step(), channel_size)
+                .end_and_return_output_receiver(channel_size)
+                .1
+            },
+            _ => unreachable!("Validated above"),
+        };
+
+        loop {
+            match buffer_receiver.recv().await {
+                Ok(txn_context) => {
+                    debug!(
+                        "Finished processing versions [{:?}, {:?}]",
+                        txn_context.metadata.start_version, txn_context.metadata.end_version,
+                    );
+                },
+                Err(e) => {
+                    info!("No more transactions in channel: {:?}", e);
+                    break Ok(());
+                },
+            }
+        }
+    }
+}
@@ -1,10 +1,12 @@
 pub mod account_restoration_processor;
 pub mod account_transactions_processor;
 pub mod ans_processor;
+pub mod clickhouse_processor;
 pub mod default_processor;
 pub mod events_processor;
 pub mod fungible_asset_processor;
 pub mod monitoring_processor;
+pub mod nft_marketplace_processor;
 pub mod objects_processor;
 pub mod stake_processor;
 pub mod token_v2_processor;
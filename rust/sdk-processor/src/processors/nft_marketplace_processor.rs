@@ -0,0 +1,197 @@
+use crate::{
+    config::{
+        db_config::DbConfig,
+        indexer_processor_config::{IndexerProcessorConfig, ProcessorMode},
+        processor_config::{DefaultProcessorConfig, ProcessorConfig},
+    },
+    steps::{
+        common::get_processor_status_saver,
+        nft_marketplace_processor::{NftMarketplaceExtractor, NftMarketplaceStorer},
+    },
+    utils::{
+        chain_id::check_or_update_chain_id,
+        database::{new_db_pool, run_migrations, ArcDbPool},
+        starting_version::get_starting_version,
+    },
+};
+use anyhow::Result;
+use aptos_indexer_processor_sdk::{
+    aptos_indexer_transaction_stream::{TransactionStream, TransactionStreamConfig},
+    builder::ProcessorBuilder,
+    common_steps::{
+        TransactionStreamStep, VersionTrackerStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
+    },
+    traits::{processor_trait::ProcessorTrait, IntoRunnableStep},
+};
+use processor::{
+    db::postgres::models::nft_marketplace_models::nft_marketplace_utils::MarketplaceContract,
+    utils::{
+        mq::{CustomProducer, CustomProducerEnum},
+        network::Network,
+        util::standardize_address,
+    },
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct NftMarketplaceProcessorConfig {
+    #[serde(flatten)]
+    pub default_config: DefaultProcessorConfig,
+    /// Marketplace contracts to index, deployments of the framework unless `parser` is set.
+    pub marketplaces: Vec<MarketplaceContract>,
+    /// Kafka brokers to also publish the rows to, nothing is published if unset.
+    #[serde(default)]
+    pub brokers: Option<String>,
+}
+
+pub struct NftMarketplaceProcessor {
+    pub config: IndexerProcessorConfig,
+    pub db_pool: ArcDbPool,
+}
+
+impl NftMarketplaceProcessor {
+    pub async fn new(config: IndexerProcessorConfig) -> Result<Self> {
+        match config.db_config {
+            DbConfig::PostgresConfig(ref postgres_config) => {
+                let conn_pool = new_db_pool(
+                    &postgres_config.connection_string,
+                    Some(postgres_config.db_pool_size),
+                )
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to create connection pool for PostgresConfig: {:?}",
+                        e
+                    )
+                })?;
+
+                Ok(Self {
+                    config,
+                    db_pool: conn_pool,
+                })
+            },
+            _ => Err(anyhow::anyhow!(
+                "Invalid db config for NftMarketplaceProcessor {:?}",
+                config.db_config
+            )),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ProcessorTrait for NftMarketplaceProcessor {
+    fn name(&self) -> &'static str {
+        self.config.processor_config.name()
+    }
+
+    async fn run_processor(&self) -> Result<()> {
+        //  Run migrations
+        if let DbConfig::PostgresConfig(ref postgres_config) = self.config.db_config {
+            run_migrations(
+                postgres_config.connection_string.clone(),
+                self.db_pool.clone(),
+            )
+            .await;
+        }
+
+        // Merge the starting version from config and the latest processed version from the DB
+        let starting_version = get_starting_version(&self.config, self.db_pool.clone()).await?;
+
+        // Check and update the ledger chain id to ensure we're indexing the correct chain
+        let grpc_chain_id = TransactionStream::new(self.config.transaction_stream_config.clone())
+            .await?
+            .get_chain_id()
+            .await?;
+        check_or_update_chain_id(grpc_chain_id as i64, self.db_pool.clone()).await?;
+
+        let processor_config = match &self.config.processor_config {
+            ProcessorConfig::NftMarketplaceProcessor(processor_config) => processor_config,
+            _ => return Err(anyhow::anyhow!("Processor config is wrong type")),
+        };
+        let channel_size = processor_config.default_config.channel_size;
+
+        // Topics are named after the network, which is only needed to publish
+        let (producer, network) = match processor_config.brokers.as_deref() {
+            Some(brokers) if !brokers.is_empty() => {
+                let network = Network::from_chain_id(grpc_chain_id).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Error getting network from chain id {}. Processor {}.",
+                        grpc_chain_id,
+                        self.name()
+                    )
+                })?;
+                (
+                    CustomProducerEnum::new(brokers)?
+                        .with_instance_id(self.config.instance_id.clone()),
+                    network.to_string(),
+                )
+            },
+            _ => (CustomProducerEnum::new("")?, String::new()),
+        };
+
+        // Define processor steps
+        let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
+            starting_version: Some(starting_version),
+            request_ending_version: match self.config.mode {
+                ProcessorMode::Default => None,
+                ProcessorMode::Backfill => self
+                    .config
+                    .backfill_config
+                    .as_ref()
+                    .map(|c| c.ending_version),
+                ProcessorMode::Testing => self
+                    .config
+                    .testing_config
+                    .as_ref()
+                    .map(|c| c.ending_version),
+            },
+            ..self.config.transaction_stream_config.clone()
+        })
+        .await?;
+        let marketplaces = processor_config
+            .marketplaces
+            .iter()
+            .map(|marketplace| MarketplaceContract {
+                name: marketplace.name.clone(),
+                contract_address: standardize_address(&marketplace.contract_address),
+                parser: marketplace.parser,
+            })
+            .collect();
+        let nft_marketplace_extractor = NftMarketplaceExtractor::new(marketplaces);
+        let nft_marketplace_storer = NftMarketplaceStorer::new(
+            self.db_pool.clone(),
+            processor_config.clone(),
+            producer,
+            network,
+        );
+        let version_tracker = VersionTrackerStep::new(
+            get_processor_status_saver(self.db_pool.clone(), self.config.clone()),
+            DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
+        );
+        // Connect processor steps together
+        let (_, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
+            transaction_stream.into_runnable_step(),
+        )
+        .connect_to(nft_marketplace_extractor.into_runnable_step(), channel_size)
+        .connect_to(nft_marketplace_storer.into_runnable_step(), channel_size)
+        .connect_to(version_tracker.into_runnable_step(), channel_size)
+        .end_and_return_output_receiver(channel_size);
+
+        loop {
+            match buffer_receiver.recv().await {
+                Ok(txn_context) => {
+                    debug!(
+                        "Finished processing versions [{:?}, {:?}]",
+                        txn_context.metadata.start_version, txn_context.metadata.end_version,
+                    );
+                },
+                Err(e) => {
+                    info!("No more transactions in channel: {:?}", e);
+                    break Ok(());
+                },
+            }
+        }
+    }
+}
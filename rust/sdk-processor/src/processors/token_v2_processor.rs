@@ -139,7 +139,7 @@ impl ProcessorTrait for TokenV2Processor {
         let token_v2_extractor = TokenV2Extractor::new(
             processor_config.query_retries,
             processor_config.query_retry_delay_ms,
-            self.db_pool.clone(),
+            Some(self.db_pool.clone()),
         );
         let token_v2_storer = TokenV2Storer::new(self.db_pool.clone(), processor_config.clone());
         let version_tracker = VersionTrackerStep::new(
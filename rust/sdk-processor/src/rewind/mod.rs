@@ -0,0 +1,406 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rolls the postgres output of a processor back to a version, e.g. after shipping a processor
+//! bug, so it can reprocess everything past that version once the fix is deployed.
+//!
+//! In a single transaction this
+//! - deletes the rows of the history tables past the version,
+//! - rebuilds the rows of the `current_*` tables last written past the version from the latest
+//!   history row at or before it, where the processor writes such a history table,
+//! - and resets the processor's checkpoint to the version.
+//!
+//! `current_*` tables without a history table can't be rebuilt. The number of their rows written
+//! past the version is reported, so they can be fixed by hand or by reprocessing from genesis.
+//! The processor must be stopped while rewinding, otherwise it will write its checkpoint again.
+
+pub mod table_specs;
+
+use crate::{
+    config::processor_config::ProcessorName,
+    utils::database::{ArcDbPool, MyDbConnection},
+};
+use anyhow::Context;
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Text},
+    OptionalExtension, QueryableByName,
+};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use processor::utils::util::{processor_instance_name, validate_instance_id};
+use serde::{Deserialize, Serialize};
+use table_specs::{get_rewind_spec, RebuildableTable, RewindSpec};
+use tracing::info;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RewindConfig {
+    pub processor_name: ProcessorName,
+    #[serde(default)]
+    pub instance_id: Option<String>,
+    /// Rows past this version are removed. The processor resumes at `version + 1`.
+    pub version: i64,
+    /// Only count the affected rows without changing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RewindAction {
+    Deleted,
+    Rebuilt,
+    /// Left untouched, see `UnrebuildableTable`.
+    NotRebuildable,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TableRewindReport {
+    pub table_name: String,
+    pub action: RewindAction,
+    /// Rows written past the version.
+    pub affected_rows: usize,
+    /// Rows restored from history for rebuilt tables. Not known on a dry run.
+    pub restored_rows: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RewindReport {
+    pub processor: String,
+    pub version: i64,
+    pub dry_run: bool,
+    /// The checkpoint before rewinding.
+    pub previous_last_success_version: i64,
+    pub tables: Vec<TableRewindReport>,
+}
+
+#[derive(QueryableByName)]
+struct Count {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct LastSuccessVersion {
+    #[diesel(sql_type = BigInt)]
+    last_success_version: i64,
+}
+
+pub async fn run_rewind(config: &RewindConfig, db_pool: ArcDbPool) -> anyhow::Result<RewindReport> {
+    anyhow::ensure!(config.version >= 0, "version must not be negative");
+    if let Some(instance_id) = &config.instance_id {
+        validate_instance_id(instance_id)?;
+    }
+    let spec = get_rewind_spec(config.processor_name)?;
+    let processor =
+        processor_instance_name(config.processor_name.into(), config.instance_id.as_deref());
+
+    let mut conn = db_pool
+        .get()
+        .await
+        .context("Failed to get connection from pool")?;
+
+    let config = config.clone();
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        async move {
+            // Locks the checkpoint so a processor that wasn't stopped can't save it before the
+            // rewind commits.
+            let previous_last_success_version = sql_query(
+                "SELECT last_success_version FROM processor_status WHERE processor = $1 FOR UPDATE",
+            )
+            .bind::<Text, _>(&processor)
+            .get_result::<LastSuccessVersion>(conn)
+            .await
+            .optional()
+            .context("Failed to read processor_status")?
+            .with_context(|| format!("No checkpoint found for processor {}", processor))?
+            .last_success_version;
+
+            let tables = if config.dry_run {
+                count_affected_rows(conn, spec, config.version).await?
+            } else {
+                let tables = rewind_tables(conn, spec, config.version).await?;
+                sql_query(
+                    "UPDATE processor_status SET last_success_version = $1, last_updated = NOW(), \
+                     last_transaction_timestamp = NULL WHERE processor = $2",
+                )
+                .bind::<BigInt, _>(config.version)
+                .bind::<Text, _>(&processor)
+                .execute(conn)
+                .await
+                .context("Failed to reset processor_status")?;
+                tables
+            };
+
+            for table in &tables {
+                info!(
+                    processor = processor.as_str(),
+                    table_name = table.table_name.as_str(),
+                    action = ?table.action,
+                    affected_rows = table.affected_rows,
+                    restored_rows = ?table.restored_rows,
+                    dry_run = config.dry_run,
+                    "[Rewind] Finished table"
+                );
+            }
+
+            Ok(RewindReport {
+                processor,
+                version: config.version,
+                dry_run: config.dry_run,
+                previous_last_success_version,
+                tables,
+            })
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+async fn count_affected_rows(
+    conn: &mut MyDbConnection,
+    spec: &RewindSpec,
+    version: i64,
+) -> anyhow::Result<Vec<TableRewindReport>> {
+    let tables = spec
+        .history_tables
+        .iter()
+        .map(|t| (t.table_name, t.version_column, RewindAction::Deleted))
+        .chain(spec.rebuildable_tables.iter().map(|t| {
+            (
+                t.table_name,
+                "last_transaction_version",
+                RewindAction::Rebuilt,
+            )
+        }))
+        .chain(
+            spec.unrebuildable_tables
+                .iter()
+                .map(|t| (t.table_name, t.version_column, RewindAction::NotRebuildable)),
+        );
+
+    let mut reports = vec![];
+    for (table_name, version_column, action) in tables {
+        reports.push(TableRewindReport {
+            table_name: table_name.to_string(),
+            action,
+            affected_rows: count_rows_past_version(conn, table_name, version_column, version)
+                .await?,
+            restored_rows: None,
+        });
+    }
+    Ok(reports)
+}
+
+async fn rewind_tables(
+    conn: &mut MyDbConnection,
+    spec: &RewindSpec,
+    version: i64,
+) -> anyhow::Result<Vec<TableRewindReport>> {
+    let mut reports = vec![];
+
+    // Current tables are rebuilt first since they are rebuilt from the history tables.
+    for table in spec.rebuildable_tables {
+        let queries = RebuildQueries::new(table);
+        let context = || format!("Failed to rebuild {}", table.table_name);
+        // `CREATE TABLE AS` can't take parameters.
+        sql_query(&queries.create_keys_table)
+            .execute(conn)
+            .await
+            .with_context(context)?;
+        sql_query(&queries.collect_keys)
+            .bind::<BigInt, _>(version)
+            .execute(conn)
+            .await
+            .with_context(context)?;
+        let affected_rows = sql_query(&queries.delete_rows)
+            .bind::<BigInt, _>(version)
+            .execute(conn)
+            .await
+            .with_context(context)?;
+        let restored_rows = sql_query(&queries.restore_rows)
+            .bind::<BigInt, _>(version)
+            .execute(conn)
+            .await
+            .with_context(context)?;
+        reports.push(TableRewindReport {
+            table_name: table.table_name.to_string(),
+            action: RewindAction::Rebuilt,
+            affected_rows,
+            restored_rows: Some(restored_rows),
+        });
+    }
+
+    for table in spec.history_tables {
+        let affected_rows = sql_query(format!(
+            "DELETE FROM {} WHERE {} > $1",
+            table.table_name, table.version_column
+        ))
+        .bind::<BigInt, _>(version)
+        .execute(conn)
+        .await
+        .with_context(|| format!("Failed to delete from {}", table.table_name))?;
+        reports.push(TableRewindReport {
+            table_name: table.table_name.to_string(),
+            action: RewindAction::Deleted,
+            affected_rows,
+            restored_rows: None,
+        });
+    }
+
+    for table in spec.unrebuildable_tables {
+        reports.push(TableRewindReport {
+            table_name: table.table_name.to_string(),
+            action: RewindAction::NotRebuildable,
+            affected_rows: count_rows_past_version(
+                conn,
+                table.table_name,
+                table.version_column,
+                version,
+            )
+            .await?,
+            restored_rows: None,
+        });
+    }
+
+    Ok(reports)
+}
+
+async fn count_rows_past_version(
+    conn: &mut MyDbConnection,
+    table_name: &str,
+    version_column: &str,
+    version: i64,
+) -> anyhow::Result<usize> {
+    let count = sql_query(format!(
+        "SELECT COUNT(*) AS count FROM {} WHERE {} > $1",
+        table_name, version_column
+    ))
+    .bind::<BigInt, _>(version)
+    .get_result::<Count>(conn)
+    .await
+    .with_context(|| format!("Failed to count rows of {}", table_name))?
+    .count;
+    Ok(count as usize)
+}
+
+/// The queries rebuilding a current table. All but `create_keys_table` take the rewind version
+/// as `$1`.
+///
+/// The keys of the rows written past the version are collected into a temporary table first,
+/// since the rows have to be deleted before the history rows can be inserted in their place.
+#[derive(Debug, PartialEq)]
+struct RebuildQueries {
+    create_keys_table: String,
+    collect_keys: String,
+    delete_rows: String,
+    restore_rows: String,
+}
+
+impl RebuildQueries {
+    fn new(table: &RebuildableTable) -> Self {
+        let keys_table = format!("rewind_{}_keys", table.table_name);
+        let keys = table.key_columns.join(", ");
+        let history_keys = prefixed(table.key_columns, "h");
+        let join_condition = table
+            .key_columns
+            .iter()
+            .map(|k| format!("h.{} = k.{}", k, k))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let (columns, expressions): (Vec<_>, Vec<_>) = table
+            .columns
+            .iter()
+            .map(|(column, expression)| (*column, format!("h.{}", expression)))
+            .unzip();
+
+        Self {
+            create_keys_table: format!(
+                "CREATE TEMP TABLE {} ON COMMIT DROP AS SELECT {} FROM {} WITH NO DATA",
+                keys_table, keys, table.table_name
+            ),
+            collect_keys: format!(
+                "INSERT INTO {} SELECT {} FROM {} WHERE last_transaction_version > $1",
+                keys_table, keys, table.table_name
+            ),
+            delete_rows: format!(
+                "DELETE FROM {} WHERE last_transaction_version > $1",
+                table.table_name
+            ),
+            restore_rows: format!(
+                "INSERT INTO {} ({}, {}) SELECT DISTINCT ON ({}) {}, {} FROM {} h \
+                 JOIN {} k ON {} WHERE h.transaction_version <= $1 \
+                 ORDER BY {}, h.transaction_version DESC, h.write_set_change_index DESC",
+                table.table_name,
+                keys,
+                columns.join(", "),
+                history_keys,
+                history_keys,
+                expressions.join(", "),
+                table.history_table_name,
+                keys_table,
+                join_condition,
+                history_keys,
+            ),
+        }
+    }
+}
+
+fn prefixed(columns: &[&str], alias: &str) -> String {
+    columns
+        .iter()
+        .map(|c| format!("{}.{}", alias, c))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebuild_queries() {
+        let spec = get_rewind_spec(ProcessorName::StakeProcessor).unwrap();
+        assert_eq!(
+            RebuildQueries::new(&spec.rebuildable_tables[0]),
+            RebuildQueries {
+                create_keys_table: "CREATE TEMP TABLE rewind_current_delegator_balances_keys ON \
+                                COMMIT DROP AS SELECT delegator_address, pool_address, \
+                                pool_type, table_handle FROM current_delegator_balances WITH \
+                                NO DATA"
+                    .to_string(),
+                collect_keys: "INSERT INTO rewind_current_delegator_balances_keys SELECT \
+                           delegator_address, pool_address, pool_type, table_handle FROM \
+                           current_delegator_balances WHERE last_transaction_version > $1"
+                    .to_string(),
+                delete_rows: "DELETE FROM current_delegator_balances WHERE \
+                          last_transaction_version > $1"
+                    .to_string(),
+                restore_rows: "INSERT INTO current_delegator_balances (delegator_address, \
+                           pool_address, pool_type, table_handle, shares, parent_table_handle, \
+                           last_transaction_version) SELECT DISTINCT ON (h.delegator_address, \
+                           h.pool_address, h.pool_type, h.table_handle) h.delegator_address, \
+                           h.pool_address, h.pool_type, h.table_handle, h.shares, \
+                           h.parent_table_handle, h.transaction_version FROM delegator_balances \
+                           h JOIN rewind_current_delegator_balances_keys k ON \
+                           h.delegator_address = k.delegator_address AND h.pool_address = \
+                           k.pool_address AND h.pool_type = k.pool_type AND h.table_handle = \
+                           k.table_handle WHERE h.transaction_version <= $1 ORDER BY \
+                           h.delegator_address, h.pool_address, h.pool_type, h.table_handle, \
+                           h.transaction_version DESC, h.write_set_change_index DESC"
+                    .to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_rewind_specs_only_cover_postgres_processors() {
+        assert!(get_rewind_spec(ProcessorName::ParquetEventsProcessor).is_err());
+        assert!(get_rewind_spec(ProcessorName::MonitoringProcessor).is_err());
+
+        let spec = get_rewind_spec(ProcessorName::TokenV2Processor).unwrap();
+        let table_names = spec.table_names().collect::<Vec<_>>();
+        assert!(table_names.contains(&"token_activities_v2"));
+        assert!(table_names.contains(&"current_token_ownerships_v2"));
+    }
+}
@@ -0,0 +1,327 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The tables each processor writes and how to roll them back.
+//!
+//! Table and column names here are interpolated into SQL, so they must only ever come from these
+//! static specs.
+
+use crate::config::processor_config::ProcessorName;
+
+/// A table with one row per change, e.g. `events`. Rows past the rewind version are deleted.
+#[derive(Debug)]
+pub struct HistoryTable {
+    pub table_name: &'static str,
+    pub version_column: &'static str,
+}
+
+/// A `current_*` table that can be rebuilt from a history table.
+///
+/// For every key whose row was last written past the rewind version, the row is replaced by the
+/// latest history row at or before the rewind version, or removed if there is none.
+#[derive(Debug)]
+pub struct RebuildableTable {
+    pub table_name: &'static str,
+    pub history_table_name: &'static str,
+    pub key_columns: &'static [&'static str],
+    /// `(current column, expression over the history row)` for every non key column.
+    pub columns: &'static [(&'static str, &'static str)],
+}
+
+/// A `current_*` table that can't be rebuilt from history, e.g. because the processor doesn't
+/// write a history table for it. Rows past the rewind version are reported but left untouched.
+#[derive(Debug)]
+pub struct UnrebuildableTable {
+    pub table_name: &'static str,
+    pub version_column: &'static str,
+}
+
+#[derive(Debug)]
+pub struct RewindSpec {
+    pub history_tables: &'static [HistoryTable],
+    pub rebuildable_tables: &'static [RebuildableTable],
+    pub unrebuildable_tables: &'static [UnrebuildableTable],
+}
+
+impl RewindSpec {
+    pub fn table_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.history_tables
+            .iter()
+            .map(|t| t.table_name)
+            .chain(self.rebuildable_tables.iter().map(|t| t.table_name))
+            .chain(self.unrebuildable_tables.iter().map(|t| t.table_name))
+    }
+}
+
+const fn history(table_name: &'static str) -> HistoryTable {
+    HistoryTable {
+        table_name,
+        version_column: "transaction_version",
+    }
+}
+
+const fn unrebuildable(table_name: &'static str) -> UnrebuildableTable {
+    UnrebuildableTable {
+        table_name,
+        version_column: "last_transaction_version",
+    }
+}
+
+const ACCOUNT_RESTORATION: RewindSpec = RewindSpec {
+    history_tables: &[],
+    rebuildable_tables: &[],
+    unrebuildable_tables: &[
+        unrebuildable("auth_key_account_addresses"),
+        unrebuildable("auth_key_multikey_layout"),
+        unrebuildable("public_key_auth_keys"),
+    ],
+};
+
+const ACCOUNT_TRANSACTIONS: RewindSpec = RewindSpec {
+    history_tables: &[history("account_transactions")],
+    rebuildable_tables: &[],
+    unrebuildable_tables: &[],
+};
+
+const ANS: RewindSpec = RewindSpec {
+    history_tables: &[
+        history("ans_lookup"),
+        history("ans_primary_name"),
+        history("ans_lookup_v2"),
+        history("ans_primary_name_v2"),
+    ],
+    rebuildable_tables: &[RebuildableTable {
+        table_name: "current_ans_primary_name_v2",
+        history_table_name: "ans_primary_name_v2",
+        key_columns: &["registered_address", "token_standard"],
+        columns: &[
+            ("domain", "domain"),
+            ("subdomain", "subdomain"),
+            ("token_name", "token_name"),
+            ("is_deleted", "is_deleted"),
+            ("last_transaction_version", "transaction_version"),
+        ],
+    }],
+    // The lookup history doesn't carry the expiration timestamp for every row, and the v1 tables
+    // are keyed differently from their history.
+    unrebuildable_tables: &[
+        unrebuildable("current_ans_lookup"),
+        unrebuildable("current_ans_primary_name"),
+        unrebuildable("current_ans_lookup_v2"),
+    ],
+};
+
+const DEFAULT: RewindSpec = RewindSpec {
+    history_tables: &[
+        HistoryTable {
+            table_name: "block_metadata_transactions",
+            version_column: "version",
+        },
+        history("table_items"),
+    ],
+    rebuildable_tables: &[],
+    unrebuildable_tables: &[unrebuildable("current_table_items")],
+};
+
+const EVENTS: RewindSpec = RewindSpec {
+    history_tables: &[history("events")],
+    rebuildable_tables: &[],
+    unrebuildable_tables: &[],
+};
+
+const FUNGIBLE_ASSET: RewindSpec = RewindSpec {
+    history_tables: &[
+        history("fungible_asset_activities"),
+        history("fungible_asset_balances"),
+        history("coin_supply"),
+    ],
+    rebuildable_tables: &[],
+    unrebuildable_tables: &[
+        unrebuildable("fungible_asset_metadata"),
+        unrebuildable("current_fungible_asset_balances"),
+        unrebuildable("fungible_asset_to_coin_mappings"),
+    ],
+};
+
+// The activities only carry the event, not the state of the listing or offer after it.
+const NFT_MARKETPLACE: RewindSpec = RewindSpec {
+    history_tables: &[history("nft_marketplace_activities")],
+    rebuildable_tables: &[],
+    unrebuildable_tables: &[
+        unrebuildable("current_nft_marketplace_listings"),
+        unrebuildable("current_nft_marketplace_token_offers"),
+        unrebuildable("current_nft_marketplace_collection_offers"),
+    ],
+};
+
+const OBJECTS: RewindSpec = RewindSpec {
+    history_tables: &[history("objects")],
+    rebuildable_tables: &[RebuildableTable {
+        table_name: "current_objects",
+        history_table_name: "objects",
+        key_columns: &["object_address"],
+        columns: &[
+            ("owner_address", "owner_address"),
+            ("state_key_hash", "state_key_hash"),
+            ("allow_ungated_transfer", "allow_ungated_transfer"),
+            ("last_guid_creation_num", "guid_creation_num"),
+            ("last_transaction_version", "transaction_version"),
+            ("is_deleted", "is_deleted"),
+            ("untransferrable", "untransferrable"),
+        ],
+    }],
+    // Depends on the owners of other objects, not just the history of the object
+    unrebuildable_tables: &[unrebuildable("current_object_ultimate_owners")],
+};
+
+const STAKE: RewindSpec = RewindSpec {
+    history_tables: &[
+        history("proposal_votes"),
+        history("delegated_staking_activities"),
+        history("delegator_balances"),
+        history("delegated_staking_pool_balances"),
+        // One row per pool, removed if the pool was first seen past the rewind version.
+        HistoryTable {
+            table_name: "delegated_staking_pools",
+            version_column: "first_transaction_version",
+        },
+    ],
+    rebuildable_tables: &[RebuildableTable {
+        table_name: "current_delegator_balances",
+        history_table_name: "delegator_balances",
+        key_columns: &[
+            "delegator_address",
+            "pool_address",
+            "pool_type",
+            "table_handle",
+        ],
+        columns: &[
+            ("shares", "shares"),
+            ("parent_table_handle", "parent_table_handle"),
+            ("last_transaction_version", "transaction_version"),
+        ],
+    }],
+    unrebuildable_tables: &[
+        unrebuildable("current_staking_pool_voter"),
+        unrebuildable("current_delegated_staking_pool_balances"),
+        unrebuildable("current_delegated_voter"),
+    ],
+};
+
+const TOKEN_V2: RewindSpec = RewindSpec {
+    history_tables: &[
+        history("collections_v2"),
+        history("token_datas_v2"),
+        history("token_ownerships_v2"),
+        history("token_activities_v2"),
+        history("token_property_mutations"),
+        history("token_transferability_v2"),
+        history("token_v1_to_v2_mappings"),
+    ],
+    rebuildable_tables: &[
+        RebuildableTable {
+            table_name: "current_collections_v2",
+            history_table_name: "collections_v2",
+            key_columns: &["collection_id"],
+            columns: &[
+                ("creator_address", "creator_address"),
+                ("collection_name", "collection_name"),
+                ("description", "description"),
+                ("uri", "uri"),
+                ("current_supply", "current_supply"),
+                ("max_supply", "max_supply"),
+                ("total_minted_v2", "total_minted_v2"),
+                ("mutable_description", "mutable_description"),
+                ("mutable_uri", "mutable_uri"),
+                ("table_handle_v1", "table_handle_v1"),
+                ("token_standard", "token_standard"),
+                ("last_transaction_version", "transaction_version"),
+                ("last_transaction_timestamp", "transaction_timestamp"),
+                ("collection_properties", "collection_properties"),
+            ],
+        },
+        RebuildableTable {
+            table_name: "current_token_datas_v2",
+            history_table_name: "token_datas_v2",
+            key_columns: &["token_data_id"],
+            columns: &[
+                ("collection_id", "collection_id"),
+                ("token_name", "token_name"),
+                ("maximum", "maximum"),
+                ("supply", "supply"),
+                ("largest_property_version_v1", "largest_property_version_v1"),
+                ("token_uri", "token_uri"),
+                ("description", "description"),
+                ("token_properties", "token_properties"),
+                ("token_standard", "token_standard"),
+                ("is_fungible_v2", "is_fungible_v2"),
+                ("last_transaction_version", "transaction_version"),
+                ("last_transaction_timestamp", "transaction_timestamp"),
+                ("decimals", "decimals"),
+                ("is_deleted_v2", "is_deleted_v2"),
+            ],
+        },
+        RebuildableTable {
+            table_name: "current_token_ownerships_v2",
+            history_table_name: "token_ownerships_v2",
+            key_columns: &[
+                "token_data_id",
+                "property_version_v1",
+                "owner_address",
+                "storage_id",
+            ],
+            columns: &[
+                ("amount", "amount"),
+                ("table_type_v1", "table_type_v1"),
+                ("token_properties_mutated_v1", "token_properties_mutated_v1"),
+                ("is_soulbound_v2", "is_soulbound_v2"),
+                ("token_standard", "token_standard"),
+                ("is_fungible_v2", "is_fungible_v2"),
+                ("last_transaction_version", "transaction_version"),
+                ("last_transaction_timestamp", "transaction_timestamp"),
+                ("non_transferrable_by_owner", "non_transferrable_by_owner"),
+            ],
+        },
+    ],
+    unrebuildable_tables: &[
+        unrebuildable("current_token_v2_metadata"),
+        unrebuildable("current_token_royalty_v1"),
+        unrebuildable("current_token_royalty"),
+        unrebuildable("current_token_pending_claims"),
+        // soulbound_at_mint is only in the history row of the mint
+        unrebuildable("current_token_transferability_v2"),
+        unrebuildable("current_collection_stats"),
+        unrebuildable("current_collection_owners"),
+        unrebuildable("collection_hourly_transfers"),
+    ],
+};
+
+const USER_TRANSACTION: RewindSpec = RewindSpec {
+    history_tables: &[
+        HistoryTable {
+            table_name: "user_transactions",
+            version_column: "version",
+        },
+        history("signatures"),
+    ],
+    rebuildable_tables: &[],
+    unrebuildable_tables: &[],
+};
+
+/// Only the postgres processors can be rewound. Parquet output is immutable once uploaded.
+pub fn get_rewind_spec(processor_name: ProcessorName) -> anyhow::Result<&'static RewindSpec> {
+    match processor_name {
+        ProcessorName::AccountRestorationProcessor => Ok(&ACCOUNT_RESTORATION),
+        ProcessorName::AccountTransactionsProcessor => Ok(&ACCOUNT_TRANSACTIONS),
+        ProcessorName::AnsProcessor => Ok(&ANS),
+        ProcessorName::DefaultProcessor => Ok(&DEFAULT),
+        ProcessorName::EventsProcessor => Ok(&EVENTS),
+        ProcessorName::FungibleAssetProcessor => Ok(&FUNGIBLE_ASSET),
+        ProcessorName::NftMarketplaceProcessor => Ok(&NFT_MARKETPLACE),
+        ProcessorName::ObjectsProcessor => Ok(&OBJECTS),
+        ProcessorName::StakeProcessor => Ok(&STAKE),
+        ProcessorName::TokenV2Processor => Ok(&TOKEN_V2),
+        ProcessorName::UserTransactionProcessor => Ok(&USER_TRANSACTION),
+        _ => anyhow::bail!("Rewinding {} is not supported", processor_name),
+    }
+}
@@ -0,0 +1,391 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Materializes who held the tokens of a collection, or the balances of a fungible asset, at a
+//! version, e.g. for airdrops. `current_*` tables only have the latest state, so the snapshot is
+//! computed from the history tables: the latest row of every holding at or before the version,
+//! keeping those with a non-zero amount.
+//!
+//! - Token holdings come from `token_ownerships_v2`, restricted to the tokens of the collection
+//!   in `current_token_datas_v2`.
+//! - Fungible asset balances come from `fungible_asset_balances`.
+//!
+//! Both use an index on the history table leading with the token or asset, so only the history
+//! of the target is read. A snapshot is only as complete as the history table, i.e. it needs the
+//! processor to have indexed the target from its creation up to the version.
+
+use crate::utils::database::ArcDbPool;
+use anyhow::Context;
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Bool, Text},
+    QueryableByName,
+};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use parquet::{file::writer::SerializedFileWriter, record::RecordWriter};
+use parquet_derive::ParquetRecordWriter;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotTarget {
+    /// Holders of the tokens of the collection with this collection_id, v1 or v2.
+    Collection(String),
+    /// Balances of the fungible asset or coin with this asset_type.
+    FungibleAsset(String),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotOutput {
+    /// `token_ownership_snapshots` or `fungible_asset_balance_snapshots`. A previous snapshot of
+    /// the same target and version is replaced.
+    Table,
+    ParquetFile(PathBuf),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotConfig {
+    pub target: SnapshotTarget,
+    /// Holdings as of the end of this version.
+    pub version: i64,
+    pub output: SnapshotOutput,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SnapshotReport {
+    pub target: SnapshotTarget,
+    pub version: i64,
+    pub output: SnapshotOutput,
+    /// Holdings with a non-zero amount.
+    pub rows: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, ParquetRecordWriter, QueryableByName, Serialize)]
+pub struct TokenOwnershipSnapshot {
+    #[diesel(sql_type = Text)]
+    pub collection_id: String,
+    #[diesel(sql_type = BigInt)]
+    pub snapshot_version: i64,
+    #[diesel(sql_type = Text)]
+    pub token_data_id: String,
+    #[diesel(sql_type = Text)]
+    pub property_version_v1: String,
+    #[diesel(sql_type = Text)]
+    pub owner_address: String,
+    #[diesel(sql_type = Text)]
+    pub storage_id: String,
+    /// String representation of the numeric amount
+    #[diesel(sql_type = Text)]
+    pub amount: String,
+    #[diesel(sql_type = Text)]
+    pub token_standard: String,
+    #[diesel(sql_type = BigInt)]
+    pub last_transaction_version: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, ParquetRecordWriter, QueryableByName, Serialize)]
+pub struct FungibleAssetBalanceSnapshot {
+    #[diesel(sql_type = Text)]
+    pub asset_type: String,
+    #[diesel(sql_type = BigInt)]
+    pub snapshot_version: i64,
+    #[diesel(sql_type = Text)]
+    pub storage_id: String,
+    #[diesel(sql_type = Text)]
+    pub owner_address: String,
+    #[diesel(sql_type = Bool)]
+    pub is_primary: bool,
+    #[diesel(sql_type = Bool)]
+    pub is_frozen: bool,
+    /// String representation of the numeric amount
+    #[diesel(sql_type = Text)]
+    pub amount: String,
+    #[diesel(sql_type = Text)]
+    pub token_standard: String,
+    #[diesel(sql_type = BigInt)]
+    pub last_transaction_version: i64,
+}
+
+/// How to compute the snapshot of a kind of target from its history table
+struct SnapshotSpec {
+    snapshot_table_name: &'static str,
+    /// Column of the snapshot table holding the collection_id or asset_type
+    target_column: &'static str,
+    /// Columns of a holding, with whether they are numeric and read as text for parquet
+    columns: &'static [(&'static str, bool)],
+    /// The latest history row of every holding, with the target as `$1` and the version as `$2`
+    latest_rows: &'static str,
+}
+
+const TOKEN_OWNERSHIP_SNAPSHOT: SnapshotSpec = SnapshotSpec {
+    snapshot_table_name: "token_ownership_snapshots",
+    target_column: "collection_id",
+    columns: &[
+        ("token_data_id", false),
+        ("property_version_v1", true),
+        ("owner_address", false),
+        ("storage_id", false),
+        ("amount", true),
+        ("token_standard", false),
+        ("last_transaction_version", false),
+    ],
+    latest_rows: "SELECT DISTINCT ON (o.token_data_id, o.property_version_v1, o.owner_address, \
+                  o.storage_id) o.token_data_id, o.property_version_v1, o.owner_address, \
+                  o.storage_id, o.amount, o.token_standard, o.transaction_version AS \
+                  last_transaction_version FROM token_ownerships_v2 o JOIN \
+                  current_token_datas_v2 d ON d.token_data_id = o.token_data_id WHERE \
+                  d.collection_id = $1 AND o.transaction_version <= $2 ORDER BY \
+                  o.token_data_id, o.property_version_v1, o.owner_address, o.storage_id, \
+                  o.transaction_version DESC, o.write_set_change_index DESC",
+};
+
+const FUNGIBLE_ASSET_BALANCE_SNAPSHOT: SnapshotSpec = SnapshotSpec {
+    snapshot_table_name: "fungible_asset_balance_snapshots",
+    target_column: "asset_type",
+    columns: &[
+        ("storage_id", false),
+        ("owner_address", false),
+        ("is_primary", false),
+        ("is_frozen", false),
+        ("amount", true),
+        ("token_standard", false),
+        ("last_transaction_version", false),
+    ],
+    latest_rows: "SELECT DISTINCT ON (b.storage_id) b.storage_id, b.owner_address, \
+                  b.is_primary, b.is_frozen, b.amount, b.token_standard, b.transaction_version \
+                  AS last_transaction_version FROM fungible_asset_balances b WHERE b.asset_type \
+                  = $1 AND b.transaction_version <= $2 ORDER BY b.storage_id, \
+                  b.transaction_version DESC, b.write_set_change_index DESC",
+};
+
+impl SnapshotTarget {
+    fn spec(&self) -> &'static SnapshotSpec {
+        match self {
+            SnapshotTarget::Collection(_) => &TOKEN_OWNERSHIP_SNAPSHOT,
+            SnapshotTarget::FungibleAsset(_) => &FUNGIBLE_ASSET_BALANCE_SNAPSHOT,
+        }
+    }
+
+    fn id(&self) -> &str {
+        match self {
+            SnapshotTarget::Collection(id) | SnapshotTarget::FungibleAsset(id) => id,
+        }
+    }
+}
+
+pub async fn run_snapshot(
+    config: &SnapshotConfig,
+    db_pool: ArcDbPool,
+) -> anyhow::Result<SnapshotReport> {
+    anyhow::ensure!(config.version >= 0, "version must not be negative");
+    let queries = SnapshotQueries::new(config.target.spec());
+
+    let mut conn = db_pool
+        .get()
+        .await
+        .context("Failed to get connection from pool")?;
+
+    let rows = match &config.output {
+        SnapshotOutput::Table => {
+            let config = config.clone();
+            conn.transaction::<_, anyhow::Error, _>(|conn| {
+                async move {
+                    let target = config.target.id();
+                    let table_name = config.target.spec().snapshot_table_name;
+                    sql_query(&queries.delete_snapshot)
+                        .bind::<Text, _>(target)
+                        .bind::<BigInt, _>(config.version)
+                        .execute(conn)
+                        .await
+                        .with_context(|| format!("Failed to delete from {}", table_name))?;
+                    sql_query(&queries.insert_snapshot)
+                        .bind::<Text, _>(target)
+                        .bind::<BigInt, _>(config.version)
+                        .execute(conn)
+                        .await
+                        .with_context(|| format!("Failed to insert into {}", table_name))
+                }
+                .scope_boxed()
+            })
+            .await?
+        },
+        SnapshotOutput::ParquetFile(path) => {
+            let query = sql_query(&queries.select_holdings)
+                .bind::<Text, _>(config.target.id())
+                .bind::<BigInt, _>(config.version);
+            match &config.target {
+                SnapshotTarget::Collection(_) => {
+                    let holdings = query
+                        .load::<TokenOwnershipSnapshot>(&mut conn)
+                        .await
+                        .context("Failed to load token holdings")?;
+                    write_parquet_file(path, &holdings)?
+                },
+                SnapshotTarget::FungibleAsset(_) => {
+                    let holdings = query
+                        .load::<FungibleAssetBalanceSnapshot>(&mut conn)
+                        .await
+                        .context("Failed to load fungible asset balances")?;
+                    write_parquet_file(path, &holdings)?
+                },
+            }
+        },
+    };
+
+    info!(
+        snapshot_target = ?config.target,
+        version = config.version,
+        output = ?config.output,
+        rows = rows,
+        "[Snapshot] Finished snapshot"
+    );
+    Ok(SnapshotReport {
+        target: config.target.clone(),
+        version: config.version,
+        output: config.output.clone(),
+        rows,
+    })
+}
+
+fn write_parquet_file<T>(path: &Path, holdings: &[T]) -> anyhow::Result<usize>
+where
+    for<'a> &'a [T]: RecordWriter<T>,
+{
+    let schema = holdings.schema().context("Failed to get parquet schema")?;
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = SerializedFileWriter::new(file, schema, Default::default())
+        .context("Failed to create parquet writer")?;
+    let mut row_group_writer = writer.next_row_group().context("Failed to get row group")?;
+    holdings
+        .write_to_row_group(&mut row_group_writer)
+        .context("Failed to write to row group")?;
+    row_group_writer
+        .close()
+        .context("Failed to close row group")?;
+    writer.close().context("Failed to close parquet writer")?;
+    Ok(holdings.len())
+}
+
+/// The queries of a snapshot. All take the target as `$1` and the version as `$2`.
+#[derive(Debug, PartialEq)]
+struct SnapshotQueries {
+    /// The holdings with the target and version, numeric columns as text
+    select_holdings: String,
+    delete_snapshot: String,
+    insert_snapshot: String,
+}
+
+impl SnapshotQueries {
+    fn new(spec: &SnapshotSpec) -> Self {
+        let columns = spec
+            .columns
+            .iter()
+            .map(|(column, _)| *column)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let text_columns = spec
+            .columns
+            .iter()
+            .map(|(column, is_numeric)| {
+                if *is_numeric {
+                    format!("h.{}::text AS {}", column, column)
+                } else {
+                    format!("h.{}", column)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        // Ownership rows of v1 tokens in an unknown table have no owner
+        let holdings = format!(
+            "FROM ({}) h WHERE h.amount > 0 AND h.owner_address IS NOT NULL",
+            spec.latest_rows
+        );
+
+        Self {
+            select_holdings: format!(
+                "SELECT $1 AS {}, $2 AS snapshot_version, {} {}",
+                spec.target_column, text_columns, holdings
+            ),
+            delete_snapshot: format!(
+                "DELETE FROM {} WHERE {} = $1 AND snapshot_version = $2",
+                spec.snapshot_table_name, spec.target_column
+            ),
+            insert_snapshot: format!(
+                "INSERT INTO {} ({}, snapshot_version, {}) SELECT $1, $2, {} {}",
+                spec.snapshot_table_name,
+                spec.target_column,
+                columns,
+                prefixed(spec.columns, "h"),
+                holdings
+            ),
+        }
+    }
+}
+
+fn prefixed(columns: &[(&str, bool)], alias: &str) -> String {
+    columns
+        .iter()
+        .map(|(c, _)| format!("{}.{}", alias, c))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_queries() {
+        let queries = SnapshotQueries::new(&FUNGIBLE_ASSET_BALANCE_SNAPSHOT);
+        let latest_rows = FUNGIBLE_ASSET_BALANCE_SNAPSHOT.latest_rows;
+        assert_eq!(queries, SnapshotQueries {
+            select_holdings: format!(
+                "SELECT $1 AS asset_type, $2 AS snapshot_version, h.storage_id, \
+                 h.owner_address, h.is_primary, h.is_frozen, h.amount::text AS amount, \
+                 h.token_standard, h.last_transaction_version FROM ({}) h WHERE h.amount > 0 \
+                 AND h.owner_address IS NOT NULL",
+                latest_rows
+            ),
+            delete_snapshot: "DELETE FROM fungible_asset_balance_snapshots WHERE asset_type = \
+                              $1 AND snapshot_version = $2"
+                .to_string(),
+            insert_snapshot: format!(
+                "INSERT INTO fungible_asset_balance_snapshots (asset_type, snapshot_version, \
+                 storage_id, owner_address, is_primary, is_frozen, amount, token_standard, \
+                 last_transaction_version) SELECT $1, $2, h.storage_id, h.owner_address, \
+                 h.is_primary, h.is_frozen, h.amount, h.token_standard, \
+                 h.last_transaction_version FROM ({}) h WHERE h.amount > 0 AND \
+                 h.owner_address IS NOT NULL",
+                latest_rows
+            ),
+        });
+    }
+
+    #[test]
+    fn test_write_parquet_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.parquet");
+        let holdings = vec![TokenOwnershipSnapshot {
+            collection_id: "0xc".to_string(),
+            snapshot_version: 10,
+            token_data_id: "0x1".to_string(),
+            property_version_v1: "0".to_string(),
+            owner_address: "0xa".to_string(),
+            storage_id: "0x1".to_string(),
+            amount: "1".to_string(),
+            token_standard: "v2".to_string(),
+            last_transaction_version: 7,
+        }];
+
+        assert_eq!(write_parquet_file(&path, &holdings).unwrap(), 1);
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+    }
+}
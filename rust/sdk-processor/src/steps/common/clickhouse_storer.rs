@@ -0,0 +1,142 @@
+use crate::utils::clickhouse::{models::ClickhouseModel, ClickhouseClient};
+use anyhow::Result;
+use aptos_indexer_processor_sdk::{
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use tracing::debug;
+
+/// Rows of one or more ClickHouse tables. A `Vec` of a model is one table, and a tuple writes
+/// each of its tables in order.
+#[async_trait]
+pub trait ClickhouseRows: Send + 'static {
+    fn table_names() -> Vec<&'static str>;
+
+    async fn insert(self, client: &ClickhouseClient, insert_chunk_size: usize) -> Result<()>;
+}
+
+#[async_trait]
+impl<T> ClickhouseRows for Vec<T>
+where
+    T: ClickhouseModel + Send + Sync + 'static,
+{
+    fn table_names() -> Vec<&'static str> {
+        vec![T::TABLE_NAME]
+    }
+
+    async fn insert(self, client: &ClickhouseClient, insert_chunk_size: usize) -> Result<()> {
+        let table_name = format!("{}.{}", client.database, T::TABLE_NAME);
+        for chunk in self.chunks(insert_chunk_size.max(1)) {
+            let rows = chunk
+                .iter()
+                .map(|item| item.to_clickhouse_row())
+                .collect::<Result<Vec<_>>>()?;
+            client.insert_rows(&table_name, &rows).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<A, B> ClickhouseRows for (A, B)
+where
+    A: ClickhouseRows,
+    B: ClickhouseRows,
+{
+    fn table_names() -> Vec<&'static str> {
+        let mut table_names = A::table_names();
+        table_names.extend(B::table_names());
+        table_names
+    }
+
+    async fn insert(self, client: &ClickhouseClient, insert_chunk_size: usize) -> Result<()> {
+        self.0.insert(client, insert_chunk_size).await?;
+        self.1.insert(client, insert_chunk_size).await
+    }
+}
+
+/// Writes tables of an extractor's output to ClickHouse.
+///
+/// `select` picks the rows to store out of the extractor output, so the same extractors used for
+/// postgres can be connected to this step directly.
+pub struct ClickhouseStorer<Input, R>
+where
+    Self: Sized + Send + 'static,
+    Input: Send + 'static,
+    R: ClickhouseRows,
+{
+    client: ClickhouseClient,
+    insert_chunk_size: usize,
+    select: fn(Input) -> R,
+}
+
+impl<Input, R> ClickhouseStorer<Input, R>
+where
+    Input: Send + 'static,
+    R: ClickhouseRows,
+{
+    pub fn new(client: ClickhouseClient, insert_chunk_size: usize, select: fn(Input) -> R) -> Self {
+        Self {
+            client,
+            insert_chunk_size,
+            select,
+        }
+    }
+}
+
+#[async_trait]
+impl<Input, R> Processable for ClickhouseStorer<Input, R>
+where
+    Input: Send + 'static,
+    R: ClickhouseRows,
+{
+    type Input = Input;
+    type Output = ();
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        input: TransactionContext<Input>,
+    ) -> Result<Option<TransactionContext<()>>, ProcessorError> {
+        let rows = (self.select)(input.data);
+        let table_names = R::table_names().join(", ");
+        match rows.insert(&self.client, self.insert_chunk_size).await {
+            Ok(_) => {
+                debug!(
+                    "{} version [{}, {}] stored successfully",
+                    table_names, input.metadata.start_version, input.metadata.end_version
+                );
+                Ok(Some(TransactionContext {
+                    data: (),
+                    metadata: input.metadata,
+                }))
+            },
+            Err(e) => Err(ProcessorError::DBStoreError {
+                message: format!(
+                    "Failed to store {} versions {} to {}: {:?}",
+                    table_names, input.metadata.start_version, input.metadata.end_version, e,
+                ),
+                query: None,
+            }),
+        }
+    }
+}
+
+impl<Input, R> AsyncStep for ClickhouseStorer<Input, R>
+where
+    Input: Send + 'static,
+    R: ClickhouseRows,
+{
+}
+
+impl<Input, R> NamedStep for ClickhouseStorer<Input, R>
+where
+    Input: Send + 'static,
+    R: ClickhouseRows,
+{
+    fn name(&self) -> String {
+        format!("ClickhouseStorer<{}>", R::table_names().join(", "))
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    config::db_config::{FileExportConfig, FileExportFormat},
+    parquet_processors::{ParquetTypeStructs, ParquetTypeTrait},
+    steps::common::gcs_uploader::Uploadable,
+};
+use anyhow::Context;
+use aptos_indexer_processor_sdk::{
+    types::transaction_context::TransactionMetadata, utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use tracing::{debug, error};
+
+/// Writes the same structs the parquet processors upload to GCS as JSON lines or CSV files on
+/// local disk. Every flush of the buffer becomes a new file named after the version range it
+/// covers, so files are never appended to and a crash can at most leave a `.tmp` file behind.
+/// Checkpoints are still saved to Postgres by the parquet processors' status saver.
+pub struct LocalFileExporter {
+    output_dir: PathBuf,
+    format: FileExportFormat,
+    processor_name: String,
+}
+
+impl LocalFileExporter {
+    pub fn new(config: &FileExportConfig, processor_name: String) -> Self {
+        Self {
+            output_dir: config.output_dir.clone(),
+            format: config.format,
+            processor_name,
+        }
+    }
+
+    fn file_path(&self, table_name: &str, metadata: &TransactionMetadata) -> PathBuf {
+        self.output_dir
+            .join(&self.processor_name)
+            .join(table_name)
+            .join(format!(
+                "{}_{}.{}",
+                metadata.start_version,
+                metadata.end_version,
+                self.format.extension()
+            ))
+    }
+
+    pub async fn write_rows(
+        &self,
+        table_name: &str,
+        rows: &[serde_json::Value],
+        metadata: &TransactionMetadata,
+    ) -> anyhow::Result<()> {
+        if rows.is_empty() {
+            debug!("Buffer is empty, skipping export.");
+            return Ok(());
+        }
+
+        let contents = match self.format {
+            FileExportFormat::JsonLines => to_json_lines(rows)?,
+            FileExportFormat::Csv => to_csv(rows),
+        };
+
+        let path = self.file_path(table_name, metadata);
+        write_atomically(&path, contents.as_bytes()).await?;
+
+        debug!(
+            "Exported {} rows for table: {} to {}",
+            rows.len(),
+            table_name,
+            path.display()
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Uploadable for LocalFileExporter {
+    async fn upload_buffer(
+        &mut self,
+        buffer: ParquetTypeStructs,
+        metadata: &TransactionMetadata,
+    ) -> anyhow::Result<(), ProcessorError> {
+        let table_name = buffer.parquet_type().to_string();
+
+        let result = match buffer.to_json_rows() {
+            Ok(rows) => self.write_rows(&table_name, &rows, metadata).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!("Failed to export buffer: {}", e);
+            return Err(ProcessorError::ProcessError {
+                message: format!("Failed to export buffer: {}", e),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Writes to a temporary file first and renames it so readers never see a partial file.
+async fn write_atomically(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    let dir = path
+        .parent()
+        .context("Export path has no parent directory")?;
+    tokio::fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+
+    let tmp_path = path.with_extension("tmp");
+    tokio::fs::write(&tmp_path, contents)
+        .await
+        .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+    tokio::fs::rename(&tmp_path, path).await.with_context(|| {
+        format!(
+            "Failed to rename {} to {}",
+            tmp_path.display(),
+            path.display()
+        )
+    })
+}
+
+fn to_json_lines(rows: &[serde_json::Value]) -> anyhow::Result<String> {
+    let mut contents = String::new();
+    for row in rows {
+        contents.push_str(&serde_json::to_string(row).context("Failed to serialize row")?);
+        contents.push('\n');
+    }
+    Ok(contents)
+}
+
+/// The header is taken from the first row. All rows of a table are serialized from the same
+/// struct, so they share the same fields in the same order.
+fn to_csv(rows: &[serde_json::Value]) -> String {
+    let header: Vec<&String> = rows
+        .first()
+        .and_then(|row| row.as_object())
+        .map(|row| row.keys().collect())
+        .unwrap_or_default();
+
+    let mut contents = header
+        .iter()
+        .map(|column| escape_csv_field(column))
+        .collect::<Vec<_>>()
+        .join(",");
+    contents.push('\n');
+
+    for row in rows {
+        let line = header
+            .iter()
+            .map(|column| {
+                let field = match row.get(column.as_str()) {
+                    None | Some(serde_json::Value::Null) => String::new(),
+                    Some(serde_json::Value::String(s)) => s.clone(),
+                    Some(value) => value.to_string(),
+                };
+                escape_csv_field(&field)
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        contents.push_str(&line);
+        contents.push('\n');
+    }
+    contents
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_to_csv() {
+        let rows = vec![
+            json!({"txn_version": 1, "data": "{\"a\":1}", "owner": null}),
+            json!({"txn_version": 2, "data": "plain, with comma", "owner": "0x1"}),
+        ];
+        assert_eq!(
+            to_csv(&rows),
+            "txn_version,data,owner\n1,\"{\"\"a\"\":1}\",\n2,\"plain, with comma\",0x1\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_rows_partitions_by_table_and_version_range() -> anyhow::Result<()> {
+        let output_dir = tempfile::tempdir()?;
+        let exporter = LocalFileExporter::new(
+            &FileExportConfig {
+                output_dir: output_dir.path().to_path_buf(),
+                format: FileExportFormat::JsonLines,
+            },
+            "parquet_events_processor".to_string(),
+        );
+        let metadata = TransactionMetadata {
+            start_version: 100,
+            end_version: 199,
+            ..TransactionMetadata::default()
+        };
+
+        exporter
+            .write_rows(
+                "events",
+                &[json!({"txn_version": 100}), json!({"txn_version": 150})],
+                &metadata,
+            )
+            .await?;
+
+        let path = output_dir
+            .path()
+            .join("parquet_events_processor/events/100_199.jsonl");
+        assert_eq!(
+            std::fs::read_to_string(path)?,
+            "{\"txn_version\":100}\n{\"txn_version\":150}\n"
+        );
+        Ok(())
+    }
+}
@@ -1,6 +1,11 @@
-use crate::parquet_processors::{ParquetTypeEnum, ParquetTypeStructs, ParquetTypeTrait};
+use crate::{
+    parquet_processors::{ParquetTypeEnum, ParquetTypeStructs, ParquetTypeTrait},
+    steps::common::file_exporter::LocalFileExporter,
+};
 use anyhow::Context;
-use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use aptos_indexer_processor_sdk::{
+    types::transaction_context::TransactionMetadata, utils::errors::ProcessorError,
+};
 use async_trait::async_trait;
 use google_cloud_storage::client::Client as GCSClient;
 use parquet::{
@@ -18,6 +23,7 @@ use tracing::{debug, error};
 pub struct GCSUploader {
     gcs_client: Arc<GCSClient>,
     parquet_type_to_schemas: HashMap<ParquetTypeEnum, Arc<Type>>,
+    parquet_type_to_writer_properties: HashMap<ParquetTypeEnum, Arc<WriterProperties>>,
     parquet_type_to_writer: HashMap<ParquetTypeEnum, SerializedFileWriter<Vec<u8>>>,
     pub bucket_name: String,
     pub bucket_root: String,
@@ -26,17 +32,52 @@ pub struct GCSUploader {
 
 #[async_trait]
 pub trait Uploadable {
+    /// `metadata` covers the versions of every batch appended to the buffer.
     async fn upload_buffer(
         &mut self,
         buffer: ParquetTypeStructs,
+        metadata: &TransactionMetadata,
     ) -> anyhow::Result<(), ProcessorError>;
 }
 
+/// The destinations a `ParquetBufferStep` can flush its buffers to.
+pub enum BufferUploader {
+    Gcs(GCSUploader),
+    LocalFile(LocalFileExporter),
+}
+
+#[async_trait]
+impl Uploadable for BufferUploader {
+    async fn upload_buffer(
+        &mut self,
+        buffer: ParquetTypeStructs,
+        metadata: &TransactionMetadata,
+    ) -> anyhow::Result<(), ProcessorError> {
+        match self {
+            BufferUploader::Gcs(uploader) => uploader.upload_buffer(buffer, metadata).await,
+            BufferUploader::LocalFile(exporter) => exporter.upload_buffer(buffer, metadata).await,
+        }
+    }
+}
+
+impl From<GCSUploader> for BufferUploader {
+    fn from(uploader: GCSUploader) -> Self {
+        BufferUploader::Gcs(uploader)
+    }
+}
+
+impl From<LocalFileExporter> for BufferUploader {
+    fn from(exporter: LocalFileExporter) -> Self {
+        BufferUploader::LocalFile(exporter)
+    }
+}
+
 #[async_trait]
 impl Uploadable for GCSUploader {
     async fn upload_buffer(
         &mut self,
         buffer: ParquetTypeStructs,
+        _metadata: &TransactionMetadata,
     ) -> anyhow::Result<(), ProcessorError> {
         let parquet_type = buffer.parquet_type();
         let table_name = parquet_type.to_string();
@@ -52,19 +93,19 @@ impl Uploadable for GCSUploader {
     }
 }
 
-pub fn create_new_writer(schema: Arc<Type>) -> anyhow::Result<SerializedFileWriter<Vec<u8>>> {
-    let props = WriterProperties::builder()
-        .set_compression(parquet::basic::Compression::LZ4)
-        .build();
-    let props_arc = Arc::new(props);
-
-    SerializedFileWriter::new(Vec::new(), schema, props_arc).context("Failed to create new writer")
+pub fn create_new_writer(
+    schema: Arc<Type>,
+    writer_properties: Arc<WriterProperties>,
+) -> anyhow::Result<SerializedFileWriter<Vec<u8>>> {
+    SerializedFileWriter::new(Vec::new(), schema, writer_properties)
+        .context("Failed to create new writer")
 }
 
 impl GCSUploader {
     pub fn new(
         gcs_client: Arc<GCSClient>,
         parquet_type_to_schemas: HashMap<ParquetTypeEnum, Arc<Type>>,
+        parquet_type_to_writer_properties: HashMap<ParquetTypeEnum, Arc<WriterProperties>>,
         parquet_type_to_writer: HashMap<ParquetTypeEnum, SerializedFileWriter<Vec<u8>>>,
         bucket_name: String,
         bucket_root: String,
@@ -73,6 +114,7 @@ impl GCSUploader {
         Ok(Self {
             gcs_client,
             parquet_type_to_schemas,
+            parquet_type_to_writer_properties,
             parquet_type_to_writer,
             bucket_name,
             bucket_root,
@@ -89,8 +131,13 @@ impl GCSUploader {
             .get(&parquet_type)
             .context("Parquet type not found in schemas")?
             .clone();
+        let writer_properties = self
+            .parquet_type_to_writer_properties
+            .get(&parquet_type)
+            .context("Parquet type not found in writer properties")?
+            .clone();
 
-        create_new_writer(schema)
+        create_new_writer(schema, writer_properties)
     }
 
     /// # Context: Why we replace our writer
@@ -152,6 +199,8 @@ impl GCSUploader {
             .into_inner()
             .context("Failed to get inner buffer")?;
 
+        let start_version = data[0].version();
+        let end_version = data[data.len() - 1].version();
         let bucket_root = PathBuf::from(&self.bucket_root);
         upload_parquet_to_gcs(
             &self.gcs_client,
@@ -160,14 +209,14 @@ impl GCSUploader {
             &self.bucket_name,
             &bucket_root,
             self.processor_name.clone(),
+            start_version,
+            end_version,
         )
         .await?;
 
         debug!(
             "Uploaded parquet to GCS for table: {}, start_version: {}, end_version: {}",
-            table_name,
-            data[0].version(),
-            data[data.len() - 1].version()
+            table_name, start_version, end_version
         );
 
         Ok(())
@@ -1,3 +1,5 @@
+pub mod clickhouse_storer;
+pub mod file_exporter;
 pub mod gcs_uploader;
 pub mod parquet_buffer_step;
 pub mod parquet_version_tracker_step;
@@ -2,7 +2,7 @@ use crate::parquet_processors::ParquetTypeTrait;
 #[allow(unused_imports)]
 use crate::{
     parquet_processors::{ParquetTypeEnum, ParquetTypeStructs},
-    steps::common::gcs_uploader::{GCSUploader, Uploadable},
+    steps::common::gcs_uploader::{BufferUploader, Uploadable},
 };
 use anyhow::Result;
 use aptos_indexer_processor_sdk::{
@@ -13,7 +13,7 @@ use aptos_indexer_processor_sdk::{
     utils::errors::ProcessorError,
 };
 use async_trait::async_trait;
-use std::{collections::HashMap, time::Duration};
+use std::{collections::HashMap, ops::RangeInclusive, time::Duration};
 use tracing::debug;
 
 /// `ParquetBuffer` is a struct that holds `ParquetTypeStructs` data
@@ -65,24 +65,24 @@ impl ParquetBuffer {
 
 /// `ParquetBufferStep` is a step that accumulates data in buffers until they reach a specified size limit.
 ///
-/// It then uploads the buffered data to Google Cloud Storage (GCS) through an uploader.
+/// It then uploads the buffered data to Google Cloud Storage (GCS) or exports it to local files
+/// through a `BufferUploader`.
 /// This step is typically used to manage large data volumes efficiently by buffering and uploading
 /// only when necessary.
-///
-///
-/// # Type Parameters
-/// - `U`: A type that implements the `Uploadable` trait, providing the uploading functionality.
 pub struct ParquetBufferStep {
     internal_buffers: HashMap<ParquetTypeEnum, ParquetBuffer>,
     pub poll_interval: Duration,
-    pub buffer_uploader: GCSUploader,
+    pub buffer_uploader: BufferUploader,
     pub buffer_max_size: usize,
+    // Versions to write for each table during a per-table backfill. Batches are kept or dropped
+    // whole, so a batch that straddles the edge of a range is written entirely.
+    table_version_ranges: Option<HashMap<String, RangeInclusive<u64>>>,
 }
 
 impl ParquetBufferStep {
     pub fn new(
         poll_interval: Duration,
-        buffer_uploader: GCSUploader,
+        buffer_uploader: BufferUploader,
         buffer_max_size: usize,
     ) -> Self {
         Self {
@@ -90,6 +90,25 @@ impl ParquetBufferStep {
             poll_interval,
             buffer_uploader,
             buffer_max_size,
+            table_version_ranges: None,
+        }
+    }
+
+    pub fn with_table_version_ranges(
+        mut self,
+        table_version_ranges: Option<HashMap<String, RangeInclusive<u64>>>,
+    ) -> Self {
+        self.table_version_ranges = table_version_ranges;
+        self
+    }
+
+    /// Whether the batch overlaps the versions left to write for the table, if there are ranges.
+    fn should_write(&self, parquet_type: &ParquetTypeEnum, metadata: &TransactionMetadata) -> bool {
+        match &self.table_version_ranges {
+            Some(ranges) => ranges.get(&parquet_type.to_string()).is_some_and(|range| {
+                metadata.start_version <= *range.end() && metadata.end_version >= *range.start()
+            }),
+            None => true,
         }
     }
 
@@ -145,11 +164,13 @@ impl ParquetBufferStep {
                 &mut buffer.buffer,
                 ParquetTypeStructs::default_for_type(&parquet_type),
             );
-            self.buffer_uploader.upload_buffer(struct_buffer).await?;
+            let upload_metadata = buffer.current_batch_metadata.clone().unwrap();
+            self.buffer_uploader
+                .upload_buffer(struct_buffer, &upload_metadata)
+                .await?;
 
             // update this metadata before insert
-            upload_metadata_map
-                .insert(parquet_type, buffer.current_batch_metadata.clone().unwrap());
+            upload_metadata_map.insert(parquet_type, upload_metadata);
             buffer.buffer_size_bytes = 0;
             buffer.current_batch_metadata = None;
         }
@@ -184,6 +205,9 @@ impl Processable for ParquetBufferStep {
 
         let mut upload_metadata_map = HashMap::new();
         for (parquet_type, parquet_data) in item.data {
+            if !self.should_write(&parquet_type, &item.metadata) {
+                continue;
+            }
             self.upload_buffer_append(
                 parquet_type,
                 parquet_data,
@@ -214,9 +238,11 @@ impl Processable for ParquetBufferStep {
                     ParquetTypeStructs::default_for_type(&parquet_type),
                 );
 
-                self.buffer_uploader.upload_buffer(struct_buffer).await?;
-
                 if let Some(buffer_metadata) = &mut buffer.current_batch_metadata {
+                    self.buffer_uploader
+                        .upload_buffer(struct_buffer, buffer_metadata)
+                        .await?;
+
                     buffer_metadata.total_size_in_bytes = buffer.buffer_size_bytes as u64;
                     metadata_map.insert(parquet_type, buffer_metadata.clone());
                 } else {
@@ -262,9 +288,11 @@ impl PollableAsyncStep for ParquetBufferStep {
                     ParquetTypeStructs::default_for_type(&parquet_type),
                 );
 
-                self.buffer_uploader.upload_buffer(struct_buffer).await?;
-
                 let metadata = buffer.current_batch_metadata.clone().unwrap();
+                self.buffer_uploader
+                    .upload_buffer(struct_buffer, &metadata)
+                    .await?;
+
                 metadata_map.insert(parquet_type, metadata);
 
                 buffer.buffer_size_bytes = 0;
@@ -293,7 +321,7 @@ mod tests {
     use crate::{
         config::db_config::ParquetConfig,
         steps::common::{
-            gcs_uploader::{create_new_writer, GCSUploader},
+            gcs_uploader::{create_new_writer, BufferUploader, GCSUploader},
             parquet_buffer_step::{ParquetBufferStep, ParquetTypeEnum, ParquetTypeStructs},
         },
     };
@@ -302,9 +330,11 @@ mod tests {
         types::transaction_context::{TransactionContext, TransactionMetadata},
     };
     use google_cloud_storage::client::{Client as GCSClient, ClientConfig as GcsClientConfig};
-    use parquet::schema::types::Type;
+    use parquet::{file::properties::WriterProperties, schema::types::Type};
     use processor::{
-        bq_analytics::generic_parquet_processor::HasParquetSchema,
+        bq_analytics::{
+            generic_parquet_processor::HasParquetSchema, parquet_writer_config::ParquetWriterConfig,
+        },
         db::parquet::models::default_models::parquet_move_resources::MoveResource,
     };
     use std::{collections::HashMap, sync::Arc, time::Duration};
@@ -382,7 +412,7 @@ mod tests {
         Ok(())
     }
 
-    async fn create_parquet_uploader(db_config: &ParquetConfig) -> anyhow::Result<GCSUploader> {
+    async fn create_parquet_uploader(db_config: &ParquetConfig) -> anyhow::Result<BufferUploader> {
         let gcs_config = GcsClientConfig::default()
             .with_auth()
             .await
@@ -394,22 +424,32 @@ mod tests {
                 .into_iter()
                 .collect();
 
+        let writer_properties = Arc::new(ParquetWriterConfig::default().writer_properties()?);
+        let parquet_type_to_writer_properties: HashMap<ParquetTypeEnum, Arc<WriterProperties>> =
+            parquet_type_to_schemas
+                .keys()
+                .map(|key| (*key, writer_properties.clone()))
+                .collect();
+
         let parquet_type_to_writer = parquet_type_to_schemas
             .iter()
             .map(|(key, schema)| {
-                let writer = create_new_writer(schema.clone()).expect("Failed to create writer");
+                let writer = create_new_writer(schema.clone(), writer_properties.clone())
+                    .expect("Failed to create writer");
                 (*key, writer)
             })
             .collect();
 
-        GCSUploader::new(
+        let uploader = GCSUploader::new(
             gcs_client,
             parquet_type_to_schemas,
+            parquet_type_to_writer_properties,
             parquet_type_to_writer,
             db_config.bucket_name.clone(),
             db_config.bucket_root.clone(),
             "processor_name".to_string(),
-        )
+        )?;
+        Ok(uploader.into())
     }
 
     fn create_parquet_db_config() -> ParquetConfig {
@@ -419,6 +459,7 @@ mod tests {
             bucket_name: "bucket_name".to_string(),
             bucket_root: "bucket_root".to_string(),
             google_application_credentials: None,
+            file_export_config: None,
         }
     }
 }
@@ -1,7 +1,7 @@
 use crate::{
     config::{
         db_config::DbConfig,
-        indexer_processor_config::{IndexerProcessorConfig, ProcessorMode},
+        indexer_processor_config::{IndexerProcessorConfig, ProcessorMode, TableBackfillConfig},
     },
     db::common::models::{
         backfill_processor_status::{BackfillProcessorStatus, BackfillStatus},
@@ -13,6 +13,7 @@ use crate::{
         parquet_processor_table_mapping::format_table_name,
     },
 };
+use ahash::AHashMap;
 use anyhow::Result;
 use aptos_indexer_processor_sdk::{
     common_steps::ProcessorStatusSaver,
@@ -21,40 +22,48 @@ use aptos_indexer_processor_sdk::{
 };
 use async_trait::async_trait;
 use diesel::{upsert::excluded, ExpressionMethods};
-use processor::schema::{backfill_processor_status, processor_status};
+use processor::{
+    schema::{backfill_processor_status, processor_status},
+    utils::status_history::StatusHistorySampler,
+};
+use tokio::sync::Mutex;
 
 pub fn get_processor_status_saver(
     conn_pool: ArcDbPool,
     config: IndexerProcessorConfig,
 ) -> ProcessorStatusSaverEnum {
+    let status_history = config
+        .status_history_config
+        .clone()
+        .map(|config| Mutex::new(StatusHistorySampler::new(config)));
     match config.mode {
         ProcessorMode::Backfill => {
             let backfill_config = config.backfill_config.clone().unwrap();
             let backfill_start_version = backfill_config.initial_starting_version;
             let backfill_end_version = backfill_config.ending_version;
-            let backfill_alias = format!(
-                "{}_{}",
-                config.processor_config.name(),
-                backfill_config.backfill_id
-            );
+            let backfill_alias = backfill_config.backfill_alias(&config.processor_name());
             ProcessorStatusSaverEnum::Backfill {
                 conn_pool,
                 backfill_alias,
                 backfill_start_version,
                 backfill_end_version,
+                per_table_versions: backfill_config.per_table_versions,
+                status_history,
             }
         },
         _ => {
-            let processor_name = config.processor_config.name().to_string();
+            let processor_name = config.processor_name();
             if let DbConfig::ParquetConfig(_) = config.db_config {
                 ProcessorStatusSaverEnum::Parquet {
                     conn_pool,
                     processor_name,
+                    status_history,
                 }
             } else {
                 ProcessorStatusSaverEnum::Postgres {
                     conn_pool,
                     processor_name,
+                    status_history,
                 }
             }
         },
@@ -65,16 +74,21 @@ pub enum ProcessorStatusSaverEnum {
     Postgres {
         conn_pool: ArcDbPool,
         processor_name: String,
+        status_history: Option<Mutex<StatusHistorySampler>>,
     },
     Backfill {
         conn_pool: ArcDbPool,
         backfill_alias: String,
         backfill_start_version: u64,
         backfill_end_version: u64,
+        // Parquet processors checkpoint every table separately, see `BackfillConfig`.
+        per_table_versions: AHashMap<String, TableBackfillConfig>,
+        status_history: Option<Mutex<StatusHistorySampler>>,
     },
     Parquet {
         conn_pool: ArcDbPool,
         processor_name: String,
+        status_history: Option<Mutex<StatusHistorySampler>>,
     },
 }
 
@@ -120,10 +134,12 @@ impl ProcessorStatusSaverEnum {
             ProcessorStatusSaverEnum::Postgres {
                 conn_pool,
                 processor_name,
+                status_history,
             }
             | ProcessorStatusSaverEnum::Parquet {
                 conn_pool,
                 processor_name,
+                status_history,
             } => {
                 let processor_name = if let Some(table_name) = table_name {
                     format_table_name(processor_name, &table_name)
@@ -132,7 +148,7 @@ impl ProcessorStatusSaverEnum {
                 };
 
                 let status = ProcessorStatus {
-                    processor: processor_name,
+                    processor: processor_name.clone(),
                     last_success_version: last_success_batch.metadata.end_version as i64,
                     last_transaction_timestamp: end_timestamp,
                 };
@@ -155,6 +171,14 @@ impl ProcessorStatusSaverEnum {
                 )
                     .await?;
 
+                record_status_history(
+                    status_history,
+                    conn_pool.clone(),
+                    &processor_name,
+                    status.last_success_version,
+                    end_timestamp,
+                )
+                .await;
                 Ok(())
             },
             ProcessorStatusSaverEnum::Backfill {
@@ -162,9 +186,30 @@ impl ProcessorStatusSaverEnum {
                 backfill_alias,
                 backfill_start_version,
                 backfill_end_version,
+                per_table_versions,
+                status_history,
             } => {
+                let (backfill_alias, backfill_start_version, backfill_end_version) =
+                    if let Some(table_name) = table_name {
+                        let (start_version, end_version) = per_table_versions
+                            .get(&table_name)
+                            .map_or((*backfill_start_version, *backfill_end_version), |c| {
+                                (c.initial_starting_version, c.ending_version)
+                            });
+                        (
+                            format_table_name(backfill_alias, &table_name),
+                            start_version,
+                            end_version,
+                        )
+                    } else {
+                        (
+                            backfill_alias.clone(),
+                            *backfill_start_version,
+                            *backfill_end_version,
+                        )
+                    };
                 let lst_success_version = last_success_batch.metadata.end_version as i64;
-                let backfill_status = if lst_success_version >= *backfill_end_version as i64 {
+                let backfill_status = if lst_success_version >= backfill_end_version as i64 {
                     BackfillStatus::Complete
                 } else {
                     BackfillStatus::InProgress
@@ -174,8 +219,8 @@ impl ProcessorStatusSaverEnum {
                     backfill_status,
                     last_success_version: lst_success_version,
                     last_transaction_timestamp: end_timestamp,
-                    backfill_start_version: *backfill_start_version as i64,
-                    backfill_end_version: *backfill_end_version as i64,
+                    backfill_start_version: backfill_start_version as i64,
+                    backfill_end_version: backfill_end_version as i64,
                 };
                 execute_with_better_error(
                     conn_pool.clone(),
@@ -198,11 +243,43 @@ impl ProcessorStatusSaverEnum {
                             backfill_processor_status::backfill_end_version
                                 .eq(excluded(backfill_processor_status::backfill_end_version)),
                         )),
-                    Some(" WHERE backfill_processor_status.last_success_version <= EXCLUDED.last_success_version "),
+                    // A backfill marked complete, e.g. by `processor_cli backfill complete`, stays
+                    // complete
+                    Some(" WHERE backfill_processor_status.last_success_version <= EXCLUDED.last_success_version AND backfill_processor_status.backfill_status <> 'complete' "),
                 )
                     .await?;
+                record_status_history(
+                    status_history,
+                    conn_pool.clone(),
+                    &backfill_alias,
+                    lst_success_version,
+                    end_timestamp,
+                )
+                .await;
                 Ok(())
             },
         }
     }
 }
+
+/// Samples the checkpoint that was just saved into `processor_status_history`, if enabled.
+async fn record_status_history(
+    status_history: &Option<Mutex<StatusHistorySampler>>,
+    conn_pool: ArcDbPool,
+    processor_name: &str,
+    last_success_version: i64,
+    last_transaction_timestamp: Option<chrono::NaiveDateTime>,
+) {
+    if let Some(status_history) = status_history {
+        status_history
+            .lock()
+            .await
+            .record_and_write(
+                conn_pool,
+                processor_name,
+                last_success_version,
+                last_transaction_timestamp,
+            )
+            .await;
+    }
+}
@@ -0,0 +1,5 @@
+pub mod nft_marketplace_extractor;
+pub mod nft_marketplace_storer;
+
+pub use nft_marketplace_extractor::NftMarketplaceExtractor;
+pub use nft_marketplace_storer::NftMarketplaceStorer;
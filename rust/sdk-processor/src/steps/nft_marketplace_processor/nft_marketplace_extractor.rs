@@ -0,0 +1,120 @@
+use ahash::AHashMap;
+use aptos_indexer_processor_sdk::{
+    aptos_protos::transaction::v1::Transaction,
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use processor::db::postgres::models::nft_marketplace_models::{
+    current_nft_marketplace_collection_offers::CurrentNftMarketplaceCollectionOffer,
+    current_nft_marketplace_listings::CurrentNftMarketplaceListing,
+    current_nft_marketplace_token_offers::CurrentNftMarketplaceTokenOffer,
+    nft_marketplace_activities::NftMarketplaceActivity,
+    nft_marketplace_utils::{
+        parse_nft_marketplace_transaction, MarketplaceContract, NftMarketplaceModels,
+    },
+};
+use rayon::prelude::*;
+use std::hash::Hash;
+use tracing::error;
+
+pub struct NftMarketplaceExtractor
+where
+    Self: Sized + Send + 'static,
+{
+    marketplaces: Vec<MarketplaceContract>,
+}
+
+impl NftMarketplaceExtractor {
+    /// `contract_address` of every marketplace must be standardized.
+    pub fn new(marketplaces: Vec<MarketplaceContract>) -> Self {
+        Self { marketplaces }
+    }
+}
+
+#[async_trait]
+impl Processable for NftMarketplaceExtractor {
+    type Input = Vec<Transaction>;
+    type Output = (
+        Vec<NftMarketplaceActivity>,
+        Vec<CurrentNftMarketplaceListing>,
+        Vec<CurrentNftMarketplaceTokenOffer>,
+        Vec<CurrentNftMarketplaceCollectionOffer>,
+    );
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        transactions: TransactionContext<Self::Input>,
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        let results = match transactions
+            .data
+            .par_iter()
+            .map(|txn| parse_nft_marketplace_transaction(txn, &self.marketplaces))
+            .collect::<anyhow::Result<Vec<NftMarketplaceModels>>>()
+        {
+            Ok(results) => results,
+            Err(e) => {
+                error!(
+                    start_version = transactions.metadata.start_version,
+                    end_version = transactions.metadata.end_version,
+                    processor_name = self.name(),
+                    error = ?e,
+                    "[Parser] Error parsing nft marketplace events",
+                );
+                return Err(ProcessorError::ProcessError {
+                    message: format!("Error parsing nft marketplace events: {:?}", e),
+                });
+            },
+        };
+
+        let mut activities = vec![];
+        let mut current_listings = AHashMap::new();
+        let mut current_token_offers: AHashMap<_, CurrentNftMarketplaceTokenOffer> =
+            AHashMap::new();
+        let mut current_collection_offers: AHashMap<_, CurrentNftMarketplaceCollectionOffer> =
+            AHashMap::new();
+        // Transactions are in order, so the last row of each listing or offer is its latest state.
+        // Offers keep the expiration of earlier rows, see `merge_previous`.
+        for models in results {
+            activities.extend(models.activities);
+            current_listings.extend(models.current_listings.into_iter().map(|l| (l.pk(), l)));
+            for offer in models.current_token_offers {
+                let pk = offer.pk();
+                let offer = offer.merge_previous(current_token_offers.get(&pk));
+                current_token_offers.insert(pk, offer);
+            }
+            for offer in models.current_collection_offers {
+                let pk = offer.pk();
+                let offer = offer.merge_previous(current_collection_offers.get(&pk));
+                current_collection_offers.insert(pk, offer);
+            }
+        }
+
+        Ok(Some(TransactionContext {
+            data: (
+                activities,
+                sorted_by_pk(current_listings),
+                sorted_by_pk(current_token_offers),
+                sorted_by_pk(current_collection_offers),
+            ),
+            metadata: transactions.metadata,
+        }))
+    }
+}
+
+/// Rows are written in a consistent order to avoid deadlocks between concurrent upserts.
+fn sorted_by_pk<K: Ord + Hash, V>(rows: AHashMap<K, V>) -> Vec<V> {
+    let mut rows: Vec<(K, V)> = rows.into_iter().collect();
+    rows.sort_by(|(a, _), (b, _)| a.cmp(b));
+    rows.into_iter().map(|(_, row)| row).collect()
+}
+
+impl AsyncStep for NftMarketplaceExtractor {}
+
+impl NamedStep for NftMarketplaceExtractor {
+    fn name(&self) -> String {
+        "NftMarketplaceExtractor".to_string()
+    }
+}
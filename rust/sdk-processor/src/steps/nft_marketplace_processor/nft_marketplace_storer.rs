@@ -0,0 +1,280 @@
+use crate::{
+    processors::nft_marketplace_processor::NftMarketplaceProcessorConfig,
+    utils::database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+};
+use ahash::AHashMap;
+use anyhow::Result;
+use aptos_indexer_processor_sdk::{
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use diesel::{
+    dsl::sql,
+    pg::{upsert::excluded, Pg},
+    query_builder::QueryFragment,
+    sql_types::{Nullable, Numeric},
+    ExpressionMethods,
+};
+use processor::{
+    db::postgres::models::nft_marketplace_models::{
+        current_nft_marketplace_collection_offers::CurrentNftMarketplaceCollectionOffer,
+        current_nft_marketplace_listings::CurrentNftMarketplaceListing,
+        current_nft_marketplace_token_offers::CurrentNftMarketplaceTokenOffer,
+        nft_marketplace_activities::NftMarketplaceActivity,
+    },
+    schema,
+    utils::mq::{CustomProducer, CustomProducerEnum},
+};
+
+pub struct NftMarketplaceStorer
+where
+    Self: Sized + Send + 'static,
+{
+    conn_pool: ArcDbPool,
+    processor_config: NftMarketplaceProcessorConfig,
+    producer: CustomProducerEnum,
+    network: String,
+}
+
+impl NftMarketplaceStorer {
+    pub fn new(
+        conn_pool: ArcDbPool,
+        processor_config: NftMarketplaceProcessorConfig,
+        producer: CustomProducerEnum,
+        network: String,
+    ) -> Self {
+        Self {
+            conn_pool,
+            processor_config,
+            producer,
+            network,
+        }
+    }
+}
+
+#[async_trait]
+impl Processable for NftMarketplaceStorer {
+    type Input = (
+        Vec<NftMarketplaceActivity>,
+        Vec<CurrentNftMarketplaceListing>,
+        Vec<CurrentNftMarketplaceTokenOffer>,
+        Vec<CurrentNftMarketplaceCollectionOffer>,
+    );
+    type Output = ();
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        input: TransactionContext<Self::Input>,
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        let (activities, current_listings, current_token_offers, current_collection_offers) =
+            input.data;
+
+        // Published first so that a failed batch is retried before it's checkpointed
+        let network = &self.network;
+        let activities_topic = format!("aptos.{}.nft.marketplace.activities", network);
+        let listings_topic = format!("aptos.{}.current.nft.marketplace.listings", network);
+        let token_offers_topic = format!("aptos.{}.current.nft.marketplace.token.offers", network);
+        let collection_offers_topic = format!(
+            "aptos.{}.current.nft.marketplace.collection.offers",
+            network
+        );
+        let mq_results = tokio::join!(
+            self.producer.send_to_mq(&activities_topic, &activities),
+            self.producer.send_to_mq(&listings_topic, &current_listings),
+            self.producer
+                .send_to_mq(&token_offers_topic, &current_token_offers),
+            self.producer
+                .send_to_mq(&collection_offers_topic, &current_collection_offers),
+        );
+        for res in [mq_results.0, mq_results.1, mq_results.2, mq_results.3] {
+            if let Err(e) = res {
+                return Err(ProcessorError::ProcessError {
+                    message: format!(
+                        "Failed to produce versions {} to {} to mq: {}",
+                        input.metadata.start_version, input.metadata.end_version, e,
+                    ),
+                });
+            }
+        }
+
+        let per_table_chunk_sizes: AHashMap<String, usize> = self
+            .processor_config
+            .default_config
+            .per_table_chunk_sizes
+            .clone();
+
+        let a_res = execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_nft_marketplace_activities_query,
+            &activities,
+            get_config_table_chunk_size::<NftMarketplaceActivity>(
+                "nft_marketplace_activities",
+                &per_table_chunk_sizes,
+            ),
+        );
+        let cl_res = execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_current_nft_marketplace_listings_query,
+            &current_listings,
+            get_config_table_chunk_size::<CurrentNftMarketplaceListing>(
+                "current_nft_marketplace_listings",
+                &per_table_chunk_sizes,
+            ),
+        );
+        let cto_res = execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_current_nft_marketplace_token_offers_query,
+            &current_token_offers,
+            get_config_table_chunk_size::<CurrentNftMarketplaceTokenOffer>(
+                "current_nft_marketplace_token_offers",
+                &per_table_chunk_sizes,
+            ),
+        );
+        let cco_res = execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_current_nft_marketplace_collection_offers_query,
+            &current_collection_offers,
+            get_config_table_chunk_size::<CurrentNftMarketplaceCollectionOffer>(
+                "current_nft_marketplace_collection_offers",
+                &per_table_chunk_sizes,
+            ),
+        );
+
+        futures::try_join!(a_res, cl_res, cto_res, cco_res)?;
+
+        Ok(Some(TransactionContext {
+            data: (),
+            metadata: input.metadata,
+        }))
+    }
+}
+
+impl AsyncStep for NftMarketplaceStorer {}
+
+impl NamedStep for NftMarketplaceStorer {
+    fn name(&self) -> String {
+        "NftMarketplaceStorer".to_string()
+    }
+}
+
+fn insert_nft_marketplace_activities_query(
+    items_to_insert: Vec<NftMarketplaceActivity>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::nft_marketplace_activities::dsl::*;
+
+    (
+        diesel::insert_into(schema::nft_marketplace_activities::table)
+            .values(items_to_insert)
+            .on_conflict((transaction_version, event_index))
+            .do_nothing(),
+        None,
+    )
+}
+
+fn insert_current_nft_marketplace_listings_query(
+    items_to_insert: Vec<CurrentNftMarketplaceListing>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::current_nft_marketplace_listings::dsl::*;
+
+    (
+        diesel::insert_into(schema::current_nft_marketplace_listings::table)
+            .values(items_to_insert)
+            .on_conflict((listing_id, token_data_id))
+            .do_update()
+            .set((
+                collection_id.eq(excluded(collection_id)),
+                fee_schedule_id.eq(excluded(fee_schedule_id)),
+                seller.eq(excluded(seller)),
+                price.eq(excluded(price)),
+                token_amount.eq(excluded(token_amount)),
+                token_standard.eq(excluded(token_standard)),
+                is_deleted.eq(excluded(is_deleted)),
+                coin_type.eq(excluded(coin_type)),
+                marketplace.eq(excluded(marketplace)),
+                contract_address.eq(excluded(contract_address)),
+                entry_function_id_str.eq(excluded(entry_function_id_str)),
+                last_transaction_version.eq(excluded(last_transaction_version)),
+                last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
+                inserted_at.eq(excluded(inserted_at)),
+            )),
+        Some(" WHERE current_nft_marketplace_listings.last_transaction_version <= excluded.last_transaction_version "),
+    )
+}
+
+fn insert_current_nft_marketplace_token_offers_query(
+    items_to_insert: Vec<CurrentNftMarketplaceTokenOffer>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::current_nft_marketplace_token_offers::dsl::*;
+
+    (
+        diesel::insert_into(schema::current_nft_marketplace_token_offers::table)
+            .values(items_to_insert)
+            .on_conflict((offer_id, token_data_id))
+            .do_update()
+            .set((
+                collection_id.eq(excluded(collection_id)),
+                fee_schedule_id.eq(excluded(fee_schedule_id)),
+                buyer.eq(excluded(buyer)),
+                price.eq(excluded(price)),
+                token_amount.eq(excluded(token_amount)),
+                // Cancels and fills don't carry the expiration when the offer is already gone
+                expiration_time.eq(sql::<Nullable<Numeric>>("COALESCE(EXCLUDED.expiration_time, current_nft_marketplace_token_offers.expiration_time)")),
+                is_deleted.eq(excluded(is_deleted)),
+                token_standard.eq(excluded(token_standard)),
+                coin_type.eq(excluded(coin_type)),
+                marketplace.eq(excluded(marketplace)),
+                contract_address.eq(excluded(contract_address)),
+                entry_function_id_str.eq(excluded(entry_function_id_str)),
+                last_transaction_version.eq(excluded(last_transaction_version)),
+                last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
+                inserted_at.eq(excluded(inserted_at)),
+            )),
+        Some(" WHERE current_nft_marketplace_token_offers.last_transaction_version <= excluded.last_transaction_version "),
+    )
+}
+
+fn insert_current_nft_marketplace_collection_offers_query(
+    items_to_insert: Vec<CurrentNftMarketplaceCollectionOffer>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::current_nft_marketplace_collection_offers::dsl::*;
+
+    (
+        diesel::insert_into(schema::current_nft_marketplace_collection_offers::table)
+            .values(items_to_insert)
+            .on_conflict((collection_offer_id, collection_id))
+            .do_update()
+            .set((
+                fee_schedule_id.eq(excluded(fee_schedule_id)),
+                buyer.eq(excluded(buyer)),
+                item_price.eq(excluded(item_price)),
+                remaining_token_amount.eq(excluded(remaining_token_amount)),
+                // Fills don't carry the expiration when the offer is already gone
+                expiration_time.eq(sql::<Nullable<Numeric>>("COALESCE(EXCLUDED.expiration_time, current_nft_marketplace_collection_offers.expiration_time)")),
+                is_deleted.eq(excluded(is_deleted)),
+                token_standard.eq(excluded(token_standard)),
+                coin_type.eq(excluded(coin_type)),
+                marketplace.eq(excluded(marketplace)),
+                contract_address.eq(excluded(contract_address)),
+                entry_function_id_str.eq(excluded(entry_function_id_str)),
+                last_transaction_version.eq(excluded(last_transaction_version)),
+                last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
+                inserted_at.eq(excluded(inserted_at)),
+            )),
+        Some(" WHERE current_nft_marketplace_collection_offers.last_transaction_version <= excluded.last_transaction_version "),
+    )
+}
@@ -8,10 +8,16 @@ use aptos_indexer_processor_sdk::{
 use async_trait::async_trait;
 use processor::{
     db::{
-        common::models::object_models::raw_v2_objects::{
-            CurrentObjectConvertible, ObjectConvertible,
+        common::models::object_models::{
+            raw_v2_object_ultimate_owners::{
+                CurrentObjectUltimateOwnerConvertible, RawCurrentObjectUltimateOwner,
+            },
+            raw_v2_objects::{CurrentObjectConvertible, ObjectConvertible},
+        },
+        postgres::models::object_models::{
+            v2_object_ultimate_owners::CurrentObjectUltimateOwner,
+            v2_objects::{CurrentObject, Object},
         },
-        postgres::models::object_models::v2_objects::{CurrentObject, Object},
     },
     processors::objects_processor::process_objects,
     utils::{database::DbContext, table_flags::TableFlags},
@@ -47,13 +53,26 @@ impl ObjectsExtractor {
 #[async_trait]
 impl Processable for ObjectsExtractor {
     type Input = Vec<Transaction>;
-    type Output = (Vec<Object>, Vec<CurrentObject>);
+    type Output = (
+        Vec<Object>,
+        Vec<CurrentObject>,
+        Vec<CurrentObjectUltimateOwner>,
+    );
     type RunType = AsyncRunType;
 
     async fn process(
         &mut self,
         transactions: TransactionContext<Vec<Transaction>>,
-    ) -> Result<Option<TransactionContext<(Vec<Object>, Vec<CurrentObject>)>>, ProcessorError> {
+    ) -> Result<
+        Option<
+            TransactionContext<(
+                Vec<Object>,
+                Vec<CurrentObject>,
+                Vec<CurrentObjectUltimateOwner>,
+            )>,
+        >,
+        ProcessorError,
+    > {
         let conn = self
             .conn_pool
             .get()
@@ -64,14 +83,23 @@ impl Processable for ObjectsExtractor {
             })?;
         let query_retries = self.query_retries;
         let query_retry_delay_ms = self.query_retry_delay_ms;
-        let db_connection = DbContext {
+        let mut db_context = Some(DbContext {
             conn,
             query_retries,
             query_retry_delay_ms,
-        };
+        });
 
         let (mut raw_all_objects, raw_all_current_objects) =
-            process_objects(transactions.data, &mut Some(db_connection)).await;
+            process_objects(transactions.data, &mut db_context).await;
+        let raw_current_object_ultimate_owners =
+            RawCurrentObjectUltimateOwner::from_current_objects(
+                &raw_all_current_objects,
+                &mut db_context,
+            )
+            .await
+            .map_err(|e| ProcessorError::ProcessError {
+                message: format!("Failed to resolve object ultimate owners: {:?}", e),
+            })?;
 
         if self.deprecated_tables.contains(TableFlags::OBJECTS) {
             raw_all_objects.clear();
@@ -85,8 +113,18 @@ impl Processable for ObjectsExtractor {
             .map(CurrentObject::from_raw)
             .collect();
 
+        let postgres_current_object_ultimate_owners: Vec<CurrentObjectUltimateOwner> =
+            raw_current_object_ultimate_owners
+                .into_iter()
+                .map(CurrentObjectUltimateOwner::from_raw)
+                .collect();
+
         Ok(Some(TransactionContext {
-            data: (postgres_all_objects, postgres_all_current_objects),
+            data: (
+                postgres_all_objects,
+                postgres_all_current_objects,
+                postgres_current_object_ultimate_owners,
+            ),
             metadata: transactions.metadata,
         }))
     }
@@ -9,8 +9,14 @@ use aptos_indexer_processor_sdk::{
 use async_trait::async_trait;
 use processor::{
     self,
-    db::postgres::models::object_models::v2_objects::{CurrentObject, Object},
-    processors::objects_processor::{insert_current_objects_query, insert_objects_query},
+    db::postgres::models::object_models::{
+        v2_object_ultimate_owners::CurrentObjectUltimateOwner,
+        v2_objects::{CurrentObject, Object},
+    },
+    processors::objects_processor::{
+        insert_current_object_ultimate_owners_query, insert_current_objects_query,
+        insert_objects_query,
+    },
 };
 
 pub struct ObjectsStorer
@@ -32,15 +38,23 @@ impl ObjectsStorer {
 
 #[async_trait]
 impl Processable for ObjectsStorer {
-    type Input = (Vec<Object>, Vec<CurrentObject>);
+    type Input = (
+        Vec<Object>,
+        Vec<CurrentObject>,
+        Vec<CurrentObjectUltimateOwner>,
+    );
     type Output = ();
     type RunType = AsyncRunType;
 
     async fn process(
         &mut self,
-        input: TransactionContext<(Vec<Object>, Vec<CurrentObject>)>,
+        input: TransactionContext<(
+            Vec<Object>,
+            Vec<CurrentObject>,
+            Vec<CurrentObjectUltimateOwner>,
+        )>,
     ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
-        let (objects, current_objects) = input.data;
+        let (objects, current_objects, current_object_ultimate_owners) = input.data;
 
         let io = execute_in_chunks(
             self.conn_pool.clone(),
@@ -57,8 +71,17 @@ impl Processable for ObjectsStorer {
                 &self.per_table_chunk_sizes,
             ),
         );
-        let (io_res, co_res) = tokio::join!(io, co);
-        for res in [io_res, co_res] {
+        let coo = execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_current_object_ultimate_owners_query,
+            &current_object_ultimate_owners,
+            get_config_table_chunk_size::<CurrentObjectUltimateOwner>(
+                "current_object_ultimate_owners",
+                &self.per_table_chunk_sizes,
+            ),
+        );
+        let (io_res, co_res, coo_res) = tokio::join!(io, co, coo);
+        for res in [io_res, co_res, coo_res] {
             match res {
                 Ok(_) => {},
                 Err(e) => {
@@ -75,6 +75,11 @@ impl Processable for ParquetTokenV2Extractor {
             raw_current_token_v2_metadata,
             raw_current_token_royalties_v1,
             raw_current_token_claims,
+            _token_property_mutations,
+            _token_transferabilities_v2,
+            _current_token_transferabilities_v2,
+            _current_token_royalties,
+            _token_v1_to_v2_mappings,
         ) = parse_v2_token(&transactions.data, &table_handle_to_owner, &mut None).await;
 
         let parquet_current_token_claims: Vec<CurrentTokenPendingClaim> = raw_current_token_claims
@@ -10,6 +10,8 @@ use processor::{
     db::{
         common::models::token_v2_models::{
             raw_token_claims::CurrentTokenPendingClaimConvertible,
+            raw_token_royalty::CurrentTokenRoyaltyConvertible,
+            raw_token_v1_to_v2_mappings::TokenV1ToV2MappingConvertible,
             raw_v1_token_royalty::CurrentTokenRoyaltyV1Convertible,
             raw_v2_token_activities::TokenActivityV2Convertible,
             raw_v2_token_datas::{CurrentTokenDataV2Convertible, TokenDataV2Convertible},
@@ -17,16 +19,25 @@ use processor::{
             raw_v2_token_ownerships::{
                 CurrentTokenOwnershipV2Convertible, TokenOwnershipV2Convertible,
             },
+            raw_v2_token_property_mutations::TokenPropertyMutationConvertible,
+            raw_v2_token_transferability::{
+                CurrentTokenTransferabilityV2Convertible, TokenTransferabilityV2Convertible,
+            },
         },
         postgres::models::{
             token_models::{token_claims::CurrentTokenPendingClaim, tokens::TableMetadataForToken},
             token_v2_models::{
+                token_royalty::CurrentTokenRoyalty,
+                token_v1_to_v2_mappings::TokenV1ToV2Mapping,
                 v1_token_royalty::CurrentTokenRoyaltyV1,
+                v2_collection_stats::CollectionStatsChanges,
                 v2_collections::{CollectionV2, CurrentCollectionV2},
                 v2_token_activities::TokenActivityV2,
                 v2_token_datas::{CurrentTokenDataV2, TokenDataV2},
                 v2_token_metadata::CurrentTokenV2Metadata,
                 v2_token_ownerships::{CurrentTokenOwnershipV2, TokenOwnershipV2},
+                v2_token_property_mutations::TokenPropertyMutation,
+                v2_token_transferability::{CurrentTokenTransferabilityV2, TokenTransferabilityV2},
             },
         },
     },
@@ -41,11 +52,17 @@ where
 {
     query_retries: u32,
     query_retry_delay_ms: u64,
-    conn_pool: ArcDbPool,
+    // Used to look up data from earlier transactions (e.g. the owner of a burned token). Without a
+    // pool those lookups are skipped, same as in the parquet processor.
+    conn_pool: Option<ArcDbPool>,
 }
 
 impl TokenV2Extractor {
-    pub fn new(query_retries: u32, query_retry_delay_ms: u64, conn_pool: ArcDbPool) -> Self {
+    pub fn new(
+        query_retries: u32,
+        query_retry_delay_ms: u64,
+        conn_pool: Option<ArcDbPool>,
+    ) -> Self {
         Self {
             query_retries,
             query_retry_delay_ms,
@@ -70,6 +87,12 @@ impl Processable for TokenV2Extractor {
         Vec<CurrentTokenV2Metadata>,
         Vec<CurrentTokenRoyaltyV1>,
         Vec<CurrentTokenPendingClaim>,
+        Vec<TokenPropertyMutation>,
+        Vec<TokenTransferabilityV2>,
+        Vec<CurrentTokenTransferabilityV2>,
+        Vec<CurrentTokenRoyalty>,
+        Vec<TokenV1ToV2Mapping>,
+        CollectionStatsChanges,
     );
     type RunType = AsyncRunType;
 
@@ -91,28 +114,38 @@ impl Processable for TokenV2Extractor {
                 Vec<CurrentTokenV2Metadata>,
                 Vec<CurrentTokenRoyaltyV1>,
                 Vec<CurrentTokenPendingClaim>,
+                Vec<TokenPropertyMutation>,
+                Vec<TokenTransferabilityV2>,
+                Vec<CurrentTokenTransferabilityV2>,
+                Vec<CurrentTokenRoyalty>,
+                Vec<TokenV1ToV2Mapping>,
+                CollectionStatsChanges,
             )>,
         >,
         ProcessorError,
     > {
-        let conn = self
-            .conn_pool
-            .get()
-            .await
-            .map_err(|e| ProcessorError::DBStoreError {
-                message: format!("Failed to get connection from pool: {:?}", e),
-                query: None,
-            })?;
+        let mut db_context = match &self.conn_pool {
+            Some(conn_pool) => {
+                let conn = conn_pool
+                    .get()
+                    .await
+                    .map_err(|e| ProcessorError::DBStoreError {
+                        message: format!("Failed to get connection from pool: {:?}", e),
+                        query: None,
+                    })?;
+                Some(DbContext {
+                    conn,
+                    query_retries: self.query_retries,
+                    query_retry_delay_ms: self.query_retry_delay_ms,
+                })
+            },
+            None => None,
+        };
 
         // First get all token related table metadata from the batch of transactions. This is in case
         // an earlier transaction has metadata (in resources) that's missing from a later transaction.
         let table_handle_to_owner: ahash::AHashMap<String, TableMetadataForToken> =
             TableMetadataForToken::get_table_handle_to_owner_from_transactions(&transactions.data);
-        let db_connection = DbContext {
-            conn,
-            query_retries: self.query_retries,
-            query_retry_delay_ms: self.query_retry_delay_ms,
-        };
 
         let (
             collections_v2,
@@ -127,12 +160,36 @@ impl Processable for TokenV2Extractor {
             raw_current_token_v2_metadata,
             raw_current_token_royalties_v1,
             raw_current_token_claims,
-        ) = parse_v2_token(
-            &transactions.data,
-            &table_handle_to_owner,
-            &mut Some(db_connection),
-        )
-        .await;
+            raw_token_property_mutations,
+            raw_token_transferabilities_v2,
+            raw_current_token_transferabilities_v2,
+            raw_current_token_royalties,
+            raw_token_v1_to_v2_mappings,
+        ) = parse_v2_token(&transactions.data, &table_handle_to_owner, &mut db_context).await;
+
+        let collection_stats_changes = CollectionStatsChanges::from_raw(
+            &raw_token_datas_v2,
+            &raw_token_ownerships_v2,
+            &raw_token_activities_v2,
+        );
+
+        let postgres_token_property_mutations: Vec<TokenPropertyMutation> =
+            raw_token_property_mutations
+                .into_iter()
+                .map(TokenPropertyMutation::from_raw)
+                .collect();
+
+        let postgres_token_transferabilities_v2: Vec<TokenTransferabilityV2> =
+            raw_token_transferabilities_v2
+                .into_iter()
+                .map(TokenTransferabilityV2::from_raw)
+                .collect();
+
+        let postgres_current_token_transferabilities_v2: Vec<CurrentTokenTransferabilityV2> =
+            raw_current_token_transferabilities_v2
+                .into_iter()
+                .map(CurrentTokenTransferabilityV2::from_raw)
+                .collect();
 
         let postgres_current_token_claims: Vec<CurrentTokenPendingClaim> = raw_current_token_claims
             .into_iter()
@@ -145,6 +202,17 @@ impl Processable for TokenV2Extractor {
                 .map(CurrentTokenRoyaltyV1::from_raw)
                 .collect();
 
+        let postgres_current_token_royalties: Vec<CurrentTokenRoyalty> =
+            raw_current_token_royalties
+                .into_iter()
+                .map(CurrentTokenRoyalty::from_raw)
+                .collect();
+
+        let postgres_token_v1_to_v2_mappings: Vec<TokenV1ToV2Mapping> = raw_token_v1_to_v2_mappings
+            .into_iter()
+            .map(TokenV1ToV2Mapping::from_raw)
+            .collect();
+
         let postgres_current_token_v2_metadata: Vec<CurrentTokenV2Metadata> =
             raw_current_token_v2_metadata
                 .into_iter()
@@ -203,6 +271,12 @@ impl Processable for TokenV2Extractor {
                 postgres_current_token_v2_metadata,
                 postgres_current_token_royalties_v1,
                 postgres_current_token_claims,
+                postgres_token_property_mutations,
+                postgres_token_transferabilities_v2,
+                postgres_current_token_transferabilities_v2,
+                postgres_current_token_royalties,
+                postgres_token_v1_to_v2_mappings,
+                collection_stats_changes,
             ),
             metadata: transactions.metadata,
         }))
@@ -10,17 +10,31 @@ use aptos_indexer_processor_sdk::{
     utils::errors::ProcessorError,
 };
 use async_trait::async_trait;
+use diesel::{
+    pg::{upsert::excluded, Pg},
+    query_builder::QueryFragment,
+    ExpressionMethods, QueryDsl,
+};
+use diesel_async::RunQueryDsl;
 use processor::{
     self,
     db::postgres::models::{
         token_models::token_claims::CurrentTokenPendingClaim,
         token_v2_models::{
+            token_royalty::CurrentTokenRoyalty,
+            token_v1_to_v2_mappings::TokenV1ToV2Mapping,
             v1_token_royalty::CurrentTokenRoyaltyV1,
+            v2_collection_stats::{
+                CollectionHourlyTransfers, CollectionStatsChanges, CollectionStatsRows,
+                CurrentCollectionOwner, CurrentCollectionStats, PreviousCollectionStats,
+            },
             v2_collections::{CollectionV2, CurrentCollectionV2},
             v2_token_activities::TokenActivityV2,
             v2_token_datas::{CurrentTokenDataV2, TokenDataV2},
             v2_token_metadata::CurrentTokenV2Metadata,
             v2_token_ownerships::{CurrentTokenOwnershipV2, TokenOwnershipV2},
+            v2_token_property_mutations::TokenPropertyMutation,
+            v2_token_transferability::{CurrentTokenTransferabilityV2, TokenTransferabilityV2},
         },
     },
     processors::token_v2_processor::{
@@ -28,10 +42,13 @@ use processor::{
         insert_current_deleted_token_datas_v2_query,
         insert_current_deleted_token_ownerships_v2_query, insert_current_token_claims_query,
         insert_current_token_datas_v2_query, insert_current_token_ownerships_v2_query,
-        insert_current_token_royalties_v1_query, insert_current_token_v2_metadatas_query,
+        insert_current_token_royalties_query, insert_current_token_royalties_v1_query,
+        insert_current_token_transferabilities_v2_query, insert_current_token_v2_metadatas_query,
         insert_token_activities_v2_query, insert_token_datas_v2_query,
-        insert_token_ownerships_v2_query,
+        insert_token_ownerships_v2_query, insert_token_property_mutations_query,
+        insert_token_transferabilities_v2_query, insert_token_v1_to_v2_mappings_query,
     },
+    schema,
 };
 
 pub struct TokenV2Storer
@@ -66,6 +83,12 @@ impl Processable for TokenV2Storer {
         Vec<CurrentTokenV2Metadata>,
         Vec<CurrentTokenRoyaltyV1>,
         Vec<CurrentTokenPendingClaim>,
+        Vec<TokenPropertyMutation>,
+        Vec<TokenTransferabilityV2>,
+        Vec<CurrentTokenTransferabilityV2>,
+        Vec<CurrentTokenRoyalty>,
+        Vec<TokenV1ToV2Mapping>,
+        CollectionStatsChanges,
     );
     type Output = ();
     type RunType = AsyncRunType;
@@ -85,6 +108,12 @@ impl Processable for TokenV2Storer {
             Vec<CurrentTokenV2Metadata>,
             Vec<CurrentTokenRoyaltyV1>,
             Vec<CurrentTokenPendingClaim>,
+            Vec<TokenPropertyMutation>,
+            Vec<TokenTransferabilityV2>,
+            Vec<CurrentTokenTransferabilityV2>,
+            Vec<CurrentTokenRoyalty>,
+            Vec<TokenV1ToV2Mapping>,
+            CollectionStatsChanges,
         )>,
     ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
         let (
@@ -100,6 +129,12 @@ impl Processable for TokenV2Storer {
             current_token_v2_metadata,
             current_token_royalties_v1,
             current_token_claims,
+            token_property_mutations,
+            token_transferabilities_v2,
+            current_token_transferabilities_v2,
+            current_token_royalties,
+            token_v1_to_v2_mappings,
+            collection_stats_changes,
         ) = input.data;
 
         let per_table_chunk_sizes: AHashMap<String, usize> = self
@@ -108,6 +143,18 @@ impl Processable for TokenV2Storer {
             .per_table_chunk_sizes
             .clone();
 
+        // The collection stats are derived from the ownerships this batch replaces, so they have
+        // to be written before anything else
+        self.store_collection_stats(collection_stats_changes, &per_table_chunk_sizes)
+            .await
+            .map_err(|e| ProcessorError::DBStoreError {
+                message: format!(
+                    "Failed to store collection stats for versions {} to {}: {:?}",
+                    input.metadata.start_version, input.metadata.end_version, e,
+                ),
+                query: None,
+            })?;
+
         let coll_v2 = execute_in_chunks(
             self.conn_pool.clone(),
             insert_collections_v2_query,
@@ -120,6 +167,15 @@ impl Processable for TokenV2Storer {
             &token_datas_v2,
             get_config_table_chunk_size::<TokenDataV2>("token_datas_v2", &per_table_chunk_sizes),
         );
+        let tv1v2m = execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_token_v1_to_v2_mappings_query,
+            &token_v1_to_v2_mappings,
+            get_config_table_chunk_size::<TokenV1ToV2Mapping>(
+                "token_v1_to_v2_mappings",
+                &per_table_chunk_sizes,
+            ),
+        );
         let to_v2 = execute_in_chunks(
             self.conn_pool.clone(),
             insert_token_ownerships_v2_query,
@@ -201,6 +257,15 @@ impl Processable for TokenV2Storer {
                 &per_table_chunk_sizes,
             ),
         );
+        let ctr = execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_current_token_royalties_query,
+            &current_token_royalties,
+            get_config_table_chunk_size::<CurrentTokenRoyalty>(
+                "current_token_royalty",
+                &per_table_chunk_sizes,
+            ),
+        );
         let ctc_v1 = execute_in_chunks(
             self.conn_pool.clone(),
             insert_current_token_claims_query,
@@ -210,10 +275,38 @@ impl Processable for TokenV2Storer {
                 &per_table_chunk_sizes,
             ),
         );
+        let tpm = execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_token_property_mutations_query,
+            &token_property_mutations,
+            get_config_table_chunk_size::<TokenPropertyMutation>(
+                "token_property_mutations",
+                &per_table_chunk_sizes,
+            ),
+        );
+        let tt_v2 = execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_token_transferabilities_v2_query,
+            &token_transferabilities_v2,
+            get_config_table_chunk_size::<TokenTransferabilityV2>(
+                "token_transferability_v2",
+                &per_table_chunk_sizes,
+            ),
+        );
+        let ctt_v2 = execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_current_token_transferabilities_v2_query,
+            &current_token_transferabilities_v2,
+            get_config_table_chunk_size::<CurrentTokenTransferabilityV2>(
+                "current_token_transferability_v2",
+                &per_table_chunk_sizes,
+            ),
+        );
 
         let (
             coll_v2_res,
             td_v2_res,
+            tv1v2m_res,
             to_v2_res,
             cc_v2_res,
             ctd_v2_res,
@@ -223,15 +316,20 @@ impl Processable for TokenV2Storer {
             ta_v2_res,
             ct_v2_res,
             ctr_v1_res,
+            ctr_res,
             ctc_v1_res,
+            tpm_res,
+            tt_v2_res,
+            ctt_v2_res,
         ) = tokio::join!(
-            coll_v2, td_v2, to_v2, cc_v2, ctd_v2, cdtd_v2, cto_v2, cdto_v2, ta_v2, ct_v2, ctr_v1,
-            ctc_v1
+            coll_v2, td_v2, tv1v2m, to_v2, cc_v2, ctd_v2, cdtd_v2, cto_v2, cdto_v2, ta_v2, ct_v2,
+            ctr_v1, ctr, ctc_v1, tpm, tt_v2, ctt_v2
         );
 
         for res in [
             coll_v2_res,
             td_v2_res,
+            tv1v2m_res,
             to_v2_res,
             cc_v2_res,
             ctd_v2_res,
@@ -241,7 +339,11 @@ impl Processable for TokenV2Storer {
             ta_v2_res,
             ct_v2_res,
             ctr_v1_res,
+            ctr_res,
             ctc_v1_res,
+            tpm_res,
+            tt_v2_res,
+            ctt_v2_res,
         ] {
             match res {
                 Ok(_) => {},
@@ -264,6 +366,71 @@ impl Processable for TokenV2Storer {
     }
 }
 
+impl TokenV2Storer {
+    async fn store_collection_stats(
+        &self,
+        changes: CollectionStatsChanges,
+        per_table_chunk_sizes: &AHashMap<String, usize>,
+    ) -> anyhow::Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let previous = {
+            let mut conn = self.conn_pool.get().await?;
+            PreviousCollectionStats::load(&mut conn, &changes).await?
+        };
+        let rows = changes.apply(previous);
+        let prune_before = rows.prune_hourly_transfers_before();
+        let CollectionStatsRows {
+            stats,
+            owners,
+            hourly_transfers,
+        } = rows;
+
+        execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_current_collection_stats_query,
+            &stats,
+            get_config_table_chunk_size::<CurrentCollectionStats>(
+                "current_collection_stats",
+                per_table_chunk_sizes,
+            ),
+        )
+        .await?;
+        execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_collection_hourly_transfers_query,
+            &hourly_transfers,
+            get_config_table_chunk_size::<CollectionHourlyTransfers>(
+                "collection_hourly_transfers",
+                per_table_chunk_sizes,
+            ),
+        )
+        .await?;
+        execute_in_chunks(
+            self.conn_pool.clone(),
+            insert_current_collection_owners_query,
+            &owners,
+            get_config_table_chunk_size::<CurrentCollectionOwner>(
+                "current_collection_owners",
+                per_table_chunk_sizes,
+            ),
+        )
+        .await?;
+
+        if let Some(prune_before) = prune_before {
+            let mut conn = self.conn_pool.get().await?;
+            diesel::delete(
+                schema::collection_hourly_transfers::table
+                    .filter(schema::collection_hourly_transfers::hour.lt(prune_before)),
+            )
+            .execute(&mut conn)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
 impl AsyncStep for TokenV2Storer {}
 
 impl NamedStep for TokenV2Storer {
@@ -271,3 +438,75 @@ impl NamedStep for TokenV2Storer {
         "TokenV2Storer".to_string()
     }
 }
+
+fn insert_current_collection_stats_query(
+    items_to_insert: Vec<CurrentCollectionStats>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::current_collection_stats::dsl::*;
+
+    (
+        diesel::insert_into(schema::current_collection_stats::table)
+            .values(items_to_insert)
+            .on_conflict(collection_id)
+            .do_update()
+            .set((
+                distinct_owners.eq(excluded(distinct_owners)),
+                total_tokens_owned.eq(excluded(total_tokens_owned)),
+                burned_count.eq(excluded(burned_count)),
+                last_mint_version.eq(excluded(last_mint_version)),
+                last_transfer_version.eq(excluded(last_transfer_version)),
+                last_transaction_version.eq(excluded(last_transaction_version)),
+                last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
+                inserted_at.eq(excluded(inserted_at)),
+            )),
+        Some(" WHERE current_collection_stats.last_transaction_version <= excluded.last_transaction_version "),
+    )
+}
+
+fn insert_current_collection_owners_query(
+    items_to_insert: Vec<CurrentCollectionOwner>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::current_collection_owners::dsl::*;
+
+    (
+        diesel::insert_into(schema::current_collection_owners::table)
+            .values(items_to_insert)
+            .on_conflict((collection_id, owner_address))
+            .do_update()
+            .set((
+                token_amount.eq(excluded(token_amount)),
+                last_transaction_version.eq(excluded(last_transaction_version)),
+                last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
+                inserted_at.eq(excluded(inserted_at)),
+            )),
+        Some(" WHERE current_collection_owners.last_transaction_version <= excluded.last_transaction_version "),
+    )
+}
+
+fn insert_collection_hourly_transfers_query(
+    items_to_insert: Vec<CollectionHourlyTransfers>,
+) -> (
+    impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send,
+    Option<&'static str>,
+) {
+    use schema::collection_hourly_transfers::dsl::*;
+
+    (
+        diesel::insert_into(schema::collection_hourly_transfers::table)
+            .values(items_to_insert)
+            .on_conflict((collection_id, hour))
+            .do_update()
+            .set((
+                transfer_count.eq(excluded(transfer_count)),
+                last_transaction_version.eq(excluded(last_transaction_version)),
+                inserted_at.eq(excluded(inserted_at)),
+            )),
+        Some(" WHERE collection_hourly_transfers.last_transaction_version <= excluded.last_transaction_version "),
+    )
+}
@@ -0,0 +1,537 @@
+//! Sharded backfills.
+//!
+//! A backfill with `sharding` set is split into `num_shards` contiguous version ranges, each
+//! recorded as its own row in `backfill_processor_status` under
+//! `{processor}_{backfill_id}#shard_{index}`, next to the row of the backfill itself. Every worker
+//! running the same config claims an unclaimed shard (`FOR UPDATE SKIP LOCKED`), processes it as a
+//! regular backfill checkpointed under the shard's alias and claims the next one until none are
+//! left. A shard whose worker stopped renewing its claim, e.g. because it crashed, is claimed again
+//! after `claim_timeout_secs`. The backfill row tracks the version up to which every shard is done
+//! and is marked complete once all shards are.
+//!
+//! Shards are processed out of order, so a shard can write a `current_*` row for an older version
+//! after another shard wrote a newer one. The `current_*` upserts only overwrite rows whose
+//! `last_transaction_version` is lower or equal, so the tables end up with the latest row no
+//! matter the order the shards finish in. Extractors that look up earlier state in the database,
+//! e.g. the previous owner of a token, can however miss state from shards that haven't been
+//! processed yet, so processors like that should only be sharded over versions indexed before.
+
+use super::database::{new_db_pool, run_migrations, ArcDbPool, MyDbConnection};
+use crate::{
+    config::{
+        db_config::DbConfig,
+        indexer_processor_config::{BackfillConfig, IndexerProcessorConfig},
+    },
+    db::common::models::backfill_processor_status::{
+        BackfillProcessorStatus, BackfillProcessorStatusQuery, BackfillStatus,
+    },
+};
+use anyhow::{Context, Result};
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Double, Text},
+    ExpressionMethods, QueryDsl, QueryableByName,
+};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use processor::schema::backfill_processor_status;
+use std::{
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+use tracing::{info, warn};
+
+const SHARD_SEPARATOR: &str = "#shard_";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BackfillShard {
+    pub index: u64,
+    pub start_version: u64,
+    pub end_version: u64,
+}
+
+impl BackfillShard {
+    /// The backfill config to process the shard with, checkpointed under the shard's own alias.
+    pub fn backfill_config(&self, backfill_config: &BackfillConfig) -> BackfillConfig {
+        BackfillConfig {
+            backfill_id: format!(
+                "{}{}{}",
+                backfill_config.backfill_id, SHARD_SEPARATOR, self.index
+            ),
+            initial_starting_version: self.start_version,
+            ending_version: self.end_version,
+            overwrite_checkpoint: false,
+            per_table_versions: Default::default(),
+            sharding: None,
+        }
+    }
+}
+
+/// Splits `[start_version, end_version]` into at most `num_shards` contiguous ranges of about the
+/// same size. Ranges with fewer versions than shards get one shard per version.
+pub fn split_into_shards(
+    start_version: u64,
+    end_version: u64,
+    num_shards: u64,
+) -> Vec<BackfillShard> {
+    let num_versions = end_version - start_version + 1;
+    let shard_size = num_versions.div_ceil(num_shards.max(1));
+    (0..)
+        .map(|index| (index, start_version + index * shard_size))
+        .take_while(|(_, shard_start)| *shard_start <= end_version)
+        .map(|(index, shard_start)| BackfillShard {
+            index,
+            start_version: shard_start,
+            end_version: std::cmp::min(shard_start + shard_size - 1, end_version),
+        })
+        .collect()
+}
+
+#[derive(Debug, QueryableByName)]
+struct ShardRow {
+    #[diesel(sql_type = Text)]
+    backfill_alias: String,
+    #[diesel(sql_type = Text)]
+    backfill_status: BackfillStatus,
+    #[diesel(sql_type = BigInt)]
+    last_success_version: i64,
+    #[diesel(sql_type = BigInt)]
+    backfill_start_version: i64,
+    #[diesel(sql_type = BigInt)]
+    backfill_end_version: i64,
+}
+
+/// Returns the version up to which every shard is done and whether all of them are. `shards` must
+/// be sorted by start version.
+fn backfill_progress(start_version: i64, shards: &[ShardRow]) -> (i64, bool) {
+    let mut last_success_version = start_version - 1;
+    for shard in shards {
+        if shard.backfill_status != BackfillStatus::Complete {
+            return (
+                std::cmp::max(last_success_version, shard.last_success_version),
+                false,
+            );
+        }
+        last_success_version = shard.backfill_end_version;
+    }
+    (last_success_version, true)
+}
+
+#[derive(Clone)]
+pub struct BackfillShardCoordinator {
+    conn_pool: ArcDbPool,
+    backfill_alias: String,
+    worker_id: String,
+    claim_timeout_secs: u64,
+}
+
+impl BackfillShardCoordinator {
+    fn shard_alias_prefix(&self) -> String {
+        format!("{}{}", self.backfill_alias, SHARD_SEPARATOR)
+    }
+
+    fn shard_index(&self, shard_alias: &str) -> Result<u64> {
+        shard_alias
+            .strip_prefix(&self.shard_alias_prefix())
+            .and_then(|index| index.parse().ok())
+            .with_context(|| format!("Invalid backfill shard alias {}", shard_alias))
+    }
+
+    /// Records the backfill and its shards, unless another worker already did.
+    pub async fn create_shards(
+        &self,
+        backfill_config: &BackfillConfig,
+        num_shards: u64,
+    ) -> Result<()> {
+        let mut conn = self.conn_pool.get().await?;
+        let backfill_alias = self.backfill_alias.clone();
+        let prefix = self.shard_alias_prefix();
+        let start_version = backfill_config.initial_starting_version;
+        let end_version = backfill_config.ending_version;
+        conn.transaction::<_, anyhow::Error, _>(|conn| {
+            async move {
+                diesel::insert_into(backfill_processor_status::table)
+                    .values(new_status(
+                        backfill_alias.clone(),
+                        start_version,
+                        end_version,
+                    ))
+                    .on_conflict_do_nothing()
+                    .execute(conn)
+                    .await?;
+                // Serializes workers starting at the same time.
+                let status = backfill_processor_status::table
+                    .filter(backfill_processor_status::backfill_alias.eq(&backfill_alias))
+                    .for_update()
+                    .first::<BackfillProcessorStatusQuery>(conn)
+                    .await?;
+                anyhow::ensure!(
+                    status.backfill_start_version == start_version as i64
+                        && status.backfill_end_version == end_version as i64,
+                    "Backfill {} already exists for versions [{}, {}], use a new backfill_id",
+                    backfill_alias,
+                    status.backfill_start_version,
+                    status.backfill_end_version
+                );
+
+                let existing_shards = load_shards(conn, &prefix).await?.len();
+                if existing_shards > 0 {
+                    return Ok(());
+                }
+                let shards = split_into_shards(start_version, end_version, num_shards)
+                    .into_iter()
+                    .map(|shard| {
+                        new_status(
+                            format!("{}{}", prefix, shard.index),
+                            shard.start_version,
+                            shard.end_version,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                info!(
+                    backfill_alias = backfill_alias.as_str(),
+                    num_shards = shards.len(),
+                    "[Backfill Shards] Created shards"
+                );
+                diesel::insert_into(backfill_processor_status::table)
+                    .values(shards)
+                    .on_conflict_do_nothing()
+                    .execute(conn)
+                    .await?;
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await
+        .context("Failed to create backfill shards")
+    }
+
+    /// Claims the first shard that isn't complete and isn't claimed by a live worker.
+    pub async fn claim_shard(&self) -> Result<Option<BackfillShard>> {
+        let mut conn = self.conn_pool.get().await?;
+        let prefix = self.shard_alias_prefix();
+        let worker_id = self.worker_id.clone();
+        let claim_timeout_secs = self.claim_timeout_secs as f64;
+        let shard = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                async move {
+                    let shard = sql_query(
+                        "SELECT backfill_alias, backfill_status, last_success_version, \
+                         backfill_start_version, backfill_end_version \
+                         FROM backfill_processor_status \
+                         WHERE starts_with(backfill_alias, $1) AND position('.' IN backfill_alias) = 0 \
+                         AND backfill_status = 'in_progress' \
+                         AND (claimed_by IS NULL \
+                         OR last_updated < NOW() - make_interval(secs => $2)) \
+                         ORDER BY backfill_start_version LIMIT 1 FOR UPDATE SKIP LOCKED",
+                    )
+                    .bind::<Text, _>(&prefix)
+                    .bind::<Double, _>(claim_timeout_secs)
+                    .load::<ShardRow>(conn)
+                    .await?
+                    .into_iter()
+                    .next();
+                    if let Some(shard) = &shard {
+                        sql_query(
+                            "UPDATE backfill_processor_status SET claimed_by = $1, \
+                             last_updated = NOW() WHERE backfill_alias = $2",
+                        )
+                        .bind::<Text, _>(&worker_id)
+                        .bind::<Text, _>(&shard.backfill_alias)
+                        .execute(conn)
+                        .await?;
+                    }
+                    Ok(shard)
+                }
+                .scope_boxed()
+            })
+            .await
+            .context("Failed to claim backfill shard")?;
+
+        shard
+            .map(|shard| {
+                Ok(BackfillShard {
+                    index: self.shard_index(&shard.backfill_alias)?,
+                    start_version: shard.backfill_start_version as u64,
+                    end_version: shard.backfill_end_version as u64,
+                })
+            })
+            .transpose()
+    }
+
+    /// Keeps the claim on a shard alive while it's being processed.
+    async fn heartbeat(self, shard: BackfillShard) {
+        let shard_alias = format!("{}{}", self.shard_alias_prefix(), shard.index);
+        let interval = Duration::from_secs(std::cmp::max(self.claim_timeout_secs / 3, 1));
+        loop {
+            tokio::time::sleep(interval).await;
+            let result = match self.conn_pool.get().await {
+                Ok(mut conn) => sql_query(
+                    "UPDATE backfill_processor_status SET last_updated = NOW() \
+                     WHERE backfill_alias = $1 AND claimed_by = $2",
+                )
+                .bind::<Text, _>(&shard_alias)
+                .bind::<Text, _>(&self.worker_id)
+                .execute(&mut conn)
+                .await
+                .map_err(anyhow::Error::from),
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = result {
+                warn!(
+                    shard_alias = shard_alias.as_str(),
+                    error = ?e,
+                    "[Backfill Shards] Failed to renew shard claim"
+                );
+            }
+        }
+    }
+
+    /// Marks the shard complete and updates the progress of the backfill. Returns whether every
+    /// shard is complete.
+    pub async fn complete_shard(&self, shard: &BackfillShard) -> Result<bool> {
+        let mut conn = self.conn_pool.get().await?;
+        let backfill_alias = self.backfill_alias.clone();
+        let prefix = self.shard_alias_prefix();
+        let shard_alias = format!("{}{}", prefix, shard.index);
+        let shard_end_version = shard.end_version as i64;
+        conn.transaction::<_, anyhow::Error, _>(|conn| {
+            async move {
+                sql_query(
+                    "UPDATE backfill_processor_status SET backfill_status = 'complete', \
+                     last_success_version = GREATEST(last_success_version, $1), \
+                     last_updated = NOW() WHERE backfill_alias = $2",
+                )
+                .bind::<BigInt, _>(shard_end_version)
+                .bind::<Text, _>(&shard_alias)
+                .execute(conn)
+                .await?;
+                update_backfill_progress(conn, &backfill_alias, &prefix).await
+            }
+            .scope_boxed()
+        })
+        .await
+        .context("Failed to complete backfill shard")
+    }
+
+    /// Updates the progress of the backfill from its shards. The status saver can mark a shard
+    /// complete before `complete_shard` runs, and a shard like that is never claimed again if its
+    /// worker stops in between, so this runs once no shard is left to claim. Returns whether every
+    /// shard is complete.
+    pub async fn update_progress(&self) -> Result<bool> {
+        let mut conn = self.conn_pool.get().await?;
+        let backfill_alias = self.backfill_alias.clone();
+        let prefix = self.shard_alias_prefix();
+        conn.transaction::<_, anyhow::Error, _>(|conn| {
+            async move { update_backfill_progress(conn, &backfill_alias, &prefix).await }
+                .scope_boxed()
+        })
+        .await
+        .context("Failed to update backfill progress")
+    }
+}
+
+async fn update_backfill_progress(
+    conn: &mut MyDbConnection,
+    backfill_alias: &str,
+    prefix: &str,
+) -> Result<bool> {
+    // Serializes workers updating the progress at the same time.
+    let status = backfill_processor_status::table
+        .filter(backfill_processor_status::backfill_alias.eq(backfill_alias))
+        .for_update()
+        .first::<BackfillProcessorStatusQuery>(conn)
+        .await?;
+    let shards = load_shards(conn, prefix).await?;
+    let (last_success_version, is_complete) =
+        backfill_progress(status.backfill_start_version, &shards);
+    let backfill_status = if is_complete {
+        BackfillStatus::Complete
+    } else {
+        BackfillStatus::InProgress
+    };
+    diesel::update(
+        backfill_processor_status::table
+            .filter(backfill_processor_status::backfill_alias.eq(backfill_alias)),
+    )
+    .set((
+        backfill_processor_status::backfill_status.eq(backfill_status),
+        backfill_processor_status::last_success_version.eq(last_success_version),
+        backfill_processor_status::last_updated.eq(diesel::dsl::now),
+    ))
+    .execute(conn)
+    .await?;
+    Ok(is_complete)
+}
+
+fn new_status(
+    backfill_alias: String,
+    start_version: u64,
+    end_version: u64,
+) -> BackfillProcessorStatus {
+    BackfillProcessorStatus {
+        backfill_alias,
+        backfill_status: BackfillStatus::InProgress,
+        // One before the start, so processing starts at the first version of the range.
+        last_success_version: start_version as i64 - 1,
+        last_transaction_timestamp: None,
+        backfill_start_version: start_version as i64,
+        backfill_end_version: end_version as i64,
+    }
+}
+
+/// Per table checkpoints of parquet processors, `{shard alias}.{table}`, are not shards.
+async fn load_shards(
+    conn: &mut MyDbConnection,
+    prefix: &str,
+) -> diesel::QueryResult<Vec<ShardRow>> {
+    sql_query(
+        "SELECT backfill_alias, backfill_status, last_success_version, backfill_start_version, \
+         backfill_end_version FROM backfill_processor_status WHERE starts_with(backfill_alias, $1) \
+         AND position('.' IN backfill_alias) = 0 ORDER BY backfill_start_version",
+    )
+    .bind::<Text, _>(prefix)
+    .load::<ShardRow>(conn)
+    .await
+}
+
+/// Identifies this worker in `claimed_by`. Unique per process, since several processes can share a
+/// host name, e.g. in the same pod.
+fn worker_id() -> String {
+    let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "worker".to_string());
+    // `RandomState` is seeded randomly, so this is a random suffix without another dependency.
+    let suffix = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    format!("{}-{}-{:08x}", hostname, std::process::id(), suffix as u32)
+}
+
+/// Claims and processes shards of the backfill until none are left.
+pub async fn run_sharded_backfill(config: &IndexerProcessorConfig) -> Result<()> {
+    let backfill_config = config
+        .backfill_config
+        .clone()
+        .context("backfill_config must be present when mode is 'backfill'")?;
+    let sharding = backfill_config
+        .sharding
+        .clone()
+        .context("backfill_config.sharding must be set for a sharded backfill")?;
+    let connection_string = match &config.db_config {
+        DbConfig::PostgresConfig(postgres_config) => postgres_config.connection_string.clone(),
+        DbConfig::ParquetConfig(parquet_config) => parquet_config.connection_string.clone(),
+        DbConfig::ClickhouseConfig(_) => {
+            anyhow::bail!("Sharded backfills are not supported for ClickHouse")
+        },
+    };
+    let conn_pool = new_db_pool(&connection_string, Some(2))
+        .await
+        .context("Failed to create connection pool")?;
+    // The shards are recorded before any processor had a chance to run the migrations.
+    run_migrations(connection_string, conn_pool.clone()).await;
+
+    let coordinator = BackfillShardCoordinator {
+        conn_pool,
+        backfill_alias: backfill_config.backfill_alias(&config.processor_name()),
+        worker_id: worker_id(),
+        claim_timeout_secs: sharding.claim_timeout_secs,
+    };
+    coordinator
+        .create_shards(&backfill_config, sharding.num_shards)
+        .await?;
+
+    while let Some(shard) = coordinator.claim_shard().await? {
+        info!(
+            backfill_alias = coordinator.backfill_alias.as_str(),
+            worker_id = coordinator.worker_id.as_str(),
+            shard_index = shard.index,
+            start_version = shard.start_version,
+            end_version = shard.end_version,
+            "[Backfill Shards] Claimed shard"
+        );
+        let heartbeat = tokio::spawn(coordinator.clone().heartbeat(shard.clone()));
+        let result = config
+            .backfill_shard_config(shard.backfill_config(&backfill_config))
+            .run_processor()
+            .await;
+        heartbeat.abort();
+        result?;
+
+        let is_complete = coordinator.complete_shard(&shard).await?;
+        info!(
+            backfill_alias = coordinator.backfill_alias.as_str(),
+            shard_index = shard.index,
+            backfill_complete = is_complete,
+            "[Backfill Shards] Completed shard"
+        );
+    }
+
+    let is_complete = coordinator.update_progress().await?;
+    info!(
+        backfill_alias = coordinator.backfill_alias.as_str(),
+        backfill_complete = is_complete,
+        "[Backfill Shards] No shards left to claim"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_into_shards() {
+        let ranges = |shards: Vec<BackfillShard>| {
+            shards
+                .into_iter()
+                .map(|s| (s.index, s.start_version, s.end_version))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ranges(split_into_shards(0, 99, 4)), vec![
+            (0, 0, 24),
+            (1, 25, 49),
+            (2, 50, 74),
+            (3, 75, 99)
+        ]);
+        assert_eq!(ranges(split_into_shards(10, 20, 3)), vec![
+            (0, 10, 13),
+            (1, 14, 17),
+            (2, 18, 20)
+        ]);
+        assert_eq!(ranges(split_into_shards(5, 6, 4)), vec![
+            (0, 5, 5),
+            (1, 6, 6)
+        ]);
+    }
+
+    #[test]
+    fn test_backfill_progress() {
+        let shard = |backfill_status, last_success_version, start, end| ShardRow {
+            backfill_alias: String::new(),
+            backfill_status,
+            last_success_version,
+            backfill_start_version: start,
+            backfill_end_version: end,
+        };
+
+        assert_eq!(
+            backfill_progress(0, &[
+                shard(BackfillStatus::InProgress, -1, 0, 49),
+                shard(BackfillStatus::Complete, 99, 50, 99),
+            ]),
+            (-1, false)
+        );
+        assert_eq!(
+            backfill_progress(0, &[
+                shard(BackfillStatus::Complete, 49, 0, 49),
+                shard(BackfillStatus::InProgress, 70, 50, 99),
+            ]),
+            (70, false)
+        );
+        assert_eq!(
+            backfill_progress(0, &[
+                shard(BackfillStatus::Complete, 49, 0, 49),
+                shard(BackfillStatus::Complete, 99, 50, 99),
+            ]),
+            (99, true)
+        );
+    }
+}
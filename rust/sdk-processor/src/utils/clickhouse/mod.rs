@@ -0,0 +1,128 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Minimal client for the ClickHouse HTTP interface. Rows are inserted and read as
+//! `JSONEachRow`, so any model that implements `Serialize` can be written without a dedicated
+//! ClickHouse row type.
+
+pub mod models;
+pub mod processor_status;
+
+use crate::config::db_config::ClickhouseConfig;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+
+#[derive(Clone)]
+pub struct ClickhouseClient {
+    client: reqwest::Client,
+    url: String,
+    pub database: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl ClickhouseClient {
+    pub fn new(config: &ClickhouseConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: config.url.clone(),
+            database: config.database.clone(),
+            username: config.username.clone(),
+            password: config.password.clone(),
+        }
+    }
+
+    /// Sends `query` as the request body and `params` as URL parameters (e.g. settings).
+    async fn post(&self, params: &[(&str, &str)], body: String) -> Result<String> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .query(&[("database", self.database.as_str())])
+            .query(params)
+            .body(body);
+        if let Some(username) = &self.username {
+            request = request.header("X-ClickHouse-User", username);
+        }
+        if let Some(password) = &self.password {
+            request = request.header("X-ClickHouse-Key", password);
+        }
+
+        let response = request
+            .send()
+            .await
+            .context("Failed to send request to ClickHouse")?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .context("Failed to read response from ClickHouse")?;
+        anyhow::ensure!(
+            status.is_success(),
+            "ClickHouse returned {}: {}",
+            status,
+            text.trim()
+        );
+        Ok(text)
+    }
+
+    pub async fn execute(&self, query: &str) -> Result<()> {
+        self.post(&[], query.to_string())
+            .await
+            .with_context(|| format!("Failed to execute query: {}", query))?;
+        Ok(())
+    }
+
+    /// Runs a `SELECT`. Values are bound to the `{name:Type}` placeholders of `query` from
+    /// `params`, so they never need to be escaped into the query itself.
+    pub async fn query<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        params: &[(&str, &str)],
+    ) -> Result<Vec<T>> {
+        let param_names = params
+            .iter()
+            .map(|(name, _)| format!("param_{}", name))
+            .collect::<Vec<_>>();
+        let mut url_params = vec![
+            // Int64 columns are quoted by default, which wouldn't deserialize into i64
+            ("output_format_json_quote_64bit_integers", "0"),
+        ];
+        url_params.extend(
+            param_names
+                .iter()
+                .zip(params)
+                .map(|(name, (_, value))| (name.as_str(), *value)),
+        );
+        let text = self
+            .post(&url_params, format!("{} FORMAT JSONEachRow", query))
+            .await
+            .with_context(|| format!("Failed to run query: {}", query))?;
+        text.lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_str(line).context("Failed to parse ClickHouse row"))
+            .collect()
+    }
+
+    pub async fn insert_rows(&self, table_name: &str, rows: &[serde_json::Value]) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let mut body = String::new();
+        for row in rows {
+            body.push_str(&serde_json::to_string(row).context("Failed to serialize row")?);
+            body.push('\n');
+        }
+        let query = format!("INSERT INTO {} FORMAT JSONEachRow", table_name);
+        self.post(
+            &[
+                ("query", query.as_str()),
+                // chrono serializes NaiveDateTime as ISO 8601, which the default parser rejects
+                ("date_time_input_format", "best_effort"),
+            ],
+            body,
+        )
+        .await
+        .with_context(|| format!("Failed to insert {} rows into {}", rows.len(), table_name))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! ClickHouse tables for the postgres models. The rows are the models' serde serialization, so
+//! the column list below only needs to map each serialized field to a column name and type.
+
+use super::ClickhouseClient;
+use anyhow::{Context, Result};
+use processor::db::{
+    common::models::fungible_asset_models::raw_v2_fungible_asset_to_coin_mappings::FungibleAssetToCoinMappings,
+    postgres::models::{
+        events_models::events::EventPG,
+        fungible_asset_models::{
+            v2_fungible_asset_activities::FungibleAssetActivity,
+            v2_fungible_asset_to_coin_mappings::FungibleAssetToCoinMapping,
+        },
+        token_v2_models::v2_token_activities::TokenActivityV2,
+        user_transactions_models::user_transactions::UserTransaction,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+/// Wide enough for any u64/u128 amount.
+const NUMERIC: &str = "Decimal(76, 0)";
+const TIMESTAMP: &str = "DateTime64(6)";
+
+pub struct ClickhouseColumn {
+    /// Serialized field name of the model
+    pub field: &'static str,
+    pub name: &'static str,
+    pub column_type: &'static str,
+}
+
+const fn column(
+    field: &'static str,
+    name: &'static str,
+    column_type: &'static str,
+) -> ClickhouseColumn {
+    ClickhouseColumn {
+        field,
+        name,
+        column_type,
+    }
+}
+
+const fn same(name: &'static str, column_type: &'static str) -> ClickhouseColumn {
+    column(name, name, column_type)
+}
+
+pub trait ClickhouseModel: Serialize {
+    const TABLE_NAME: &'static str;
+    /// Column names of the sorting key. Tables use `ReplacingMergeTree`, so rows with the same
+    /// key are deduplicated and reprocessing a version range is idempotent.
+    const ORDER_BY: &'static [&'static str];
+
+    fn columns() -> &'static [ClickhouseColumn];
+
+    fn create_table_ddl(database: &str) -> String {
+        let columns = Self::columns()
+            .iter()
+            .map(|c| format!("    `{}` {}", c.name, c.column_type))
+            .chain(std::iter::once(format!(
+                "    `inserted_at` {} DEFAULT now64(6)",
+                TIMESTAMP
+            )))
+            .collect::<Vec<_>>()
+            .join(",\n");
+        format!(
+            "CREATE TABLE IF NOT EXISTS {}.{} (\n{}\n) ENGINE = ReplacingMergeTree(inserted_at)\nORDER BY ({})",
+            database,
+            Self::TABLE_NAME,
+            columns,
+            Self::ORDER_BY.join(", "),
+        )
+    }
+
+    /// Renames the serialized fields to column names. JSON objects are stored as strings.
+    fn to_clickhouse_row(&self) -> Result<serde_json::Value> {
+        let serialized = serde_json::to_value(self).context("Failed to serialize row")?;
+        let row = Self::columns()
+            .iter()
+            .map(|c| {
+                let value = match serialized.get(c.field).cloned().unwrap_or_default() {
+                    value @ (serde_json::Value::Object(_) | serde_json::Value::Array(_)) => {
+                        serde_json::Value::String(value.to_string())
+                    },
+                    value => value,
+                };
+                (c.name.to_string(), value)
+            })
+            .collect();
+        Ok(serde_json::Value::Object(row))
+    }
+}
+
+impl ClickhouseModel for EventPG {
+    const ORDER_BY: &'static [&'static str] = &["transaction_version", "event_index"];
+    const TABLE_NAME: &'static str = "events";
+
+    fn columns() -> &'static [ClickhouseColumn] {
+        &[
+            same("sequence_number", "Int64"),
+            same("creation_number", "Int64"),
+            same("account_address", "String"),
+            same("transaction_version", "Int64"),
+            same("transaction_block_height", "Int64"),
+            column("type_", "type", "String"),
+            same("data", "String"),
+            same("event_index", "Int64"),
+            same("indexed_type", "String"),
+        ]
+    }
+}
+
+impl ClickhouseModel for FungibleAssetActivity {
+    const ORDER_BY: &'static [&'static str] = &["transaction_version", "event_index"];
+    const TABLE_NAME: &'static str = "fungible_asset_activities";
+
+    fn columns() -> &'static [ClickhouseColumn] {
+        &[
+            same("transaction_version", "Int64"),
+            same("event_index", "Int64"),
+            same("owner_address", "Nullable(String)"),
+            same("storage_id", "String"),
+            same("asset_type", "Nullable(String)"),
+            same("is_frozen", "Nullable(Bool)"),
+            same("amount", "Nullable(Decimal(76, 0))"),
+            column("type_", "type", "String"),
+            same("is_gas_fee", "Bool"),
+            same("gas_fee_payer_address", "Nullable(String)"),
+            same("is_transaction_success", "Bool"),
+            same("entry_function_id_str", "Nullable(String)"),
+            same("block_height", "Int64"),
+            same("token_standard", "String"),
+            same("transaction_timestamp", TIMESTAMP),
+            same("storage_refund_amount", NUMERIC),
+        ]
+    }
+}
+
+impl ClickhouseModel for FungibleAssetToCoinMapping {
+    const ORDER_BY: &'static [&'static str] = &["coin_type"];
+    const TABLE_NAME: &'static str = "fungible_asset_to_coin_mappings";
+
+    fn columns() -> &'static [ClickhouseColumn] {
+        &[
+            same("fungible_asset_metadata_address", "String"),
+            same("coin_type", "String"),
+            same("last_transaction_version", "Int64"),
+        ]
+    }
+}
+
+#[derive(Deserialize)]
+struct ClickhouseFungibleAssetToCoinMapping {
+    fungible_asset_metadata_address: String,
+    coin_type: String,
+}
+
+/// Same as `get_all_mappings` for postgres, to bootstrap the mapping of the fungible asset
+/// extractor on startup.
+pub async fn get_fa_to_coin_mappings(
+    client: &ClickhouseClient,
+) -> Result<FungibleAssetToCoinMappings> {
+    let rows: Vec<ClickhouseFungibleAssetToCoinMapping> = client
+        .query(
+            &format!(
+                "SELECT fungible_asset_metadata_address, coin_type FROM {}.{} FINAL",
+                client.database,
+                FungibleAssetToCoinMapping::TABLE_NAME,
+            ),
+            &[],
+        )
+        .await
+        .context("Failed to query fungible_asset_to_coin_mappings table.")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.fungible_asset_metadata_address, row.coin_type))
+        .collect())
+}
+
+impl ClickhouseModel for TokenActivityV2 {
+    const ORDER_BY: &'static [&'static str] = &["transaction_version", "event_index"];
+    const TABLE_NAME: &'static str = "token_activities_v2";
+
+    fn columns() -> &'static [ClickhouseColumn] {
+        &[
+            same("transaction_version", "Int64"),
+            same("event_index", "Int64"),
+            same("event_account_address", "String"),
+            same("token_data_id", "String"),
+            same("property_version_v1", NUMERIC),
+            column("type_", "type", "String"),
+            same("from_address", "Nullable(String)"),
+            same("to_address", "Nullable(String)"),
+            same("token_amount", NUMERIC),
+            same("before_value", "Nullable(String)"),
+            same("after_value", "Nullable(String)"),
+            same("entry_function_id_str", "Nullable(String)"),
+            same("token_standard", "String"),
+            same("is_fungible_v2", "Nullable(Bool)"),
+            same("transaction_timestamp", TIMESTAMP),
+        ]
+    }
+}
+
+impl ClickhouseModel for UserTransaction {
+    const ORDER_BY: &'static [&'static str] = &["version"];
+    const TABLE_NAME: &'static str = "user_transactions";
+
+    fn columns() -> &'static [ClickhouseColumn] {
+        &[
+            same("version", "Int64"),
+            same("block_height", "Int64"),
+            same("parent_signature_type", "String"),
+            same("sender", "String"),
+            same("sequence_number", "Int64"),
+            same("max_gas_amount", NUMERIC),
+            same("expiration_timestamp_secs", TIMESTAMP),
+            same("gas_unit_price", NUMERIC),
+            same("timestamp", TIMESTAMP),
+            same("entry_function_id_str", "String"),
+            same("epoch", "Int64"),
+            same("entry_function_contract_address", "Nullable(String)"),
+            same("entry_function_module_name", "Nullable(String)"),
+            same("entry_function_function_name", "Nullable(String)"),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_table_ddl() {
+        assert_eq!(
+            EventPG::create_table_ddl("analytics"),
+            "CREATE TABLE IF NOT EXISTS analytics.events (
+    `sequence_number` Int64,
+    `creation_number` Int64,
+    `account_address` String,
+    `transaction_version` Int64,
+    `transaction_block_height` Int64,
+    `type` String,
+    `data` String,
+    `event_index` Int64,
+    `indexed_type` String,
+    `inserted_at` DateTime64(6) DEFAULT now64(6)
+) ENGINE = ReplacingMergeTree(inserted_at)
+ORDER BY (transaction_version, event_index)"
+        );
+    }
+
+    #[test]
+    fn test_to_clickhouse_row() {
+        let event = EventPG {
+            sequence_number: 1,
+            creation_number: 2,
+            account_address: "0x1".to_string(),
+            transaction_version: 3,
+            transaction_block_height: 4,
+            type_: "0x1::coin::DepositEvent".to_string(),
+            data: serde_json::json!({"amount": "100"}),
+            event_index: 0,
+            indexed_type: "0x1::coin::DepositEvent".to_string(),
+        };
+        let row = event.to_clickhouse_row().unwrap();
+        assert_eq!(row["type"], "0x1::coin::DepositEvent");
+        assert_eq!(row["data"], "{\"amount\":\"100\"}");
+        assert!(row.get("type_").is_none());
+    }
+}
@@ -0,0 +1,306 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Processor status and chain id for processors writing to ClickHouse, mirroring the postgres
+//! `processor_status`, `backfill_processor_status` and `ledger_infos` tables.
+
+use super::ClickhouseClient;
+use crate::config::indexer_processor_config::{IndexerProcessorConfig, ProcessorMode};
+use anyhow::{Context, Result};
+use aptos_indexer_processor_sdk::{
+    common_steps::ProcessorStatusSaver,
+    types::transaction_context::TransactionContext,
+    utils::{errors::ProcessorError, time::parse_timestamp},
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+pub fn create_processor_status_table_ddl(database: &str) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {}.processor_status (
+    `processor` String,
+    `last_success_version` Int64,
+    `last_transaction_timestamp` Nullable(DateTime64(6)),
+    `last_updated` DateTime64(6) DEFAULT now64(6)
+) ENGINE = ReplacingMergeTree(last_updated)
+ORDER BY processor",
+        database
+    )
+}
+
+pub fn create_backfill_processor_status_table_ddl(database: &str) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {}.backfill_processor_status (
+    `backfill_alias` String,
+    `backfill_status` String,
+    `last_success_version` Int64,
+    `last_transaction_timestamp` Nullable(DateTime64(6)),
+    `backfill_start_version` Int64,
+    `backfill_end_version` Int64,
+    `last_updated` DateTime64(6) DEFAULT now64(6)
+) ENGINE = ReplacingMergeTree(last_updated)
+ORDER BY backfill_alias",
+        database
+    )
+}
+
+pub fn create_ledger_infos_table_ddl(database: &str) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {}.ledger_infos (
+    `chain_id` Int64
+) ENGINE = ReplacingMergeTree
+ORDER BY chain_id",
+        database
+    )
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ClickhouseProcessorStatus {
+    pub processor: String,
+    pub last_success_version: i64,
+    pub last_transaction_timestamp: Option<chrono::NaiveDateTime>,
+}
+
+impl ClickhouseProcessorStatus {
+    pub async fn get_by_processor(
+        client: &ClickhouseClient,
+        processor_name: &str,
+    ) -> Result<Option<Self>> {
+        let rows: Vec<ClickhouseLastSuccessVersion> = client
+            .query(
+                &format!(
+                    "SELECT last_success_version FROM {}.processor_status FINAL WHERE processor = {{processor:String}}",
+                    client.database,
+                ),
+                &[("processor", processor_name)],
+            )
+            .await
+            .context("Failed to query processor_status table.")?;
+        Ok(rows.into_iter().next().map(|row| Self {
+            processor: processor_name.to_string(),
+            last_success_version: row.last_success_version,
+            last_transaction_timestamp: None,
+        }))
+    }
+}
+
+#[derive(Deserialize)]
+struct ClickhouseLastSuccessVersion {
+    last_success_version: i64,
+}
+
+const BACKFILL_IN_PROGRESS: &str = "in_progress";
+const BACKFILL_COMPLETE: &str = "complete";
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ClickhouseBackfillProcessorStatus {
+    pub backfill_alias: String,
+    /// `in_progress` or `complete`, same as in postgres
+    pub backfill_status: String,
+    pub last_success_version: i64,
+    pub last_transaction_timestamp: Option<chrono::NaiveDateTime>,
+    pub backfill_start_version: i64,
+    pub backfill_end_version: i64,
+}
+
+#[derive(Deserialize)]
+struct ClickhouseBackfillProgress {
+    backfill_status: String,
+    last_success_version: i64,
+}
+
+impl ClickhouseBackfillProgress {
+    async fn get_by_alias(client: &ClickhouseClient, backfill_alias: &str) -> Result<Option<Self>> {
+        let rows: Vec<Self> = client
+            .query(
+                &format!(
+                    "SELECT backfill_status, last_success_version FROM {}.backfill_processor_status FINAL WHERE backfill_alias = {{backfill_alias:String}}",
+                    client.database,
+                ),
+                &[("backfill_alias", backfill_alias)],
+            )
+            .await
+            .context("Failed to query backfill_processor_status table.")?;
+        Ok(rows.into_iter().next())
+    }
+}
+
+#[derive(Deserialize)]
+struct ClickhouseChainId {
+    chain_id: i64,
+}
+
+/// Same as `get_starting_version` for postgres. Backfills are checkpointed in
+/// `backfill_processor_status` under `{processor_name}_{backfill_id}`, and a complete backfill
+/// ends right away.
+pub async fn get_starting_version(
+    indexer_processor_config: &IndexerProcessorConfig,
+    client: &ClickhouseClient,
+) -> Result<u64> {
+    let processor_name = indexer_processor_config.processor_name();
+    match indexer_processor_config.mode {
+        ProcessorMode::Backfill => {
+            let backfill_config = indexer_processor_config.backfill_config.clone().unwrap();
+            if backfill_config.overwrite_checkpoint {
+                return Ok(backfill_config.initial_starting_version);
+            }
+            let backfill_alias = backfill_config.backfill_alias(&processor_name);
+            let status = ClickhouseBackfillProgress::get_by_alias(client, &backfill_alias).await?;
+            Ok(match status {
+                Some(status) if status.backfill_status == BACKFILL_COMPLETE => {
+                    backfill_config.ending_version
+                },
+                Some(status) => std::cmp::min(
+                    status.last_success_version as u64 + 1,
+                    backfill_config.ending_version,
+                ),
+                None => backfill_config.initial_starting_version,
+            })
+        },
+        ProcessorMode::Default => {
+            let status =
+                ClickhouseProcessorStatus::get_by_processor(client, &processor_name).await?;
+            let default_starting_version = indexer_processor_config
+                .bootstrap_config
+                .clone()
+                .map_or(0, |config| config.initial_starting_version);
+            Ok(status.map_or(default_starting_version, |status| {
+                std::cmp::max(status.last_success_version as u64, default_starting_version)
+            }))
+        },
+        ProcessorMode::Testing => {
+            let testing_config = indexer_processor_config.testing_config.clone().unwrap();
+            Ok(testing_config.override_starting_version)
+        },
+    }
+}
+
+/// Verify the chain id from GRPC against the one stored in ClickHouse.
+pub async fn check_or_update_chain_id(
+    grpc_chain_id: i64,
+    client: &ClickhouseClient,
+) -> Result<u64> {
+    info!("Checking if chain id is correct");
+    let rows: Vec<ClickhouseChainId> = client
+        .query(
+            &format!(
+                "SELECT chain_id FROM {}.ledger_infos FINAL LIMIT 1",
+                client.database
+            ),
+            &[],
+        )
+        .await
+        .context("Failed to query ledger_infos table.")?;
+
+    match rows.first() {
+        Some(row) => {
+            anyhow::ensure!(
+                row.chain_id == grpc_chain_id,
+                "Wrong chain detected! Trying to index chain {} now but existing data is for chain {}",
+                grpc_chain_id,
+                row.chain_id
+            );
+            info!(
+                chain_id = row.chain_id,
+                "Chain id matches! Continue to index..."
+            );
+        },
+        None => {
+            info!(
+                chain_id = grpc_chain_id,
+                "Adding chain id to db, continue to index..."
+            );
+            client
+                .insert_rows(&format!("{}.ledger_infos", client.database), &[
+                    serde_json::json!({ "chain_id": grpc_chain_id }),
+                ])
+                .await
+                .context("Error updating chain_id!")?;
+        },
+    }
+    Ok(grpc_chain_id as u64)
+}
+
+pub struct ClickhouseProcessorStatusSaver {
+    pub client: ClickhouseClient,
+    /// The backfill alias in backfill mode
+    pub processor_name: String,
+    /// Start and end version of the backfill in backfill mode, which is complete once the end
+    /// version is processed.
+    pub backfill_versions: Option<(u64, u64)>,
+}
+
+impl ClickhouseProcessorStatusSaver {
+    pub fn new(client: ClickhouseClient, config: &IndexerProcessorConfig) -> Self {
+        match (&config.mode, &config.backfill_config) {
+            (ProcessorMode::Backfill, Some(backfill_config)) => Self {
+                client,
+                processor_name: backfill_config.backfill_alias(&config.processor_name()),
+                backfill_versions: Some((
+                    backfill_config.initial_starting_version,
+                    backfill_config.ending_version,
+                )),
+            },
+            _ => Self {
+                client,
+                processor_name: config.processor_name(),
+                backfill_versions: None,
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl ProcessorStatusSaver for ClickhouseProcessorStatusSaver {
+    async fn save_processor_status(
+        &self,
+        last_success_batch: &TransactionContext<()>,
+    ) -> Result<(), ProcessorError> {
+        let last_transaction_timestamp = last_success_batch
+            .metadata
+            .end_transaction_timestamp
+            .as_ref()
+            .map(|t| parse_timestamp(t, last_success_batch.metadata.end_version as i64))
+            .map(|t| t.naive_utc());
+        let last_success_version = last_success_batch.metadata.end_version as i64;
+        let (table_name, row) = match self.backfill_versions {
+            Some((backfill_start_version, backfill_end_version)) => {
+                let backfill_status = if last_success_version >= backfill_end_version as i64 {
+                    BACKFILL_COMPLETE
+                } else {
+                    BACKFILL_IN_PROGRESS
+                };
+                let status = ClickhouseBackfillProcessorStatus {
+                    backfill_alias: self.processor_name.clone(),
+                    backfill_status: backfill_status.to_string(),
+                    last_success_version,
+                    last_transaction_timestamp,
+                    backfill_start_version: backfill_start_version as i64,
+                    backfill_end_version: backfill_end_version as i64,
+                };
+                ("backfill_processor_status", serde_json::to_value(&status))
+            },
+            None => {
+                let status = ClickhouseProcessorStatus {
+                    processor: self.processor_name.clone(),
+                    last_success_version,
+                    last_transaction_timestamp,
+                };
+                ("processor_status", serde_json::to_value(&status))
+            },
+        };
+        let row = row.map_err(|e| ProcessorError::DBStoreError {
+            message: format!("Failed to serialize processor status: {:?}", e),
+            query: None,
+        })?;
+
+        self.client
+            .insert_rows(&format!("{}.{}", self.client.database, table_name), &[row])
+            .await
+            .map_err(|e| ProcessorError::DBStoreError {
+                message: format!("Failed to save processor status: {:?}", e),
+                query: None,
+            })
+    }
+}
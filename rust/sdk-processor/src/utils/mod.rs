@@ -1,4 +1,6 @@
+pub mod backfill_shards;
 pub mod chain_id;
+pub mod clickhouse;
 pub mod database;
 pub mod parquet_extractor_helper;
 pub mod parquet_processor_table_mapping;
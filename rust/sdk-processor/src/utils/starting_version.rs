@@ -12,6 +12,8 @@ use crate::{
 use anyhow::{Context, Result};
 use diesel::{result::Error as DieselError, upsert::excluded, ExpressionMethods};
 use processor::schema::backfill_processor_status;
+use std::{collections::HashMap, ops::RangeInclusive};
+use tracing::info;
 
 /// Get the appropriate starting version for the processor.
 ///
@@ -38,7 +40,7 @@ pub async fn get_starting_version(
         ProcessorMode::Backfill => {
             let backfill_config = indexer_processor_config.backfill_config.clone().unwrap();
             let backfill_status_option = BackfillProcessorStatusQuery::get_by_processor(
-                indexer_processor_config.processor_config.name(),
+                &indexer_processor_config.processor_name(),
                 &backfill_config.backfill_id,
                 &mut conn,
             )
@@ -64,37 +66,15 @@ pub async fn get_starting_version(
                         backfill_start_version: backfill_config.initial_starting_version as i64,
                         backfill_end_version: backfill_config.ending_version as i64,
                     };
-                    execute_with_better_error(
-                        conn_pool.clone(),
-                        diesel::insert_into(backfill_processor_status::table)
-                            .values(&status)
-                            .on_conflict(backfill_processor_status::backfill_alias)
-                            .do_update()
-                            .set((
-                                backfill_processor_status::backfill_status
-                                    .eq(excluded(backfill_processor_status::backfill_status)),
-                                backfill_processor_status::last_success_version
-                                    .eq(excluded(backfill_processor_status::last_success_version)),
-                                backfill_processor_status::last_updated
-                                    .eq(excluded(backfill_processor_status::last_updated)),
-                                backfill_processor_status::last_transaction_timestamp.eq(excluded(
-                                    backfill_processor_status::last_transaction_timestamp,
-                                )),
-                                backfill_processor_status::backfill_start_version.eq(excluded(
-                                    backfill_processor_status::backfill_start_version,
-                                )),
-                                backfill_processor_status::backfill_end_version
-                                    .eq(excluded(backfill_processor_status::backfill_end_version)),
-                            )),
-                        None,
-                    )
-                    .await?;
+                    upsert_backfill_status(conn_pool.clone(), &status).await?;
                     return Ok(backfill_config.initial_starting_version);
                 }
 
                 // `backfill_config.initial_starting_version` is NOT respected.
                 // Return the last success version + 1.
-                let starting_version = status.last_success_version as u64 + 1;
+                // Shards of a sharded backfill are created with `last_success_version` set to
+                // one before their start, which is -1 for a shard starting at genesis.
+                let starting_version = (status.last_success_version + 1) as u64;
                 log_ascii_warning(starting_version);
                 Ok(starting_version)
             } else {
@@ -105,7 +85,7 @@ pub async fn get_starting_version(
             // Return initial_starting_version if there is no checkpoint. Otherwise,
             // return the higher of the checkpointed version and `initial_starting_version`.
             let status = ProcessorStatusQuery::get_by_processor(
-                indexer_processor_config.processor_config.name(),
+                &indexer_processor_config.processor_name(),
                 &mut conn,
             )
             .await
@@ -128,6 +108,38 @@ pub async fn get_starting_version(
     }
 }
 
+/// Inserts or overwrites a backfill checkpoint, used to (re)start a backfill job.
+pub(crate) async fn upsert_backfill_status(
+    conn_pool: ArcDbPool,
+    status: &BackfillProcessorStatus,
+) -> Result<()> {
+    execute_with_better_error(
+        conn_pool,
+        diesel::insert_into(backfill_processor_status::table)
+            .values(status)
+            .on_conflict(backfill_processor_status::backfill_alias)
+            .do_update()
+            .set((
+                backfill_processor_status::backfill_status
+                    .eq(excluded(backfill_processor_status::backfill_status)),
+                backfill_processor_status::last_success_version
+                    .eq(excluded(backfill_processor_status::last_success_version)),
+                backfill_processor_status::last_updated
+                    .eq(excluded(backfill_processor_status::last_updated)),
+                backfill_processor_status::last_transaction_timestamp.eq(excluded(
+                    backfill_processor_status::last_transaction_timestamp,
+                )),
+                backfill_processor_status::backfill_start_version
+                    .eq(excluded(backfill_processor_status::backfill_start_version)),
+                backfill_processor_status::backfill_end_version
+                    .eq(excluded(backfill_processor_status::backfill_end_version)),
+            )),
+        None,
+    )
+    .await?;
+    Ok(())
+}
+
 fn log_ascii_warning(version: u64) {
     println!(
         r#"
@@ -185,6 +197,159 @@ pub async fn get_min_last_success_version_parquet(
     }
 }
 
+/// Versions a parquet processor should stream, and which versions each table still needs.
+#[derive(Debug)]
+pub struct ParquetVersionRange {
+    pub starting_version: u64,
+    pub ending_version: Option<u64>,
+    /// Versions left to write for each table, keyed by table name. Only set in backfill mode;
+    /// otherwise every table is written for every streamed version.
+    pub table_version_ranges: Option<HashMap<String, RangeInclusive<u64>>>,
+}
+
+/// Get the versions a parquet processor should process.
+///
+/// Outside of backfill mode, this streams from `get_min_last_success_version_parquet` to the
+/// testing `ending_version`, if any, for all tables.
+///
+/// In backfill mode, every table is backfilled on its own: it has its own range, from
+/// `backfill_config.per_table_versions` or the backfill config itself, and its own checkpoint under
+/// `{processor}_{backfill_id}.{table}`. Tables that are complete are skipped, the others resume
+/// from their own checkpoint. The stream covers the union of the remaining ranges.
+pub async fn get_parquet_version_range(
+    indexer_processor_config: &IndexerProcessorConfig,
+    conn_pool: ArcDbPool,
+    table_names: Vec<String>,
+) -> Result<ParquetVersionRange> {
+    match indexer_processor_config.mode {
+        ProcessorMode::Backfill => {
+            get_parquet_backfill_version_range(indexer_processor_config, conn_pool, table_names)
+                .await
+        },
+        ProcessorMode::Default | ProcessorMode::Testing => {
+            let ending_version = match indexer_processor_config.mode {
+                ProcessorMode::Testing => indexer_processor_config
+                    .testing_config
+                    .as_ref()
+                    .map(|config| config.ending_version),
+                _ => None,
+            };
+            Ok(ParquetVersionRange {
+                starting_version: get_min_last_success_version_parquet(
+                    indexer_processor_config,
+                    conn_pool,
+                    table_names,
+                )
+                .await?,
+                ending_version,
+                table_version_ranges: None,
+            })
+        },
+    }
+}
+
+async fn get_parquet_backfill_version_range(
+    indexer_processor_config: &IndexerProcessorConfig,
+    conn_pool: ArcDbPool,
+    table_names: Vec<String>,
+) -> Result<ParquetVersionRange> {
+    let backfill_config = indexer_processor_config.backfill_config.clone().unwrap();
+    let processor_name = indexer_processor_config.processor_name();
+    let mut conn = conn_pool.get().await?;
+
+    let mut table_version_ranges = HashMap::new();
+    let mut resumed = false;
+    for table_name in table_names {
+        // Processor status names are prefixed with the processor name, which isn't part of the
+        // table name.
+        let table_name = table_name
+            .rsplit('.')
+            .next()
+            .unwrap_or(&table_name)
+            .to_string();
+        let (start_version, end_version) = backfill_config.table_version_range(&table_name);
+        let backfill_alias = backfill_config.table_backfill_alias(&processor_name, &table_name);
+
+        let next_version = if backfill_config.overwrite_checkpoint {
+            let status = BackfillProcessorStatus {
+                backfill_alias,
+                backfill_status: BackfillStatus::InProgress,
+                last_success_version: 0,
+                last_transaction_timestamp: None,
+                backfill_start_version: start_version as i64,
+                backfill_end_version: end_version as i64,
+            };
+            upsert_backfill_status(conn_pool.clone(), &status).await?;
+            Some(start_version)
+        } else {
+            let status = BackfillProcessorStatusQuery::get_by_alias(&backfill_alias, &mut conn)
+                .await
+                .context("Failed to query backfill_processor_status table.")?;
+            resumed |= status
+                .as_ref()
+                .is_some_and(|status| status.backfill_status == BackfillStatus::InProgress);
+            next_table_backfill_version(status.as_ref(), start_version, end_version)
+        };
+
+        match next_version {
+            Some(next_version) => {
+                info!(
+                    table_name = table_name,
+                    start_version = next_version,
+                    end_version = end_version,
+                    "Backfilling table"
+                );
+                table_version_ranges.insert(table_name, next_version..=end_version);
+            },
+            None => info!(
+                table_name = table_name,
+                "Table backfill is complete, skipping"
+            ),
+        }
+    }
+
+    let starting_version = table_version_ranges
+        .values()
+        .map(|range| *range.start())
+        .min();
+    let ending_version = table_version_ranges
+        .values()
+        .map(|range| *range.end())
+        .max();
+    match (starting_version, ending_version) {
+        (Some(starting_version), Some(ending_version)) => {
+            if resumed {
+                log_ascii_warning(starting_version);
+            }
+            Ok(ParquetVersionRange {
+                starting_version,
+                ending_version: Some(ending_version),
+                table_version_ranges: Some(table_version_ranges),
+            })
+        },
+        // Every table is complete, so end the backfill right away.
+        _ => Ok(ParquetVersionRange {
+            starting_version: backfill_config.ending_version,
+            ending_version: Some(backfill_config.ending_version),
+            table_version_ranges: Some(table_version_ranges),
+        }),
+    }
+}
+
+/// Returns the next version to backfill a table from, or `None` if its backfill is complete.
+fn next_table_backfill_version(
+    status: Option<&BackfillProcessorStatusQuery>,
+    start_version: u64,
+    end_version: u64,
+) -> Option<u64> {
+    let next_version = match status {
+        None => start_version,
+        Some(status) if status.backfill_status == BackfillStatus::Complete => return None,
+        Some(status) => std::cmp::max(status.last_success_version as u64 + 1, start_version),
+    };
+    (next_version <= end_version).then_some(next_version)
+}
+
 /// Get the minimum last success version from the database for the given processors.
 ///
 /// This should return the minimum of the last success version of the processors in the list.
@@ -251,12 +416,14 @@ mod tests {
             db_config::{DbConfig, PostgresConfig},
             indexer_processor_config::{
                 BackfillConfig, BootStrapConfig, IndexerProcessorConfig, ProcessorMode,
-                TestingConfig,
+                TableBackfillConfig, TestingConfig,
             },
             processor_config::{DefaultProcessorConfig, ProcessorConfig},
         },
         db::common::models::{
-            backfill_processor_status::{BackfillProcessorStatus, BackfillStatus},
+            backfill_processor_status::{
+                BackfillProcessorStatus, BackfillProcessorStatusQuery, BackfillStatus,
+            },
             processor_status::ProcessorStatus,
         },
         utils::database::{new_db_pool, run_migrations},
@@ -315,6 +482,8 @@ mod tests {
             bootstrap_config,
             testing_config,
             mode,
+            instance_id: None,
+            status_history_config: None,
         }
     }
 
@@ -397,6 +566,8 @@ mod tests {
                 initial_starting_version: 0,
                 ending_version: 10,
                 overwrite_checkpoint: false,
+                per_table_versions: AHashMap::new(),
+                sharding: None,
             }),
             None,
             None,
@@ -438,6 +609,8 @@ mod tests {
                 initial_starting_version: 0,
                 ending_version: 10,
                 overwrite_checkpoint: false,
+                per_table_versions: AHashMap::new(),
+                sharding: None,
             }),
             None,
             None,
@@ -479,6 +652,8 @@ mod tests {
                 initial_starting_version: 3,
                 ending_version: 10,
                 overwrite_checkpoint: true,
+                per_table_versions: AHashMap::new(),
+                sharding: None,
             }),
             None,
             None,
@@ -520,6 +695,8 @@ mod tests {
                 initial_starting_version: 3,
                 ending_version: 10,
                 overwrite_checkpoint: false,
+                per_table_versions: AHashMap::new(),
+                sharding: None,
             }),
             None,
             Some(3),
@@ -656,4 +833,108 @@ mod tests {
         // Since processor_2 has no checkpoint, the minimum version should be the starting version of processor_1
         assert_eq!(min_version, 15);
     }
+
+    #[test]
+    fn test_next_table_backfill_version() {
+        let status = |backfill_status, last_success_version| BackfillProcessorStatusQuery {
+            backfill_alias: "parquet_default_processor_1.move_resources".to_string(),
+            backfill_status,
+            last_success_version,
+            last_updated: chrono::Utc::now().naive_utc(),
+            last_transaction_timestamp: None,
+            backfill_start_version: 100,
+            backfill_end_version: 200,
+            claimed_by: None,
+        };
+
+        assert_eq!(next_table_backfill_version(None, 100, 200), Some(100));
+        assert_eq!(
+            next_table_backfill_version(Some(&status(BackfillStatus::InProgress, 150)), 100, 200),
+            Some(151)
+        );
+        // A reset checkpoint starts from the beginning of the range.
+        assert_eq!(
+            next_table_backfill_version(Some(&status(BackfillStatus::InProgress, 0)), 100, 200),
+            Some(100)
+        );
+        assert_eq!(
+            next_table_backfill_version(Some(&status(BackfillStatus::InProgress, 200)), 100, 200),
+            None
+        );
+        assert_eq!(
+            next_table_backfill_version(Some(&status(BackfillStatus::Complete, 150)), 100, 200),
+            None
+        );
+    }
+
+    #[tokio::test]
+    #[allow(clippy::needless_return)]
+    async fn test_get_parquet_version_range_per_table_backfill() {
+        let mut db = PostgresTestDatabase::new();
+        db.setup().await.unwrap();
+        let indexer_processor_config = create_indexer_config(
+            db.get_db_url(),
+            Some(BackfillConfig {
+                backfill_id: "1".to_string(),
+                initial_starting_version: 0,
+                ending_version: 100,
+                overwrite_checkpoint: false,
+                per_table_versions: AHashMap::from_iter([(
+                    "move_resources".to_string(),
+                    TableBackfillConfig {
+                        initial_starting_version: 50,
+                        ending_version: 300,
+                    },
+                )]),
+                sharding: None,
+            }),
+            None,
+            None,
+            ProcessorMode::Backfill,
+        );
+        let conn_pool = new_db_pool(db.get_db_url().as_str(), Some(10))
+            .await
+            .expect("Failed to create connection pool");
+        run_migrations(db.get_db_url(), conn_pool.clone()).await;
+        diesel::insert_into(processor::schema::backfill_processor_status::table)
+            .values(vec![
+                BackfillProcessorStatus {
+                    backfill_alias: "events_processor_1.transactions".to_string(),
+                    backfill_status: BackfillStatus::Complete,
+                    last_success_version: 100,
+                    last_transaction_timestamp: None,
+                    backfill_start_version: 0,
+                    backfill_end_version: 100,
+                },
+                BackfillProcessorStatus {
+                    backfill_alias: "events_processor_1.move_resources".to_string(),
+                    backfill_status: BackfillStatus::InProgress,
+                    last_success_version: 120,
+                    last_transaction_timestamp: None,
+                    backfill_start_version: 50,
+                    backfill_end_version: 300,
+                },
+            ])
+            .execute(&mut conn_pool.clone().get().await.unwrap())
+            .await
+            .expect("Failed to insert backfill processor status");
+
+        let version_range = get_parquet_version_range(&indexer_processor_config, conn_pool, vec![
+            "transactions".to_string(),
+            "move_resources".to_string(),
+            "table_items".to_string(),
+        ])
+        .await
+        .unwrap();
+
+        assert_eq!(version_range.starting_version, 0);
+        assert_eq!(version_range.ending_version, Some(300));
+        assert_eq!(
+            version_range.table_version_ranges,
+            Some(HashMap::from([
+                ("move_resources".to_string(), 121..=300),
+                ("table_items".to_string(), 0..=100),
+            ]))
+        );
+    }
 }
@@ -0,0 +1,106 @@
+// Copyright © Aptos Foundation
+
+//! Readiness and liveness probes served on the health check port.
+//!
+//! Without a registered [`HealthCheck`] both probes always succeed. Services register one with
+//! [`register_health_check`] to drive the probes from their own state, e.g. to stay unready until
+//! startup completes or to be restarted when they stop making progress. A failing probe responds
+//! with 503 and the reason in the body.
+
+use anyhow::Result;
+use std::{
+    convert::Infallible,
+    sync::{Arc, RwLock},
+};
+use warp::{http::StatusCode, reply::WithStatus, Filter};
+
+pub trait HealthCheck: Send + Sync {
+    /// Fails with the reason the service should not be considered ready, e.g. it's still
+    /// starting or is too far behind.
+    fn readiness(&self) -> Result<()>;
+
+    /// Fails with the reason the service should be restarted, e.g. it's stuck.
+    fn liveness(&self) -> Result<()>;
+}
+
+static HEALTH_CHECK: RwLock<Option<Arc<dyn HealthCheck>>> = RwLock::new(None);
+
+/// Registers the check backing the probes, replacing any previously registered one.
+pub fn register_health_check(health_check: Arc<dyn HealthCheck>) {
+    *HEALTH_CHECK.write().unwrap() = Some(health_check);
+}
+
+fn health_check() -> Option<Arc<dyn HealthCheck>> {
+    HEALTH_CHECK.read().unwrap().clone()
+}
+
+fn probe_reply(result: Result<()>, ok: &str) -> WithStatus<String> {
+    match result {
+        Ok(()) => warp::reply::with_status(ok.to_string(), StatusCode::OK),
+        Err(e) => warp::reply::with_status(format!("{:#}", e), StatusCode::SERVICE_UNAVAILABLE),
+    }
+}
+
+/// `/readiness` and `/liveness`.
+pub(crate) fn probe_routes(
+) -> impl Filter<Extract = (WithStatus<String>,), Error = warp::Rejection> + Clone {
+    let readiness = warp::path("readiness").and_then(|| async {
+        let result = health_check().map_or(Ok(()), |check| check.readiness());
+        Ok::<_, Infallible>(probe_reply(result, "ready"))
+    });
+    let liveness = warp::path("liveness").and_then(|| async {
+        let result = health_check().map_or(Ok(()), |check| check.liveness());
+        Ok::<_, Infallible>(probe_reply(result, "alive"))
+    });
+    readiness.or(liveness).unify()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Default)]
+    struct TestHealthCheck {
+        started: AtomicBool,
+    }
+
+    impl HealthCheck for TestHealthCheck {
+        fn readiness(&self) -> Result<()> {
+            anyhow::ensure!(self.started.load(Ordering::SeqCst), "Still starting");
+            Ok(())
+        }
+
+        fn liveness(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_routes() {
+        let routes = probe_routes();
+        let res = warp::test::request()
+            .path("/readiness")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let health_check = Arc::new(TestHealthCheck::default());
+        register_health_check(health_check.clone());
+        let res = warp::test::request()
+            .path("/readiness")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.body().as_ref(), b"Still starting");
+        let res = warp::test::request().path("/liveness").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        health_check.started.store(true, Ordering::SeqCst);
+        let res = warp::test::request()
+            .path("/readiness")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.body().as_ref(), b"ready");
+    }
+}
@@ -1,5 +1,11 @@
 // Copyright © Aptos Foundation
 
+use crate::{
+    admin::{admin_routes, AdminConfig},
+    health::probe_routes,
+    overrides::apply_overrides,
+    reload::{ConfigChange, ConfigReloader, Reload},
+};
 use anyhow::{Context, Result};
 #[cfg(target_os = "linux")]
 use aptos_system_utils::profiling::start_cpu_profiling;
@@ -11,12 +17,19 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::convert::Infallible;
 // TODO: remove deprecated lint when new clippy nightly is released
 #[allow(deprecated)]
-use std::{fs::File, io::Read, panic::PanicInfo, path::PathBuf, process};
+use std::{
+    fs::File, io::Read, panic::PanicInfo, path::PathBuf, process, sync::Arc, time::Duration,
+};
 use tokio::runtime::Handle;
 use tracing::error;
 use tracing_subscriber::EnvFilter;
 use warp::{http::Response, Filter};
 
+pub mod admin;
+pub mod health;
+pub mod overrides;
+pub mod reload;
+
 /// ServerArgs bootstraps a server with all common pieces. And then triggers the run method for
 /// the specific service.
 #[derive(Parser)]
@@ -34,20 +47,48 @@ impl ServerArgs {
         setup_logging();
         setup_panic_handler();
         let config = load::<GenericConfig<C>>(&self.config_path)?;
-        run_server_with_config(config, handle).await
+        run_server(config, Some(self.config_path.clone()), handle).await
     }
 }
 
 /// Run a server and the necessary probes. For spawning these tasks, the user must
 /// provide a handle to a runtime they already have.
 pub async fn run_server_with_config<C>(config: GenericConfig<C>, handle: Handle) -> Result<()>
+where
+    C: RunnableConfig,
+{
+    run_server(config, None, handle).await
+}
+
+/// Like `run_server_with_config`, also reloading the config from `config_path` when given.
+async fn run_server<C>(
+    config: GenericConfig<C>,
+    config_path: Option<PathBuf>,
+    handle: Handle,
+) -> Result<()>
 where
     C: RunnableConfig,
 {
     let health_port = config.health_check_port;
+    let admin_config = config.admin_config.clone();
+    let config_watch_interval = config.config_watch_interval_secs.map(Duration::from_secs);
+    let config = Arc::new(config);
+    let reloader = config_path.map(|path| Arc::new(ConfigReloader::new(path, config.clone())));
+    if let Some(reloader) = reloader.clone() {
+        handle.spawn(async move {
+            if let Err(e) = reloader.watch(config_watch_interval).await {
+                error!(error = ?e, "[Server] Config reloading stopped");
+            }
+        });
+    }
     // Start liveness and readiness probes.
     let task_handler = handle.spawn(async move {
-        register_probes_and_metrics_handler(health_port).await;
+        register_probes_and_metrics_handler(
+            health_port,
+            admin_config,
+            reloader.map(|reloader| reloader as Arc<dyn Reload>),
+        )
+        .await;
         anyhow::Ok(())
     });
     let main_task_handler = handle.spawn(async move { config.run().await });
@@ -65,6 +106,12 @@ where
 pub struct GenericConfig<T> {
     // Shared configuration among all services.
     pub health_check_port: u16,
+    // Enables the admin API on the health check port, see `admin`.
+    #[serde(default)]
+    pub admin_config: Option<AdminConfig>,
+    // Reload the config when the file changes, checked this often, see `reload`.
+    #[serde(default)]
+    pub config_watch_interval_secs: Option<u64>,
 
     // Specific configuration for each service.
     pub server_config: T,
@@ -82,6 +129,10 @@ where
     fn get_server_name(&self) -> String {
         self.server_config.get_server_name()
     }
+
+    fn reload(&self, new_config: &Self) -> Result<Vec<ConfigChange>> {
+        self.server_config.reload(&new_config.server_config)
+    }
 }
 
 /// RunnableConfig is a trait that all services must implement for their configuration.
@@ -89,16 +140,28 @@ where
 pub trait RunnableConfig: DeserializeOwned + Send + Sync + 'static {
     async fn run(&self) -> Result<()>;
     fn get_server_name(&self) -> String;
+
+    /// Applies the fields of `new_config` that can change while running and returns them. Must
+    /// not apply anything if `new_config` is invalid.
+    fn reload(&self, _new_config: &Self) -> Result<Vec<ConfigChange>> {
+        anyhow::bail!("Reloading the config is not supported by this service")
+    }
 }
 
-/// Parse a yaml file into a struct.
+/// Parse a yaml file into a struct, with the environment variable and secret file overrides of
+/// `overrides` applied.
 pub fn load<T: for<'de> Deserialize<'de>>(path: &PathBuf) -> Result<T> {
     let mut file =
         File::open(path).with_context(|| format!("failed to open the file at path: {:?}", path))?;
     let mut contents = String::new();
     file.read_to_string(&mut contents)
         .with_context(|| format!("failed to read the file at path: {:?}", path))?;
-    serde_yaml::from_str::<T>(&contents).context("Unable to parse yaml file")
+    let mut config: serde_yaml::Value =
+        serde_yaml::from_str(&contents).context("Unable to parse yaml file")?;
+    let env = std::env::vars_os()
+        .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)));
+    apply_overrides(&mut config, env).context("Unable to apply config overrides")?;
+    serde_yaml::from_value::<T>(config).context("Unable to parse yaml file")
 }
 
 #[derive(Debug, Serialize)]
@@ -154,10 +217,13 @@ pub fn setup_logging() {
         .init();
 }
 
-/// Register readiness and liveness probes and set up metrics endpoint.
-async fn register_probes_and_metrics_handler(port: u16) {
-    let readiness = warp::path("readiness")
-        .map(move || warp::reply::with_status("ready", warp::http::StatusCode::OK));
+/// Register readiness and liveness probes and set up metrics and admin endpoints.
+async fn register_probes_and_metrics_handler(
+    port: u16,
+    admin_config: Option<AdminConfig>,
+    reloader: Option<Arc<dyn Reload>>,
+) {
+    let probes = probe_routes();
     let metrics_endpoint = warp::path("metrics").map(|| {
         // Metrics encoding.
         let metrics = prometheus::gather();
@@ -173,6 +239,7 @@ async fn register_probes_and_metrics_handler(port: u16) {
             .header("Content-Type", "text/plain")
             .body(encode_buffer)
     });
+    let admin = admin_routes(admin_config, reloader);
 
     if cfg!(target_os = "linux") {
         #[cfg(target_os = "linux")]
@@ -201,11 +268,11 @@ async fn register_probes_and_metrics_handler(port: u16) {
             })
         });
         #[cfg(target_os = "linux")]
-        warp::serve(readiness.or(metrics_endpoint).or(profilez))
+        warp::serve(probes.or(metrics_endpoint).or(profilez).or(admin))
             .run(([0, 0, 0, 0], port))
             .await;
     } else {
-        warp::serve(readiness.or(metrics_endpoint))
+        warp::serve(probes.or(metrics_endpoint).or(admin))
             .run(([0, 0, 0, 0], port))
             .await;
     }
@@ -253,6 +320,7 @@ mod tests {
 
         let config = load::<GenericConfig<TestConfig>>(&file_path).unwrap();
         assert_eq!(config.health_check_port, 12345);
+        assert!(config.admin_config.is_none());
         assert_eq!(config.server_config.test, 123);
         assert_eq!(config.server_config.test_name, "test");
     }
@@ -0,0 +1,220 @@
+// Copyright © Aptos Foundation
+
+//! Layers applied on top of the config file by [`load`](crate::load), so that secrets can be kept
+//! out of it. In order of precedence:
+//!
+//! 1. Environment variables starting with [`ENV_PREFIX`] set the field at the path that follows,
+//!    lowercased and with nested fields separated by `__`, e.g.
+//!    `INDEXER__SERVER_CONFIG__POSTGRES_CONNECTION_STRING` sets
+//!    `server_config.postgres_connection_string`. Values are parsed as YAML unless they replace a
+//!    string, so lists and numbers can be set too.
+//! 2. Fields ending in `_file` are read from the file they point to, e.g. a mounted secret, and
+//!    replaced by the field without the suffix: `auth_token_file: /run/secrets/auth_token` sets
+//!    `auth_token` to the file's contents without the trailing newline. They can be set through
+//!    the environment like any other field.
+//! 3. The config file.
+//!
+//! Setting a field and its `_file` counterpart in the same layer is an error, while a higher
+//! layer replaces both.
+
+use anyhow::{Context, Result};
+use serde_yaml::{Mapping, Value};
+
+pub const ENV_PREFIX: &str = "INDEXER__";
+const ENV_SEPARATOR: &str = "__";
+const FILE_SUFFIX: &str = "_file";
+
+/// Applies the environment variables in `env` and then reads the `_file` fields.
+pub fn apply_overrides(
+    config: &mut Value,
+    env: impl IntoIterator<Item = (String, String)>,
+) -> Result<()> {
+    let mut overrides: Vec<(String, String)> = env
+        .into_iter()
+        .filter(|(key, _)| key.starts_with(ENV_PREFIX))
+        .collect();
+    // Applied in a stable order so that a parent, e.g. a whole list, is set before its fields.
+    overrides.sort();
+    for (key, raw) in overrides {
+        let path: Vec<String> = key[ENV_PREFIX.len()..]
+            .split(ENV_SEPARATOR)
+            .map(|field| field.to_lowercase())
+            .collect();
+        anyhow::ensure!(
+            path.iter().all(|field| !field.is_empty()),
+            "Invalid config override {}",
+            key
+        );
+        set_field(config, &path, &raw).with_context(|| format!("Failed to apply {}", key))?;
+    }
+    read_secret_files(config)
+}
+
+fn set_field(config: &mut Value, path: &[String], raw: &str) -> Result<()> {
+    let (field, parents) = path.split_last().context("Empty path")?;
+    let mut current = config;
+    for parent in parents {
+        let map = current
+            .as_mapping_mut()
+            .with_context(|| format!("{} is not a map", parent))?;
+        let key = Value::String(parent.clone());
+        if !map.contains_key(&key) {
+            map.insert(key.clone(), Value::Mapping(Mapping::new()));
+        }
+        current = map.get_mut(&key).unwrap();
+    }
+    let map = current
+        .as_mapping_mut()
+        .with_context(|| format!("Parent of {} is not a map", field))?;
+
+    let key = Value::String(field.clone());
+    let value = match map.get(&key) {
+        Some(Value::String(_)) => Value::String(raw.to_string()),
+        _ => serde_yaml::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string())),
+    };
+    map.insert(key, value);
+    // The override takes the place of the counterpart set in the config file.
+    let counterpart = match field.strip_suffix(FILE_SUFFIX) {
+        Some(field) => field.to_string(),
+        None => format!("{}{}", field, FILE_SUFFIX),
+    };
+    map.remove(&Value::String(counterpart));
+    Ok(())
+}
+
+fn read_secret_files(value: &mut Value) -> Result<()> {
+    match value {
+        Value::Mapping(map) => {
+            let file_fields: Vec<String> = map
+                .iter()
+                .filter_map(|(key, _)| key.as_str())
+                .filter(|key| key.len() > FILE_SUFFIX.len() && key.ends_with(FILE_SUFFIX))
+                .map(str::to_string)
+                .collect();
+            for file_field in file_fields {
+                let field = file_field.strip_suffix(FILE_SUFFIX).unwrap();
+                anyhow::ensure!(
+                    !map.contains_key(&Value::String(field.to_string())),
+                    "Only one of {} and {} can be set",
+                    field,
+                    file_field
+                );
+                let path = map.remove(&Value::String(file_field.clone())).unwrap();
+                let path = path
+                    .as_str()
+                    .with_context(|| format!("{} must be a path", file_field))?;
+                let contents = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read {} from {}", field, path))?;
+                let contents = contents.trim_end_matches(['\n', '\r']).to_string();
+                map.insert(Value::String(field.to_string()), Value::String(contents));
+            }
+            for (_, value) in map.iter_mut() {
+                read_secret_files(value)?;
+            }
+        },
+        Value::Sequence(values) => {
+            for value in values {
+                read_secret_files(value)?;
+            }
+        },
+        _ => {},
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        let dir = tempdir().unwrap();
+        let secret_path = dir.path().join("auth_token");
+        std::fs::write(&secret_path, "secret\n").unwrap();
+
+        let mut config: Value = serde_yaml::from_str(&format!(
+            r#"
+            health_check_port: 8084
+            server_config:
+                auth_token_file: {}
+                postgres_connection_string: postgresql://localhost:5432/postgres
+                starting_version: 1
+                processor_config:
+                    type: default_processor
+            "#,
+            secret_path.display()
+        ))
+        .unwrap();
+        apply_overrides(
+            &mut config,
+            env(&[
+                ("INDEXER__HEALTH_CHECK_PORT", "9090"),
+                (
+                    "INDEXER__SERVER_CONFIG__POSTGRES_CONNECTION_STRING",
+                    "postgresql://db",
+                ),
+                (
+                    "INDEXER__SERVER_CONFIG__DEPRECATED_TABLES",
+                    "[MOVE_RESOURCES]",
+                ),
+                (
+                    "INDEXER__SERVER_CONFIG__PROCESSOR_CONFIG__TYPE",
+                    "events_processor",
+                ),
+                ("PATH", "/usr/bin"),
+            ]),
+        )
+        .unwrap();
+        let server_config = &config["server_config"];
+        assert_eq!(config["health_check_port"].as_u64(), Some(9090));
+        assert_eq!(server_config["auth_token"].as_str(), Some("secret"));
+        assert!(server_config.get("auth_token_file").is_none());
+        assert_eq!(
+            server_config["postgres_connection_string"].as_str(),
+            Some("postgresql://db")
+        );
+        assert_eq!(server_config["starting_version"].as_u64(), Some(1));
+        assert_eq!(
+            server_config["processor_config"]["type"].as_str(),
+            Some("events_processor")
+        );
+        assert_eq!(
+            server_config["deprecated_tables"],
+            Value::Sequence(vec![Value::String("MOVE_RESOURCES".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_env_replaces_secret_file() {
+        let mut config: Value = serde_yaml::from_str(
+            r#"
+            server_config:
+                auth_token_file: /does/not/exist
+            "#,
+        )
+        .unwrap();
+        apply_overrides(
+            &mut config,
+            env(&[("INDEXER__SERVER_CONFIG__AUTH_TOKEN", "abc")]),
+        )
+        .unwrap();
+        assert_eq!(config["server_config"]["auth_token"].as_str(), Some("abc"));
+        assert!(config["server_config"].get("auth_token_file").is_none());
+
+        let mut config: Value = serde_yaml::from_str(
+            r#"
+            auth_token: abc
+            auth_token_file: /does/not/exist
+            "#,
+        )
+        .unwrap();
+        assert!(apply_overrides(&mut config, vec![]).is_err());
+    }
+}
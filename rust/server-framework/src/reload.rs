@@ -0,0 +1,193 @@
+// Copyright © Aptos Foundation
+
+//! Reloading the config file while running.
+//!
+//! A reload parses the config file again and hands the new service config to
+//! [`RunnableConfig::reload`] of the running one, which applies what can change at runtime. It is
+//! triggered by `SIGHUP`, by `POST /admin/reload` or, if `config_watch_interval_secs` is set, by
+//! the config file changing. Only `server_config` is reloaded; the health check port and admin
+//! config require a restart.
+
+use crate::{admin::redact_config, load, GenericConfig, RunnableConfig};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use tracing::{error, info};
+
+/// A top level config field that differs between two configs. Secrets are redacted.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ConfigChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Lists the top level fields that differ between the two configs, including secrets that changed.
+pub fn config_diff<T: Serialize>(old: &T, new: &T) -> Result<Vec<ConfigChange>> {
+    let (old_raw, new_raw) = (
+        to_map(serde_json::to_value(old)?)?,
+        to_map(serde_json::to_value(new)?)?,
+    );
+    let (old_redacted, new_redacted) = (to_map(redact_config(old)?)?, to_map(redact_config(new)?)?);
+    let fields = old_raw
+        .keys()
+        .chain(new_raw.keys().filter(|field| !old_raw.contains_key(*field)));
+    Ok(fields
+        .filter(|field| old_raw.get(*field) != new_raw.get(*field))
+        .map(|field| ConfigChange {
+            field: field.clone(),
+            old: old_redacted.get(field).cloned().unwrap_or(Value::Null),
+            new: new_redacted.get(field).cloned().unwrap_or(Value::Null),
+        })
+        .collect())
+}
+
+fn to_map(value: Value) -> Result<Map<String, Value>> {
+    match value {
+        Value::Object(map) => Ok(map),
+        _ => anyhow::bail!("Config must serialize to a map"),
+    }
+}
+
+/// Type erased so the admin API can trigger reloads.
+#[async_trait::async_trait]
+pub(crate) trait Reload: Send + Sync {
+    /// Returns the changes applied.
+    async fn reload(&self) -> Result<Vec<ConfigChange>>;
+}
+
+pub(crate) struct ConfigReloader<C> {
+    config_path: PathBuf,
+    config: Arc<GenericConfig<C>>,
+}
+
+impl<C: RunnableConfig> ConfigReloader<C> {
+    pub(crate) fn new(config_path: PathBuf, config: Arc<GenericConfig<C>>) -> Self {
+        Self {
+            config_path,
+            config,
+        }
+    }
+
+    fn reload_and_log(&self, trigger: &str) -> Result<Vec<ConfigChange>> {
+        let result = load::<GenericConfig<C>>(&self.config_path)
+            .and_then(|new_config| self.config.server_config.reload(&new_config.server_config));
+        match &result {
+            Ok(changes) if changes.is_empty() => info!(
+                trigger,
+                config_path = ?self.config_path,
+                "[Server] Config reloaded, nothing to apply"
+            ),
+            Ok(changes) => {
+                for change in changes {
+                    info!(
+                        trigger,
+                        field = change.field,
+                        old = %change.old,
+                        new = %change.new,
+                        "[Server] Applied config change"
+                    );
+                }
+            },
+            Err(e) => error!(
+                trigger,
+                config_path = ?self.config_path,
+                error = ?e,
+                "[Server] Failed to reload config, keeping the current one"
+            ),
+        }
+        result
+    }
+
+    /// Reloads on `SIGHUP` and, with a `watch_interval`, whenever the modification time of the
+    /// config file changes.
+    pub(crate) async fn watch(self: Arc<Self>, watch_interval: Option<Duration>) -> Result<()> {
+        #[cfg(unix)]
+        let mut sighup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+            .context("Failed to listen for SIGHUP")?;
+        let mut last_modified = self.modified();
+        loop {
+            #[cfg(unix)]
+            let hangup = sighup.recv();
+            #[cfg(not(unix))]
+            let hangup = std::future::pending::<Option<()>>();
+            let tick = async {
+                match watch_interval {
+                    Some(interval) => tokio::time::sleep(interval).await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                _ = hangup => {
+                    let _ = self.reload_and_log("signal");
+                },
+                _ = tick => {
+                    let modified = self.modified();
+                    if modified != last_modified {
+                        last_modified = modified;
+                        let _ = self.reload_and_log("file_change");
+                    }
+                },
+            }
+        }
+    }
+
+    fn modified(&self) -> Option<std::time::SystemTime> {
+        std::fs::metadata(&self.config_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: RunnableConfig> Reload for ConfigReloader<C> {
+    async fn reload(&self) -> Result<Vec<ConfigChange>> {
+        self.reload_and_log("admin_api")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    struct TestConfig {
+        chunk_size: u64,
+        auth_token: String,
+        filter: Option<Vec<String>>,
+    }
+
+    #[test]
+    fn test_config_diff() {
+        let old = TestConfig {
+            chunk_size: 100,
+            auth_token: "abc".to_string(),
+            filter: None,
+        };
+        let new = TestConfig {
+            chunk_size: 200,
+            auth_token: "def".to_string(),
+            filter: Some(vec!["0x1".to_string()]),
+        };
+        assert_eq!(config_diff(&old, &old).unwrap(), vec![]);
+        assert_eq!(config_diff(&old, &new).unwrap(), vec![
+            ConfigChange {
+                field: "chunk_size".to_string(),
+                old: json!(100),
+                new: json!(200),
+            },
+            ConfigChange {
+                field: "auth_token".to_string(),
+                old: json!("redacted"),
+                new: json!("redacted"),
+            },
+            ConfigChange {
+                field: "filter".to_string(),
+                old: Value::Null,
+                new: json!(["0x1"]),
+            },
+        ]);
+    }
+}